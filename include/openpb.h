@@ -0,0 +1,40 @@
+#ifndef OPENPB_H
+#define OPENPB_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Returned by openpb_predict when an argument is invalid */
+#define OPENPB_ERROR_INVALID -1
+
+/* Returned by openpb_predict when prediction panicked */
+#define OPENPB_ERROR_PANIC -2
+
+/* Opaque handle to a trained network */
+typedef struct OpenPbModel OpenPbModel;
+
+/* Load a trained network from a model or results file, JSON or MessagePack (NULL on failure) */
+OpenPbModel *openpb_load_model(const char *path);
+
+/* Size of the model's expected input vector */
+size_t openpb_input_size(const OpenPbModel *model);
+
+/* Size of the model's raw output vector */
+size_t openpb_output_size(const OpenPbModel *model);
+
+/* Row-major inputs (rows x input size) to row-major raw outputs (rows x output size).
+ * Returns 0 on success, OPENPB_ERROR_INVALID on invalid arguments (or sizes that
+ * overflow), or OPENPB_ERROR_PANIC if prediction failed unexpectedly */
+int openpb_predict(const OpenPbModel *model, const double *inputs, size_t rows, double *outputs);
+
+/* Free a model returned by openpb_load_model */
+void openpb_free(OpenPbModel *model);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* OPENPB_H */
@@ -0,0 +1,111 @@
+use crate::error::OpenPbError;
+use core_affinity::CoreId;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+
+/// Parse a list of CPU core IDs, where each comma-separated
+/// item is a single ID or an inclusive range (e.g. "0-3,6")
+///
+/// # Arguments
+///
+/// * `value` - Core list as written on the command line
+pub fn parse_cores(value: &str) -> Result<Vec<usize>, OpenPbError> {
+    let mut cores: Vec<usize> = vec![];
+    for item in value.split(',').map(str::trim) {
+        let parse = |id: &str| -> Result<usize, OpenPbError> {
+            match id.trim().parse::<usize>() {
+                Ok(id) => Ok(id),
+                Err(_) => Err(format!("\"{item}\" isn't a core ID or a range of core IDs").into()),
+            }
+        };
+        match item.split_once('-') {
+            Some((first, last)) => {
+                let (first, last): (usize, usize) = (parse(first)?, parse(last)?);
+                if first > last {
+                    return Err(format!("Core range \"{item}\" is empty").into());
+                }
+                cores.extend(first..=last);
+            }
+            None => cores.push(parse(item)?),
+        }
+    }
+    Ok(cores)
+}
+
+/// Check that every core exists and can have threads pinned to it
+///
+/// # Arguments
+///
+/// * `cores` - IDs of the cores
+pub fn check_cores(cores: &[usize]) -> Result<(), OpenPbError> {
+    if cores.is_empty() {
+        return Err("At least one core is needed to pin threads to".into());
+    }
+    let available: Vec<usize> = match core_affinity::get_core_ids() {
+        Some(core_ids) => core_ids.iter().map(|core_id| core_id.id).collect(),
+        None => return Err("Threads can't be pinned to cores on this platform".into()),
+    };
+    match cores.iter().find(|core| !available.contains(core)) {
+        Some(core) => Err(format!(
+            "Core {core} isn't available to pin threads to (available cores: {})",
+            available
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<String>>()
+                .join(", ")
+        )
+        .into()),
+        None => Ok(()),
+    }
+}
+
+/// Create the pool of worker threads runs are trained on. When cores are
+/// given, worker `i` is pinned to the `i`-th core (cycling through them
+/// when there are more workers than cores), so the scheduler can't move
+/// runs between cores partway through a benchmark
+///
+/// # Arguments
+///
+/// * `threads` - Number of worker threads
+/// * `cores` - IDs of the cores the workers are pinned to (not pinned when `None`)
+pub fn thread_pool(threads: usize, cores: Option<&[usize]>) -> Result<ThreadPool, OpenPbError> {
+    let mut builder: ThreadPoolBuilder = ThreadPoolBuilder::new().num_threads(threads);
+    if let Some(cores) = cores {
+        check_cores(cores)?;
+        if threads > cores.len() {
+            eprintln!(
+                "Warning: {threads} threads are pinned to {} cores, so some threads share a core",
+                cores.len()
+            );
+        }
+        let cores: Vec<usize> = cores.to_vec();
+        builder = builder.start_handler(move |index| {
+            let id: usize = cores[index % cores.len()];
+            if !core_affinity::set_for_current(CoreId { id }) {
+                eprintln!("Warning: couldn't pin worker thread {index} to core {id}");
+            }
+        });
+    }
+    match builder.build() {
+        Ok(pool) => Ok(pool),
+        Err(error) => Err(format!("Failed to create thread pool: {error}").into()),
+    }
+}
+
+/// Limit the number of threads in the global thread pool, which parallel
+/// work outside of the training pool (e.g. batch size tuning and bulk
+/// inference) runs on. By default it has one thread per CPU, which would
+/// compete with pinned training threads. Can only be set once per process,
+/// before anything runs on the global pool
+///
+/// # Arguments
+///
+/// * `threads` - Number of threads in the global thread pool
+pub fn limit_internal_threads(threads: usize) -> Result<(), OpenPbError> {
+    if threads == 0 {
+        return Err("The number of internal threads must be at least 1".into());
+    }
+    match ThreadPoolBuilder::new().num_threads(threads).build_global() {
+        Ok(()) => Ok(()),
+        Err(error) => Err(format!("Failed to limit internal threads: {error}").into()),
+    }
+}
@@ -1,28 +1,489 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use openpb::affinity;
+use openpb::daemon::DEFAULT_MAX_FINISHED_JOBS;
+use openpb::error::OpenPbError;
+use openpb::file_io::output_format::OutputFormat;
+use openpb::inference::DEFAULT_INFERENCE_CHUNK_SIZE;
+use openpb::nn::batches::Sampler;
+use openpb::nn::bootstrap::DEFAULT_CONFIDENCE;
+use openpb::nn::divergence::DEFAULT_DIVERGENCE_WINDOW;
+use openpb::nn::stats::DEFAULT_HISTOGRAM_BINS;
+use openpb::online::{DEFAULT_ONLINE_REPORT_EVERY, DEFAULT_ONLINE_WINDOW};
+use openpb::verify::DEFAULT_VERIFY_TOLERANCE;
 
 #[doc(hidden)]
 #[derive(Parser, Debug)]
 #[clap(author = "Dusten Knull <dakatk97@gmail.com>", version = "0.1", name = "Open Neural Network Benchmarker (ONNB)", about, long_about = None)]
+#[clap(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 pub struct Args {
-    /// JSON file with training and validation sets (required)
-    #[clap(short, long, value_parser)]
-    pub data: String,
+    /// Helper commands (training runs when no command is given)
+    #[clap(subcommand)]
+    pub command: Option<Command>,
+    /// JSON file with training and validation sets ("-" for stdin), repeat to merge several files. CSV data is given as the training file, then optional validation and test files (required)
+    #[clap(short, long, value_parser, required_unless_present = "train-images")]
+    pub data: Vec<String>,
+    /// Format of the data files: "json", "csv", or "idx" (optional)
+    #[clap(long, value_parser = parse_data_format, default_value = "json")]
+    pub data_format: DataFormat,
+    /// Name or (0-based) index of the CSV column with the output values, defaults to the last column (optional)
+    #[clap(long, value_parser)]
+    pub label_column: Option<String>,
+    /// IDX file with the training images, used instead of -d with --data-format idx
+    #[clap(long, value_parser, requires = "train-labels")]
+    pub train_images: Option<String>,
+    /// IDX file with the training labels (with --data-format idx)
+    #[clap(long, value_parser, requires = "train-images")]
+    pub train_labels: Option<String>,
+    /// IDX file with the validation images (with --data-format idx, optional)
+    #[clap(long, value_parser, requires = "test-labels")]
+    pub test_images: Option<String>,
+    /// IDX file with the validation labels (with --data-format idx, optional)
+    #[clap(long, value_parser, requires = "test-images")]
+    pub test_labels: Option<String>,
     /// JSON file with network structure and hyperparameters (required)
-    #[clap(short, long, value_parser)]
-    pub network: String,
-    /// JSON file where training results are stored (optional)
+    #[clap(short, long, value_parser, required = true)]
+    pub network: Option<String>,
+    /// JSON file where training results are stored ("-" for stdout) (optional)
     #[clap(short, long, value_parser)]
     pub output: Option<String>,
-    /// Number of threads spawned to train multiple samples of the same network setup (optional)
+    /// Template of the results file name used when no output file is given, with {date}, {time}, {timestamp}, {network_name}, and {seed} placeholders (optional)
+    #[clap(long, value_parser, conflicts_with = "output")]
+    pub output_template: Option<String>,
+    /// Encoding of the results file: json or msgpack (MessagePack, a compact binary format) (optional)
+    #[clap(long, value_parser = parse_output_format, default_value = "json")]
+    pub output_format: OutputFormat,
+    /// File where the network config, data, trained networks, encoder state, and results are bundled together, as a compressed archive when it ends in .tar.zst and JSON otherwise (optional)
+    #[clap(long, value_parser)]
+    pub bundle: Option<String>,
+    /// Number of worker threads in the pool used to train each run (optional)
     #[clap(short, long, value_parser, default_value_t = 1)]
     pub threads: usize,
+    /// CPU cores the worker threads are pinned to, one per thread in order, as IDs and inclusive ranges, e.g. "0-3,6" (optional)
+    #[clap(long, value_parser = parse_core_list)]
+    pub pin_cores: Option<CoreListArg>,
+    /// Number of threads in the global thread pool used for parallel work outside of training runs, e.g. batch size tuning and bulk inference, defaults to the number of CPUs (optional)
+    #[clap(long, value_parser)]
+    pub internal_threads: Option<usize>,
+    /// Number of samples of the same network setup to train, defaults to the number of threads (optional)
+    #[clap(short, long, value_parser)]
+    pub runs: Option<usize>,
     /// Flag that indicates whether or not to shuffle training data during each cycle (optional)
     #[clap(short, long, value_parser, default_value_t = false)]
     pub shuffle: bool,
-    /// Maximum number of epochs (iterations) until training loop finishes (required)
-    #[clap(short, long, value_parser)]
-    pub epochs: usize,
-    /// Maximum number of input vectors trained during each cycle (optional)
-    #[clap(short, long, value_parser)]
-    pub batch_size: Option<usize>,
+    /// Maximum number of epochs (full passes over the training set, one step per minibatch) until training loop finishes (required)
+    #[clap(short, long, value_parser, required = true)]
+    pub epochs: Option<usize>,
+    /// Maximum number of input vectors trained during each cycle, or "auto" to benchmark a few candidates and pick one (optional)
+    #[clap(short, long, value_parser = parse_batch_size)]
+    pub batch_size: Option<BatchSizeArg>,
+    /// Flag that indicates whether or not to skip the final minibatch when it's smaller than the batch size (optional)
+    #[clap(long, value_parser, default_value_t = false)]
+    pub drop_last: bool,
+    /// How minibatch samples are chosen: "sequential", "balanced" (drawn with probability inversely proportional to class frequency), or "weighted" (drawn with probability proportional to the data's train_weights) (optional)
+    #[clap(long, value_parser = Sampler::from_name, default_value = "sequential")]
+    pub sampler: Sampler,
+    /// Flag that indicates whether or not to record a per-phase timing breakdown of training (optional)
+    #[clap(long, value_parser, default_value_t = false)]
+    pub profile: bool,
+    /// Number of epochs between recordings of per-layer weight and gradient statistics (optional)
+    #[clap(long, value_parser)]
+    pub stats_every: Option<usize>,
+    /// Number of epochs between weight snapshots, reported as the per-layer distance between consecutive snapshots (optional)
+    #[clap(long, value_parser)]
+    pub snapshot_every: Option<usize>,
+    /// Number of epochs between per-layer weight and bias histograms saved with the results (optional)
+    #[clap(long, value_parser)]
+    pub histogram_every: Option<usize>,
+    /// Number of equal-width bins of each weight histogram (optional)
+    #[clap(long, value_parser, default_value_t = DEFAULT_HISTOGRAM_BINS, requires = "histogram-every")]
+    pub histogram_bins: usize,
+    /// Directory where each run's weight histograms are saved as run_<id>.json instead of in the results (optional)
+    #[clap(long, value_parser, requires = "histogram-every")]
+    pub histogram_dir: Option<String>,
+    /// Flag that indicates whether or not to record the largest global and per-layer gradient norms of each epoch in the results history (optional)
+    #[clap(long, value_parser, default_value_t = false)]
+    pub gradient_norms: bool,
+    /// Largest global gradient norm a training step can have before training halts with an error, implies --gradient-norms (optional)
+    #[clap(long, value_parser)]
+    pub max_gradient_norm: Option<f64>,
+    /// Largest factor the training loss can grow by over the previous --divergence-window epochs before a run is stopped and marked as diverged in the results (optional)
+    #[clap(long, value_parser)]
+    pub divergence_factor: Option<f64>,
+    /// Number of previous epochs the training loss is compared against for --divergence-factor (optional)
+    #[clap(long, value_parser, default_value_t = DEFAULT_DIVERGENCE_WINDOW, requires = "divergence-factor")]
+    pub divergence_window: usize,
+    /// Number of highest raw output values (and their classes) saved for each validation sample (optional)
+    #[clap(long, value_parser)]
+    pub top_n: Option<usize>,
+    /// Number of validation samples whose per-layer activation percentiles are saved after training (optional)
+    #[clap(long, value_parser)]
+    pub activation_samples: Option<usize>,
+    /// Largest absolute activation a hidden neuron can have across the whole validation set to be reported as dead (optional)
+    #[clap(long, value_parser)]
+    pub dead_neurons: Option<f64>,
+    /// Number of epochs between warnings about dead neurons during training (optional)
+    #[clap(long, value_parser, requires = "dead-neurons")]
+    pub dead_warn_every: Option<usize>,
+    /// Flag that indicates whether or not to fit an output temperature to the validation set after training (optional)
+    #[clap(long, value_parser, default_value_t = false)]
+    pub calibrate: bool,
+    /// Flag that indicates whether or not to save the undecoded output layer values of each validation sample (optional)
+    #[clap(long, value_parser, default_value_t = false)]
+    pub raw_output: bool,
+    /// Highest standard deviation (relative to each input feature's) of the Gaussian noise validation inputs are corrupted with to measure robustness (optional)
+    #[clap(long, value_parser)]
+    pub noise_max: Option<f64>,
+    /// Number of evenly spaced noise levels between zero and the highest noise level (optional)
+    #[clap(long, value_parser, default_value_t = 5, requires = "noise-max")]
+    pub noise_steps: usize,
+    /// Number of times the validation predictions are resampled (with replacement) to estimate a confidence interval for each run's metric (optional)
+    #[clap(long, value_parser)]
+    pub bootstrap: Option<usize>,
+    /// Confidence level of the bootstrap interval, between 0 and 1 (optional)
+    #[clap(long, value_parser, default_value_t = DEFAULT_CONFIDENCE, requires = "bootstrap")]
+    pub confidence: f64,
+    /// Fraction of the validation set randomly sampled to check the early stopping metric each epoch (optional)
+    #[clap(long, value_parser)]
+    pub validation_fraction: Option<f64>,
+    /// Number of epochs between early stopping checks against the full validation set when sampling (optional)
+    #[clap(
+        long,
+        value_parser,
+        default_value_t = 10,
+        requires = "validation-fraction"
+    )]
+    pub full_validation_every: usize,
+    /// Flag that indicates whether or not to train for every epoch instead of stopping once the metric is satisfied (optional)
+    #[clap(long, value_parser, default_value_t = false)]
+    pub no_early_stopping: bool,
+    /// Largest relative change randomly applied to each run's learning rate, e.g. 0.2 for a factor in [0.8, 1.2] (optional)
+    #[clap(long, value_parser)]
+    pub jitter: Option<f64>,
+    /// Seed of the first run's random number generator, chosen randomly when omitted (optional)
+    #[clap(long, value_parser)]
+    pub seed: Option<u64>,
+    /// Number of random initializations trained one after another within each run, keeping the one with the best validation metric (optional)
+    #[clap(long, value_parser, default_value_t = 1)]
+    pub restarts: usize,
+    /// Directory where a timestamped run directory with the configs, seed, per-epoch logs, checkpoints, and results is created (optional)
+    #[clap(long, value_parser)]
+    pub run_dir: Option<String>,
+    /// Number of epochs between network checkpoints saved in the run directory (optional)
+    #[clap(long, value_parser, requires = "run-dir")]
+    pub checkpoint_every: Option<usize>,
+    /// Number of epochs between console lines with each run's training loss and validation metric, also applied to the run directory's epoch logs (optional)
+    #[clap(long, value_parser)]
+    pub log_every: Option<usize>,
+    /// Address per-epoch progress events are streamed to as JSON: ws://host:port/path (or wss://) for a WebSocket, or tcp://host:port or a Unix socket path for newline-delimited JSON (optional)
+    #[clap(long, value_parser)]
+    pub progress: Option<String>,
+    /// Shell command (given OPENPB_RUN, OPENPB_EPOCH, OPENPB_LOSS, OPENPB_METRIC, and OPENPB_ELAPSED_TIME, plus the same values as JSON on stdin) or http(s):// webhook (POSTed the JSON) invoked at the end of every epoch (optional)
+    #[clap(long, value_parser)]
+    pub epoch_hook: Option<String>,
+    /// Flag that indicates whether or not to write a markdown model card (architecture, dataset, hyperparameters, results, and seed) next to the results file (optional)
+    #[clap(long, value_parser, default_value_t = false)]
+    pub model_card: bool,
+    /// Results or network file whose (first run's) trained weights, and optimizer state if saved, training continues from (optional)
+    #[clap(long, value_parser)]
+    pub resume: Option<String>,
+    /// Flag that indicates whether or not to save the optimizer's internal state (e.g. Adam moments) with the results, so training can be resumed exactly (optional)
+    #[clap(long, value_parser, default_value_t = false)]
+    pub save_optimizer_state: bool,
+    /// Flag that indicates whether or not to write a sha256sum-compatible checksum (<results>.sha256) next to the results file (optional)
+    #[clap(long, value_parser, default_value_t = false)]
+    pub checksum: bool,
+    /// Flag that indicates whether or not to only print the network summary (layers, parameters, and estimated FLOPs per forward pass) without training (optional)
+    #[clap(long, value_parser, default_value_t = false)]
+    pub summary_only: bool,
+}
+
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy)]
+pub enum BatchSizeArg {
+    /// Benchmark candidate batch sizes before training
+    Auto,
+    /// Fixed number of samples in each minibatch
+    Fixed(usize),
+}
+
+#[doc(hidden)]
+fn parse_batch_size(value: &str) -> Result<BatchSizeArg, OpenPbError> {
+    if value.eq_ignore_ascii_case("auto") {
+        return Ok(BatchSizeArg::Auto);
+    }
+    match value.parse::<usize>() {
+        Ok(batch_size) => Ok(BatchSizeArg::Fixed(batch_size)),
+        Err(_) => Err(format!("\"{value}\" isn't a number or \"auto\"").into()),
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug, Clone)]
+pub struct CoreListArg(pub Vec<usize>);
+
+#[doc(hidden)]
+fn parse_core_list(value: &str) -> Result<CoreListArg, OpenPbError> {
+    affinity::parse_cores(value).map(CoreListArg)
+}
+
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataFormat {
+    /// Data JSON files (see `DataDe`)
+    Json,
+    /// CSV files with a header row
+    Csv,
+    /// IDX image and label files (e.g. MNIST)
+    Idx,
+}
+
+#[doc(hidden)]
+fn parse_data_format(value: &str) -> Result<DataFormat, OpenPbError> {
+    match value.to_lowercase().as_str() {
+        "json" => Ok(DataFormat::Json),
+        "csv" => Ok(DataFormat::Csv),
+        "idx" => Ok(DataFormat::Idx),
+        _ => Err(format!("Unknown data format \"{value}\", expected json, csv, or idx").into()),
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigKind {
+    /// Network config files (see `NetworkDe`)
+    Network,
+    /// Data JSON files (see `DataDe`)
+    Data,
+}
+
+#[doc(hidden)]
+fn parse_output_format(value: &str) -> Result<OutputFormat, OpenPbError> {
+    match value.to_lowercase().as_str() {
+        "json" => Ok(OutputFormat::Json),
+        "msgpack" | "messagepack" => Ok(OutputFormat::MessagePack),
+        _ => Err(format!("Unknown output format \"{value}\", expected json or msgpack").into()),
+    }
+}
+
+#[doc(hidden)]
+fn parse_config_kind(value: &str) -> Result<ConfigKind, OpenPbError> {
+    match value.to_lowercase().as_str() {
+        "network" => Ok(ConfigKind::Network),
+        "data" => Ok(ConfigKind::Data),
+        _ => Err(format!("Unknown config kind \"{value}\", expected network or data").into()),
+    }
+}
+
+#[doc(hidden)]
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Download MNIST and convert it into a training data JSON file
+    FetchMnist {
+        /// Directory where the downloaded IDX files are cached (optional)
+        #[clap(long, value_parser, default_value = "data/mnist")]
+        cache_dir: String,
+        /// JSON file where the converted training and validation sets are stored (optional)
+        #[clap(short, long, value_parser, default_value = "mnist_data.json")]
+        output: String,
+        /// Maximum number of training samples to keep, defaults to all 60000 (optional)
+        #[clap(long, value_parser)]
+        train_samples: Option<usize>,
+        /// Maximum number of validation samples to keep, defaults to all 10000 (optional)
+        #[clap(long, value_parser)]
+        test_samples: Option<usize>,
+        /// URL of the directory with the gzipped IDX files (optional)
+        #[clap(long, value_parser, default_value = openpb::file_io::mnist::MNIST_URL)]
+        url: String,
+    },
+    /// Train every trial of a hyperparameter sweep config (JSON or YAML)
+    Sweep {
+        /// Sweep config file listing the parameters, ranges, strategy, and trial budget (required)
+        #[clap(value_parser)]
+        config: String,
+    },
+    /// Search learning rates, layer sizes, dropout, and batch sizes, ranking every configuration
+    Search {
+        /// Search spec file listing the hyperparameter ranges, strategy, and trial budget (required)
+        #[clap(value_parser)]
+        spec: String,
+    },
+    /// Sweep exponentially increasing learning rates to find a good starting value
+    LrFind {
+        /// JSON file with training and validation sets (required)
+        #[clap(short, long, value_parser)]
+        data: String,
+        /// JSON file with network structure and hyperparameters (required)
+        #[clap(short, long, value_parser)]
+        network: String,
+        /// JSON file where the recorded learning rates and losses are stored (optional)
+        #[clap(short, long, value_parser)]
+        output: Option<String>,
+        /// Learning rate of the first training step (optional)
+        #[clap(long, value_parser, default_value_t = 1e-6)]
+        min_lr: f64,
+        /// Learning rate of the last training step (optional)
+        #[clap(long, value_parser, default_value_t = 10.0)]
+        max_lr: f64,
+        /// Number of training steps (minibatches) in the sweep (optional)
+        #[clap(long, value_parser, default_value_t = 100)]
+        steps: usize,
+        /// Maximum number of input vectors trained during each step (optional)
+        #[clap(short, long, value_parser)]
+        batch_size: Option<usize>,
+        /// Flag that indicates whether or not to shuffle training data (optional)
+        #[clap(short, long, value_parser, default_value_t = false)]
+        shuffle: bool,
+        /// Seed of the network's initial weights and the shuffling, so sweeps are reproducible (optional)
+        #[clap(long, value_parser)]
+        seed: Option<u64>,
+    },
+    /// Predict the output of a trained network for a single sample, every sample of a JSON file (--inputs, printed or written to --output as JSON), or every row of a CSV file (--data, written to --output as CSV)
+    Predict {
+        /// Model or results file (JSON or MessagePack) with the trained network (required)
+        #[clap(short, long, value_parser)]
+        model: String,
+        /// Comma-separated input values, read as a JSON array from stdin when omitted (optional)
+        #[clap(short, long, value_parser, allow_hyphen_values = true)]
+        input: Option<String>,
+        /// JSON file with an array of input rows (one per sample), predicted in chunks (optional)
+        #[clap(long, value_parser, conflicts_with = "input")]
+        inputs: Option<String>,
+        /// Network config whose encoder decodes the output, defaults to the index of the highest output (optional)
+        #[clap(short, long, value_parser)]
+        network: Option<String>,
+        /// Flag that indicates whether or not to only print the undecoded outputs as a JSON array (optional)
+        #[clap(
+            long,
+            value_parser,
+            default_value_t = false,
+            conflicts_with = "network"
+        )]
+        raw: bool,
+        /// JSON file where the decoded predictions and undecoded outputs of every sample are stored, instead of printing them (optional)
+        #[clap(short, long, value_parser)]
+        output: Option<String>,
+        /// CSV file with a header row and one input value per column, streamed in chunks with the predictions written to --output as CSV (optional)
+        #[clap(
+            short,
+            long,
+            value_parser,
+            conflicts_with_all = &["input", "inputs", "raw"],
+            requires = "output"
+        )]
+        data: Option<String>,
+        /// Number of samples read, predicted, and written at once with --data (optional)
+        #[clap(long, value_parser, default_value_t = DEFAULT_INFERENCE_CHUNK_SIZE, requires = "data")]
+        chunk_size: usize,
+        /// Flag that indicates whether or not to predict several chunks at the same time with --data (optional)
+        #[clap(long, value_parser, default_value_t = false, requires = "data")]
+        parallel: bool,
+    },
+    /// Compute input-gradient magnitudes (saliency) of a trained network's predictions
+    Saliency {
+        /// Model or results file (JSON or MessagePack) with the trained network (required)
+        #[clap(short, long, value_parser)]
+        model: String,
+        /// JSON file whose validation set inputs are explained (required)
+        #[clap(short, long, value_parser)]
+        data: String,
+        /// Comma-separated indices of validation samples, defaults to every sample (optional)
+        #[clap(short, long, value_parser, use_value_delimiter = true)]
+        samples: Vec<usize>,
+        /// JSON file where the input-gradient magnitudes are stored (optional)
+        #[clap(short, long, value_parser)]
+        output: Option<String>,
+    },
+    /// Predict a trained 2-feature network over a grid covering the data's input range (decision boundaries)
+    DecisionBoundary {
+        /// Model or results file (JSON or MessagePack) with the trained network (required)
+        #[clap(short, long, value_parser)]
+        model: String,
+        /// Data file whose training and validation inputs the grid covers (required)
+        #[clap(short, long, value_parser)]
+        data: String,
+        /// Network config whose encoder decodes the outputs, defaults to the index of the highest output (optional)
+        #[clap(short, long, value_parser)]
+        network: Option<String>,
+        /// Number of grid points along each axis (optional)
+        #[clap(short, long, value_parser, default_value_t = 100)]
+        resolution: usize,
+        /// File where the grid's predictions are stored, as CSV when it ends in .csv and JSON otherwise (required)
+        #[clap(short, long, value_parser)]
+        output: String,
+    },
+    /// Learn from samples streamed to stdin as NDJSON ({"input": [...], "output": [...]} per line), reporting rolling metrics to stdout
+    Online {
+        /// JSON file with network structure and hyperparameters (required)
+        #[clap(short, long, value_parser)]
+        network: String,
+        /// Results, network, or online checkpoint file whose trained weights, and optimizer state if saved, learning continues from (optional)
+        #[clap(long, value_parser)]
+        resume: Option<String>,
+        /// JSON file where the network and optimizer state are checkpointed (optional)
+        #[clap(short, long, value_parser, default_value = "output/online.json")]
+        output: String,
+        /// Number of samples in each update of the weights (optional)
+        #[clap(short, long, value_parser, default_value_t = 1)]
+        batch_size: usize,
+        /// Number of most recent samples the rolling loss and metric cover (optional)
+        #[clap(short, long, value_parser, default_value_t = DEFAULT_ONLINE_WINDOW)]
+        window: usize,
+        /// Number of samples between rolling metric reports (optional)
+        #[clap(long, value_parser, default_value_t = DEFAULT_ONLINE_REPORT_EVERY)]
+        report_every: usize,
+        /// Number of samples between checkpoints, only saved once the stream ends when omitted (optional)
+        #[clap(long, value_parser)]
+        checkpoint_every: Option<usize>,
+        /// Seed of the network's random number generator (optional)
+        #[clap(long, value_parser)]
+        seed: Option<u64>,
+    },
+    /// Run as a service that trains jobs submitted over HTTP
+    Daemon {
+        /// Host and port the HTTP API listens on (optional)
+        #[clap(short, long, value_parser, default_value = "127.0.0.1:7878")]
+        address: String,
+        /// Number of jobs that are trained at the same time (optional)
+        #[clap(short, long, value_parser, default_value_t = 1)]
+        workers: usize,
+        /// Maximum number of threads each job trains its runs on, defaults to the number of CPUs (optional)
+        #[clap(long, value_parser)]
+        max_threads: Option<usize>,
+        /// Number of finished jobs whose status and results are kept before the oldest are evicted (optional)
+        #[clap(long, value_parser, default_value_t = DEFAULT_MAX_FINISHED_JOBS)]
+        keep_jobs: usize,
+    },
+    /// Train a seeded run directory's runs again and check that their metric scores match
+    Verify {
+        /// Run directory with the manifest, network config, and results (required)
+        #[clap(value_parser)]
+        run_dir: String,
+        /// Data files to train on, defaults to the ones in the manifest (optional)
+        #[clap(short, long, value_parser)]
+        data: Vec<String>,
+        /// Largest difference between recorded and reproduced metric scores that still matches (optional)
+        #[clap(short, long, value_parser, default_value_t = DEFAULT_VERIFY_TOLERANCE)]
+        tolerance: f32,
+    },
+    /// Print the JSON Schema of a config file format, for validation and autocompletion in editors
+    Schema {
+        /// Config format: network or data (required)
+        #[clap(value_parser = parse_config_kind)]
+        kind: ConfigKind,
+        /// File the schema is written to instead of stdout (optional)
+        #[clap(short, long, value_parser)]
+        output: Option<String>,
+    },
+    /// Compare two results files and highlight regressions
+    Compare {
+        /// Results file (JSON or MessagePack) of the baseline benchmark (required)
+        #[clap(value_parser)]
+        baseline: String,
+        /// Results file (JSON or MessagePack) of the new benchmark (required)
+        #[clap(value_parser)]
+        results: String,
+        /// SVG file where both benchmarks' loss and metric curves are overlaid, e.g. to compare optimizers (optional)
+        #[clap(short, long, value_parser)]
+        plot: Option<String>,
+    },
 }
@@ -0,0 +1,670 @@
+use crate::error::OpenPbError;
+use crate::file_io::job_de::JobDe;
+use crate::file_io::json_de::NetworkDataDe;
+use crate::file_io::results_ser::ThreadedResultsSer;
+use crate::nn::batches::{BatchConfig, Sampler};
+use crate::nn::cancellation::CancellationToken;
+use crate::nn::history::EpochLog;
+use crate::nn::perceptron::Perceptron;
+use crate::trainer::{self, TrainerOptions};
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, VecDeque};
+use std::io::{prelude::*, BufReader};
+use std::net::{TcpListener, TcpStream};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Largest request body the daemon accepts (256 MiB)
+const MAX_BODY_SIZE: usize = 256 << 20;
+
+/// Longest request or header line the daemon accepts, in bytes
+const MAX_LINE_LENGTH: usize = 8 << 10;
+
+/// Most headers the daemon accepts in a request
+const MAX_HEADERS: usize = 100;
+
+/// Number of connections answered at the same time. Further
+/// connections wait to be accepted until one of them is done
+const MAX_CONNECTIONS: usize = 32;
+
+/// Seconds a client may stall while sending its request or reading
+/// the response before the connection is dropped, so idle or slow
+/// clients can't hold on to a connection handler indefinitely
+const CONNECTION_TIMEOUT_SECS: u64 = 30;
+
+/// Default number of finished, failed, or cancelled jobs whose
+/// status and results are kept before the oldest are evicted
+pub const DEFAULT_MAX_FINISHED_JOBS: usize = 100;
+
+/// Lifecycle of a submitted training job
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum JobState {
+    /// Waiting for a free worker
+    Queued,
+
+    /// Being trained by a worker
+    Running,
+
+    /// Trained, results are available
+    Finished,
+
+    /// Stopped by an error (e.g. an invalid config)
+    Failed,
+
+    /// Cancelled before it finished (partial results are kept when it had started)
+    Cancelled,
+}
+
+/// Status of a single job, returned by the status endpoints
+#[derive(Serialize, Debug, Clone)]
+pub struct JobStatus {
+    /// ID of the job (the order it was submitted in)
+    pub id: usize,
+
+    /// Where the job is in its lifecycle
+    pub state: JobState,
+
+    /// Time the job was submitted (RFC 3339)
+    pub submitted: String,
+
+    /// Time a worker started training the job (RFC 3339)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started: Option<String>,
+
+    /// Time the job finished, failed, or was cancelled (RFC 3339)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished: Option<String>,
+
+    /// Latest epoch finished by any of the job's runs
+    pub epoch: usize,
+
+    /// Maximum number of epochs of each run
+    pub epochs: usize,
+
+    /// Reason the job failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Submitted job and everything recorded about it
+struct Job {
+    /// Current status
+    status: JobStatus,
+
+    /// Submitted config (taken by the worker that trains it)
+    job_de: Option<JobDe>,
+
+    /// Training results, serialized once the job is over
+    results: Option<Value>,
+
+    /// Token that stops the job's runs when it's cancelled
+    token: CancellationToken,
+
+    /// Latest epoch finished by any of the job's runs (updated by the runs)
+    epoch: Arc<AtomicUsize>,
+}
+
+/// Every job the daemon still knows about
+#[derive(Default)]
+struct Jobs {
+    /// Jobs that haven't been evicted, indexed by ID
+    by_id: BTreeMap<usize, Job>,
+
+    /// ID of the next submitted job
+    next_id: usize,
+
+    /// IDs of the jobs that are over, in the order they ended
+    finished: VecDeque<usize>,
+}
+
+impl Jobs {
+    /// Record that a job is over, then evict the jobs that
+    /// ended first until at most `max_finished` are left
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - ID of the job that's over
+    /// * `max_finished` - Number of finished jobs that are kept
+    fn finish(&mut self, id: usize, max_finished: usize) {
+        self.finished.push_back(id);
+        while self.finished.len() > max_finished {
+            if let Some(evicted) = self.finished.pop_front() {
+                self.by_id.remove(&evicted);
+            }
+        }
+    }
+}
+
+/// HTTP response sent back to a client
+struct Response {
+    /// Status code
+    code: u16,
+
+    /// JSON body
+    body: Value,
+}
+
+impl Response {
+    fn ok(body: Value) -> Response {
+        Response { code: 200, body }
+    }
+
+    fn error(code: u16, message: &str) -> Response {
+        Response {
+            code,
+            body: json!({ "error": message }),
+        }
+    }
+}
+
+/// Long-running training service. Jobs (network config, data, and training
+/// options) are submitted over HTTP, queued, and trained one at a time by
+/// each worker of a fixed pool. Status and results are kept in memory
+/// until too many later jobs have ended (see `with_max_finished`)
+pub struct Daemon {
+    /// Every job that hasn't been evicted
+    jobs: Mutex<Jobs>,
+
+    /// IDs of the jobs waiting for a worker, oldest first
+    queue: Mutex<VecDeque<usize>>,
+
+    /// Signalled whenever a job is queued
+    queued: Condvar,
+
+    /// Maximum number of threads each job trains its runs on
+    max_threads: usize,
+
+    /// Number of jobs that are over whose status and results are kept
+    max_finished: usize,
+}
+
+impl Daemon {
+    /// # Arguments
+    ///
+    /// * `max_threads` - Maximum number of threads each job trains its runs on
+    pub fn new(max_threads: usize) -> Daemon {
+        Daemon {
+            jobs: Mutex::new(Jobs::default()),
+            queue: Mutex::new(VecDeque::new()),
+            queued: Condvar::new(),
+            max_threads: max_threads.max(1),
+            max_finished: DEFAULT_MAX_FINISHED_JOBS,
+        }
+    }
+
+    /// Set the number of finished, failed, or cancelled jobs that are kept
+    /// (at least 1). Once more jobs are over, the ones that ended first are forgotten
+    ///
+    /// # Arguments
+    ///
+    /// * `max_finished` - Number of jobs that are over whose status and results are kept
+    pub fn with_max_finished(mut self, max_finished: usize) -> Daemon {
+        self.max_finished = max_finished.max(1);
+        self
+    }
+
+    /// Start the worker pool, then answer HTTP requests until the process exits
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - Host and port to listen on (e.g. `127.0.0.1:7878`)
+    /// * `workers` - Number of jobs that are trained at the same time
+    pub fn serve(self: Arc<Self>, address: &str, workers: usize) -> Result<(), OpenPbError> {
+        let listener: TcpListener = match TcpListener::bind(address) {
+            Ok(listener) => listener,
+            Err(error) => return Err(format!("Failed to listen on {address}: {error}").into()),
+        };
+        for _ in 0..workers.max(1) {
+            let daemon: Arc<Daemon> = self.clone();
+            thread::spawn(move || daemon.work());
+        }
+        let (sender, receiver): (SyncSender<TcpStream>, Receiver<TcpStream>) =
+            mpsc::sync_channel(0);
+        let receiver: Arc<Mutex<Receiver<TcpStream>>> = Arc::new(Mutex::new(receiver));
+        for _ in 0..MAX_CONNECTIONS {
+            let daemon: Arc<Daemon> = self.clone();
+            let receiver: Arc<Mutex<Receiver<TcpStream>>> = receiver.clone();
+            thread::spawn(move || daemon.answer(&receiver));
+        }
+        eprintln!(
+            "Listening on http://{address} with {} worker(s)",
+            workers.max(1)
+        );
+
+        for stream in listener.incoming() {
+            let stream: TcpStream = match stream {
+                Ok(stream) => stream,
+                Err(error) => {
+                    eprintln!("Warning: failed to accept connection: {error}");
+                    continue;
+                }
+            };
+            // Blocks until a connection handler is free
+            if sender.send(stream).is_err() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Queue a job for training, returning its status
+    ///
+    /// # Arguments
+    ///
+    /// * `job_de` - Network config, data, and training options
+    pub fn submit(&self, job_de: JobDe) -> JobStatus {
+        let mut jobs = self.jobs.lock().unwrap();
+        let id: usize = jobs.next_id;
+        jobs.next_id += 1;
+        let status = JobStatus {
+            id,
+            state: JobState::Queued,
+            submitted: Utc::now().to_rfc3339(),
+            started: None,
+            finished: None,
+            epoch: 0,
+            epochs: job_de.training.epochs,
+            error: None,
+        };
+        jobs.by_id.insert(
+            id,
+            Job {
+                status: status.clone(),
+                job_de: Some(job_de),
+                results: None,
+                token: CancellationToken::new(),
+                epoch: Arc::new(AtomicUsize::new(0)),
+            },
+        );
+        drop(jobs);
+
+        self.queue.lock().unwrap().push_back(status.id);
+        self.queued.notify_one();
+        status
+    }
+
+    /// Status of every job that hasn't been evicted, in the order they were submitted
+    pub fn statuses(&self) -> Vec<JobStatus> {
+        let jobs = self.jobs.lock().unwrap();
+        jobs.by_id.values().map(Job::current_status).collect()
+    }
+
+    /// Status of a single job (`None` when there's no job with the ID, or it was evicted)
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - ID of the job
+    pub fn status(&self, id: usize) -> Option<JobStatus> {
+        let jobs = self.jobs.lock().unwrap();
+        jobs.by_id.get(&id).map(Job::current_status)
+    }
+
+    /// Stop a job. Queued jobs are never trained, and running
+    /// jobs stop at the end of their runs' current epochs
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - ID of the job
+    pub fn cancel(&self, id: usize) -> Option<JobStatus> {
+        let mut jobs = self.jobs.lock().unwrap();
+        let job: &mut Job = jobs.by_id.get_mut(&id)?;
+        job.token.cancel();
+        let ended: bool = job.status.state == JobState::Queued;
+        if ended {
+            job.status.state = JobState::Cancelled;
+            job.status.finished = Some(Utc::now().to_rfc3339());
+            job.job_de = None;
+        }
+        let status: JobStatus = job.current_status();
+        if ended {
+            jobs.finish(id, self.max_finished);
+        }
+        Some(status)
+    }
+
+    /// Train queued jobs one at a time, until the process exits. Each
+    /// worker blocks while the queue is empty
+    fn work(&self) {
+        loop {
+            let id: usize = {
+                let mut queue = self.queue.lock().unwrap();
+                loop {
+                    match queue.pop_front() {
+                        Some(id) => break id,
+                        None => queue = self.queued.wait(queue).unwrap(),
+                    }
+                }
+            };
+
+            // Jobs cancelled while queued have already given up their config
+            let (job_de, token, epoch) = {
+                let mut jobs = self.jobs.lock().unwrap();
+                let job: &mut Job = match jobs.by_id.get_mut(&id) {
+                    Some(job) => job,
+                    None => continue,
+                };
+                let job_de: JobDe = match job.job_de.take() {
+                    Some(job_de) => job_de,
+                    None => continue,
+                };
+                job.status.state = JobState::Running;
+                job.status.started = Some(Utc::now().to_rfc3339());
+                (job_de, job.token.clone(), job.epoch.clone())
+            };
+            eprintln!("Job {id}: training");
+
+            // A panic in one job shouldn't take its worker down with it
+            let outcome: Result<ThreadedResultsSer, OpenPbError> =
+                match panic::catch_unwind(AssertUnwindSafe(|| self.train(&job_de, &token, &epoch)))
+                {
+                    Ok(outcome) => outcome,
+                    Err(_) => Err("Training panicked".into()),
+                };
+
+            let mut jobs = self.jobs.lock().unwrap();
+            let job: &mut Job = match jobs.by_id.get_mut(&id) {
+                Some(job) => job,
+                None => continue,
+            };
+            job.status.finished = Some(Utc::now().to_rfc3339());
+            match outcome {
+                Ok(results) => {
+                    job.status.state = if token.is_cancelled() {
+                        JobState::Cancelled
+                    } else {
+                        JobState::Finished
+                    };
+                    job.results = match serde_json::to_value(&results) {
+                        Ok(results) => Some(results),
+                        Err(error) => {
+                            job.status.state = JobState::Failed;
+                            job.status.error = Some(error.to_string());
+                            None
+                        }
+                    };
+                }
+                Err(error) => {
+                    job.status.state = JobState::Failed;
+                    job.status.error = Some(error.to_string());
+                }
+            }
+            eprintln!("Job {id}: {:?}", job.status.state);
+            jobs.finish(id, self.max_finished);
+        }
+    }
+
+    /// Train every run of a single job
+    ///
+    /// # Arguments
+    ///
+    /// * `job_de` - Network config, data, and training options
+    /// * `token` - Token that stops the runs when the job is cancelled
+    /// * `epoch` - Updated with the latest epoch finished by any run
+    fn train(
+        &self,
+        job_de: &JobDe,
+        token: &CancellationToken,
+        epoch: &AtomicUsize,
+    ) -> Result<ThreadedResultsSer, OpenPbError> {
+        let network_data_de: NetworkDataDe = job_de.network_data()?;
+        let options = TrainerOptions {
+            threads: job_de.training.threads.clamp(1, self.max_threads),
+            // Jobs train at the same time, so pinning them would stack them on the same cores
+            pin_cores: None,
+            runs: job_de.training.runs,
+            epochs: job_de.training.epochs,
+            batch_config: BatchConfig {
+                batch_size: job_de.training.batch_size,
+                shuffle: job_de.training.shuffle,
+                drop_last: false,
+                sampler: Sampler::Sequential,
+            },
+            auto_batch_size: false,
+            profile: false,
+            stats_interval: None,
+            snapshot_interval: None,
+            histograms: None,
+            top_n: None,
+            activation_samples: None,
+            dead_neurons: None,
+            calibrate: false,
+            raw_output: false,
+            robustness: None,
+            bootstrap: None,
+            seed: None,
+            restarts: 1,
+            validation_sampling: None,
+            early_stopping: true,
+            save_optimizer_state: false,
+            jitter: None,
+            log_every: job_de.training.log_every,
+            gradient_norms: None,
+            divergence: job_de.training.divergence,
+        };
+        let results: ThreadedResultsSer = trainer::train_from_json_with_callback(
+            &network_data_de,
+            &options,
+            &|_, epoch_log: &EpochLog, _: &Perceptron| {
+                epoch.fetch_max(epoch_log.epoch, Ordering::Relaxed);
+                !token.is_cancelled()
+            },
+        )?;
+        Ok(results.with_cancelled(token.is_cancelled()))
+    }
+
+    /// Answer connections handed over by the listener one at a time,
+    /// until the listener stops
+    ///
+    /// # Arguments
+    ///
+    /// * `receiver` - Accepted connections, shared by every connection handler
+    fn answer(&self, receiver: &Mutex<Receiver<TcpStream>>) {
+        loop {
+            let stream: TcpStream = match receiver.lock().unwrap().recv() {
+                Ok(stream) => stream,
+                Err(_) => return,
+            };
+            // A panic while answering shouldn't shrink the pool of handlers
+            let outcome: Result<(), OpenPbError> =
+                match panic::catch_unwind(AssertUnwindSafe(|| self.handle_connection(stream))) {
+                    Ok(outcome) => outcome,
+                    Err(_) => Err("Answering panicked".into()),
+                };
+            if let Err(error) = outcome {
+                eprintln!("Warning: failed to answer request: {error}");
+            }
+        }
+    }
+
+    /// Read a single request from a connection and write back the response
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - Connection to the client
+    fn handle_connection(&self, mut stream: TcpStream) -> Result<(), OpenPbError> {
+        let timeout: Option<Duration> = Some(Duration::from_secs(CONNECTION_TIMEOUT_SECS));
+        if let Err(error) = stream
+            .set_read_timeout(timeout)
+            .and_then(|_| stream.set_write_timeout(timeout))
+        {
+            return Err(format!("Failed to set connection timeout: {error}").into());
+        }
+        let response: Response = match read_request(&mut stream) {
+            Ok((method, path, body)) => self.route(&method, &path, &body),
+            Err(error) => Response::error(400, &error.to_string()),
+        };
+        write_response(&mut stream, &response)
+    }
+
+    /// Answer a request
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - HTTP method (e.g. `GET`)
+    /// * `path` - Requested path, without any query string
+    /// * `body` - Body of the request
+    fn route(&self, method: &str, path: &str, body: &str) -> Response {
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        match (method, segments.as_slice()) {
+            ("GET", ["jobs"]) => Response::ok(json!(self.statuses())),
+            ("POST", ["jobs"]) => match JobDe::from_json(body) {
+                Ok(job_de) => Response {
+                    code: 202,
+                    body: json!(self.submit(job_de)),
+                },
+                Err(error) => Response::error(400, &error.to_string()),
+            },
+            ("GET", ["jobs", id]) => match id.parse().ok().and_then(|id| self.status(id)) {
+                Some(status) => Response::ok(json!(status)),
+                None => Response::error(404, "No such job"),
+            },
+            ("DELETE", ["jobs", id]) => match id.parse().ok().and_then(|id| self.cancel(id)) {
+                Some(status) => Response::ok(json!(status)),
+                None => Response::error(404, "No such job"),
+            },
+            ("GET", ["jobs", id, "results"]) => {
+                let jobs = self.jobs.lock().unwrap();
+                match id.parse::<usize>().ok().and_then(|id| jobs.by_id.get(&id)) {
+                    Some(Job {
+                        results: Some(results),
+                        ..
+                    }) => Response::ok(results.clone()),
+                    Some(job) => Response::error(
+                        409,
+                        &format!("Job is {:?}, no results", job.current_status().state),
+                    ),
+                    None => Response::error(404, "No such job"),
+                }
+            }
+            (_, ["jobs"]) | (_, ["jobs", _]) | (_, ["jobs", _, "results"]) => {
+                Response::error(405, "Method not allowed")
+            }
+            _ => Response::error(404, "Not found"),
+        }
+    }
+}
+
+impl Job {
+    /// Status, including the latest epoch reported by the job's runs
+    fn current_status(&self) -> JobStatus {
+        JobStatus {
+            epoch: self.epoch.load(Ordering::Relaxed),
+            ..self.status.clone()
+        }
+    }
+}
+
+/// Read the method, path, and body of an HTTP/1.1 request
+///
+/// # Arguments
+///
+/// * `stream` - Connection to the client
+fn read_request(stream: &mut TcpStream) -> Result<(String, String, String), OpenPbError> {
+    let mut reader = BufReader::new(stream);
+    let request_line: String = match read_line(&mut reader) {
+        Ok(request_line) => request_line,
+        Err(error) => return Err(format!("Failed to read request: {error}").into()),
+    };
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => (method.to_string(), target),
+        _ => return Err("Malformed request line".into()),
+    };
+    let path: String = target.split('?').next().unwrap_or_default().to_string();
+
+    let mut content_length: usize = 0;
+    let mut header_count: usize = 0;
+    loop {
+        let header: String = match read_line(&mut reader) {
+            Ok(header) => header,
+            Err(error) => return Err(format!("Failed to read headers: {error}").into()),
+        };
+        let header: &str = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        header_count += 1;
+        if header_count > MAX_HEADERS {
+            return Err(format!("Request has more than {MAX_HEADERS} headers").into());
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = match value.trim().parse() {
+                    Ok(content_length) => content_length,
+                    Err(_) => return Err("Invalid Content-Length".into()),
+                };
+            }
+        }
+    }
+    if content_length > MAX_BODY_SIZE {
+        return Err(format!("Body is larger than {MAX_BODY_SIZE} bytes").into());
+    }
+
+    // The body grows as it arrives rather than being allocated up front,
+    // so a large Content-Length alone doesn't reserve any memory
+    let mut body: Vec<u8> = Vec::new();
+    if let Err(error) = reader.take(content_length as u64).read_to_end(&mut body) {
+        return Err(format!("Failed to read body: {error}").into());
+    }
+    if body.len() < content_length {
+        return Err("Body is shorter than its Content-Length".into());
+    }
+    match String::from_utf8(body) {
+        Ok(body) => Ok((method, path, body)),
+        Err(_) => Err("Body isn't valid UTF-8".into()),
+    }
+}
+
+/// Read a line of a request, failing if it's longer than `MAX_LINE_LENGTH`
+///
+/// # Arguments
+///
+/// * `reader` - Buffered connection to the client
+fn read_line(reader: &mut BufReader<&mut TcpStream>) -> Result<String, OpenPbError> {
+    let mut line = String::new();
+    if let Err(error) = reader.take(MAX_LINE_LENGTH as u64 + 1).read_line(&mut line) {
+        return Err(error.to_string().into());
+    }
+    if line.len() > MAX_LINE_LENGTH {
+        return Err(format!("Line is longer than {MAX_LINE_LENGTH} bytes").into());
+    }
+    Ok(line)
+}
+
+/// Write an HTTP/1.1 response with a JSON body, then close the connection
+///
+/// # Arguments
+///
+/// * `stream` - Connection to the client
+/// * `response` - Status code and body
+fn write_response(stream: &mut TcpStream, response: &Response) -> Result<(), OpenPbError> {
+    let reason: &str = match response.code {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        _ => "Internal Server Error",
+    };
+    let body: String = response.body.to_string();
+    let head: String = format!(
+        "HTTP/1.1 {} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.code,
+        body.len()
+    );
+    match stream
+        .write_all(head.as_bytes())
+        .and_then(|_| stream.write_all(body.as_bytes()))
+    {
+        Ok(_) => Ok(()),
+        Err(error) => Err(error.to_string().into()),
+    }
+}
@@ -0,0 +1,177 @@
+use crate::error::OpenPbError;
+use crate::nn::history::EpochLog;
+use crate::nn::perceptron::Perceptron;
+use serde::Serialize;
+use std::io::prelude::*;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// Seconds a webhook request may take (connecting, sending, and reading
+/// the response) before it's abandoned, so an unresponsive endpoint
+/// can't stall training indefinitely
+pub const WEBHOOK_TIMEOUT_SECS: u64 = 10;
+
+/// Summary of a finished epoch, passed to the hook as JSON
+#[derive(Serialize, Debug, Clone)]
+pub struct EpochHookPayload {
+    /// ID of the training run
+    pub run: usize,
+
+    /// Epoch number, loss, metric, and elapsed time
+    #[serde(flatten)]
+    pub epoch_log: EpochLog,
+}
+
+/// What is invoked at the end of each epoch
+#[derive(Debug, Clone)]
+enum HookTarget {
+    /// Shell command, which receives the epoch's summary as `OPENPB_*`
+    /// environment variables and as JSON on stdin
+    Command(String),
+
+    /// URL the epoch's summary is POSTed to as JSON, and the
+    /// HTTP agent (with a timeout) that sends every request
+    Webhook(String, ureq::Agent),
+}
+
+/// User-supplied shell command or webhook that is invoked at the end of
+/// every epoch, so external systems can react to training progress. The
+/// hook runs on the training thread, so slow hooks slow training down
+pub struct EpochHook {
+    /// Command or webhook that is invoked
+    target: HookTarget,
+
+    /// Set once the hook has failed (later failures aren't reported again)
+    failed: AtomicBool,
+}
+
+impl EpochHook {
+    /// # Arguments
+    ///
+    /// * `hook` - `http://` or `https://` URL of a webhook, or a shell command
+    pub fn new(hook: &str) -> EpochHook {
+        let target: HookTarget = if hook.starts_with("http://") || hook.starts_with("https://") {
+            let agent: ureq::Agent = ureq::AgentBuilder::new()
+                .timeout(Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+                .build();
+            HookTarget::Webhook(hook.to_string(), agent)
+        } else {
+            HookTarget::Command(hook.to_string())
+        };
+        EpochHook {
+            target,
+            failed: AtomicBool::new(false),
+        }
+    }
+
+    /// Invoke the hook with the summary of a single epoch. Failures are
+    /// reported as a warning (only the first time), and never interrupt training
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - Summary of the epoch that has just finished
+    pub fn call(&self, payload: &EpochHookPayload) {
+        let payload_json: String = match serde_json::to_string(payload) {
+            Ok(payload_json) => payload_json,
+            Err(error) => {
+                eprintln!("Warning: failed to serialize epoch hook payload: {error}");
+                return;
+            }
+        };
+        let result: Result<(), OpenPbError> = match &self.target {
+            HookTarget::Command(command) => run_command(command, payload, &payload_json),
+            HookTarget::Webhook(url, agent) => post_webhook(agent, url, &payload_json),
+        };
+        if let Err(error) = result {
+            if !self.failed.swap(true, Ordering::Relaxed) {
+                eprintln!("Warning: epoch hook failed: {error}");
+            }
+        }
+    }
+
+    /// Invoke the hook with the epoch's summary. Meant to be used as a
+    /// `trainer::EpochCallback`
+    ///
+    /// # Arguments
+    ///
+    /// * `run` - ID of the training run
+    /// * `epoch_log` - Summary of the epoch that has just finished
+    /// * `_network` - Network at the end of the epoch (unused)
+    pub fn on_epoch(&self, run: usize, epoch_log: &EpochLog, _network: &Perceptron) -> bool {
+        self.call(&EpochHookPayload {
+            run,
+            epoch_log: epoch_log.clone(),
+        });
+        true
+    }
+}
+
+/// Run a shell command with the epoch's summary in its environment
+/// and on its stdin, waiting for it to finish. The command's output
+/// goes to stderr, so it can't corrupt results written to stdout
+///
+/// # Arguments
+///
+/// * `command` - Shell command to run
+/// * `payload` - Summary of the epoch
+/// * `payload_json` - Summary of the epoch as JSON
+fn run_command(
+    command: &str,
+    payload: &EpochHookPayload,
+    payload_json: &str,
+) -> Result<(), OpenPbError> {
+    let mut shell: Command = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.args(["-c", command]);
+        shell
+    };
+    let mut child = match shell
+        .env("OPENPB_RUN", payload.run.to_string())
+        .env("OPENPB_EPOCH", payload.epoch_log.epoch.to_string())
+        .env("OPENPB_LOSS", payload.epoch_log.loss.to_string())
+        .env("OPENPB_METRIC", payload.epoch_log.metric.to_string())
+        .env(
+            "OPENPB_ELAPSED_TIME",
+            payload.epoch_log.elapsed_time.to_string(),
+        )
+        .stdin(Stdio::piped())
+        .stdout(std::io::stderr())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(error) => return Err(format!("failed to run \"{command}\": {error}").into()),
+    };
+
+    // Commands that don't read their stdin close it early, which isn't an error
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(payload_json.as_bytes());
+    }
+    match child.wait() {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(format!("\"{command}\" exited with {status}").into()),
+        Err(error) => Err(format!("failed to wait for \"{command}\": {error}").into()),
+    }
+}
+
+/// POST the epoch's summary to a webhook
+///
+/// # Arguments
+///
+/// * `agent` - HTTP agent the request is sent with
+/// * `url` - URL of the webhook
+/// * `payload_json` - Summary of the epoch as JSON
+fn post_webhook(agent: &ureq::Agent, url: &str, payload_json: &str) -> Result<(), OpenPbError> {
+    match agent
+        .post(url)
+        .set("Content-Type", "application/json")
+        .send_string(payload_json)
+    {
+        Ok(_) => Ok(()),
+        Err(error) => Err(format!("POST to {url} failed: {error}").into()),
+    }
+}
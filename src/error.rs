@@ -0,0 +1,137 @@
+use std::io;
+use thiserror::Error;
+
+/// Errors raised while loading configs and data, building Networks, and
+/// training them. Every message is meant to be shown to users as is, and
+/// errors caused by a specific file are wrapped with its path (see `in_file`)
+#[derive(Error, Debug)]
+pub enum OpenPbError {
+    /// A file couldn't be read or written
+    #[error("Couldn't access {path}: {source}")]
+    Io {
+        /// Path of the file
+        path: String,
+
+        /// Underlying IO error
+        source: io::Error,
+    },
+
+    /// JSON that doesn't match the expected format (e.g. a missing field
+    /// or a value of the wrong type), with the field and line it's at
+    #[error("Invalid {context}{}: {source}", at_field(.field))]
+    Json {
+        /// What the JSON holds (e.g. "network config")
+        context: String,
+
+        /// Path of the field that failed (e.g. `layers[1].neurons`),
+        /// or `None` when the JSON as a whole failed
+        field: Option<String>,
+
+        /// Underlying parse error
+        source: serde_json::Error,
+    },
+
+    /// Sizes of vectors or matrices that don't line up
+    #[error("{context}: expected {expected}, found {found}")]
+    Shape {
+        /// Which sizes must match
+        context: String,
+
+        /// Size required by the rest of the data or config
+        expected: usize,
+
+        /// Size that was given
+        found: usize,
+    },
+
+    /// Name in a config that doesn't match any built-in or registered function
+    #[error(
+        "Unknown {kind} '{name}' at {field}{} (built-in names: {})",
+        did_you_mean(.suggestion),
+        valid.join(", ")
+    )]
+    UnknownName {
+        /// Kind of function (e.g. "activation function")
+        kind: &'static str,
+
+        /// Name given in the config
+        name: String,
+
+        /// Path of the field the name is in (e.g. `layers[0].activation`)
+        field: String,
+
+        /// Closest built-in name, when one is similar enough
+        suggestion: Option<&'static str>,
+
+        /// Built-in names of the same kind
+        valid: &'static [&'static str],
+    },
+
+    /// Any other invalid config, data, or training option
+    #[error("{0}")]
+    Invalid(String),
+
+    /// Error caused by a specific file
+    #[error("{path}: {source}")]
+    InFile {
+        /// Path of the file
+        path: String,
+
+        /// Error caused by the file's contents
+        source: Box<OpenPbError>,
+    },
+}
+
+impl OpenPbError {
+    /// Name the file that caused the error. Errors that already
+    /// name their file (e.g. IO errors) are left as they are
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the file
+    pub fn in_file(self, path: &str) -> OpenPbError {
+        match self {
+            OpenPbError::Io { .. } | OpenPbError::InFile { .. } => self,
+            error => OpenPbError::InFile {
+                path: path.to_string(),
+                source: Box::new(error),
+            },
+        }
+    }
+}
+
+/// Location of a failed field in an error message
+///
+/// # Arguments
+///
+/// * `field` - Path of the field (if known)
+fn at_field(field: &Option<String>) -> String {
+    match field {
+        Some(field) => format!(" at {field}"),
+        None => String::new(),
+    }
+}
+
+/// Suggested replacement for an unknown name in an error message
+///
+/// # Arguments
+///
+/// * `suggestion` - Closest valid name (if any)
+fn did_you_mean(suggestion: &Option<&str>) -> String {
+    match suggestion {
+        Some(suggestion) => format!(", did you mean '{suggestion}'?"),
+        None => String::new(),
+    }
+}
+
+impl From<String> for OpenPbError {
+    fn from(message: String) -> Self {
+        OpenPbError::Invalid(message)
+    }
+}
+
+impl From<&str> for OpenPbError {
+    fn from(message: &str) -> Self {
+        OpenPbError::Invalid(message.to_string())
+    }
+}
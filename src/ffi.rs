@@ -0,0 +1,182 @@
+use crate::file_io::model_de::model_from_bytes;
+use crate::nn::perceptron::Perceptron;
+use ndarray::{Array2, ArrayView2};
+use std::ffi::CStr;
+use std::fs;
+use std::mem;
+use std::os::raw::{c_char, c_int};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+
+/// Returned by `openpb_predict` when an argument is invalid
+pub const OPENPB_ERROR_INVALID: c_int = -1;
+
+/// Returned by `openpb_predict` when prediction panicked
+pub const OPENPB_ERROR_PANIC: c_int = -2;
+
+/// Opaque handle to a trained network loaded from a JSON file
+pub struct OpenPbModel {
+    /// Trained network
+    network: Perceptron,
+}
+
+/// Run the body of an exported function, returning `fallback` if it
+/// panics, since unwinding into the calling C code is undefined behavior
+///
+/// # Arguments
+///
+/// * `fallback` - Value returned to the caller after a panic
+/// * `body` - Body of the exported function
+fn guard<T>(fallback: T, body: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or(fallback)
+}
+
+/// Number of `f64` values in a `rows` x `columns` matrix, or `None`
+/// when it's too large to be a valid slice
+///
+/// # Arguments
+///
+/// * `rows` - Number of rows
+/// * `columns` - Number of columns
+fn matrix_len(rows: usize, columns: usize) -> Option<usize> {
+    rows.checked_mul(columns)
+        .filter(|&len| len <= isize::MAX as usize / mem::size_of::<f64>())
+}
+
+/// Load a trained network from a model or results file (JSON or MessagePack).
+/// Returns a null pointer if the file can't be read or parsed
+///
+/// # Safety
+///
+/// `path` must be a valid, null-terminated C string
+#[no_mangle]
+pub unsafe extern "C" fn openpb_load_model(path: *const c_char) -> *mut OpenPbModel {
+    guard(ptr::null_mut(), || load_model(path))
+}
+
+/// Body of `openpb_load_model`
+///
+/// # Safety
+///
+/// See `openpb_load_model`
+unsafe fn load_model(path: *const c_char) -> *mut OpenPbModel {
+    if path.is_null() {
+        return ptr::null_mut();
+    }
+    let path: &str = match CStr::from_ptr(path).to_str() {
+        Ok(path) => path,
+        Err(_) => return ptr::null_mut(),
+    };
+    let model_file: Vec<u8> = match fs::read(path) {
+        Ok(model_file) => model_file,
+        Err(_) => return ptr::null_mut(),
+    };
+    match model_from_bytes(&model_file) {
+        Ok(network) => Box::into_raw(Box::new(OpenPbModel { network })),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Size of the model's expected input vector
+///
+/// # Safety
+///
+/// `model` must be a pointer returned by `openpb_load_model`
+#[no_mangle]
+pub unsafe extern "C" fn openpb_input_size(model: *const OpenPbModel) -> usize {
+    guard(0, || match model.as_ref() {
+        Some(model) => model.network.input_size(),
+        None => 0,
+    })
+}
+
+/// Size of the model's (raw) output vector
+///
+/// # Safety
+///
+/// `model` must be a pointer returned by `openpb_load_model`
+#[no_mangle]
+pub unsafe extern "C" fn openpb_output_size(model: *const OpenPbModel) -> usize {
+    guard(0, || match model.as_ref() {
+        Some(model) => model.network.output_size(),
+        None => 0,
+    })
+}
+
+/// Feed a row-major matrix of input vectors (one vector per row) into the
+/// model and write the raw output layer values, also row-major, into `outputs`.
+/// Returns 0 on success, `OPENPB_ERROR_INVALID` (-1) if any argument is
+/// invalid (including sizes that overflow), or `OPENPB_ERROR_PANIC` (-2)
+/// if prediction panicked
+///
+/// # Safety
+///
+/// `model` must be a pointer returned by `openpb_load_model`, `inputs` must point
+/// to `rows * openpb_input_size(model)` values, and `outputs` must point to
+/// `rows * openpb_output_size(model)` writable values
+#[no_mangle]
+pub unsafe extern "C" fn openpb_predict(
+    model: *const OpenPbModel,
+    inputs: *const f64,
+    rows: usize,
+    outputs: *mut f64,
+) -> c_int {
+    guard(OPENPB_ERROR_PANIC, || predict(model, inputs, rows, outputs))
+}
+
+/// Body of `openpb_predict`
+///
+/// # Safety
+///
+/// See `openpb_predict`
+unsafe fn predict(
+    model: *const OpenPbModel,
+    inputs: *const f64,
+    rows: usize,
+    outputs: *mut f64,
+) -> c_int {
+    let model: &OpenPbModel = match model.as_ref() {
+        Some(model) => model,
+        None => return OPENPB_ERROR_INVALID,
+    };
+    if inputs.is_null() || outputs.is_null() {
+        return OPENPB_ERROR_INVALID;
+    }
+    let input_size: usize = model.network.input_size();
+    let output_size: usize = model.network.output_size();
+    let (inputs_len, outputs_len): (usize, usize) =
+        match (matrix_len(rows, input_size), matrix_len(rows, output_size)) {
+            (Some(inputs_len), Some(outputs_len)) => (inputs_len, outputs_len),
+            _ => return OPENPB_ERROR_INVALID,
+        };
+
+    let inputs: &[f64] = slice::from_raw_parts(inputs, inputs_len);
+    let inputs: ArrayView2<f64> = match ArrayView2::from_shape((rows, input_size), inputs) {
+        Ok(inputs) => inputs,
+        Err(_) => return OPENPB_ERROR_INVALID,
+    };
+    // Network expects each input vector as a column
+    let predicted: Array2<f64> = model.network.predict_raw(&inputs.t().to_owned());
+
+    let outputs: &mut [f64] = slice::from_raw_parts_mut(outputs, outputs_len);
+    for (output, value) in outputs.iter_mut().zip(predicted.t().iter()) {
+        *output = *value;
+    }
+    0
+}
+
+/// Free a model returned by `openpb_load_model`
+///
+/// # Safety
+///
+/// `model` must be a pointer returned by `openpb_load_model` (or null),
+/// and must not be used after this call
+#[no_mangle]
+pub unsafe extern "C" fn openpb_free(model: *mut OpenPbModel) {
+    guard((), || {
+        if !model.is_null() {
+            drop(Box::from_raw(model));
+        }
+    })
+}
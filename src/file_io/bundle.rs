@@ -0,0 +1,294 @@
+use super::json_de::{DataDe, NetworkDataDe, NetworkDe};
+use crate::error::OpenPbError;
+use crate::nn::perceptron::Perceptron;
+#[cfg(feature = "file_io")]
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+#[cfg(feature = "file_io")]
+use std::collections::BTreeMap;
+#[cfg(feature = "file_io")]
+use std::io::Read;
+use std::sync::Arc;
+
+/// Magic number every zstd frame starts with
+pub(crate) const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Compression level of bundle archives (zstd's default)
+#[cfg(feature = "file_io")]
+const ARCHIVE_COMPRESSION_LEVEL: i32 = 3;
+
+/// An entire experiment as a single portable artifact: the network
+/// configuration, the training and validation data, every network
+/// trained with them, the fitted encoder state, and the training results
+#[derive(Serialize, Deserialize)]
+pub struct ExperimentBundle {
+    /// Network structure and hyperparameters (including the
+    /// cost, optimizer, encoder, and metric configurations)
+    pub config: NetworkDe,
+
+    /// Training and validation data
+    pub data: DataDe,
+
+    /// Trained networks (one for each training run)
+    pub networks: Vec<Perceptron>,
+
+    /// Values the encoder learned from the training outputs
+    /// (e.g. target scaling), empty for stateless encoders
+    #[serde(default)]
+    pub encoder_state: Map<String, Value>,
+
+    /// Full training results, including each run's per-epoch history (optional)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub results: Option<Value>,
+}
+
+impl ExperimentBundle {
+    /// # Arguments
+    ///
+    /// * `network_data_de` - Network parameters with training and validation data
+    /// * `networks` - Networks trained with the given parameters and data
+    pub fn new(network_data_de: &NetworkDataDe, networks: Vec<Perceptron>) -> Self {
+        Self {
+            config: network_data_de.config().clone(),
+            data: DataDe::from_source(network_data_de.data.as_ref()),
+            networks,
+            encoder_state: network_data_de.encoder.state(),
+            results: None,
+        }
+    }
+
+    /// Include the training results (and with them each run's history)
+    ///
+    /// # Arguments
+    ///
+    /// * `results` - Serialized training results
+    pub fn with_results(mut self, results: Value) -> Self {
+        self.results = Some(results);
+        self
+    }
+
+    /// # Arguments
+    ///
+    /// * `bundle_json` - Raw contents of a bundle JSON file
+    pub fn from_json(bundle_json: &str) -> Result<Self, OpenPbError> {
+        match serde_json::from_str(bundle_json) {
+            Ok(bundle) => Ok(bundle),
+            Err(error) => Err(format!("Invalid experiment bundle: {error}").into()),
+        }
+    }
+
+    /// Read a bundle saved either as JSON or as a `.tar.zst` archive
+    ///
+    /// # Arguments
+    ///
+    /// * `contents` - Raw contents of the bundle file
+    pub fn from_bytes(contents: &[u8]) -> Result<Self, OpenPbError> {
+        if contents.starts_with(&ZSTD_MAGIC) {
+            return Self::from_archive(contents);
+        }
+        match std::str::from_utf8(contents) {
+            Ok(bundle_json) => Self::from_json(bundle_json),
+            Err(error) => Err(format!("Invalid experiment bundle: {error}").into()),
+        }
+    }
+
+    /// Serialize the entire bundle to (pretty-printed) JSON
+    pub fn to_json(&self) -> Result<String, OpenPbError> {
+        match serde_json::to_string_pretty(self) {
+            Ok(bundle_json) => Ok(bundle_json),
+            Err(error) => Err(format!("Failed to serialize experiment bundle: {error}").into()),
+        }
+    }
+
+    /// Write the bundle as a zstd-compressed tar archive with one JSON file
+    /// per part, so each can be inspected (or extracted) on its own:
+    ///
+    /// * `config.json` - Resolved network config
+    /// * `data.json` - Training and validation data
+    /// * `encoder.json` - Fitted encoder state
+    /// * `networks/run_N.json` - Each trained network
+    /// * `results.json` - Training results and history (if included)
+    #[cfg(feature = "file_io")]
+    pub fn to_archive(&self) -> Result<Vec<u8>, OpenPbError> {
+        let mut entries: Vec<(String, Vec<u8>)> = vec![
+            ("config.json".into(), archive_entry(&self.config)?),
+            ("data.json".into(), archive_entry(&self.data)?),
+            ("encoder.json".into(), archive_entry(&self.encoder_state)?),
+        ];
+        for (run, network) in self.networks.iter().enumerate() {
+            entries.push((format!("networks/run_{run}.json"), archive_entry(network)?));
+        }
+        if let Some(results) = &self.results {
+            entries.push(("results.json".into(), archive_entry(results)?));
+        }
+
+        let encoder = match zstd::Encoder::new(Vec::new(), ARCHIVE_COMPRESSION_LEVEL) {
+            Ok(encoder) => encoder,
+            Err(error) => {
+                return Err(format!("Failed to compress experiment bundle: {error}").into())
+            }
+        };
+        let mut archive = tar::Builder::new(encoder);
+        for (path, contents) in &entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            if let Err(error) = archive.append_data(&mut header, path, contents.as_slice()) {
+                return Err(format!("Failed to archive {path}: {error}").into());
+            }
+        }
+        match archive.into_inner().and_then(|encoder| encoder.finish()) {
+            Ok(archive) => Ok(archive),
+            Err(error) => Err(format!("Failed to compress experiment bundle: {error}").into()),
+        }
+    }
+
+    /// Read a bundle written by `to_archive`
+    ///
+    /// # Arguments
+    ///
+    /// * `contents` - Raw contents of the `.tar.zst` file
+    #[cfg(feature = "file_io")]
+    pub fn from_archive(contents: &[u8]) -> Result<Self, OpenPbError> {
+        let decoder = match zstd::Decoder::new(contents) {
+            Ok(decoder) => decoder,
+            Err(error) => return Err(format!("Invalid experiment bundle archive: {error}").into()),
+        };
+        let mut archive = tar::Archive::new(decoder);
+        let mut entries: BTreeMap<String, Vec<u8>> = BTreeMap::new();
+        let archive_entries = match archive.entries() {
+            Ok(archive_entries) => archive_entries,
+            Err(error) => return Err(format!("Invalid experiment bundle archive: {error}").into()),
+        };
+        for entry in archive_entries {
+            let mut contents: Vec<u8> = vec![];
+            let path: String = match entry.and_then(|mut entry| {
+                entry.read_to_end(&mut contents)?;
+                Ok(entry.path()?.to_string_lossy().to_string())
+            }) {
+                Ok(path) => path,
+                Err(error) => {
+                    return Err(format!("Invalid experiment bundle archive: {error}").into())
+                }
+            };
+            entries.insert(path, contents);
+        }
+
+        // Networks are ordered by run rather than by name (run_10 after run_9)
+        let mut runs: Vec<(usize, &String)> = vec![];
+        for path in entries.keys() {
+            if let Some(run) = path
+                .strip_prefix("networks/run_")
+                .and_then(|name| name.strip_suffix(".json"))
+            {
+                match run.parse::<usize>() {
+                    Ok(run) => runs.push((run, path)),
+                    Err(_) => {
+                        return Err(format!("Unexpected network file {path} in bundle").into())
+                    }
+                }
+            }
+        }
+        runs.sort();
+        let mut networks: Vec<Perceptron> = Vec::with_capacity(runs.len());
+        for (_, path) in runs {
+            networks.push(read_entry(&entries, path)?);
+        }
+
+        Ok(Self {
+            config: read_entry(&entries, "config.json")?,
+            data: read_entry(&entries, "data.json")?,
+            networks,
+            encoder_state: match entries.contains_key("encoder.json") {
+                true => read_entry(&entries, "encoder.json")?,
+                false => Map::new(),
+            },
+            results: match entries.contains_key("results.json") {
+                true => Some(read_entry(&entries, "results.json")?),
+                false => None,
+            },
+        })
+    }
+
+    /// Archives can't be read without the `file_io` feature
+    ///
+    /// # Arguments
+    ///
+    /// * `_contents` - Raw contents of the `.tar.zst` file
+    #[cfg(not(feature = "file_io"))]
+    pub fn from_archive(_contents: &[u8]) -> Result<Self, OpenPbError> {
+        Err("Reading experiment bundle archives requires the file_io feature".into())
+    }
+
+    /// Rebuild the deserialized training setup (with the encoder
+    /// restored to its bundled state) and trained networks
+    pub fn into_parts(self) -> Result<(NetworkDataDe, Vec<Perceptron>), OpenPbError> {
+        let mut network_data_de: NetworkDataDe =
+            NetworkDataDe::from_config(Arc::new(self.data), self.config)?;
+        network_data_de.encoder.load_state(&self.encoder_state)?;
+        Ok((network_data_de, self.networks))
+    }
+}
+
+/// Results saved in a bundle archive, or just its first network when the
+/// bundle has no results, so that archives can be read like results files
+///
+/// # Arguments
+///
+/// * `contents` - Raw contents of the `.tar.zst` file
+pub(crate) fn archive_results(contents: &[u8]) -> Result<Value, OpenPbError> {
+    let bundle: ExperimentBundle = ExperimentBundle::from_archive(contents)?;
+    match (bundle.results, bundle.networks.first()) {
+        (Some(results), _) => Ok(results),
+        (None, Some(network)) => match serde_json::to_value(network) {
+            Ok(network) => Ok(serde_json::json!({ "network": network })),
+            Err(error) => Err(format!("Invalid network in experiment bundle: {error}").into()),
+        },
+        (None, None) => Err("Experiment bundle has no trained networks".into()),
+    }
+}
+
+/// Whether or not a bundle file name asks for a `.tar.zst` archive instead of JSON
+///
+/// # Arguments
+///
+/// * `filepath` - Name of the bundle file
+pub fn is_archive_path(filepath: &str) -> bool {
+    filepath.ends_with(".tar.zst") || filepath.ends_with(".tzst")
+}
+
+/// Serialize a single part of a bundle archive to pretty-printed JSON
+///
+/// # Arguments
+///
+/// * `value` - Part of the bundle
+#[cfg(feature = "file_io")]
+fn archive_entry<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, OpenPbError> {
+    match serde_json::to_vec_pretty(value) {
+        Ok(entry) => Ok(entry),
+        Err(error) => Err(format!("Failed to serialize experiment bundle: {error}").into()),
+    }
+}
+
+/// Deserialize a single part of a bundle archive
+///
+/// # Arguments
+///
+/// * `entries` - Contents of every file in the archive, by path
+/// * `path` - Path of the part within the archive
+#[cfg(feature = "file_io")]
+fn read_entry<T: DeserializeOwned>(
+    entries: &BTreeMap<String, Vec<u8>>,
+    path: &str,
+) -> Result<T, OpenPbError> {
+    let contents: &Vec<u8> = match entries.get(path) {
+        Some(contents) => contents,
+        None => return Err(format!("Experiment bundle is missing {path}").into()),
+    };
+    match serde_json::from_slice(contents) {
+        Ok(value) => Ok(value),
+        Err(error) => Err(format!("Invalid {path} in experiment bundle: {error}").into()),
+    }
+}
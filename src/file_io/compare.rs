@@ -0,0 +1,452 @@
+use super::json_de::metric_from_label;
+use super::output_format;
+use super::plot::LearningCurve;
+use crate::error::OpenPbError;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Relative slowdown in timing values that is
+/// still considered noise rather than a regression
+pub const TIMING_TOLERANCE: f32 = 0.05;
+
+/// Deserialized metric of a single run in a results file
+#[derive(Deserialize, Debug)]
+struct RunMetricDe {
+    /// Name (label) of the metric
+    name: String,
+    /// Metric value (score) after training
+    value: f32,
+    /// Whether or not the score passed
+    passed: bool,
+}
+
+/// Deserialized activation function of a single layer
+#[derive(Deserialize, Debug)]
+struct LayerSummaryDe {
+    /// Name of the activation function
+    activation: String,
+}
+
+/// Deserialized structure of a trained network
+#[derive(Deserialize, Debug)]
+struct NetworkSummaryDe {
+    /// Total number of trainable values
+    num_parameters: usize,
+    /// Shape of each layer's weight matrix
+    layer_shapes: Vec<(usize, usize)>,
+    /// Trained layers
+    layers: Vec<LayerSummaryDe>,
+    /// Calibrated output temperature
+    #[serde(default)]
+    temperature: Option<f64>,
+}
+
+/// Deserialized CPU usage and throughput of a single run
+#[derive(Deserialize, Debug)]
+struct RunTimingDe {
+    /// Training samples processed per second
+    samples_per_second: f32,
+}
+
+/// Deserialized summary of a single epoch of a run
+#[derive(Deserialize, Debug)]
+struct EpochSummaryDe {
+    /// Epoch that had just finished (starting at 1)
+    epoch: usize,
+    /// Mean training loss over the epoch
+    loss: f64,
+    /// Value of the metric on the validation set
+    metric: f32,
+}
+
+/// Deserialized results of a single run
+#[derive(Deserialize, Debug)]
+struct RunSummaryDe {
+    /// Trained network
+    network: NetworkSummaryDe,
+    /// Seed of the run's random number generator
+    #[serde(default)]
+    seed: Option<u64>,
+    /// Metric of the trained network
+    metric: RunMetricDe,
+    /// Time it took for training to complete (in seconds)
+    elapsed_time: f32,
+    /// Total number of epochs until training stopped
+    total_epochs: usize,
+    /// CPU time and throughput of training
+    #[serde(default)]
+    timing: Option<RunTimingDe>,
+    /// Summary of every logged epoch (absent in older results files)
+    #[serde(default)]
+    history: Vec<EpochSummaryDe>,
+}
+
+/// The parts of a results file written by `save_output`
+/// that are compared between two benchmarks
+#[derive(Deserialize, Debug)]
+pub struct ResultsSummaryDe {
+    /// Results of each run
+    all_results: Vec<RunSummaryDe>,
+    /// Size of minibatches (if applicable)
+    #[serde(default)]
+    batch_size: Option<usize>,
+    /// Fully-resolved optimizer config (absent in older results files)
+    #[serde(default)]
+    optimizer: Option<Value>,
+}
+
+impl ResultsSummaryDe {
+    /// # Arguments
+    ///
+    /// * `results_json` - Raw contents of a results JSON file
+    pub fn from_json(results_json: &str) -> Result<Self, OpenPbError> {
+        Self::from_bytes(results_json.as_bytes())
+    }
+
+    /// Same as `from_json`, but the results file can also
+    /// be saved in MessagePack (see `OutputFormat`)
+    ///
+    /// # Arguments
+    ///
+    /// * `results` - Raw contents of a results file
+    pub fn from_bytes(results: &[u8]) -> Result<Self, OpenPbError> {
+        let value: Value = output_format::decode(results, "results")?;
+        let summary: Self = match serde_json::from_value(value) {
+            Ok(summary) => summary,
+            Err(error) => return Err(format!("Invalid results: {error}").into()),
+        };
+        if summary.all_results.is_empty() {
+            return Err("Results file doesn't contain any runs".into());
+        }
+        Ok(summary)
+    }
+
+    /// Size of minibatches (if applicable)
+    pub fn batch_size(&self) -> Option<usize> {
+        self.batch_size
+    }
+
+    /// Seed, metric score, and total epochs of each run
+    pub fn run_outcomes(&self) -> Vec<(Option<u64>, f32, usize)> {
+        self.all_results
+            .iter()
+            .map(|run| (run.seed, run.metric.value, run.total_epochs))
+            .collect()
+    }
+
+    /// Fully-resolved optimizer config (absent in older results files)
+    pub fn optimizer(&self) -> Option<&Value> {
+        self.optimizer.as_ref()
+    }
+
+    /// Mean training loss and validation metric of every run at each epoch
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - Name of the benchmark in plot legends
+    pub fn learning_curve(&self, label: String) -> LearningCurve {
+        let runs: Vec<Vec<(usize, f64, f32)>> = self
+            .all_results
+            .iter()
+            .map(|run| {
+                run.history
+                    .iter()
+                    .map(|epoch| (epoch.epoch, epoch.loss, epoch.metric))
+                    .collect()
+            })
+            .collect();
+        LearningCurve::mean(label, &runs)
+    }
+
+    /// Label of the metric the runs were scored with
+    pub fn metric_label(&self) -> &str {
+        &self.all_results[0].metric.name
+    }
+
+    /// Mean of a value over every run
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Reads the value from a single run
+    fn mean<F: Fn(&RunSummaryDe) -> f32>(&self, value: F) -> f32 {
+        self.all_results.iter().map(value).sum::<f32>() / self.all_results.len() as f32
+    }
+
+    /// Value shared by every run, or a comma-separated
+    /// list of each run's value when they differ
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Formats the value of a single run
+    fn shared<F: Fn(&RunSummaryDe) -> String>(&self, value: F) -> String {
+        let mut values: Vec<String> = self.all_results.iter().map(value).collect();
+        values.dedup();
+        values.join(", ")
+    }
+}
+
+/// Section of a comparison that a row belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    /// Network structure and training setup
+    Config,
+    /// Metric scores after training
+    Metrics,
+    /// Epochs until training stopped
+    Convergence,
+    /// Training time and throughput
+    Timing,
+}
+
+impl Section {
+    /// Section name for command line output
+    pub fn label(&self) -> &str {
+        match self {
+            Section::Config => "Config",
+            Section::Metrics => "Metrics",
+            Section::Convergence => "Convergence",
+            Section::Timing => "Timing",
+        }
+    }
+}
+
+/// A single compared value of two results files
+#[derive(Debug, Clone)]
+pub struct ComparisonRow {
+    /// Section the value belongs to
+    pub section: Section,
+    /// Name of the compared value
+    pub name: String,
+    /// Value in the baseline results
+    pub before: String,
+    /// Value in the new results
+    pub after: String,
+    /// Relative change of numeric values (if applicable)
+    pub change: Option<f32>,
+    /// Whether or not the new value is worse than the baseline
+    pub regression: bool,
+}
+
+impl ComparisonRow {
+    /// Row for a value that can't get better or worse
+    ///
+    /// # Arguments
+    ///
+    /// * `section` - Section the value belongs to
+    /// * `name` - Name of the compared value
+    /// * `before` - Value in the baseline results
+    /// * `after` - Value in the new results
+    fn text(section: Section, name: &str, before: String, after: String) -> Self {
+        Self {
+            section,
+            name: name.to_string(),
+            before,
+            after,
+            change: None,
+            regression: false,
+        }
+    }
+
+    /// Row for a numeric value
+    ///
+    /// # Arguments
+    ///
+    /// * `section` - Section the value belongs to
+    /// * `name` - Name of the compared value
+    /// * `before` - Value in the baseline results
+    /// * `after` - Value in the new results
+    /// * `lower_is_better` - Whether or not lower values are better
+    /// * `tolerance` - Relative worsening that isn't considered a regression
+    fn numeric(
+        section: Section,
+        name: &str,
+        before: f32,
+        after: f32,
+        lower_is_better: bool,
+        tolerance: f32,
+    ) -> Self {
+        let change: Option<f32> = if before != 0.0 {
+            Some((after - before) / before.abs())
+        } else {
+            None
+        };
+        let worsening: f32 = if lower_is_better {
+            after - before
+        } else {
+            before - after
+        };
+        Self {
+            section,
+            name: name.to_string(),
+            before: before.to_string(),
+            after: after.to_string(),
+            change,
+            regression: worsening > tolerance * before.abs(),
+        }
+    }
+
+    /// Whether or not the value differs between the two results
+    pub fn changed(&self) -> bool {
+        self.before != self.after
+    }
+}
+
+/// Compare the configs, final metrics, epochs-to-converge, and timing
+/// of two results files. Values averaged over runs are compared when
+/// either file has more than one run
+///
+/// # Arguments
+///
+/// * `before` - Baseline results
+/// * `after` - New results
+pub fn compare(before: &ResultsSummaryDe, after: &ResultsSummaryDe) -> Vec<ComparisonRow> {
+    let mut rows: Vec<ComparisonRow> = vec![];
+
+    // Config
+    let layers = |results: &ResultsSummaryDe| {
+        results.shared(|run| {
+            let shapes: Vec<String> = run
+                .network
+                .layer_shapes
+                .iter()
+                .zip(&run.network.layers)
+                .map(|((neurons, inputs), layer)| {
+                    format!("{inputs}->{neurons} {}", layer.activation)
+                })
+                .collect();
+            shapes.join(" | ")
+        })
+    };
+    rows.push(ComparisonRow::text(
+        Section::Config,
+        "layers",
+        layers(before),
+        layers(after),
+    ));
+    let parameters =
+        |results: &ResultsSummaryDe| results.shared(|run| run.network.num_parameters.to_string());
+    rows.push(ComparisonRow::text(
+        Section::Config,
+        "parameters",
+        parameters(before),
+        parameters(after),
+    ));
+    let batch_size = |results: &ResultsSummaryDe| match results.batch_size {
+        Some(batch_size) => batch_size.to_string(),
+        None => "full".to_string(),
+    };
+    rows.push(ComparisonRow::text(
+        Section::Config,
+        "batch size",
+        batch_size(before),
+        batch_size(after),
+    ));
+    let optimizer = |results: &ResultsSummaryDe| match &results.optimizer {
+        Some(optimizer) => optimizer.to_string(),
+        None => "-".to_string(),
+    };
+    rows.push(ComparisonRow::text(
+        Section::Config,
+        "optimizer",
+        optimizer(before),
+        optimizer(after),
+    ));
+    rows.push(ComparisonRow::text(
+        Section::Config,
+        "runs",
+        before.all_results.len().to_string(),
+        after.all_results.len().to_string(),
+    ));
+    let seeds = |results: &ResultsSummaryDe| {
+        results.shared(|run| match run.seed {
+            Some(seed) => seed.to_string(),
+            None => "-".to_string(),
+        })
+    };
+    rows.push(ComparisonRow::text(
+        Section::Config,
+        "seeds",
+        seeds(before),
+        seeds(after),
+    ));
+    let temperature = |results: &ResultsSummaryDe| {
+        results.shared(|run| match run.network.temperature {
+            Some(temperature) => temperature.to_string(),
+            None => "-".to_string(),
+        })
+    };
+    rows.push(ComparisonRow::text(
+        Section::Config,
+        "temperature",
+        temperature(before),
+        temperature(after),
+    ));
+
+    // Metrics
+    let metric_label: &str = after.metric_label();
+    let lower_is_better: bool = match metric_from_label(metric_label) {
+        Some(metric) => metric.lower_is_better(),
+        None => false,
+    };
+    if before.metric_label() != metric_label {
+        rows.push(ComparisonRow::text(
+            Section::Metrics,
+            "metric",
+            before.metric_label().to_string(),
+            metric_label.to_string(),
+        ));
+    }
+    rows.push(ComparisonRow::numeric(
+        Section::Metrics,
+        &format!("mean {metric_label}"),
+        before.mean(|run| run.metric.value),
+        after.mean(|run| run.metric.value),
+        lower_is_better,
+        0.0,
+    ));
+    let pass_rate =
+        |results: &ResultsSummaryDe| results.mean(|run| if run.metric.passed { 1.0 } else { 0.0 });
+    rows.push(ComparisonRow::numeric(
+        Section::Metrics,
+        "pass rate",
+        pass_rate(before),
+        pass_rate(after),
+        false,
+        0.0,
+    ));
+
+    // Convergence
+    rows.push(ComparisonRow::numeric(
+        Section::Convergence,
+        "mean epochs",
+        before.mean(|run| run.total_epochs as f32),
+        after.mean(|run| run.total_epochs as f32),
+        true,
+        0.0,
+    ));
+
+    // Timing
+    rows.push(ComparisonRow::numeric(
+        Section::Timing,
+        "mean elapsed time (s)",
+        before.mean(|run| run.elapsed_time),
+        after.mean(|run| run.elapsed_time),
+        true,
+        TIMING_TOLERANCE,
+    ));
+    let throughput = |results: &ResultsSummaryDe| {
+        results.mean(|run| match &run.timing {
+            Some(timing) => timing.samples_per_second,
+            None => 0.0,
+        })
+    };
+    rows.push(ComparisonRow::numeric(
+        Section::Timing,
+        "mean samples/second",
+        throughput(before),
+        throughput(after),
+        false,
+        TIMING_TOLERANCE,
+    ));
+    rows
+}
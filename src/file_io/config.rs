@@ -0,0 +1,107 @@
+use crate::error::OpenPbError;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Key of the base config file a config inherits from
+pub(crate) const EXTENDS_KEY: &str = "extends";
+
+/// Read a JSON config file, resolving any `"extends"` chain. A config that
+/// extends a base file is deep-merged on top of it: objects are merged key
+/// by key, and every other value (including arrays) replaces the base value.
+/// Base file paths are relative to the file that extends them
+///
+/// # Arguments
+///
+/// * `path` - JSON config file
+///
+/// # Returns
+///
+/// Raw JSON contents of the fully resolved config
+pub fn read_config(path: &str) -> Result<String, OpenPbError> {
+    // Configs without a base file are returned as written, so
+    // deserialization errors point at their actual line and column
+    if let Ok(config_json) = fs::read_to_string(path) {
+        if let Ok(Value::Object(config)) = serde_json::from_str(&config_json) {
+            if !config.contains_key(EXTENDS_KEY) {
+                return Ok(config_json);
+            }
+        }
+    }
+    let config: Value = resolve(Path::new(path), &mut vec![])?;
+    match serde_json::to_string(&config) {
+        Ok(config_json) => Ok(config_json),
+        Err(error) => Err(error.to_string().into()),
+    }
+}
+
+/// Load a config file and every base file it extends
+///
+/// # Arguments
+///
+/// * `path` - JSON config file
+/// * `chain` - Files already being resolved (used to detect cycles)
+fn resolve(path: &Path, chain: &mut Vec<PathBuf>) -> Result<Value, OpenPbError> {
+    let config_json: String = match fs::read_to_string(path) {
+        Ok(result) => result,
+        _ => return Err(format!("File {} missing or corrupted", path.display()).into()),
+    };
+    let mut config: Value = match serde_json::from_str(&config_json) {
+        Ok(config) => config,
+        Err(error) => return Err(format!("Invalid config {}: {error}", path.display()).into()),
+    };
+
+    let base_path: String = match config.as_object_mut().and_then(|c| c.remove(EXTENDS_KEY)) {
+        Some(Value::String(base_path)) => base_path,
+        Some(_) => {
+            return Err(format!(
+                "\"{EXTENDS_KEY}\" in {} must be a file path",
+                path.display()
+            )
+            .into())
+        }
+        None => return Ok(config),
+    };
+
+    let canonical: PathBuf = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    if chain.contains(&canonical) {
+        return Err(format!(
+            "Config {} extends itself (directly or indirectly)",
+            path.display()
+        )
+        .into());
+    }
+    chain.push(canonical);
+
+    let base_path: PathBuf = match path.parent() {
+        Some(parent_dir) => parent_dir.join(base_path),
+        None => PathBuf::from(base_path),
+    };
+    let mut base: Value = resolve(&base_path, chain)?;
+    chain.pop();
+
+    merge(&mut base, config);
+    Ok(base)
+}
+
+/// Deep-merge an overriding value into a base value
+///
+/// # Arguments
+///
+/// * `base` - Value being overridden (modified in place)
+/// * `overrides` - Values that take precedence over the base
+fn merge(base: &mut Value, overrides: Value) {
+    match (base, overrides) {
+        (Value::Object(base), Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(&key) {
+                    Some(base_value) => merge(base_value, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overrides) => *base = overrides,
+    }
+}
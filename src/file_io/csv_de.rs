@@ -0,0 +1,429 @@
+use super::json_de::DataDe;
+use super::save_output::STDIO_PATH;
+use crate::error::OpenPbError;
+use ndarray::Array2;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::io::Read;
+
+/// Contents of a CSV file with a header row
+#[derive(Debug, Clone)]
+pub struct CsvTable {
+    /// Name of each column
+    pub header: Vec<String>,
+
+    /// Cells of each row (after the header), in column order
+    pub rows: Vec<Vec<String>>,
+}
+
+impl CsvTable {
+    /// Parse CSV text whose first row names each column. Every
+    /// row must have as many cells as the header
+    ///
+    /// # Arguments
+    ///
+    /// * `contents` - Raw contents of the CSV file
+    pub fn parse(contents: &str) -> Result<CsvTable, OpenPbError> {
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(true)
+            .trim(csv::Trim::All)
+            .from_reader(contents.as_bytes());
+
+        let header: Vec<String> = match reader.headers() {
+            Ok(header) => header.iter().map(str::to_string).collect(),
+            Err(error) => return Err(format!("Invalid CSV header: {error}").into()),
+        };
+        let mut rows: Vec<Vec<String>> = vec![];
+        for record in reader.records() {
+            match record {
+                Ok(record) => rows.push(record.iter().map(str::to_string).collect()),
+                Err(error) => return Err(format!("Invalid CSV row: {error}").into()),
+            }
+        }
+        Ok(CsvTable { header, rows })
+    }
+
+    /// Index of a column, given either its name in the header
+    /// or its (0-based) position
+    ///
+    /// # Arguments
+    ///
+    /// * `column` - Column name or index
+    pub fn column_index(&self, column: &str) -> Result<usize, OpenPbError> {
+        if let Some(index) = self.header.iter().position(|name| name == column) {
+            return Ok(index);
+        }
+        match column.parse::<usize>() {
+            Ok(index) if index < self.header.len() => Ok(index),
+            _ => Err(format!("CSV has no column \"{column}\"").into()),
+        }
+    }
+
+    /// Indices of every selected column, in order
+    ///
+    /// # Arguments
+    ///
+    /// * `columns` - Selected column names, indices, and index ranges
+    pub fn select(&self, columns: &[ColumnDe]) -> Result<Vec<usize>, OpenPbError> {
+        let mut indices: Vec<usize> = vec![];
+        for column in columns {
+            match column {
+                ColumnDe::Index(index) if *index < self.header.len() => indices.push(*index),
+                ColumnDe::Index(index) => return Err(format!("CSV has no column {index}").into()),
+                ColumnDe::Name(name) => match self.column_index(name) {
+                    Ok(index) => indices.push(index),
+                    // Names that aren't in the header may be index ranges
+                    Err(error) => match self.column_range(name) {
+                        Some(range) => indices.extend(range),
+                        None => return Err(error),
+                    },
+                },
+            }
+        }
+        Ok(indices)
+    }
+
+    /// Indices of an index range such as `"2..5"` (end exclusive) or
+    /// `"2..=5"` (end inclusive), when the text is a valid range
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - Text of the range
+    fn column_range(&self, range: &str) -> Option<Vec<usize>> {
+        let (start, end) = range.split_once("..")?;
+        let start: usize = start.trim().parse().ok()?;
+        let end: usize = match end.strip_prefix('=') {
+            Some(end) => end.trim().parse::<usize>().ok()? + 1,
+            None => end.trim().parse().ok()?,
+        };
+        if start >= end || end > self.header.len() {
+            return None;
+        }
+        Some((start..end).collect())
+    }
+
+    /// Whether or not every cell of a column is a number
+    ///
+    /// # Arguments
+    ///
+    /// * `column` - Index of the column
+    fn is_numeric(&self, column: usize) -> bool {
+        self.rows
+            .iter()
+            .all(|cells| cells[column].parse::<f64>().is_ok())
+    }
+
+    /// Distinct values of a column, in sorted order
+    ///
+    /// # Arguments
+    ///
+    /// * `column` - Index of the column
+    fn categories(&self, column: usize) -> Vec<String> {
+        let categories: BTreeSet<&String> = self.rows.iter().map(|cells| &cells[column]).collect();
+        categories.into_iter().cloned().collect()
+    }
+
+    /// Values of the given columns, one row per sample
+    ///
+    /// # Arguments
+    ///
+    /// * `columns` - How each column is converted, in order
+    fn encode(&self, columns: &[ColumnEncoding]) -> Result<Array2<f64>, OpenPbError> {
+        let width: usize = columns.iter().map(ColumnEncoding::width).sum();
+        let mut matrix: Array2<f64> = Array2::zeros((self.rows.len(), width));
+        for (row, cells) in self.rows.iter().enumerate() {
+            let mut offset: usize = 0;
+            for column in columns {
+                let cell: &str = &cells[column.index];
+                // Line 1 is the header
+                let location =
+                    || format!("Line {}, column \"{}\"", row + 2, self.header[column.index]);
+                match &column.kind {
+                    ColumnKind::Numeric => match cell.parse::<f64>() {
+                        Ok(value) => matrix[[row, offset]] = value,
+                        Err(_) => {
+                            return Err(format!("{}: \"{cell}\" isn't a number", location()).into())
+                        }
+                    },
+                    ColumnKind::OneHot(categories) | ColumnKind::ClassIndex(categories) => {
+                        let category: usize =
+                            match categories.iter().position(|category| category == cell) {
+                                Some(category) => category,
+                                None => {
+                                    return Err(format!(
+                                        "{}: category \"{cell}\" isn't in the training set",
+                                        location()
+                                    )
+                                    .into())
+                                }
+                            };
+                        match column.kind {
+                            ColumnKind::OneHot(_) => matrix[[row, offset + category]] = 1.0,
+                            _ => matrix[[row, offset]] = category as f64,
+                        }
+                    }
+                }
+                offset += column.width();
+            }
+        }
+        Ok(matrix)
+    }
+}
+
+/// A selected CSV column: either its name or (0-based) index, or an
+/// index range such as `"2..5"` (end exclusive) or `"2..=5"` (end inclusive)
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum ColumnDe {
+    /// Position of the column
+    Index(usize),
+
+    /// Name of the column in the header, or an index range
+    Name(String),
+}
+
+/// How a CSV column is converted into matrix values
+#[derive(Debug, Clone)]
+enum ColumnKind {
+    /// Cells are parsed as numbers
+    Numeric,
+
+    /// Each category (of the training set) gets its own 0/1 column
+    OneHot(Vec<String>),
+
+    /// Cells are replaced by the index of their category (used for
+    /// outputs, so they work with the class encoders)
+    ClassIndex(Vec<String>),
+}
+
+/// Conversion of a single CSV column
+#[derive(Debug, Clone)]
+struct ColumnEncoding {
+    /// Index of the column
+    index: usize,
+
+    /// How the column's cells are converted
+    kind: ColumnKind,
+}
+
+impl ColumnEncoding {
+    /// Number of matrix columns the CSV column is converted into
+    fn width(&self) -> usize {
+        match &self.kind {
+            ColumnKind::OneHot(categories) => categories.len(),
+            ColumnKind::Numeric | ColumnKind::ClassIndex(_) => 1,
+        }
+    }
+}
+
+/// Deserialized values representing tabular (CSV) data in a data
+/// JSON file, under a `"tabular"` key. File paths are relative to
+/// the working directory
+#[derive(Deserialize, Debug, Clone)]
+pub struct TabularDe {
+    /// CSV file with the training set
+    train: String,
+
+    /// CSV file with the validation set (optional)
+    #[serde(default)]
+    validation: Option<String>,
+
+    /// CSV file with the hold-out test set (optional)
+    #[serde(default)]
+    test: Option<String>,
+
+    /// Columns with the input features (every column
+    /// that isn't a target when omitted)
+    #[serde(default)]
+    inputs: Option<Vec<ColumnDe>>,
+
+    /// Columns with the output values (the last column when omitted)
+    #[serde(default)]
+    targets: Option<Vec<ColumnDe>>,
+
+    /// Input columns that are one-hot expanded (every input column with
+    /// a non-numeric value in the training set when omitted)
+    #[serde(default)]
+    categorical: Option<Vec<ColumnDe>>,
+}
+
+/// Shape of the tabular data wrapper in a data JSON file
+#[derive(Deserialize, Debug)]
+pub(crate) struct TabularFileDe {
+    /// CSV files and column selection (absent for regular data files).
+    /// Kept as raw JSON, so errors name the tabular data's own fields
+    #[serde(default)]
+    pub tabular: Option<Value>,
+}
+
+impl TabularDe {
+    /// # Arguments
+    ///
+    /// * `tabular` - The `"tabular"` object of a data JSON file
+    pub fn from_value(tabular: Value) -> Result<TabularDe, OpenPbError> {
+        match serde_json::from_value(tabular) {
+            Ok(tabular_de) => Ok(tabular_de),
+            Err(error) => Err(format!("Invalid tabular data: {error}").into()),
+        }
+    }
+
+    /// Load the CSV files and convert the selected columns. Categories
+    /// are taken from the training set, in sorted order: categorical input
+    /// columns are one-hot expanded, and non-numeric target columns are
+    /// replaced by the index of each value's category
+    pub fn to_data(&self) -> Result<DataDe, OpenPbError> {
+        let paths: Vec<&String> = vec![
+            Some(&self.train),
+            self.validation.as_ref(),
+            self.test.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        let mut tables: Vec<CsvTable> = Vec::with_capacity(paths.len());
+        for path in &paths {
+            let table: CsvTable = match CsvTable::parse(&read_file(path)?) {
+                Ok(table) => table,
+                Err(error) => return Err(format!("{path}: {error}").into()),
+            };
+            if let Some(first) = tables.first() {
+                if table.header != first.header {
+                    return Err(
+                        format!("{path}: CSV header doesn't match the training file's").into(),
+                    );
+                }
+            }
+            tables.push(table);
+        }
+        let train: &CsvTable = &tables[0];
+
+        let targets: Vec<usize> = match &self.targets {
+            Some(targets) => train.select(targets)?,
+            None => train.header.len().checked_sub(1).into_iter().collect(),
+        };
+        let inputs: Vec<usize> = match &self.inputs {
+            Some(inputs) => train.select(inputs)?,
+            None => (0..train.header.len())
+                .filter(|column| !targets.contains(column))
+                .collect(),
+        };
+        if inputs.is_empty() || targets.is_empty() {
+            return Err("Tabular data needs at least one input and one target column".into());
+        }
+        if let Some(column) = inputs.iter().find(|column| targets.contains(column)) {
+            return Err(format!(
+                "Column \"{}\" is both an input and a target",
+                train.header[*column]
+            )
+            .into());
+        }
+        let categorical: Vec<usize> = match &self.categorical {
+            Some(categorical) => train.select(categorical)?,
+            None => inputs
+                .iter()
+                .copied()
+                .filter(|column| !train.is_numeric(*column))
+                .collect(),
+        };
+
+        let input_encodings: Vec<ColumnEncoding> = inputs
+            .iter()
+            .map(|&index| ColumnEncoding {
+                index,
+                kind: if categorical.contains(&index) {
+                    ColumnKind::OneHot(train.categories(index))
+                } else {
+                    ColumnKind::Numeric
+                },
+            })
+            .collect();
+        let target_encodings: Vec<ColumnEncoding> = targets
+            .iter()
+            .map(|&index| ColumnEncoding {
+                index,
+                kind: if train.is_numeric(index) {
+                    ColumnKind::Numeric
+                } else {
+                    ColumnKind::ClassIndex(train.categories(index))
+                },
+            })
+            .collect();
+
+        let mut splits: Vec<(Array2<f64>, Array2<f64>)> = Vec::with_capacity(tables.len());
+        for (path, table) in paths.iter().zip(&tables) {
+            let split = match (
+                table.encode(&input_encodings),
+                table.encode(&target_encodings),
+            ) {
+                (Ok(inputs), Ok(outputs)) => (inputs, outputs),
+                (Err(error), _) | (_, Err(error)) => return Err(format!("{path}: {error}").into()),
+            };
+            splits.push(split);
+        }
+
+        let mut splits = splits.into_iter();
+        let (train_inputs, train_outputs) = splits.next().unwrap();
+        let (test_inputs, test_outputs) = match self.validation {
+            Some(_) => splits.next().unwrap(),
+            None => (Array2::zeros((0, 0)), Array2::zeros((0, 0))),
+        };
+        let data_de: DataDe = DataDe::new(train_inputs, train_outputs, test_inputs, test_outputs)?;
+        match splits.next() {
+            Some((final_test_inputs, final_test_outputs)) => {
+                data_de.with_test_set(final_test_inputs, final_test_outputs)
+            }
+            None => Ok(data_de),
+        }
+    }
+}
+
+/// Load training data from CSV files, given in order: the training set,
+/// then the validation set and the hold-out test set (both optional).
+/// Every file needs the same header row, and every column except the
+/// label column is an input (categorical columns are one-hot expanded)
+///
+/// # Arguments
+///
+/// * `paths` - CSV files (`"-"` reads a file from stdin)
+/// * `label_column` - Name or index of the column with the output
+///   values (the last column when `None`)
+pub fn data_from_csv(paths: &[String], label_column: Option<&str>) -> Result<DataDe, OpenPbError> {
+    if paths.is_empty() || paths.len() > 3 {
+        return Err(
+            "CSV data needs a training file, plus optional validation and test files".into(),
+        );
+    }
+    let tabular_de = TabularDe {
+        train: paths[0].clone(),
+        validation: paths.get(1).cloned(),
+        test: paths.get(2).cloned(),
+        inputs: None,
+        targets: label_column.map(|column| vec![ColumnDe::Name(column.to_string())]),
+        categorical: None,
+    };
+    let data_de: DataDe = tabular_de.to_data()?;
+    if !data_de.has_validation_set() {
+        eprintln!("Warning: no validation set, metrics are computed on the training set");
+    }
+    Ok(data_de)
+}
+
+/// Read the contents of a file, or stdin for `"-"`
+///
+/// # Arguments
+///
+/// * `path` - File to read
+fn read_file(path: &str) -> Result<String, OpenPbError> {
+    if path == STDIO_PATH {
+        let mut contents = String::new();
+        if let Err(error) = std::io::stdin().read_to_string(&mut contents) {
+            return Err(format!("Failed to read data from stdin: {error}").into());
+        }
+        return Ok(contents);
+    }
+    match std::fs::read_to_string(path) {
+        Ok(result) => Ok(result),
+        _ => Err(format!("File {} missing or corrupted", path).into()),
+    }
+}
@@ -0,0 +1,197 @@
+use crate::error::OpenPbError;
+use serde::de::{DeserializeOwned, IgnoredAny};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// Smallest Jaro-Winkler similarity between a misspelled name and
+/// a valid one for the valid name to be suggested in its place
+const SUGGESTION_SIMILARITY: f64 = 0.8;
+
+/// Valid name most similar to a misspelled one, if any is similar enough
+/// to be a likely typo (names are compared case-insensitively)
+///
+/// # Arguments
+///
+/// * `name` - Name given in a config or data file
+/// * `candidates` - Valid names
+pub fn closest_name<'a>(name: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let name: String = name.to_lowercase();
+    candidates
+        .iter()
+        .map(|candidate| {
+            (
+                *candidate,
+                strsim::jaro_winkler(&name, &candidate.to_lowercase()),
+            )
+        })
+        .filter(|(_, similarity)| *similarity >= SUGGESTION_SIMILARITY)
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(candidate, _)| candidate)
+}
+
+/// Deserialize JSON, naming the field that failed (e.g. `layers[1].neurons`)
+/// when it doesn't match the expected format
+///
+/// # Arguments
+///
+/// * `json` - Raw JSON contents
+/// * `context` - What the JSON holds (e.g. "network config")
+pub fn from_json_path<T: DeserializeOwned>(json: &str, context: &str) -> Result<T, OpenPbError> {
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    let result: Result<T, serde_path_to_error::Error<serde_json::Error>> =
+        serde_path_to_error::deserialize(&mut deserializer);
+    let value: T = match result {
+        Ok(value) => value,
+        Err(error) => {
+            // Errors outside of any field (e.g. a syntax error) are at the root "."
+            let field: String = error.path().to_string();
+            return Err(OpenPbError::Json {
+                context: context.to_string(),
+                field: if field == "." { None } else { Some(field) },
+                source: error.into_inner(),
+            });
+        }
+    };
+    match deserializer.end() {
+        Ok(()) => Ok(value),
+        Err(source) => Err(OpenPbError::Json {
+            context: context.to_string(),
+            field: None,
+            source,
+        }),
+    }
+}
+
+/// Describe every field of a JSON value that its schema doesn't have.
+/// Unknown fields are ignored while deserializing, so a misspelled
+/// optional field (e.g. `dropout` instead of `dropout_rate`) would
+/// otherwise silently fall back to its default
+///
+/// # Arguments
+///
+/// * `value` - Deserialized JSON value
+/// * `schema` - JSON Schema generated for the value's type (see `schemars`)
+pub fn unknown_fields(value: &Value, schema: &Value) -> Vec<String> {
+    let empty: Map<String, Value> = Map::new();
+    let definitions: &Map<String, Value> = schema
+        .get("definitions")
+        .and_then(Value::as_object)
+        .unwrap_or(&empty);
+    let mut unknown: Vec<String> = vec![];
+    check_fields(value, schema, definitions, "", &mut unknown);
+    unknown
+}
+
+/// Same as `unknown_fields`, but only the top-level keys of a JSON
+/// object are checked, without building the (possibly large) value
+///
+/// # Arguments
+///
+/// * `json` - Raw JSON contents of an object
+/// * `schema` - JSON Schema generated for the object's type
+pub fn unknown_keys(json: &str, schema: &Value) -> Vec<String> {
+    let keys: BTreeMap<String, IgnoredAny> = match serde_json::from_str(json) {
+        Ok(keys) => keys,
+        // Anything that isn't an object is reported while deserializing
+        Err(_) => return vec![],
+    };
+    let value: Value = Value::Object(
+        keys.into_keys()
+            .map(|key| (key, Value::Null))
+            .collect::<Map<String, Value>>(),
+    );
+    unknown_fields(&value, schema)
+}
+
+/// Recursively collect the fields of a value that its schema doesn't have
+///
+/// # Arguments
+///
+/// * `value` - Part of the JSON value being checked
+/// * `schema` - Schema of that part
+/// * `definitions` - Definitions that `$ref`s in the schema point to
+/// * `path` - Path of the part (e.g. `layers[0]`, empty at the root)
+/// * `unknown` - Descriptions of the unknown fields found so far
+fn check_fields(
+    value: &Value,
+    schema: &Value,
+    definitions: &Map<String, Value>,
+    path: &str,
+    unknown: &mut Vec<String>,
+) {
+    let schema: &Value = match resolve(schema, definitions) {
+        Some(schema) => schema,
+        None => return,
+    };
+    match value {
+        Value::Object(object) => {
+            let properties: &Map<String, Value> =
+                match schema.get("properties").and_then(Value::as_object) {
+                    Some(properties) => properties,
+                    None => return,
+                };
+            // Types with a catch-all map of extra keys accept any field
+            let open: bool = schema
+                .get("additionalProperties")
+                .is_some_and(|additional| additional != &Value::Bool(false));
+            let names: Vec<&str> = properties.keys().map(String::as_str).collect();
+            for (key, field) in object {
+                let field_path: String = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                match properties.get(key) {
+                    Some(field_schema) => {
+                        check_fields(field, field_schema, definitions, &field_path, unknown)
+                    }
+                    None if open => {}
+                    None => unknown.push(match closest_name(key, &names) {
+                        Some(name) => {
+                            format!("unknown field {field_path} is ignored, did you mean '{name}'?")
+                        }
+                        None => format!("unknown field {field_path} is ignored"),
+                    }),
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items").filter(|items| items.is_object()) {
+                for (i, item) in items.iter().enumerate() {
+                    let item_path: String = format!("{path}[{i}]");
+                    check_fields(item, item_schema, definitions, &item_path, unknown);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Follow `$ref`s and single-option wrappers (e.g. the `anyOf` of an
+/// optional field) to the schema that describes a value's fields
+///
+/// # Arguments
+///
+/// * `schema` - Schema (or part of a schema) to resolve
+/// * `definitions` - Definitions that `$ref`s in the schema point to
+///
+/// Returns `None` when the schema has several possible shapes
+/// (e.g. untagged enums), since any of them might apply
+fn resolve<'a>(schema: &'a Value, definitions: &'a Map<String, Value>) -> Option<&'a Value> {
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        let name: &str = reference.trim_start_matches("#/definitions/");
+        return resolve(definitions.get(name)?, definitions);
+    }
+    for key in ["allOf", "anyOf", "oneOf"] {
+        if let Some(options) = schema.get(key).and_then(Value::as_array) {
+            let mut shapes = options
+                .iter()
+                .filter(|option| option.get("type") != Some(&Value::from("null")));
+            return match (shapes.next(), shapes.next()) {
+                (Some(shape), None) => resolve(shape, definitions),
+                _ => None,
+            };
+        }
+    }
+    Some(schema)
+}
@@ -0,0 +1,87 @@
+use super::json_de::NetworkDe;
+use crate::error::OpenPbError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Default number of folds the training set is split into to get
+/// the out-of-fold member predictions a stacking network trains on
+pub const DEFAULT_STACKING_FOLDS: usize = 5;
+
+/// Method used to combine the predictions of each ensemble member
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CombineDe {
+    /// Majority vote of the decoded predictions
+    Vote,
+
+    /// Mean of the raw network outputs, decoded
+    /// with the first member's encoder
+    Average,
+
+    /// Raw outputs of every member are fed into a separately
+    /// trained network that learns how to combine them
+    Stacking,
+}
+
+/// Deserialized values representing an ensemble of
+/// (differently configured) networks in JSON
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EnsembleDe {
+    /// Network setup of each member
+    pub members: Vec<NetworkDe>,
+
+    /// How member predictions are combined
+    pub combine: CombineDe,
+
+    /// Network setup of the stacking layer(s), required
+    /// when members are combined with stacking
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stacking: Option<NetworkDe>,
+
+    /// Number of folds the training set is split into for stacking
+    /// (at least 2, `DEFAULT_STACKING_FOLDS` when not given). Each member
+    /// is trained once per fold, on every other fold
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stacking_folds: Option<usize>,
+}
+
+impl EnsembleDe {
+    /// # Arguments
+    ///
+    /// * `ensemble_json` - Raw contents of JSON file containing
+    ///   the ensemble configuration
+    pub fn from_json(ensemble_json: &str) -> Result<EnsembleDe, OpenPbError> {
+        let ensemble_de: EnsembleDe = match serde_json::from_str(ensemble_json) {
+            Ok(ensemble_de) => ensemble_de,
+            Err(error) => return Err(format!("Invalid ensemble config: {error}").into()),
+        };
+
+        if ensemble_de.members.is_empty() {
+            return Err("Ensemble must have at least one member".into());
+        }
+        if let Some(folds) = ensemble_de.stacking_folds {
+            if folds < 2 {
+                return Err(format!("Stacking needs at least 2 folds, got {folds}").into());
+            }
+        }
+        match (ensemble_de.combine, &ensemble_de.stacking) {
+            (CombineDe::Stacking, None) => {
+                Err("Ensembles combined with stacking require a \"stacking\" network".into())
+            }
+            _ => Ok(ensemble_de),
+        }
+    }
+
+    /// True if the JSON describes an ensemble (has a "members" list)
+    /// instead of a single network
+    ///
+    /// # Arguments
+    ///
+    /// * `network_json` - Raw contents of the network config file
+    pub fn is_ensemble(network_json: &str) -> bool {
+        match serde_json::from_str::<Value>(network_json) {
+            Ok(value) => value.get("members").is_some(),
+            Err(_) => false,
+        }
+    }
+}
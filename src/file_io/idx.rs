@@ -0,0 +1,183 @@
+use super::json_de::DataDe;
+use crate::error::OpenPbError;
+use ndarray::Array2;
+use std::fs;
+
+/// Magic number data type for unsigned bytes (the only type MNIST uses)
+const IDX_UNSIGNED_BYTE: u8 = 0x08;
+
+/// Contents of an IDX file (the binary format used by MNIST and Fashion-MNIST)
+#[derive(Debug, Clone)]
+pub struct IdxArray {
+    /// Size of each dimension, outermost (number of items) first
+    pub dims: Vec<usize>,
+
+    /// Raw values in row-major order
+    pub data: Vec<u8>,
+}
+
+impl IdxArray {
+    /// Parse the magic number, dimension headers, and data of an IDX file
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Raw (uncompressed) contents of the IDX file
+    pub fn parse(bytes: &[u8]) -> Result<IdxArray, OpenPbError> {
+        if bytes.len() < 4 || bytes[0] != 0 || bytes[1] != 0 {
+            return Err("Invalid IDX magic number".into());
+        }
+        if bytes[2] != IDX_UNSIGNED_BYTE {
+            return Err(format!(
+                "Unsupported IDX data type 0x{:02x} (only unsigned bytes are supported)",
+                bytes[2]
+            )
+            .into());
+        }
+
+        // Each dimension is a big-endian 32 bit integer following the magic number
+        let num_dims: usize = bytes[3] as usize;
+        let header_len: usize = 4 + 4 * num_dims;
+        if bytes.len() < header_len {
+            return Err("IDX file ends before the dimension headers".into());
+        }
+        let dims: Vec<usize> = bytes[4..header_len]
+            .chunks_exact(4)
+            .map(|dim| u32::from_be_bytes([dim[0], dim[1], dim[2], dim[3]]) as usize)
+            .collect();
+
+        // Corrupted headers can claim more values than fit in memory, so
+        // the size is checked against the payload before it's copied
+        let data_len: usize = match dims
+            .iter()
+            .try_fold(1usize, |len, &dim| len.checked_mul(dim))
+        {
+            Some(data_len) => data_len,
+            None => {
+                return Err(format!("IDX dimensions {dims:?} are too large").into());
+            }
+        };
+        if bytes.len() - header_len != data_len {
+            return Err(format!(
+                "IDX file has {} data bytes, but its dimensions {:?} require {}",
+                bytes.len() - header_len,
+                dims,
+                data_len
+            )
+            .into());
+        }
+        Ok(IdxArray {
+            dims,
+            data: bytes[header_len..].to_vec(),
+        })
+    }
+
+    /// Read and parse an (uncompressed) IDX file
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the IDX file
+    pub fn read(path: &str) -> Result<IdxArray, OpenPbError> {
+        let bytes: Vec<u8> = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(error) => return Err(format!("Failed to read {path}: {error}").into()),
+        };
+        match IdxArray::parse(&bytes) {
+            Ok(idx_array) => Ok(idx_array),
+            Err(error) => Err(format!("{path}: {error}").into()),
+        }
+    }
+
+    /// Number of items (size of the outermost dimension)
+    pub fn len(&self) -> usize {
+        self.dims.first().copied().unwrap_or_default()
+    }
+
+    /// True if the file contains no items
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Flatten every item into a row with values normalized
+    /// to the range [0, 1] (e.g. pixel intensities)
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - Maximum number of items to keep (optional)
+    pub fn to_inputs(&self, limit: Option<usize>) -> Array2<f64> {
+        let rows: usize = limit.map_or(self.len(), |limit| limit.min(self.len()));
+        let cols: usize = self.dims.iter().skip(1).product();
+        let values: Vec<f64> = self.data[..rows * cols]
+            .iter()
+            .map(|&value| value as f64 / 255.0)
+            .collect();
+        Array2::from_shape_vec((rows, cols), values).unwrap()
+    }
+
+    /// Convert each (single value) item into a row with one
+    /// column, as expected by integer encoders (e.g. class labels)
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - Maximum number of items to keep (optional)
+    pub fn to_labels(&self, limit: Option<usize>) -> Array2<f64> {
+        let rows: usize = limit.map_or(self.len(), |limit| limit.min(self.len()));
+        let values: Vec<f64> = self.data[..rows]
+            .iter()
+            .map(|&value| value as f64)
+            .collect();
+        Array2::from_shape_vec((rows, 1), values).unwrap()
+    }
+}
+
+/// Load training and validation sets from IDX image and label files (e.g.
+/// MNIST or Fashion-MNIST). Pixel values are normalized to [0, 1], and
+/// labels are kept as integers for the `one_hot` encoder. Without
+/// validation files, the network is validated on the training set
+///
+/// # Arguments
+///
+/// * `train_images` - IDX file with the training images
+/// * `train_labels` - IDX file with the training labels
+/// * `test_images` - IDX file with the validation images (optional)
+/// * `test_labels` - IDX file with the validation labels (optional)
+pub fn data_from_idx(
+    train_images: &str,
+    train_labels: &str,
+    test_images: Option<&str>,
+    test_labels: Option<&str>,
+) -> Result<DataDe, OpenPbError> {
+    let (train_inputs, train_outputs) = read_set(train_images, train_labels)?;
+    let (test_inputs, test_outputs) = match (test_images, test_labels) {
+        (Some(test_images), Some(test_labels)) => read_set(test_images, test_labels)?,
+        (None, None) => (Array2::zeros((0, 0)), Array2::zeros((0, 0))),
+        _ => return Err("Validation images and labels must be given together".into()),
+    };
+    DataDe::new(train_inputs, train_outputs, test_inputs, test_outputs)
+}
+
+/// Read the inputs and outputs of a single set from its image and label files
+///
+/// # Arguments
+///
+/// * `images` - IDX file with one image (or other item) per sample
+/// * `labels` - IDX file with one label per sample
+fn read_set(images: &str, labels: &str) -> Result<(Array2<f64>, Array2<f64>), OpenPbError> {
+    let images_idx: IdxArray = IdxArray::read(images)?;
+    let labels_idx: IdxArray = IdxArray::read(labels)?;
+    if labels_idx.dims.len() != 1 {
+        return Err(format!(
+            "{labels} has dimensions {:?}, but labels must have one value per item",
+            labels_idx.dims
+        )
+        .into());
+    }
+    if images_idx.len() != labels_idx.len() {
+        return Err(format!(
+            "{images} has {} items, but {labels} has {} labels",
+            images_idx.len(),
+            labels_idx.len()
+        )
+        .into());
+    }
+    Ok((images_idx.to_inputs(None), labels_idx.to_labels(None)))
+}
@@ -0,0 +1,39 @@
+use crate::error::OpenPbError;
+use crate::file_io::json_de::{DataDe, NetworkDataDe, NetworkDe};
+use crate::file_io::sweep_de::SweepTrainingDe;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Deserialized training job submitted to the daemon. The network and
+/// data are embedded in the same format as the network and data files
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct JobDe {
+    /// Network config
+    pub network: Value,
+
+    /// Training and validation data
+    pub data: Value,
+
+    /// Number of epochs, runs, threads, and batching options
+    pub training: SweepTrainingDe,
+}
+
+impl JobDe {
+    /// # Arguments
+    ///
+    /// * `job_json` - Raw contents of the submitted job
+    pub fn from_json(job_json: &str) -> Result<JobDe, OpenPbError> {
+        match serde_json::from_str(job_json) {
+            Ok(job_de) => Ok(job_de),
+            Err(error) => Err(format!("Invalid job: {error}").into()),
+        }
+    }
+
+    /// Deserialize and check the job's network config and data
+    pub fn network_data(&self) -> Result<NetworkDataDe, OpenPbError> {
+        let data_de: DataDe = DataDe::from_json(&self.data.to_string())?;
+        let network_de: NetworkDe = NetworkDe::from_json(&self.network.to_string())?;
+        NetworkDataDe::from_config(Arc::new(data_de), network_de)
+    }
+}
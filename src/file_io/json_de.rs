@@ -1,62 +1,667 @@
-use crate::nn::functions::activation::{ActivationFn, LeakyReLU, ReLU, Sigmoid};
-use crate::nn::functions::cost::{Cost, MSE};
-use crate::nn::functions::encoder::{Encoder, OneHot};
-use crate::nn::functions::metric::{Accuracy, Metric};
-use crate::nn::functions::optimizer::{self, Adam, Optimizer, SGD};
+use super::diagnostics;
+use super::matrix_de::{deserialize_inputs, deserialize_matrix, MatrixDe};
+use super::model_de::Checkpoint;
+use super::registry;
+use super::sanity;
+use super::split::SplitDe;
+use super::time_series::{TimeSeriesDe, TimeSeriesFileDe};
+use crate::error::OpenPbError;
+use crate::nn::activations::DeadNeuronReinit;
+use crate::nn::averaging::AveragingConfig;
+use crate::nn::data_source::{self, DataMetadata, DataSource};
+use crate::nn::functions::activation::{
+    ActivationFn, LeakyReLU, Linear, ReLU, Sigmoid, Softmax, Swish, Tanh, ELU,
+};
+use crate::nn::functions::cost::{Cost, CrossEntropy, MSE};
+use crate::nn::functions::encoder::{Encoder, Identity, OneHot};
+use crate::nn::functions::metric::{Accuracy, Metric, RSquared, RMSE};
+use crate::nn::functions::optimizer::{self, AdaGrad, Adam, AdamW, Optimizer, RMSProp, SGD};
+use crate::nn::initializer::Initializer;
 use crate::nn::perceptron::Perceptron;
-use ndarray::Array2;
-use serde::Deserialize;
-use serde_json::{Map, Value};
-
-/// Deserialized values representing both input and output data in JSON
-#[derive(Deserialize, Debug)]
-struct DataDe {
-    /// Training set input data
+use crate::nn::pretrain::PretrainConfig;
+use crate::nn::regularization::Regularization;
+use ndarray::{concatenate, Array2, Axis};
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{InstanceType, Schema, SchemaObject};
+use schemars::{schema_for, JsonSchema};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::sync::Arc;
+
+/// Largest difference from 1 allowed in the sum of a soft target's
+/// probabilities (externally produced distributions are often rounded)
+const SOFT_TARGET_TOLERANCE: f64 = 1e-3;
+
+/// Deserialized values representing both input and output data in JSON.
+/// Each sample is stored as a row of the matrices, and cells may be
+/// integers or numeric strings as well as floats
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct DataDe {
+    /// Training set input data (image-shaped samples are flattened)
+    #[serde(deserialize_with = "deserialize_inputs")]
+    #[schemars(with = "MatrixDe")]
     train_inputs: Array2<f64>,
 
     /// Training set output data
+    #[serde(deserialize_with = "deserialize_matrix")]
+    #[schemars(with = "MatrixDe")]
     train_outputs: Array2<f64>,
 
-    /// Validation set input data
+    /// Validation set input data (image-shaped samples are flattened).
+    /// Empty when the data has no validation set
+    #[serde(
+        default = "no_samples",
+        deserialize_with = "deserialize_inputs",
+        skip_serializing_if = "has_no_samples"
+    )]
+    #[schemars(with = "MatrixDe")]
     test_inputs: Array2<f64>,
 
-    /// Validation set output data
+    /// Validation set output data. Empty when the data has no validation set
+    #[serde(
+        default = "no_samples",
+        deserialize_with = "deserialize_matrix",
+        skip_serializing_if = "has_no_samples"
+    )]
+    #[schemars(with = "MatrixDe")]
     test_outputs: Array2<f64>,
+
+    /// Hold-out test set input data, evaluated once after training
+    /// (image-shaped samples are flattened). Empty when absent
+    #[serde(
+        default = "no_samples",
+        deserialize_with = "deserialize_inputs",
+        skip_serializing_if = "has_no_samples"
+    )]
+    #[schemars(with = "MatrixDe")]
+    final_test_inputs: Array2<f64>,
+
+    /// Hold-out test set output data. Empty when absent
+    #[serde(
+        default = "no_samples",
+        deserialize_with = "deserialize_matrix",
+        skip_serializing_if = "has_no_samples"
+    )]
+    #[schemars(with = "MatrixDe")]
+    final_test_outputs: Array2<f64>,
+
+    /// Relative sampling weight of each training sample (optional),
+    /// used by the weighted minibatch sampler
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    train_weights: Option<Vec<f64>>,
+
+    /// Partition of the training set into training and validation sets
+    /// (optional), for data files without a validation set. Applied
+    /// while loading, so it's never part of loaded data
+    #[serde(default, skip_serializing)]
+    split: Option<SplitDe>,
+
+    /// Whether or not the training outputs are soft targets: probability
+    /// distributions in the network's output format that skip the encoder.
+    /// The validation and test outputs stay human-readable (e.g. class labels)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    soft_targets: bool,
+}
+
+/// Matrix without any samples, used in place of a missing validation set
+fn no_samples() -> Array2<f64> {
+    Array2::zeros((0, 0))
+}
+
+/// Whether or not a matrix has no samples (rows)
+///
+/// # Arguments
+///
+/// * `matrix` - Matrix with one sample per row
+fn has_no_samples(matrix: &Array2<f64>) -> bool {
+    matrix.nrows() == 0
+}
+
+impl DataDe {
+    /// # Arguments
+    ///
+    /// * `data_json` - Raw contents of JSON file containing training and
+    ///   validation data, or a `"time_series"` that's split into windows
+    pub fn from_json(data_json: &str) -> Result<DataDe, OpenPbError> {
+        // Time series files are converted into sliding-window samples
+        if let Ok(TimeSeriesFileDe {
+            time_series: Some(time_series),
+        }) = serde_json::from_str(data_json)
+        {
+            return TimeSeriesDe::from_value(time_series)?.to_data();
+        }
+
+        // Deserialize raw file contents into struct values
+        let mut data_de: DataDe = diagnostics::from_json_path(data_json, "data JSON")?;
+        for warning in diagnostics::unknown_keys(data_json, &json!(schema_for!(DataDe))) {
+            eprintln!("Warning: {warning}");
+        }
+        data_de.check_rows()?;
+        data_de.check_values()?;
+        data_de.check_soft_targets()?;
+        match data_de.split.take() {
+            Some(split) => Ok(data_de.split_validation(&split)?),
+            None => Ok(data_de),
+        }
+    }
+
+    /// Hold out part of the training set as the validation set. Only data
+    /// without a validation set can be split. Training weights are kept
+    /// for the samples that stay in the training set
+    ///
+    /// # Arguments
+    ///
+    /// * `split` - Fraction of samples held out, shuffle seed, and stratification
+    pub fn split_validation(mut self, split: &SplitDe) -> Result<DataDe, OpenPbError> {
+        if self.has_validation_set() {
+            return Err(
+                "Data with a validation set (test_inputs and test_outputs) can't be split".into(),
+            );
+        }
+        // Held out soft targets couldn't be compared to decoded predictions
+        if self.soft_targets {
+            return Err(
+                "Data with soft targets can't be split, since validation outputs must be labels"
+                    .into(),
+            );
+        }
+        let validation_rows: Vec<usize> = split.validation_rows(&self.train_outputs)?;
+        let training_rows: Vec<usize> = (0..self.train_inputs.nrows())
+            .filter(|row| validation_rows.binary_search(row).is_err())
+            .collect();
+
+        self.test_inputs = self.train_inputs.select(Axis(0), &validation_rows);
+        self.test_outputs = self.train_outputs.select(Axis(0), &validation_rows);
+        self.train_inputs = self.train_inputs.select(Axis(0), &training_rows);
+        self.train_outputs = self.train_outputs.select(Axis(0), &training_rows);
+        if let Some(weights) = &self.train_weights {
+            self.train_weights = Some(training_rows.iter().map(|row| weights[*row]).collect());
+        }
+        Ok(self)
+    }
+
+    /// Create from matrices where each sample is stored as a row
+    ///
+    /// # Arguments
+    ///
+    /// * `train_inputs` - Training set input data
+    /// * `train_outputs` - Training set output data
+    /// * `test_inputs` - Validation set input data
+    /// * `test_outputs` - Validation set output data
+    pub fn new(
+        train_inputs: Array2<f64>,
+        train_outputs: Array2<f64>,
+        test_inputs: Array2<f64>,
+        test_outputs: Array2<f64>,
+    ) -> Result<DataDe, OpenPbError> {
+        let data_de = DataDe {
+            train_inputs,
+            train_outputs,
+            test_inputs,
+            test_outputs,
+            final_test_inputs: no_samples(),
+            final_test_outputs: no_samples(),
+            train_weights: None,
+            split: None,
+            soft_targets: false,
+        };
+        data_de.check_rows()?;
+        data_de.check_values()?;
+        Ok(data_de)
+    }
+
+    /// Add a hold-out test set, where each sample is stored as a row
+    ///
+    /// # Arguments
+    ///
+    /// * `final_test_inputs` - Hold-out test set input data
+    /// * `final_test_outputs` - Hold-out test set output data
+    pub fn with_test_set(
+        mut self,
+        final_test_inputs: Array2<f64>,
+        final_test_outputs: Array2<f64>,
+    ) -> Result<DataDe, OpenPbError> {
+        self.final_test_inputs = final_test_inputs;
+        self.final_test_outputs = final_test_outputs;
+        self.check_rows()?;
+        self.check_values()?;
+        Ok(self)
+    }
+
+    /// Mark the training outputs as soft targets (probability
+    /// distributions in the network's output format)
+    ///
+    /// # Arguments
+    ///
+    /// * `soft_targets` - Whether or not the training outputs are soft targets
+    pub fn with_soft_targets(mut self, soft_targets: bool) -> Result<DataDe, OpenPbError> {
+        self.soft_targets = soft_targets;
+        self.check_soft_targets()?;
+        Ok(self)
+    }
+
+    /// Concatenate the training and validation sets of several datasets
+    /// (e.g. sharded exports) into a single dataset
+    ///
+    /// # Arguments
+    ///
+    /// * `parts` - Datasets to merge, in order. Every part must have
+    ///   the same number of input and output columns
+    pub fn concatenate(parts: Vec<DataDe>) -> Result<DataDe, OpenPbError> {
+        let first: &DataDe = match parts.first() {
+            Some(first) => first,
+            None => return Err("No data files given".into()),
+        };
+        let input_cols: usize = first.train_inputs.ncols();
+        let output_cols: usize = first.train_outputs.ncols();
+        for (i, part) in parts.iter().enumerate() {
+            let shapes: [(&str, usize, usize); 6] = [
+                ("training inputs", part.train_inputs.ncols(), input_cols),
+                ("training outputs", part.train_outputs.ncols(), output_cols),
+                ("validation inputs", part.test_inputs.ncols(), input_cols),
+                ("validation outputs", part.test_outputs.ncols(), output_cols),
+                ("test inputs", part.final_test_inputs.ncols(), input_cols),
+                ("test outputs", part.final_test_outputs.ncols(), output_cols),
+            ];
+            for (label, cols, expected_cols) in shapes {
+                // Parts without a validation or test set have no columns to compare
+                let missing_split: bool = cols == 0 && !label.starts_with("training");
+                if cols != expected_cols && !missing_split {
+                    return Err(format!(
+                        "Data file {} has {cols} columns of {label}, expected {expected_cols}",
+                        i + 1
+                    )
+                    .into());
+                }
+            }
+        }
+
+        // Weights are only kept when every part has them
+        let train_weights: Option<Vec<f64>> = match parts
+            .iter()
+            .filter(|part| part.train_weights.is_some())
+            .count()
+        {
+            0 => None,
+            count if count == parts.len() => Some(
+                parts
+                    .iter()
+                    .flat_map(|part| part.train_weights.iter().flatten().copied())
+                    .collect(),
+            ),
+            _ => return Err("Either every data file or none must have train_weights".into()),
+        };
+
+        let soft_targets: bool = first.soft_targets;
+        if parts.iter().any(|part| part.soft_targets != soft_targets) {
+            return Err("Either every data file or none must have soft_targets".into());
+        }
+
+        let merge = |label: &str,
+                     matrix: fn(&DataDe) -> &Array2<f64>|
+         -> Result<Array2<f64>, OpenPbError> {
+            let views: Vec<_> = parts
+                .iter()
+                .map(|part| matrix(part).view())
+                .filter(|view| view.nrows() > 0)
+                .collect();
+            if views.is_empty() {
+                return Ok(no_samples());
+            }
+            match concatenate(Axis(0), &views) {
+                Ok(merged) => Ok(merged),
+                Err(error) => {
+                    Err(format!("Failed to merge the {label} of the data files: {error}").into())
+                }
+            }
+        };
+        Ok(DataDe {
+            train_inputs: merge("training inputs", |part| &part.train_inputs)?,
+            train_outputs: merge("training outputs", |part| &part.train_outputs)?,
+            test_inputs: merge("validation inputs", |part| &part.test_inputs)?,
+            test_outputs: merge("validation outputs", |part| &part.test_outputs)?,
+            final_test_inputs: merge("test inputs", |part| &part.final_test_inputs)?,
+            final_test_outputs: merge("test outputs", |part| &part.final_test_outputs)?,
+            train_weights,
+            split: None,
+            soft_targets,
+        })
+    }
+
+    /// Read and merge one or more data JSON files
+    ///
+    /// # Arguments
+    ///
+    /// * `paths` - JSON files containing training and validation data
+    ///   (`"-"` reads a file from stdin)
+    #[cfg(feature = "file_io")]
+    pub fn from_files(paths: &[String]) -> Result<DataDe, OpenPbError> {
+        use super::csv_de::{TabularDe, TabularFileDe};
+        use super::split::SplitFileDe;
+        use std::io::Read;
+
+        let mut parts: Vec<DataDe> = Vec::with_capacity(paths.len());
+        for path in paths {
+            let data_json: String = if path == super::save_output::STDIO_PATH {
+                let mut data_json = String::new();
+                if let Err(source) = std::io::stdin().read_to_string(&mut data_json) {
+                    return Err(OpenPbError::Io {
+                        path: "stdin".to_string(),
+                        source,
+                    });
+                }
+                data_json
+            } else {
+                match std::fs::read_to_string(path) {
+                    Ok(result) => result,
+                    Err(source) => {
+                        return Err(OpenPbError::Io {
+                            path: path.clone(),
+                            source,
+                        })
+                    }
+                }
+            };
+            // Tabular data files select columns of CSV files
+            if let Ok(TabularFileDe {
+                tabular: Some(tabular),
+            }) = serde_json::from_str(&data_json)
+            {
+                let split: Option<Value> = match serde_json::from_str(&data_json) {
+                    Ok(SplitFileDe { split }) => split,
+                    Err(_) => None,
+                };
+                let data_de: Result<DataDe, OpenPbError> = TabularDe::from_value(tabular)
+                    .and_then(|tabular_de| tabular_de.to_data())
+                    .and_then(|data_de| match split {
+                        Some(split) => data_de.split_validation(&SplitDe::from_value(split)?),
+                        None => Ok(data_de),
+                    });
+                match data_de {
+                    Ok(data_de) => parts.push(data_de),
+                    Err(error) => return Err(error.in_file(path)),
+                }
+                continue;
+            }
+            match DataDe::from_json(&data_json) {
+                Ok(data_de) => parts.push(data_de),
+                Err(error) => return Err(error.in_file(path)),
+            }
+        }
+        let data_de: DataDe = if parts.len() == 1 {
+            parts.remove(0)
+        } else {
+            let data_de: DataDe = DataDe::concatenate(parts)?;
+            data_de.check_values()?;
+            data_de
+        };
+        if !data_de.has_validation_set() {
+            eprintln!("Warning: no validation set, metrics are computed on the training set");
+        }
+        Ok(data_de)
+    }
+
+    /// Whether or not the data includes a validation set. Without one,
+    /// the training set stands in for it (see `DataSource::validation_set`)
+    pub fn has_validation_set(&self) -> bool {
+        self.test_inputs.nrows() > 0
+    }
+
+    /// Fail on NaN/Inf cells, and warn about constant columns and
+    /// duplicate rows, naming the offending rows and columns
+    fn check_values(&self) -> Result<(), OpenPbError> {
+        let matrices: [(&str, &Array2<f64>); 6] = [
+            ("training inputs", &self.train_inputs),
+            ("training outputs", &self.train_outputs),
+            ("validation inputs", &self.test_inputs),
+            ("validation outputs", &self.test_outputs),
+            ("test inputs", &self.final_test_inputs),
+            ("test outputs", &self.final_test_outputs),
+        ];
+        for (label, values) in matrices {
+            sanity::check_finite(label, values)?;
+        }
+        if let Some(weights) = &self.train_weights {
+            if let Some(row) = weights
+                .iter()
+                .position(|weight| !(weight.is_finite() && *weight >= 0.0))
+            {
+                return Err(format!(
+                    "Training weight of row {row} ({}) isn't a finite, non-negative number",
+                    weights[row]
+                )
+                .into());
+            }
+            if weights.iter().all(|weight| *weight == 0.0) {
+                return Err("Every training weight is 0".into());
+            }
+        }
+
+        // Only the inputs are checked, since constant or repeated
+        // outputs are expected (e.g. class labels)
+        let warnings = [
+            sanity::constant_columns("training inputs", &self.train_inputs),
+            sanity::duplicate_rows("training inputs", &self.train_inputs),
+        ];
+        for warning in warnings.iter().flatten() {
+            eprintln!("Warning: {warning}");
+        }
+        Ok(())
+    }
+
+    /// Check that soft targets are probability distributions (non-negative
+    /// values that sum to 1), and that there are validation labels to
+    /// score the network with, since soft targets can't stand in for them
+    fn check_soft_targets(&self) -> Result<(), OpenPbError> {
+        if !self.soft_targets {
+            return Ok(());
+        }
+        if !self.has_validation_set() {
+            return Err(
+                "Data with soft targets needs a validation set (test_inputs and test_outputs)"
+                    .into(),
+            );
+        }
+        for (row, output) in self.train_outputs.rows().into_iter().enumerate() {
+            let total: f64 = output.sum();
+            if output.iter().any(|value| *value < 0.0)
+                || (total - 1.0).abs() > SOFT_TARGET_TOLERANCE
+            {
+                return Err(format!(
+                    "Soft target of training row {row} isn't a probability distribution \
+                     (values must be non-negative and sum to 1, got a sum of {total})"
+                )
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that every input matrix has the same number
+    /// of rows (samples) as its corresponding output matrix
+    fn check_rows(&self) -> Result<(), OpenPbError> {
+        let sets: [(&str, &Array2<f64>, &Array2<f64>); 3] = [
+            ("training", &self.train_inputs, &self.train_outputs),
+            ("validation", &self.test_inputs, &self.test_outputs),
+            ("test", &self.final_test_inputs, &self.final_test_outputs),
+        ];
+        for (label, inputs, outputs) in sets {
+            if inputs.nrows() != outputs.nrows() {
+                return Err(OpenPbError::Shape {
+                    context: format!("Rows of {label} outputs (one per {label} input)"),
+                    expected: inputs.nrows(),
+                    found: outputs.nrows(),
+                });
+            }
+        }
+
+        if let Some(weights) = &self.train_weights {
+            if weights.len() != self.train_inputs.nrows() {
+                return Err(OpenPbError::Shape {
+                    context: "Number of train_weights (one per training input)".to_string(),
+                    expected: self.train_inputs.nrows(),
+                    found: weights.len(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl DataDe {
+    /// Copy the training and validation sets of any
+    /// data source into (serializable) matrices
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Source of training and validation data
+    pub fn from_source(data: &dyn DataSource) -> DataDe {
+        let (train_inputs, train_outputs) = data.training_set();
+        // Sources without a validation set stand in with the training set
+        let (test_inputs, test_outputs) = if data.metadata().validation_samples == 0 {
+            (no_samples(), no_samples())
+        } else {
+            data.validation_set()
+        };
+        let (final_test_inputs, final_test_outputs) = data
+            .test_set()
+            .unwrap_or_else(|| (no_samples(), no_samples()));
+
+        // Data sources store input vectors as columns
+        DataDe {
+            train_inputs: train_inputs.reversed_axes(),
+            train_outputs,
+            test_inputs: test_inputs.reversed_axes(),
+            test_outputs,
+            final_test_inputs: final_test_inputs.reversed_axes(),
+            final_test_outputs,
+            train_weights: data.sample_weights(),
+            split: None,
+            soft_targets: data.soft_targets(),
+        }
+    }
+}
+
+impl DataSource for DataDe {
+    fn metadata(&self) -> DataMetadata {
+        DataMetadata {
+            train_samples: self.train_inputs.nrows(),
+            validation_samples: self.test_inputs.nrows(),
+            test_samples: self.final_test_inputs.nrows(),
+            input_size: self.train_inputs.ncols(),
+            output_size: self.train_outputs.ncols(),
+        }
+    }
+
+    fn training_set(&self) -> (Array2<f64>, Array2<f64>) {
+        (
+            self.train_inputs.t().to_owned(),
+            self.train_outputs.to_owned(),
+        )
+    }
+
+    fn validation_set(&self) -> (Array2<f64>, Array2<f64>) {
+        // Quick benchmarks without held-out data are validated on the training set
+        if !self.has_validation_set() {
+            return self.training_set();
+        }
+        (
+            self.test_inputs.t().to_owned(),
+            self.test_outputs.to_owned(),
+        )
+    }
+
+    fn test_set(&self) -> Option<(Array2<f64>, Array2<f64>)> {
+        if self.final_test_inputs.nrows() == 0 {
+            return None;
+        }
+        Some((
+            self.final_test_inputs.t().to_owned(),
+            self.final_test_outputs.to_owned(),
+        ))
+    }
+
+    fn sample_weights(&self) -> Option<Vec<f64>> {
+        self.train_weights.clone()
+    }
+
+    fn soft_targets(&self) -> bool {
+        self.soft_targets
+    }
 }
 
 /// Deserialized values representing a single Layer in JSON
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 struct LayerDe {
     /// Number of neurons
     neurons: usize,
 
     /// Dropout chance (for regularization)
+    #[serde(skip_serializing_if = "Option::is_none")]
     dropout_rate: Option<f32>,
 
+    /// Weight initialization scheme (picked from the activation when omitted)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    initializer: Option<Initializer>,
+
+    /// Periodic re-initialization of dead neurons during training (optional)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reinit_dead: Option<DeadNeuronReinit>,
+
     /// Name of activation function
+    #[schemars(schema_with = "activation_name")]
     activation: String,
+
+    /// Shape parameter of the activation function (ELU's alpha, 1.0 when omitted)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    alpha: Option<f64>,
+
+    /// Whether or not the activation values are batch normalized
+    /// before the activation function is applied
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    batch_norm: bool,
 }
 
 /// Deserialized values representing the Optimizer in JSON
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 struct OptimizerDe {
     /// Name of the optimization method
+    #[schemars(schema_with = "optimizer_name")]
     name: String,
 
     /// Learning rate constant
     learning_rate: f64,
 
     /// Optional primary momentum constant
+    #[serde(skip_serializing_if = "Option::is_none")]
     beta1: Option<f64>,
 
     /// Optional secondary momentum constant
+    #[serde(skip_serializing_if = "Option::is_none")]
     beta2: Option<f64>,
+
+    /// Optional decay rate of RMSProp's running average of squared gradients
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    decay: Option<f64>,
+
+    /// Optional AdamW weight decay (fraction of each weight removed per step)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    weight_decay: Option<f64>,
+
+    /// Optional Polyak averaging of the weights during training
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    averaging: Option<AveragingConfig>,
+
+    /// Any other constructor arguments (used by registered optimizers)
+    #[serde(flatten)]
+    args: Map<String, Value>,
 }
 
 /// Deserialized values representing the Encoder in JSON
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 struct EncoderDe {
     /// Name of the Decoder
+    #[schemars(schema_with = "encoder_name")]
     name: String,
 
     /// Constructor arguments
@@ -64,19 +669,36 @@ struct EncoderDe {
 }
 
 /// Deserialized values representing the evaluation Metric in JSON
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 struct MetricDe {
     /// Name of the Metric
+    #[schemars(schema_with = "metric_name")]
     name: String,
 
     /// Constructor arguments
     args: Map<String, Value>,
 }
 
+/// Deserialized values representing autoencoder pre-training in JSON
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+struct PretrainDe {
+    /// Number of epochs the autoencoder is trained for
+    epochs: usize,
+
+    /// Optional batch size (defaults to the supervised batch size)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    batch_size: Option<usize>,
+
+    /// Optional optimizer (defaults to the network's optimizer)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    optimizer: Option<OptimizerDe>,
+}
+
 /// Deserialized values representing the Network setup in JSON
-#[derive(Deserialize, Debug, Clone)]
-struct NetworkDe {
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct NetworkDe {
     /// Cost function name
+    #[schemars(schema_with = "cost_name")]
     cost: String,
 
     /// Hidden layer values
@@ -90,22 +712,137 @@ struct NetworkDe {
 
     /// Metric values
     metric: MetricDe,
+
+    /// Optional autoencoder pre-training of the hidden layers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pretrain: Option<PretrainDe>,
+
+    /// Dropout chance of the input features (for regularization)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    input_dropout: Option<f32>,
+
+    /// Optional L1 and L2 penalties on the weights
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    regularization: Option<Regularization>,
+
+    /// Whether or not every layer's dropout stream is derived from the seed
+    /// and the layer's index, so adding or removing a layer doesn't change
+    /// the dropout pattern of the others
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    independent_dropout: bool,
 }
 
-#[derive(Clone)]
-/// Container for all deserialized data needed to train a network
-pub struct NetworkDataDe {
-    /// Training set input data
-    pub train_inputs: Array2<f64>,
+impl NetworkDe {
+    /// # Arguments
+    ///
+    /// * `network_json` - Raw contents of JSON file containg
+    ///   network parameters
+    pub fn from_json(network_json: &str) -> Result<NetworkDe, OpenPbError> {
+        let network_de: NetworkDe = diagnostics::from_json_path(network_json, "network config")?;
+        // Misspelled optional fields would otherwise silently fall back to their defaults
+        if let Ok(network) = serde_json::from_str::<Value>(network_json) {
+            for warning in diagnostics::unknown_fields(&network, &json!(schema_for!(NetworkDe))) {
+                eprintln!("Warning: {warning}");
+            }
+        }
+        Ok(network_de)
+    }
 
-    /// Training set output data
-    pub train_outputs: Array2<f64>,
+    /// Check that every layer has neurons, and that the output layer has
+    /// one neuron per encoded output, before any network is built
+    ///
+    /// # Arguments
+    ///
+    /// * `output_size` - Size of each encoded output vector
+    pub fn check_layers(&self, output_size: usize) -> Result<(), OpenPbError> {
+        let last: usize = match self.layers.len().checked_sub(1) {
+            Some(last) => last,
+            None => return Err("Network config needs at least one layer".into()),
+        };
+        if let Some(index) = self.layers.iter().position(|layer| layer.neurons == 0) {
+            return Err(format!("layers[{index}].neurons must be at least 1").into());
+        }
+        if self.layers[last].neurons != output_size {
+            return Err(OpenPbError::Shape {
+                context: format!(
+                    "Neurons of the output layer (layers[{last}].neurons, one per encoded output)"
+                ),
+                expected: output_size,
+                found: self.layers[last].neurons,
+            });
+        }
+        Ok(())
+    }
+
+    /// Create the output encoder named in the config. Encoders with
+    /// state (e.g. target scaling) aren't fit to any data
+    pub fn encoder(&self) -> Result<Box<dyn Encoder>, OpenPbError> {
+        match encoder_from_str(&self.encoder) {
+            Some(value) => Ok(value),
+            None => Err(unknown_name(
+                "encoder",
+                &self.encoder.name,
+                "encoder.name",
+                &ENCODER_NAMES,
+            )),
+        }
+    }
+}
+
+/// Deserialized values representing the shared layers (trunk)
+/// of a network with multiple output heads in JSON
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TrunkDe {
+    /// Shared layer values
+    layers: Vec<LayerDe>,
+
+    /// Optimizer values of the shared layers
+    optimizer: OptimizerDe,
+}
+
+impl TrunkDe {
+    /// Create the optimizer of the shared layers
+    pub fn optimizer(&self) -> Result<Box<dyn Optimizer>, OpenPbError> {
+        match optimizer_from_str(&self.optimizer) {
+            Some(value) => Ok(value),
+            None => Err(unknown_name(
+                "trunk optimizer",
+                &self.optimizer.name,
+                "trunk.optimizer.name",
+                &OPTIMIZER_NAMES,
+            )),
+        }
+    }
 
-    /// Validation set input data
-    pub test_inputs: Array2<f64>,
+    /// Create a new Perceptron from the shared layers, whose initial
+    /// weights, dropout, and shuffling are derived from a seed
+    ///
+    /// # Arguments
+    ///
+    /// * `input_shape` - Size of each input vector and number of training samples
+    /// * `seed` - Seed of the Perceptron's random number generator
+    pub fn create_seeded_network(
+        &self,
+        input_shape: (usize, usize),
+        seed: u64,
+    ) -> Result<Perceptron, OpenPbError> {
+        if self.layers.is_empty() {
+            return Err("Trunk must have at least one layer".into());
+        }
+        add_layers(
+            Perceptron::with_seed(seed),
+            &self.layers,
+            "trunk.layers",
+            input_shape,
+        )
+    }
+}
 
-    /// Validation set output data
-    pub test_outputs: Array2<f64>,
+#[derive(Clone)]
+/// Container for all deserialized data needed to train a network
+pub struct NetworkDataDe {
+    /// Training and validation data (shared between runs)
+    pub data: Arc<dyn DataSource>,
 
     /// Network cost function
     pub cost: Box<dyn Cost>,
@@ -119,111 +856,344 @@ pub struct NetworkDataDe {
     /// Output encoder
     pub encoder: Box<dyn Encoder>,
 
+    /// Autoencoder pre-training of the hidden layers (if configured)
+    pub pretrain: Option<PretrainConfig>,
+
+    /// Polyak averaging of the weights during training (if configured)
+    pub averaging: Option<AveragingConfig>,
+
     /// Deserailized paramaters for network creation
     network_de: NetworkDe,
+
+    /// Trained network whose weights and biases every new
+    /// network starts from (only present when resuming)
+    initial_network: Option<Perceptron>,
 }
 
 impl NetworkDataDe {
     /// # Arguments
     ///
     /// * `data_json` - Raw contents of JSON file containing
-    /// training and validation data
+    ///   training and validation data
     /// * `network_json` - Raw contents of JSON file containg
-    /// network parameters
+    ///   network parameters
     pub fn from_json<'a>(
         data_json: &'a str,
         network_json: &'a str,
-    ) -> Result<NetworkDataDe, String> {
-        // Deserialize raw file contents into struct values
-        let data_de: DataDe = serde_json::from_str(data_json).unwrap();
-        let network_de: NetworkDe = serde_json::from_str(network_json).unwrap();
-
-        // Get row counts for training input and output data
-        let input_rows: usize = data_de.train_inputs.nrows();
-        let output_rows: usize = data_de.train_outputs.nrows();
-
-        // Check size of validation data sets
-        if input_rows != output_rows {
-            return Err(format!("Number of rows for training inputs ({}) != number of rows for training outputs ({})", input_rows, output_rows));
-        }
-
-        // Get row counts for validation input and output data
-        let input_rows: usize = data_de.test_inputs.nrows();
-        let output_rows: usize = data_de.test_outputs.nrows();
+    ) -> Result<NetworkDataDe, OpenPbError> {
+        let data_de: DataDe = DataDe::from_json(data_json)?;
+        NetworkDataDe::from_source(Arc::new(data_de), network_json)
+    }
 
-        // Check size of validation data sets
-        if input_rows != output_rows {
-            return Err(format!("Number of rows for validation inputs ({}) != number of rows for validation outputs ({})", input_rows, output_rows));
-        }
+    /// # Arguments
+    ///
+    /// * `data` - Source of training and validation data
+    /// * `network_json` - Raw contents of JSON file containg
+    ///   network parameters
+    pub fn from_source(
+        data: Arc<dyn DataSource>,
+        network_json: &str,
+    ) -> Result<NetworkDataDe, OpenPbError> {
+        // Deserialize raw file contents into struct values
+        let network_de: NetworkDe = NetworkDe::from_json(network_json)?;
+        NetworkDataDe::from_config(data, network_de)
+    }
 
-        let cost: Box<dyn Cost> = match cost_from_str(network_de.cost.to_lowercase()) {
-            Some(value) => value,
-            None => return Err("Invalid cost function name".to_string()),
+    /// # Arguments
+    ///
+    /// * `data` - Source of training and validation data
+    /// * `network_de` - Previously deserialized network parameters
+    pub fn from_config(
+        data: Arc<dyn DataSource>,
+        network_de: NetworkDe,
+    ) -> Result<NetworkDataDe, OpenPbError> {
+        let output_activation: String = match network_de.layers.last() {
+            Some(layer) => layer.activation.to_lowercase(),
+            None => String::new(),
         };
+        let cost: Box<dyn Cost> =
+            match cost_from_str(network_de.cost.to_lowercase(), &output_activation) {
+                Some(value) => value,
+                None => {
+                    return Err(unknown_name(
+                        "cost function",
+                        &network_de.cost,
+                        "cost",
+                        &COST_NAMES,
+                    ))
+                }
+            };
         let metric: Box<dyn Metric> = match metric_from_str(&network_de.metric) {
             Some(value) => value,
-            None => return Err("Invalid metric name".to_string()),
-        };
-        let encoder: Box<dyn Encoder> = match encoder_from_str(&network_de.encoder) {
-            Some(value) => value,
-            None => return Err("Invalid decoder name".to_string()),
+            None => {
+                return Err(unknown_name(
+                    "metric",
+                    &network_de.metric.name,
+                    "metric.name",
+                    &METRIC_NAMES,
+                ))
+            }
         };
+        let mut encoder: Box<dyn Encoder> = network_de.encoder()?;
+        // Outputs the encoder can't represent would fail partway through training
+        let output_size: usize = data_source::prepare_encoder(data.as_ref(), encoder.as_mut())?;
+        network_de.check_layers(output_size)?;
         let optimizer: Box<dyn Optimizer> = match optimizer_from_str(&network_de.optimizer) {
             Some(value) => value,
-            None => return Err("Invalid activation function name".to_string()),
+            None => {
+                return Err(unknown_name(
+                    "optimizer",
+                    &network_de.optimizer.name,
+                    "optimizer.name",
+                    &OPTIMIZER_NAMES,
+                ))
+            }
+        };
+
+        let pretrain: Option<PretrainConfig> = match &network_de.pretrain {
+            Some(pretrain_de) => {
+                let (optimizer_de, optimizer_field): (&OptimizerDe, &str) =
+                    match &pretrain_de.optimizer {
+                        Some(optimizer_de) => (optimizer_de, "pretrain.optimizer.name"),
+                        None => (&network_de.optimizer, "optimizer.name"),
+                    };
+                let pretrain_optimizer: Box<dyn Optimizer> = match optimizer_from_str(optimizer_de)
+                {
+                    Some(value) => value,
+                    None => {
+                        return Err(unknown_name(
+                            "pre-training optimizer",
+                            &optimizer_de.name,
+                            optimizer_field,
+                            &OPTIMIZER_NAMES,
+                        ))
+                    }
+                };
+                Some(PretrainConfig {
+                    epochs: pretrain_de.epochs,
+                    batch_size: pretrain_de.batch_size,
+                    optimizer: pretrain_optimizer,
+                })
+            }
+            None => None,
         };
 
         Ok(NetworkDataDe {
-            train_inputs: data_de.train_inputs,
-            train_outputs: data_de.train_outputs,
-            test_inputs: data_de.test_inputs,
-            test_outputs: data_de.test_outputs,
+            data,
             cost,
             metric,
             encoder,
             optimizer,
+            pretrain,
+            averaging: network_de.optimizer.averaging,
             network_de,
+            initial_network: None,
         })
     }
 
+    /// Start every new network from a checkpoint's trained weights and
+    /// biases, and the optimizer from its saved internal state, so training
+    /// continues where it left off. Pre-training is skipped, since it would
+    /// overwrite the resumed weights
+    ///
+    /// # Arguments
+    ///
+    /// * `checkpoint` - Trained network (with the same layer shapes as the
+    ///   config) and optimizer state to resume from
+    pub fn resume_from(&mut self, checkpoint: Checkpoint) -> Result<(), OpenPbError> {
+        let mut network: Perceptron = self.create_network()?;
+        network.load_parameters(&checkpoint.network)?;
+        self.optimizer.load_state(&checkpoint.optimizer_state)?;
+        self.pretrain = None;
+        self.initial_network = Some(checkpoint.network);
+        Ok(())
+    }
+
+    /// Whether or not new networks start from a checkpoint
+    pub fn is_resumed(&self) -> bool {
+        self.initial_network.is_some()
+    }
+
+    /// Deserialized network structure and hyperparameters
+    pub fn config(&self) -> &NetworkDe {
+        &self.network_de
+    }
+
+    /// Name of the cost function, as written in the network config
+    pub fn cost_name(&self) -> &str {
+        &self.network_de.cost
+    }
+
+    /// Optimizer config with every default filled in: the configured
+    /// name (and any other keys) plus the optimizer's resolved hyperparameters
+    pub fn optimizer_config(&self) -> Map<String, Value> {
+        let mut optimizer_config: Map<String, Value> =
+            match serde_json::to_value(&self.network_de.optimizer) {
+                Ok(Value::Object(optimizer_config)) => optimizer_config,
+                _ => Map::new(),
+            };
+        optimizer_config.extend(self.optimizer.hyperparameters());
+        optimizer_config
+    }
+
     /// Create new Perceptron instance from previously
     /// deserialized values
-    pub fn create_network(&self) -> Result<Perceptron, &'static str> {
-        let mut network = Perceptron::new();
-        let input_shape: (usize, usize) = (self.train_inputs.ncols(), self.train_inputs.nrows());
-        let mut input_shape: Option<(usize, usize)> = Some(input_shape);
-
-        for layer in self.network_de.layers.iter() {
-            let activation_fn: Box<dyn ActivationFn> =
-                match activation_from_str(layer.activation.to_lowercase()) {
-                    Some(value) => value,
-                    None => return Err("Invalid activation function name"),
-                };
+    pub fn create_network(&self) -> Result<Perceptron, OpenPbError> {
+        self.build_network(Perceptron::new())
+    }
 
-            network.add_layer(
-                layer.neurons,
-                input_shape,
-                activation_fn,
-                layer.dropout_rate,
-            );
-            if input_shape.is_some() {
-                input_shape = None
+    /// Same as `create_network`, but the initial weights, dropout, and
+    /// shuffling of the new Perceptron are derived from a seed
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - Seed of the Perceptron's random number generator
+    pub fn create_seeded_network(&self, seed: u64) -> Result<Perceptron, OpenPbError> {
+        self.build_network(Perceptron::with_seed(seed))
+    }
+
+    /// Add every configured Layer to an empty Perceptron
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - Empty Perceptron
+    fn build_network(&self, network: Perceptron) -> Result<Perceptron, OpenPbError> {
+        let metadata: DataMetadata = self.data.metadata();
+        self.build_attached_network(network, metadata.input_size)
+    }
+
+    /// Same as `create_seeded_network`, but the new Perceptron's inputs are
+    /// the outputs of another network (e.g. the shared trunk of a network
+    /// with multiple output heads) instead of the data's input vectors
+    ///
+    /// # Arguments
+    ///
+    /// * `input_size` - Size of the other network's output vector
+    /// * `seed` - Seed of the Perceptron's random number generator
+    pub fn create_seeded_head(
+        &self,
+        input_size: usize,
+        seed: u64,
+    ) -> Result<Perceptron, OpenPbError> {
+        self.build_attached_network(Perceptron::with_seed(seed), input_size)
+    }
+
+    /// Add every configured Layer to an empty Perceptron
+    /// with input vectors of the given size
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - Empty Perceptron
+    /// * `input_size` - Size of each input vector
+    fn build_attached_network(
+        &self,
+        network: Perceptron,
+        input_size: usize,
+    ) -> Result<Perceptron, OpenPbError> {
+        let metadata: DataMetadata = self.data.metadata();
+        let input_shape: (usize, usize) = (input_size, metadata.train_samples);
+        let mut network: Perceptron =
+            add_layers(network, &self.network_de.layers, "layers", input_shape)?;
+        if let Some(trained) = &self.initial_network {
+            // Shapes were checked when the checkpoint was loaded
+            if network.load_parameters(trained).is_err() {
+                return Err("Checkpoint layers don't match the network config".into());
+            }
+        }
+
+        if let Some(rate) = self.network_de.input_dropout {
+            if !(0.0..1.0).contains(&rate) {
+                return Err("Input dropout must be in [0, 1)".into());
             }
+            network.enable_input_dropout(rate);
+        }
+        if let Some(regularization) = self.network_de.regularization {
+            if regularization.l1 < 0.0 || regularization.l2 < 0.0 {
+                return Err("Regularization penalties can't be negative".into());
+            }
+            network.enable_regularization(regularization);
+        }
+        if self.network_de.independent_dropout {
+            network.enable_independent_dropout();
         }
         Ok(network)
     }
 }
 
+/// Add configured Layers to an empty Perceptron
+///
+/// # Arguments
+///
+/// * `network` - Empty Perceptron
+/// * `layers` - Deserialized values of each Layer
+/// * `field` - Path of the layers in the config (e.g. `layers`)
+/// * `input_shape` - Size of each input vector and number of training samples
+fn add_layers(
+    mut network: Perceptron,
+    layers: &[LayerDe],
+    field: &str,
+    input_shape: (usize, usize),
+) -> Result<Perceptron, OpenPbError> {
+    let mut input_shape: Option<(usize, usize)> = Some(input_shape);
+
+    for (index, layer) in layers.iter().enumerate() {
+        if let Some(rate) = layer.dropout_rate {
+            if !(0.0..1.0).contains(&rate) {
+                return Err(format!("Dropout rate of layer {index} must be in [0, 1)").into());
+            }
+        }
+        let activation_fn: Box<dyn ActivationFn> =
+            match activation_from_str(layer.activation.to_lowercase(), layer.alpha) {
+                Some(value) => value,
+                None => {
+                    return Err(unknown_name(
+                        "activation function",
+                        &layer.activation,
+                        &format!("{field}[{index}].activation"),
+                        &ACTIVATION_NAMES,
+                    ))
+                }
+            };
+
+        network.add_initialized_layer(
+            layer.neurons,
+            input_shape,
+            activation_fn,
+            layer.dropout_rate,
+            layer.initializer,
+        );
+        if let Some(config) = layer.reinit_dead {
+            if config.every == 0 {
+                return Err(
+                    "Dead neuron re-initialization needs a positive number of epochs".into(),
+                );
+            }
+            network.enable_dead_reinit(index, config);
+        }
+        if layer.batch_norm {
+            network.enable_batch_norm(index);
+        }
+        if input_shape.is_some() {
+            input_shape = None
+        }
+    }
+    Ok(network)
+}
+
 /// Create new 'Cost' object if the provided name
 /// matches an existing cost function
 ///
 /// # Arguments
 ///
 /// * `name` - Cost function's name
-fn cost_from_str(name: String) -> Option<Box<dyn Cost>> {
+/// * `output_activation` - Name of the output layer's activation function
+fn cost_from_str(name: String, output_activation: &str) -> Option<Box<dyn Cost>> {
     match name.as_str() {
         "mean squared error" | "mean_squared_error" | "mse" => Some(Box::new(MSE)),
-        _ => None,
+        "cross entropy" | "cross_entropy" | "crossentropy" | "log_loss" => {
+            Some(Box::new(CrossEntropy::for_activation(output_activation)))
+        }
+        _ => registry::create_cost(&name, &Map::new()),
     }
 }
 
@@ -233,11 +1203,23 @@ fn cost_from_str(name: String) -> Option<Box<dyn Cost>> {
 /// # Arguments
 ///
 /// * `name` - Activation function's name
-fn activation_from_str(name: String) -> Option<Box<dyn ActivationFn>> {
+/// * `alpha` - Shape parameter of the activation function (optional)
+pub(crate) fn activation_from_str(
+    name: String,
+    alpha: Option<f64>,
+) -> Option<Box<dyn ActivationFn>> {
     match name.as_str() {
         "sigmoid" => Some(Box::new(Sigmoid)),
         "relu" => Some(Box::new(ReLU)),
         "leaky relu" | "leaky_relu" | "leakyrelu" => Some(Box::new(LeakyReLU)),
+        "linear" | "identity" => Some(Box::new(Linear)),
+        "tanh" => Some(Box::new(Tanh)),
+        "elu" => Some(Box::new(match alpha {
+            Some(alpha) => ELU { alpha },
+            None => ELU::default(),
+        })),
+        "swish" | "silu" => Some(Box::new(Swish)),
+        "softmax" => Some(Box::new(Softmax)),
         _ => None,
     }
 }
@@ -250,11 +1232,30 @@ fn activation_from_str(name: String) -> Option<Box<dyn ActivationFn>> {
 /// * `metric_de` - Metric's name and constructor arguments
 fn metric_from_str(metric_de: &MetricDe) -> Option<Box<dyn Metric>> {
     match metric_de.name.to_lowercase().as_str() {
-        "accuracy" | "acc" => Some(Box::new(Accuracy::new(&metric_de.args))),
-        _ => None,
+        "accuracy" | "acc" | "categorical accuracy" | "categorical_accuracy" => {
+            Some(Box::new(Accuracy::new(&metric_de.args)))
+        }
+        "root mean squared error" | "root_mean_squared_error" | "rmse" => {
+            Some(Box::new(RMSE::new(&metric_de.args)))
+        }
+        "r squared" | "r_squared" | "r2" => Some(Box::new(RSquared::new(&metric_de.args))),
+        _ => registry::create_metric(&metric_de.name, &metric_de.args),
     }
 }
 
+/// Create a Metric with default arguments from the label
+/// it reports in results files (e.g. "RMSE" or "Accuracy")
+///
+/// # Arguments
+///
+/// * `label` - Metric's label
+pub(crate) fn metric_from_label(label: &str) -> Option<Box<dyn Metric>> {
+    metric_from_str(&MetricDe {
+        name: label.to_string(),
+        args: Map::new(),
+    })
+}
+
 /// Create new 'Encoder' object if the provided name
 /// matches an existing encoder
 ///
@@ -264,7 +1265,8 @@ fn metric_from_str(metric_de: &MetricDe) -> Option<Box<dyn Metric>> {
 fn encoder_from_str(encoder_de: &EncoderDe) -> Option<Box<dyn Encoder>> {
     match encoder_de.name.to_lowercase().as_str() {
         "one hot" | "one_hot" | "onehot" => Some(Box::new(OneHot::new(&encoder_de.args))),
-        _ => None,
+        "identity" | "none" => Some(Box::new(Identity::new(&encoder_de.args))),
+        _ => registry::create_encoder(&encoder_de.name, &encoder_de.args),
     }
 }
 
@@ -289,6 +1291,127 @@ fn optimizer_from_str(optimizer_de: &OptimizerDe) -> Option<Box<dyn Optimizer>>
             beta1,
             beta2,
         ))),
-        _ => None,
+        "adamw" | "adam_w" => Some(Box::new(AdamW::new(
+            optimizer_de.learning_rate,
+            beta1,
+            beta2,
+            optimizer_de
+                .weight_decay
+                .unwrap_or(optimizer::DEFAULT_WEIGHT_DECAY),
+        ))),
+        "root mean square propagation" | "rmsprop" => Some(Box::new(RMSProp::new(
+            optimizer_de.learning_rate,
+            optimizer_de
+                .decay
+                .unwrap_or(optimizer::DEFAULT_RMSPROP_DECAY),
+        ))),
+        "adaptive gradient" | "adagrad" => Some(Box::new(AdaGrad::new(optimizer_de.learning_rate))),
+        _ => {
+            // Registered optimizers receive every key of the "optimizer" object
+            let mut args: Map<String, Value> = optimizer_de.args.clone();
+            args.insert("learning_rate".into(), optimizer_de.learning_rate.into());
+            if let Some(beta1) = optimizer_de.beta1 {
+                args.insert("beta1".into(), beta1.into());
+            }
+            if let Some(beta2) = optimizer_de.beta2 {
+                args.insert("beta2".into(), beta2.into());
+            }
+            if let Some(decay) = optimizer_de.decay {
+                args.insert("decay".into(), decay.into());
+            }
+            if let Some(weight_decay) = optimizer_de.weight_decay {
+                args.insert("weight_decay".into(), weight_decay.into());
+            }
+            registry::create_optimizer(&optimizer_de.name, &args)
+        }
+    }
+}
+
+/// Error for a name that doesn't match any built-in or registered function
+///
+/// # Arguments
+///
+/// * `kind` - Kind of function (e.g. "activation function")
+/// * `name` - Name given in the config
+/// * `field` - Path of the field the name is in (e.g. `layers[0].activation`)
+/// * `valid` - Built-in names of the same kind
+fn unknown_name(
+    kind: &'static str,
+    name: &str,
+    field: &str,
+    valid: &'static [&'static str],
+) -> OpenPbError {
+    OpenPbError::UnknownName {
+        kind,
+        name: name.to_string(),
+        field: field.to_string(),
+        suggestion: diagnostics::closest_name(name, valid),
+        valid,
     }
 }
+
+/// Built-in activation function names
+const ACTIVATION_NAMES: [&str; 8] = [
+    "sigmoid",
+    "relu",
+    "leaky_relu",
+    "linear",
+    "tanh",
+    "elu",
+    "swish",
+    "softmax",
+];
+
+/// Built-in cost function names
+const COST_NAMES: [&str; 2] = ["mse", "cross_entropy"];
+
+/// Built-in optimizer names
+const OPTIMIZER_NAMES: [&str; 5] = ["sgd", "adam", "adamw", "rmsprop", "adagrad"];
+
+/// Built-in encoder names
+const ENCODER_NAMES: [&str; 2] = ["one_hot", "identity"];
+
+/// Built-in metric names
+const METRIC_NAMES: [&str; 3] = ["accuracy", "rmse", "r_squared"];
+
+/// Schema of the name of a function picked in a config file. Names are
+/// matched case-insensitively and more can be registered at runtime, so
+/// the built-in names are offered as examples (for autocompletion)
+/// rather than as the only allowed values
+///
+/// # Arguments
+///
+/// * `names` - Built-in names
+fn name_schema(names: &[&str]) -> Schema {
+    let mut schema = SchemaObject {
+        instance_type: Some(InstanceType::String.into()),
+        ..Default::default()
+    };
+    schema.metadata().examples = names.iter().map(|&name| Value::from(name)).collect();
+    Schema::Object(schema)
+}
+
+#[doc(hidden)]
+fn activation_name(_: &mut SchemaGenerator) -> Schema {
+    name_schema(&ACTIVATION_NAMES)
+}
+
+#[doc(hidden)]
+fn cost_name(_: &mut SchemaGenerator) -> Schema {
+    name_schema(&COST_NAMES)
+}
+
+#[doc(hidden)]
+fn optimizer_name(_: &mut SchemaGenerator) -> Schema {
+    name_schema(&OPTIMIZER_NAMES)
+}
+
+#[doc(hidden)]
+fn encoder_name(_: &mut SchemaGenerator) -> Schema {
+    name_schema(&ENCODER_NAMES)
+}
+
+#[doc(hidden)]
+fn metric_name(_: &mut SchemaGenerator) -> Schema {
+    name_schema(&METRIC_NAMES)
+}
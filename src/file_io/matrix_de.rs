@@ -0,0 +1,108 @@
+use ndarray::Array2;
+use schemars::JsonSchema;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+
+/// Single matrix cell, which some tools export as a string
+#[derive(Deserialize, JsonSchema, Debug)]
+#[serde(untagged)]
+enum CellDe {
+    /// Integer or float literal
+    Number(f64),
+
+    /// Number written as a string (e.g. "5.1")
+    Text(String),
+}
+
+/// Deserialized values representing an array in ndarray's JSON format
+#[derive(Deserialize, JsonSchema, Debug)]
+pub(crate) struct MatrixDe {
+    /// Format version (always 1)
+    v: u8,
+
+    /// Length of each axis (rows and columns for a matrix)
+    dim: Vec<usize>,
+
+    /// Every cell in row-major order
+    data: Vec<CellDe>,
+}
+
+/// Deserialize a matrix in ndarray's JSON format, coercing integer
+/// and string cells to `f64`. Use with `#[serde(deserialize_with)]`
+///
+/// # Arguments
+///
+/// * `deserializer` - Deserializer positioned at the matrix
+pub fn deserialize_matrix<'de, D>(deserializer: D) -> Result<Array2<f64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let matrix_de: MatrixDe = MatrixDe::deserialize(deserializer)?;
+    if matrix_de.dim.len() != 2 {
+        return Err(D::Error::custom(format!(
+            "Expected a 2D matrix, got {} dimensions",
+            matrix_de.dim.len()
+        )));
+    }
+    matrix_from_de(matrix_de)
+}
+
+/// Deserialize input samples in ndarray's JSON format, where each sample
+/// is either a vector (a 2D matrix of samples x features) or a tensor such
+/// as an image (samples x height x width, optionally x channels). Tensor
+/// samples are flattened in row-major order, so each becomes a single row
+/// that dense layers can consume. Use with `#[serde(deserialize_with)]`
+///
+/// # Arguments
+///
+/// * `deserializer` - Deserializer positioned at the array
+pub fn deserialize_inputs<'de, D>(deserializer: D) -> Result<Array2<f64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let mut matrix_de: MatrixDe = MatrixDe::deserialize(deserializer)?;
+    if matrix_de.dim.len() < 2 {
+        return Err(D::Error::custom(format!(
+            "Expected at least 2 input dimensions (samples x features), got {}",
+            matrix_de.dim.len()
+        )));
+    }
+    // Every axis after the samples is flattened into a single feature axis
+    let features: usize = matrix_de.dim[1..].iter().product();
+    matrix_de.dim = vec![matrix_de.dim[0], features];
+    matrix_from_de(matrix_de)
+}
+
+/// Convert a deserialized 2D array into a matrix, coercing every cell to `f64`
+///
+/// # Arguments
+///
+/// * `matrix_de` - Deserialized array with exactly 2 dimensions
+fn matrix_from_de<E: Error>(matrix_de: MatrixDe) -> Result<Array2<f64>, E> {
+    if matrix_de.v != 1 {
+        return Err(E::custom(format!("Unknown array version: {}", matrix_de.v)));
+    }
+
+    let mut data: Vec<f64> = Vec::with_capacity(matrix_de.data.len());
+    for (i, cell) in matrix_de.data.into_iter().enumerate() {
+        let value: f64 = match cell {
+            CellDe::Number(value) => value,
+            CellDe::Text(text) => match text.trim().parse::<f64>() {
+                Ok(value) => value,
+                Err(_) => {
+                    let cols: usize = matrix_de.dim[1];
+                    return Err(E::custom(format!(
+                        "Invalid number \"{text}\" at row {}, column {}",
+                        i / cols.max(1),
+                        i % cols.max(1)
+                    )));
+                }
+            },
+        };
+        data.push(value);
+    }
+    match Array2::from_shape_vec((matrix_de.dim[0], matrix_de.dim[1]), data) {
+        Ok(matrix) => Ok(matrix),
+        Err(error) => Err(E::custom(error.to_string())),
+    }
+}
@@ -0,0 +1,121 @@
+#[cfg(feature = "fetch")]
+use super::checksum;
+use super::idx::IdxArray;
+use super::json_de::DataDe;
+use crate::error::OpenPbError;
+use std::fs;
+use std::path::Path;
+
+/// Default mirror the MNIST archives are downloaded from
+pub const MNIST_URL: &str = "https://storage.googleapis.com/cvdf-datasets/mnist/";
+
+/// Training images, training labels, test images, and test labels
+const MNIST_FILES: [&str; 4] = [
+    "train-images-idx3-ubyte",
+    "train-labels-idx1-ubyte",
+    "t10k-images-idx3-ubyte",
+    "t10k-labels-idx1-ubyte",
+];
+
+/// SHA-256 digests of the gzipped archives, in the same order as
+/// `MNIST_FILES`. Downloads that don't match are rejected
+#[cfg(feature = "fetch")]
+const MNIST_SHA256: [&str; 4] = [
+    "440fcabf73cc546fa21475e81ea370265605f56be210a4024d2ca8f203523609",
+    "3552534a0a558bbed6aed32b30c495cca23d567ec52cac8be1a0730e8010255c",
+    "8d422c7b0a1c1c79245a5bcf07fe86e33eeafee792b84584aec276f5a2dbc4e6",
+    "f7ae60f92e00ec6debd23a6088c31dbd2371eca3ffa0defaefb259924204aec6",
+];
+
+/// Download, verify, and decompress any MNIST files that aren't already
+/// in the cache. Each file is written to a temporary file first and
+/// then renamed into place, so an interrupted download never leaves a
+/// partial file in the cache
+///
+/// # Arguments
+///
+/// * `cache_dir` - Directory where the uncompressed IDX files are stored
+/// * `base_url` - URL of the directory containing the gzipped IDX files
+#[cfg(feature = "fetch")]
+pub fn fetch_mnist(cache_dir: &Path, base_url: &str) -> Result<(), OpenPbError> {
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    if let Err(error) = fs::create_dir_all(cache_dir) {
+        return Err(format!("Failed to create {:#?}: {error}", cache_dir).into());
+    }
+
+    for (name, expected) in MNIST_FILES.iter().zip(MNIST_SHA256) {
+        let filepath = cache_dir.join(name);
+        if filepath.exists() {
+            eprintln!("Found cached {:#?}", filepath);
+            continue;
+        }
+
+        let url: String = format!("{}/{name}.gz", base_url.trim_end_matches('/'));
+        eprintln!("Downloading {url}...");
+        let response = match ureq::get(&url).call() {
+            Ok(response) => response,
+            Err(error) => return Err(format!("Failed to download {url}: {error}").into()),
+        };
+
+        let mut archive: Vec<u8> = vec![];
+        if let Err(error) = response.into_reader().read_to_end(&mut archive) {
+            return Err(format!("Failed to download {url}: {error}").into());
+        }
+        let digest: String = checksum::sha256_hex(&archive);
+        if digest != expected {
+            return Err(format!(
+                "Checksum mismatch for {url}: expected SHA-256 {expected}, got {digest}"
+            )
+            .into());
+        }
+
+        // Archives are gzipped IDX files
+        let mut bytes: Vec<u8> = vec![];
+        let mut decoder = GzDecoder::new(archive.as_slice());
+        if let Err(error) = decoder.read_to_end(&mut bytes) {
+            return Err(format!("Failed to decompress {url}: {error}").into());
+        }
+        IdxArray::parse(&bytes)?;
+
+        let temp_path = cache_dir.join(format!("{name}.part"));
+        if let Err(error) =
+            fs::write(&temp_path, bytes).and_then(|_| fs::rename(&temp_path, &filepath))
+        {
+            let _ = fs::remove_file(&temp_path);
+            return Err(format!("Failed to write {:#?}: {error}", filepath).into());
+        }
+    }
+    Ok(())
+}
+
+/// Load cached MNIST IDX files into training and validation sets
+///
+/// # Arguments
+///
+/// * `cache_dir` - Directory containing the uncompressed IDX files
+/// * `train_samples` - Maximum number of training samples (optional)
+/// * `test_samples` - Maximum number of validation samples (optional)
+pub fn load_mnist(
+    cache_dir: &Path,
+    train_samples: Option<usize>,
+    test_samples: Option<usize>,
+) -> Result<DataDe, OpenPbError> {
+    let mut idx_arrays: Vec<IdxArray> = Vec::with_capacity(MNIST_FILES.len());
+    for name in MNIST_FILES {
+        let filepath = cache_dir.join(name);
+        let bytes: Vec<u8> = match fs::read(&filepath) {
+            Ok(bytes) => bytes,
+            Err(error) => return Err(format!("Failed to read {:#?}: {error}", filepath).into()),
+        };
+        idx_arrays.push(IdxArray::parse(&bytes)?);
+    }
+
+    DataDe::new(
+        idx_arrays[0].to_inputs(train_samples),
+        idx_arrays[1].to_labels(train_samples),
+        idx_arrays[2].to_inputs(test_samples),
+        idx_arrays[3].to_labels(test_samples),
+    )
+}
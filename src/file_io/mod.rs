@@ -1,3 +1,31 @@
+pub mod bundle;
+pub mod checksum;
+pub mod compare;
+#[cfg(feature = "file_io")]
+pub mod config;
+#[cfg(feature = "file_io")]
+pub mod csv_de;
+pub mod diagnostics;
+pub mod ensemble_de;
+pub mod idx;
+#[cfg(feature = "file_io")]
+pub mod job_de;
 pub mod json_de;
+pub mod matrix_de;
+#[cfg(feature = "file_io")]
+pub mod mnist;
+pub mod model_de;
+pub mod multi_head_de;
+pub mod output_format;
+pub mod plot;
+pub mod registry;
 pub mod results_ser;
+pub mod sanity;
+#[cfg(feature = "file_io")]
 pub mod save_output;
+#[cfg(feature = "file_io")]
+pub mod schema;
+pub mod split;
+#[cfg(feature = "file_io")]
+pub mod sweep_de;
+pub mod time_series;
@@ -0,0 +1,195 @@
+use super::json_de::activation_from_str;
+use super::output_format;
+use crate::error::OpenPbError;
+use crate::nn::batch_norm::BatchNorm;
+use crate::nn::functions::activation::ActivationFn;
+use crate::nn::layer::Layer;
+use crate::nn::perceptron::Perceptron;
+use ndarray::Array2;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+use serde_json::{Map, Value};
+
+/// Deserialized values representing a single trained Layer in JSON
+#[derive(Deserialize, Debug)]
+struct LayerWeightsDe {
+    /// Matrix of weights
+    weights: Array2<f64>,
+
+    /// Vector of bias offsets
+    biases: Array2<f64>,
+
+    /// Name of activation function
+    activation: String,
+
+    /// Shape parameter of the activation function (optional)
+    #[serde(default)]
+    alpha: Option<f64>,
+
+    /// Trained batch normalization of the activation values (optional)
+    #[serde(default)]
+    batch_norm: Option<BatchNormDe>,
+}
+
+/// Deserialized values representing a Layer's trained batch normalization in JSON
+#[derive(Deserialize, Debug)]
+struct BatchNormDe {
+    /// Learnable scale of each neuron
+    gamma: Array2<f64>,
+
+    /// Learnable shift of each neuron
+    beta: Array2<f64>,
+
+    /// Mean activation of each neuron
+    running_mean: Array2<f64>,
+
+    /// Variance of each neuron's activations
+    running_variance: Array2<f64>,
+}
+
+impl BatchNormDe {
+    /// Reconstruct the batch normalization of a Layer
+    ///
+    /// # Arguments
+    ///
+    /// * `neurons` - Number of neurons in the Layer
+    fn into_batch_norm(self, neurons: usize) -> Result<BatchNorm, OpenPbError> {
+        for values in [
+            &self.gamma,
+            &self.beta,
+            &self.running_mean,
+            &self.running_variance,
+        ] {
+            if values.dim() != (neurons, 1) {
+                return Err(format!(
+                    "Batch normalization values must have the shape ({neurons}, 1), found {:?}",
+                    values.dim()
+                )
+                .into());
+            }
+        }
+        Ok(BatchNorm::from_parameters(
+            self.gamma,
+            self.beta,
+            self.running_mean,
+            self.running_variance,
+        ))
+    }
+}
+
+/// Deserialized values representing a trained Network in JSON
+#[derive(Deserialize, Debug)]
+struct ModelDe {
+    /// Trained layers
+    layers: Vec<LayerWeightsDe>,
+
+    /// Calibrated temperature of the output layer
+    #[serde(default)]
+    temperature: Option<f64>,
+}
+
+impl<'de> Deserialize<'de> for Perceptron {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let model_de: ModelDe = ModelDe::deserialize(deserializer)?;
+
+        let mut layers: Vec<Layer> = vec![];
+        for layer in model_de.layers {
+            let activation_fn: Box<dyn ActivationFn> =
+                match activation_from_str(layer.activation.to_lowercase(), layer.alpha) {
+                    Some(value) => value,
+                    None => return Err(D::Error::custom("Invalid activation function name")),
+                };
+            let neurons: usize = layer.weights.nrows();
+            let mut trained_layer: Layer =
+                Layer::from_parameters(layer.weights, layer.biases, activation_fn);
+            if let Some(batch_norm) = layer.batch_norm {
+                match batch_norm.into_batch_norm(neurons) {
+                    Ok(batch_norm) => trained_layer.enable_batch_norm(batch_norm),
+                    Err(error) => return Err(D::Error::custom(error)),
+                }
+            }
+            layers.push(trained_layer);
+        }
+        let mut network: Perceptron = Perceptron::from_layers(layers);
+        network.set_temperature(model_de.temperature);
+        Ok(network)
+    }
+}
+
+/// Reconstruct a trained Perceptron from JSON. Accepts either a serialized
+/// network (an object with a "layers" key), a results file written by
+/// `save_output`, in which case the network from the first run is used,
+/// or a checkpoint saved while learning online
+///
+/// # Arguments
+///
+/// * `model_json` - Raw contents of the model or results JSON file
+pub fn model_from_json(model_json: &str) -> Result<Perceptron, OpenPbError> {
+    model_from_bytes(model_json.as_bytes())
+}
+
+/// Same as `model_from_json`, but the file can also be
+/// saved in MessagePack (see `OutputFormat`)
+///
+/// # Arguments
+///
+/// * `model` - Raw contents of the model or results file
+pub fn model_from_bytes(model: &[u8]) -> Result<Perceptron, OpenPbError> {
+    model_from_value(&output_format::decode(model, "model")?)
+}
+
+/// Reconstruct a trained Perceptron from a decoded model or results file
+///
+/// # Arguments
+///
+/// * `value` - Decoded contents of the model or results file
+fn model_from_value(value: &Value) -> Result<Perceptron, OpenPbError> {
+    let network: &Value = match value
+        .pointer("/all_results/0/network")
+        .or_else(|| value.pointer("/network"))
+    {
+        Some(network) => network,
+        None => value,
+    };
+    match Perceptron::deserialize(network) {
+        Ok(network) => Ok(network),
+        Err(error) => Err(format!("Invalid model: {error}").into()),
+    }
+}
+
+/// Trained network and optimizer state to resume training from
+pub struct Checkpoint {
+    /// Previously trained network
+    pub network: Perceptron,
+
+    /// Internal optimizer values (e.g. momentum vectors), empty
+    /// when they weren't saved with the network
+    pub optimizer_state: Map<String, Value>,
+}
+
+/// Read a checkpoint from JSON or MessagePack. Accepts the same files as
+/// `model_from_bytes`. For results files, the optimizer state of the first
+/// run is restored when it was saved with `--save-optimizer-state`, and
+/// online learning checkpoints always include it
+///
+/// # Arguments
+///
+/// * `checkpoint` - Raw contents of the model or results file
+pub fn checkpoint_from_bytes(checkpoint: &[u8]) -> Result<Checkpoint, OpenPbError> {
+    let value: Value = output_format::decode(checkpoint, "model")?;
+    let network: Perceptron = model_from_value(&value)?;
+    let optimizer_state: Map<String, Value> = match value
+        .pointer("/all_results/0/optimizer_state")
+        .or_else(|| value.pointer("/optimizer_state"))
+    {
+        Some(Value::Object(optimizer_state)) => optimizer_state.clone(),
+        _ => Map::new(),
+    };
+    Ok(Checkpoint {
+        network,
+        optimizer_state,
+    })
+}
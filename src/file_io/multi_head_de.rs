@@ -0,0 +1,78 @@
+use super::json_de::{NetworkDe, TrunkDe};
+use crate::error::OpenPbError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// Deserialized values representing a single output head in JSON
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HeadDe {
+    /// Name of the head (e.g. the task it predicts)
+    pub name: String,
+
+    /// Number of (unencoded) output columns the head predicts. Heads
+    /// take consecutive columns of the output values, in order
+    pub outputs: usize,
+
+    /// Layers, cost, optimizer, encoder, and metric of the head
+    #[serde(flatten)]
+    pub network: NetworkDe,
+}
+
+/// Deserialized values representing a network with multiple output
+/// heads branching from shared layers (the trunk) in JSON
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MultiHeadDe {
+    /// Layers shared by every head
+    pub trunk: TrunkDe,
+
+    /// Network setup of each head
+    pub heads: Vec<HeadDe>,
+}
+
+impl MultiHeadDe {
+    /// # Arguments
+    ///
+    /// * `network_json` - Raw contents of JSON file containing
+    ///   the multi-head network configuration
+    pub fn from_json(network_json: &str) -> Result<MultiHeadDe, OpenPbError> {
+        let multi_head_de: MultiHeadDe = match serde_json::from_str(network_json) {
+            Ok(multi_head_de) => multi_head_de,
+            Err(error) => return Err(format!("Invalid multi-head network config: {error}").into()),
+        };
+
+        if multi_head_de.heads.is_empty() {
+            return Err("Multi-head networks must have at least one head".into());
+        }
+        let mut names: HashSet<&str> = HashSet::new();
+        for head in &multi_head_de.heads {
+            if head.outputs == 0 {
+                return Err(
+                    format!("Head \"{}\" must predict at least one output", head.name).into(),
+                );
+            }
+            if !names.insert(&head.name) {
+                return Err(format!("Head name \"{}\" is used more than once", head.name).into());
+            }
+        }
+        Ok(multi_head_de)
+    }
+
+    /// True if the JSON describes a network with multiple output
+    /// heads (has a "heads" list) instead of a single network
+    ///
+    /// # Arguments
+    ///
+    /// * `network_json` - Raw contents of the network config file
+    pub fn is_multi_head(network_json: &str) -> bool {
+        match serde_json::from_str::<Value>(network_json) {
+            Ok(value) => value.get("heads").is_some(),
+            Err(_) => false,
+        }
+    }
+
+    /// Total number of output columns predicted by every head
+    pub fn output_size(&self) -> usize {
+        self.heads.iter().map(|head| head.outputs).sum()
+    }
+}
@@ -0,0 +1,67 @@
+use super::bundle::{self, ZSTD_MAGIC};
+use crate::error::OpenPbError;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Encoding of saved results files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Pretty-printed JSON (human-readable, the default)
+    Json,
+
+    /// MessagePack with named fields, a compact binary encoding of the
+    /// same structure (weights are stored as 8-byte floats instead of text)
+    MessagePack,
+}
+
+impl OutputFormat {
+    /// File extension of the format (without the leading dot)
+    pub fn extension(&self) -> &str {
+        match self {
+            OutputFormat::Json => "json",
+            OutputFormat::MessagePack => "msgpack",
+        }
+    }
+
+    /// Encode any serializable value in this format
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Value to be serialized
+    pub fn encode<T: Serialize + ?Sized>(&self, value: &T) -> Result<Vec<u8>, OpenPbError> {
+        let encoded: Result<Vec<u8>, String> = match self {
+            OutputFormat::Json => serde_json::to_vec_pretty(value).map_err(|e| e.to_string()),
+            OutputFormat::MessagePack => rmp_serde::to_vec_named(value).map_err(|e| e.to_string()),
+        };
+        match encoded {
+            Ok(encoded) => Ok(encoded),
+            Err(error) => Err(format!("Failed to encode {}: {error}", self.extension()).into()),
+        }
+    }
+}
+
+/// Decode the contents of a file saved in either format. Every
+/// results file (and serialized network) is an object, so files that
+/// start with a MessagePack map marker are read as MessagePack, experiment
+/// bundle archives (`.tar.zst`) as their bundled results, and anything else as JSON
+///
+/// # Arguments
+///
+/// * `contents` - Raw contents of the file
+/// * `label` - Description of the file for error messages (e.g. "model")
+pub fn decode(contents: &[u8], label: &str) -> Result<Value, OpenPbError> {
+    if contents.starts_with(&ZSTD_MAGIC) {
+        return bundle::archive_results(contents);
+    }
+    match contents.first() {
+        // fixmap, map 16, or map 32
+        Some(0x80..=0x8f | 0xde | 0xdf) => match rmp_serde::from_slice(contents) {
+            Ok(value) => Ok(value),
+            Err(error) => Err(format!("Invalid {label} MessagePack: {error}").into()),
+        },
+        _ => match serde_json::from_slice(contents) {
+            Ok(value) => Ok(value),
+            Err(error) => Err(format!("Invalid {label} JSON: {error}").into()),
+        },
+    }
+}
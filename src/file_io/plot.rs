@@ -0,0 +1,306 @@
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Width of each panel's plot area (in pixels)
+const PANEL_WIDTH: f64 = 420.0;
+
+/// Height of each panel's plot area (in pixels)
+const PANEL_HEIGHT: f64 = 280.0;
+
+/// Space around each plot area for titles and axis labels (in pixels)
+const MARGIN: f64 = 60.0;
+
+/// Height of each line of the legend (in pixels)
+const LEGEND_LINE_HEIGHT: f64 = 18.0;
+
+/// Number of labeled values along each axis
+const AXIS_TICKS: usize = 5;
+
+/// Loss curves whose values span more than this factor are plotted on a log scale
+const LOG_SCALE_SPAN: f64 = 10.0;
+
+/// Line colors, cycled through when there are more curves than colors
+const PALETTE: [&str; 10] = [
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b", "#e377c2", "#7f7f7f",
+    "#bcbd22", "#17becf",
+];
+
+/// Mean training loss and validation metric at each epoch of
+/// one training setup (e.g. a single optimizer), over every run
+#[derive(Debug, Clone)]
+pub struct LearningCurve {
+    /// Name of the setup in the legend
+    pub label: String,
+
+    /// Mean training loss at each epoch
+    pub losses: Vec<(usize, f64)>,
+
+    /// Mean validation metric at each epoch
+    pub metrics: Vec<(usize, f64)>,
+}
+
+impl LearningCurve {
+    /// Average the epoch histories of several runs. Each epoch is averaged
+    /// over the runs that reached it, since early stopping can end runs
+    /// at different epochs
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - Name of the setup in the legend
+    /// * `runs` - Epoch, training loss, and validation metric of every logged epoch of each run
+    pub fn mean(label: String, runs: &[Vec<(usize, f64, f32)>]) -> Self {
+        let mut epochs: BTreeMap<usize, (f64, f64, usize)> = BTreeMap::new();
+        for (epoch, loss, metric) in runs.iter().flatten() {
+            let totals: &mut (f64, f64, usize) = epochs.entry(*epoch).or_insert((0.0, 0.0, 0));
+            totals.0 += loss;
+            totals.1 += *metric as f64;
+            totals.2 += 1;
+        }
+        Self {
+            label,
+            losses: epochs
+                .iter()
+                .map(|(epoch, (loss, _, count))| (*epoch, loss / *count as f64))
+                .collect(),
+            metrics: epochs
+                .iter()
+                .map(|(epoch, (_, metric, count))| (*epoch, metric / *count as f64))
+                .collect(),
+        }
+    }
+}
+
+/// Short description of an optimizer config for a legend, e.g. "adam, lr 0.01"
+///
+/// # Arguments
+///
+/// * `optimizer` - "optimizer" object of a network config or results file
+pub fn optimizer_label(optimizer: &Value) -> String {
+    let name: &str = optimizer
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or("optimizer");
+    match optimizer.get("learning_rate").and_then(Value::as_f64) {
+        Some(learning_rate) => format!("{name}, lr {learning_rate}"),
+        None => name.to_string(),
+    }
+}
+
+/// Render the learning curves of several setups as an SVG image with two
+/// side-by-side panels, training loss and validation metric against the
+/// epoch, where every setup's curves share a color. The loss axis uses a
+/// log scale when the losses span more than an order of magnitude
+///
+/// # Arguments
+///
+/// * `curves` - Learning curves of each setup, in legend order
+/// * `metric_label` - Name of the validation metric
+pub fn learning_curves_svg(curves: &[LearningCurve], metric_label: &str) -> String {
+    let width: f64 = 2.0 * (PANEL_WIDTH + 2.0 * MARGIN);
+    let legend_top: f64 = PANEL_HEIGHT + 2.0 * MARGIN;
+    let height: f64 = legend_top + LEGEND_LINE_HEIGHT * curves.len() as f64 + MARGIN / 2.0;
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         viewBox=\"0 0 {width} {height}\" font-family=\"sans-serif\" font-size=\"12\">"
+    );
+    let _ = writeln!(
+        svg,
+        "<rect width=\"{width}\" height=\"{height}\" fill=\"white\"/>"
+    );
+
+    let losses: Vec<&[(usize, f64)]> = curves.iter().map(|curve| &curve.losses[..]).collect();
+    let metrics: Vec<&[(usize, f64)]> = curves.iter().map(|curve| &curve.metrics[..]).collect();
+    let loss_min: f64 = losses
+        .iter()
+        .flat_map(|points| points.iter().map(|(_, loss)| *loss))
+        .fold(f64::INFINITY, f64::min);
+    let loss_max: f64 = losses
+        .iter()
+        .flat_map(|points| points.iter().map(|(_, loss)| *loss))
+        .fold(f64::NEG_INFINITY, f64::max);
+    let log_scale: bool = loss_min > 0.0 && loss_max / loss_min > LOG_SCALE_SPAN;
+
+    let loss_title: &str = if log_scale {
+        "Training loss (log scale)"
+    } else {
+        "Training loss"
+    };
+    render_panel(&mut svg, MARGIN, loss_title, &losses, log_scale);
+    render_panel(
+        &mut svg,
+        PANEL_WIDTH + 3.0 * MARGIN,
+        &format!("Validation {metric_label}"),
+        &metrics,
+        false,
+    );
+
+    for (i, curve) in curves.iter().enumerate() {
+        let y: f64 = legend_top + LEGEND_LINE_HEIGHT * i as f64;
+        let _ = writeln!(
+            svg,
+            "<line x1=\"{MARGIN}\" y1=\"{y}\" x2=\"{}\" y2=\"{y}\" stroke=\"{}\" stroke-width=\"3\"/>",
+            MARGIN + 24.0,
+            PALETTE[i % PALETTE.len()]
+        );
+        let _ = writeln!(
+            svg,
+            "<text x=\"{}\" y=\"{}\">{}</text>",
+            MARGIN + 32.0,
+            y + 4.0,
+            escape(&curve.label)
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Draw the axes, tick labels, and one line per curve of a single panel
+///
+/// # Arguments
+///
+/// * `svg` - Image being rendered
+/// * `left` - Left edge of the plot area (in pixels)
+/// * `title` - Panel title
+/// * `curves` - Epoch and value of every point of each curve
+/// * `log_scale` - Whether or not values are plotted on a log scale
+fn render_panel(
+    svg: &mut String,
+    left: f64,
+    title: &str,
+    curves: &[&[(usize, f64)]],
+    log_scale: bool,
+) {
+    let top: f64 = MARGIN;
+    let scale = |value: f64| -> f64 {
+        if log_scale {
+            value.log10()
+        } else {
+            value
+        }
+    };
+    let points = || {
+        curves
+            .iter()
+            .flat_map(|points| points.iter())
+            .filter(|(_, value)| scale(*value).is_finite())
+    };
+    let max_epoch: f64 = points().map(|(epoch, _)| *epoch).max().unwrap_or(1).max(1) as f64;
+    let mut y_min: f64 = points()
+        .map(|(_, value)| scale(*value))
+        .fold(f64::INFINITY, f64::min);
+    let mut y_max: f64 = points()
+        .map(|(_, value)| scale(*value))
+        .fold(f64::NEG_INFINITY, f64::max);
+    if !y_min.is_finite() || !y_max.is_finite() {
+        (y_min, y_max) = (0.0, 1.0);
+    }
+    // Flat curves are centered instead of dividing by a zero range
+    if (y_max - y_min).abs() < f64::EPSILON {
+        (y_min, y_max) = (y_min - 0.5, y_max + 0.5);
+    }
+    let x_pixel =
+        |epoch: f64| -> f64 { left + PANEL_WIDTH * (epoch - 1.0) / (max_epoch - 1.0).max(1.0) };
+    let y_pixel = |value: f64| -> f64 { top + PANEL_HEIGHT * (y_max - value) / (y_max - y_min) };
+
+    let _ = writeln!(
+        svg,
+        "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"14\">{}</text>",
+        left + PANEL_WIDTH / 2.0,
+        top - 20.0,
+        escape(title)
+    );
+    let _ = writeln!(
+        svg,
+        "<rect x=\"{left}\" y=\"{top}\" width=\"{PANEL_WIDTH}\" height=\"{PANEL_HEIGHT}\" \
+         fill=\"none\" stroke=\"#444\"/>"
+    );
+    for tick in 0..AXIS_TICKS {
+        let fraction: f64 = tick as f64 / (AXIS_TICKS - 1) as f64;
+        let value: f64 = y_min + (y_max - y_min) * fraction;
+        let y: f64 = y_pixel(value);
+        let label: f64 = if log_scale { 10f64.powf(value) } else { value };
+        let _ = writeln!(
+            svg,
+            "<line x1=\"{left}\" y1=\"{y}\" x2=\"{}\" y2=\"{y}\" stroke=\"#ddd\"/>",
+            left + PANEL_WIDTH
+        );
+        let _ = writeln!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"end\">{}</text>",
+            left - 6.0,
+            y + 4.0,
+            format_tick(label)
+        );
+
+        let epoch: f64 = 1.0 + (max_epoch - 1.0) * fraction;
+        let _ = writeln!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\">{}</text>",
+            x_pixel(epoch),
+            top + PANEL_HEIGHT + 16.0,
+            epoch.round()
+        );
+    }
+    let _ = writeln!(
+        svg,
+        "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\">Epoch</text>",
+        left + PANEL_WIDTH / 2.0,
+        top + PANEL_HEIGHT + 36.0
+    );
+
+    for (i, points) in curves.iter().enumerate() {
+        let coordinates: Vec<String> = points
+            .iter()
+            .filter(|(_, value)| scale(*value).is_finite())
+            .map(|(epoch, value)| {
+                format!(
+                    "{:.1},{:.1}",
+                    x_pixel(*epoch as f64),
+                    y_pixel(scale(*value))
+                )
+            })
+            .collect();
+        if coordinates.is_empty() {
+            continue;
+        }
+        let _ = writeln!(
+            svg,
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"2\"/>",
+            coordinates.join(" "),
+            PALETTE[i % PALETTE.len()]
+        );
+    }
+}
+
+/// Format an axis value compactly, switching to scientific
+/// notation for very large or very small values
+///
+/// # Arguments
+///
+/// * `value` - Value at the tick
+fn format_tick(value: f64) -> String {
+    if value != 0.0 && (value.abs() >= 1e4 || value.abs() < 1e-3) {
+        return format!("{value:.1e}");
+    }
+    let formatted: String = format!("{value:.3}");
+    formatted
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+/// Escape text for use in SVG (XML) content
+///
+/// # Arguments
+///
+/// * `text` - Raw text
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
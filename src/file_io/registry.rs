@@ -0,0 +1,152 @@
+use crate::nn::functions::cost::Cost;
+use crate::nn::functions::encoder::Encoder;
+use crate::nn::functions::metric::Metric;
+use crate::nn::functions::optimizer::Optimizer;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+/// Constructor for a user-defined component. Receives the
+/// constructor arguments given in the network JSON
+pub type Factory<T> = Box<dyn Fn(&Map<String, Value>) -> Box<T> + Send + Sync>;
+
+/// User-defined components, keyed by their (lowercase) names
+#[derive(Default)]
+struct Registry {
+    /// Registered cost functions
+    costs: HashMap<String, Factory<dyn Cost>>,
+
+    /// Registered metrics
+    metrics: HashMap<String, Factory<dyn Metric>>,
+
+    /// Registered optimization functions
+    optimizers: HashMap<String, Factory<dyn Optimizer>>,
+
+    /// Registered encoders
+    encoders: HashMap<String, Factory<dyn Encoder>>,
+}
+
+/// Global registry shared by every call to `NetworkDataDe::from_json`
+fn registry() -> &'static RwLock<Registry> {
+    static REGISTRY: OnceLock<RwLock<Registry>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+/// Register a custom 'Cost' so it can be referenced by name in the network JSON.
+/// Built-in cost functions take precedence over registered ones with the same name
+///
+/// # Arguments
+///
+/// * `name` - Name used in the network JSON (case insensitive)
+/// * `factory` - Creates a new instance of the cost function
+pub fn register_cost<F>(name: &str, factory: F)
+where
+    F: Fn(&Map<String, Value>) -> Box<dyn Cost> + Send + Sync + 'static,
+{
+    let mut registry = registry().write().unwrap();
+    registry
+        .costs
+        .insert(name.to_lowercase(), Box::new(factory));
+}
+
+/// Register a custom 'Metric' so it can be referenced by name in the network JSON.
+/// Built-in metrics take precedence over registered ones with the same name
+///
+/// # Arguments
+///
+/// * `name` - Name used in the network JSON (case insensitive)
+/// * `factory` - Creates a new instance of the metric from its constructor arguments
+pub fn register_metric<F>(name: &str, factory: F)
+where
+    F: Fn(&Map<String, Value>) -> Box<dyn Metric> + Send + Sync + 'static,
+{
+    let mut registry = registry().write().unwrap();
+    registry
+        .metrics
+        .insert(name.to_lowercase(), Box::new(factory));
+}
+
+/// Register a custom 'Optimizer' so it can be referenced by name in the network JSON.
+/// Built-in optimizers take precedence over registered ones with the same name
+///
+/// # Arguments
+///
+/// * `name` - Name used in the network JSON (case insensitive)
+/// * `factory` - Creates a new instance of the optimizer from its constructor
+///   arguments (every key of the "optimizer" object, including "learning_rate")
+pub fn register_optimizer<F>(name: &str, factory: F)
+where
+    F: Fn(&Map<String, Value>) -> Box<dyn Optimizer> + Send + Sync + 'static,
+{
+    let mut registry = registry().write().unwrap();
+    registry
+        .optimizers
+        .insert(name.to_lowercase(), Box::new(factory));
+}
+
+/// Register a custom 'Encoder' so it can be referenced by name in the network JSON.
+/// Built-in encoders take precedence over registered ones with the same name
+///
+/// # Arguments
+///
+/// * `name` - Name used in the network JSON (case insensitive)
+/// * `factory` - Creates a new instance of the encoder from its constructor arguments
+pub fn register_encoder<F>(name: &str, factory: F)
+where
+    F: Fn(&Map<String, Value>) -> Box<dyn Encoder> + Send + Sync + 'static,
+{
+    let mut registry = registry().write().unwrap();
+    registry
+        .encoders
+        .insert(name.to_lowercase(), Box::new(factory));
+}
+
+/// Create a registered 'Cost' if one exists with the given name
+///
+/// # Arguments
+///
+/// * `name` - Cost function's name
+/// * `args` - Constructor arguments
+pub(crate) fn create_cost(name: &str, args: &Map<String, Value>) -> Option<Box<dyn Cost>> {
+    let registry = registry().read().unwrap();
+    registry.costs.get(&name.to_lowercase()).map(|f| f(args))
+}
+
+/// Create a registered 'Metric' if one exists with the given name
+///
+/// # Arguments
+///
+/// * `name` - Metric's name
+/// * `args` - Constructor arguments
+pub(crate) fn create_metric(name: &str, args: &Map<String, Value>) -> Option<Box<dyn Metric>> {
+    let registry = registry().read().unwrap();
+    registry.metrics.get(&name.to_lowercase()).map(|f| f(args))
+}
+
+/// Create a registered 'Optimizer' if one exists with the given name
+///
+/// # Arguments
+///
+/// * `name` - Optimization function's name
+/// * `args` - Constructor arguments
+pub(crate) fn create_optimizer(
+    name: &str,
+    args: &Map<String, Value>,
+) -> Option<Box<dyn Optimizer>> {
+    let registry = registry().read().unwrap();
+    registry
+        .optimizers
+        .get(&name.to_lowercase())
+        .map(|f| f(args))
+}
+
+/// Create a registered 'Encoder' if one exists with the given name
+///
+/// # Arguments
+///
+/// * `name` - Encoder's name
+/// * `args` - Constructor arguments
+pub(crate) fn create_encoder(name: &str, args: &Map<String, Value>) -> Option<Box<dyn Encoder>> {
+    let registry = registry().read().unwrap();
+    registry.encoders.get(&name.to_lowercase()).map(|f| f(args))
+}
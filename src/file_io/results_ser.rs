@@ -1,6 +1,22 @@
+use super::ensemble_de::CombineDe;
+use super::plot::LearningCurve;
+use crate::nn::activations::{LayerActivations, LayerDeadNeurons};
+use crate::nn::batch_tuner::BatchSizeTuning;
+use crate::nn::bootstrap::ConfidenceInterval;
+use crate::nn::calibration::Calibration;
+use crate::nn::confusion::ConfusionMatrix;
+use crate::nn::divergence::Divergence;
+use crate::nn::history::EpochLog;
 use crate::nn::perceptron::Perceptron;
+use crate::nn::pretrain::Pretraining;
+use crate::nn::profiler::Profile;
+use crate::nn::robustness::NoiseLevel;
+use crate::nn::stats::{TrainingStats, WeightHistograms, WeightSnapshots};
+use crate::nn::top_n::ClassProbability;
 use ndarray::Array2;
 use serde::Serialize;
+use serde_json::{Map, Value};
+use std::mem;
 
 /// Serialized data for the metric that
 /// was used during training
@@ -14,15 +30,207 @@ struct MetricSer {
     /// Whether or not the the metric's score
     /// is considered a "passing" score
     passed: bool,
+    /// Bootstrap confidence interval of the score
+    /// (only present when bootstrapping is enabled)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    confidence_interval: Option<ConfidenceInterval>,
+}
+
+/// Serialized memory usage (in bytes) of a single training run, estimated
+/// from the sizes of the data, parameters, buffers, and optimizer state
+/// rather than measured (allocator overhead and temporaries aren't counted)
+#[derive(Serialize, Debug)]
+pub struct MemorySer {
+    /// Training and validation data
+    dataset_bytes: usize,
+    /// Total number of trainable values (weights and biases)
+    parameter_count: usize,
+    /// Trainable values (weights and biases)
+    parameter_bytes: usize,
+    /// Feed forward and backprop buffers of each layer
+    buffer_bytes: usize,
+    /// Internal state kept by the optimizer
+    /// between training steps
+    optimizer_state_bytes: usize,
+    /// Sum of all of the above (an estimate, not a measured peak)
+    estimated_total_bytes: usize,
+}
+
+impl MemorySer {
+    /// # Arguments
+    ///
+    /// * `network` - Trained network
+    /// * `dataset_len` - Number of values in the training and validation data
+    /// * `optimizer_state_len` - Number of values stored as optimizer state
+    pub fn new(network: &Perceptron, dataset_len: usize, optimizer_state_len: usize) -> Self {
+        let value_size: usize = mem::size_of::<f64>();
+
+        let parameter_count: usize = network.num_parameters();
+        let dataset_bytes: usize = dataset_len * value_size;
+        let parameter_bytes: usize = parameter_count * value_size;
+        let buffer_bytes: usize = network.buffer_len() * value_size;
+        let optimizer_state_bytes: usize = optimizer_state_len * value_size;
+
+        Self {
+            dataset_bytes,
+            parameter_count,
+            parameter_bytes,
+            buffer_bytes,
+            optimizer_state_bytes,
+            estimated_total_bytes: dataset_bytes
+                + parameter_bytes
+                + buffer_bytes
+                + optimizer_state_bytes,
+        }
+    }
+}
+
+/// Serialized CPU usage and throughput of a single training run
+#[derive(Serialize, Debug)]
+pub struct TimingSer {
+    /// Index of the worker thread the run was trained on
+    thread: Option<usize>,
+    /// CPU time spent by the worker thread during training
+    /// (in seconds). Only the run's own thread is measured, so
+    /// work it hands off to other threads of the pool (e.g. rayon
+    /// tasks stolen by idle workers) isn't counted. Process CPU time
+    /// isn't used instead, since it includes every concurrent run
+    cpu_time: f32,
+    /// CPU time divided by wall-clock time. Values well below 1 mean
+    /// the thread spent time waiting (or handing work off to other
+    /// threads) instead of computing
+    cpu_utilization: f32,
+    /// Training samples processed per second of wall-clock time
+    samples_per_second: f32,
+}
+
+impl TimingSer {
+    /// # Arguments
+    ///
+    /// * `thread` - Index of the worker thread the run was trained on
+    /// * `cpu_time` - CPU time spent by the worker thread (in seconds)
+    /// * `elapsed_time` - Wall-clock time spent training (in seconds)
+    /// * `samples_trained` - Total number of training samples processed
+    pub fn new(
+        thread: Option<usize>,
+        cpu_time: f32,
+        elapsed_time: f32,
+        samples_trained: usize,
+    ) -> Self {
+        // Avoid dividing by zero for runs that finish instantly
+        let elapsed_time: f32 = elapsed_time.max(f32::EPSILON);
+        Self {
+            thread,
+            cpu_time,
+            cpu_utilization: cpu_time / elapsed_time,
+            samples_per_second: samples_trained as f32 / elapsed_time,
+        }
+    }
+}
+
+/// Serialized outcome of a single random initialization of a run
+#[derive(Serialize, Debug)]
+pub struct AttemptSer {
+    /// Index of the initialization within the run
+    attempt: usize,
+    /// Seed of the initialization (only present for seeded runs)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+    /// Number of epochs it took for training to complete
+    total_epochs: usize,
+    /// Metric score of the validation set prediction
+    metric: f32,
+    /// Whether or not the score is a "passing" score
+    passed: bool,
+    /// Whether or not training was stopped because the loss exploded
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    diverged: bool,
+    /// Time it took for training to complete (in seconds)
+    elapsed_time: f32,
+}
+
+impl AttemptSer {
+    /// # Arguments
+    ///
+    /// * `attempt` - Index of the initialization within the run
+    /// * `seed` - Seed of the initialization (optional)
+    /// * `total_epochs` - Number of epochs it took for training to complete
+    /// * `metric` - Metric score of the validation set prediction
+    /// * `passed` - Whether or not the score is a "passing" score
+    /// * `diverged` - Whether or not training was stopped because the loss exploded
+    /// * `elapsed_time` - Time it took for training to complete (in seconds)
+    pub fn new(
+        attempt: usize,
+        seed: Option<u64>,
+        total_epochs: usize,
+        metric: f32,
+        passed: bool,
+        diverged: bool,
+        elapsed_time: f32,
+    ) -> Self {
+        Self {
+            attempt,
+            seed,
+            total_epochs,
+            metric,
+            passed,
+            diverged,
+            elapsed_time,
+        }
+    }
+
+    /// Number of epochs it took for training to complete
+    pub fn total_epochs(&self) -> usize {
+        self.total_epochs
+    }
+}
+
+/// Serialized record of every random initialization
+/// trained within a run, and which one was kept
+#[derive(Serialize, Debug)]
+pub struct RestartsSer {
+    /// Index of the initialization that was kept
+    best: usize,
+    /// Outcome of every initialization, in training order
+    attempts: Vec<AttemptSer>,
+}
+
+impl RestartsSer {
+    /// # Arguments
+    ///
+    /// * `best` - Index of the initialization that was kept
+    /// * `attempts` - Outcome of every initialization
+    pub fn new(best: usize, attempts: Vec<AttemptSer>) -> Self {
+        Self { best, attempts }
+    }
 }
 
 #[derive(Serialize, Debug)]
 pub struct TrainingResultsSer {
     /// Trained network
     network: Perceptron,
+    /// Seed of the run's random number generator
+    /// (only present for seeded runs)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+    /// Fully-resolved optimizer hyperparameters the run started from
+    #[serde(skip_serializing_if = "Map::is_empty")]
+    hyperparameters: Map<String, Value>,
+    /// Random factor the run's learning rate was scaled by
+    /// (only present when hyperparameter jitter is enabled)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    jitter_scale: Option<f64>,
+    /// Every random initialization trained within the run
+    /// (only present when restarts are enabled)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    restarts: Option<RestartsSer>,
     /// Data for the metric that was used to
     /// validate the network's results during training
     metric: MetricSer,
+    /// Metric for the hold-out test set, evaluated once after training
+    /// (only present when the data has a test set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    test_metric: Option<MetricSer>,
     /// Time it took for training to complete
     /// (in seconds)
     elapsed_time: f32,
@@ -32,6 +240,76 @@ pub struct TrainingResultsSer {
     /// Predicted values from feeding validtion
     /// set inputs into the trained network
     predicted_output: Array2<f64>,
+    /// Counts of each actual vs. predicted class of the validation set
+    /// (only present for outputs that are single class labels)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    confusion_matrix: Option<ConfusionMatrix>,
+    /// Epoch whose training loss exploded, stopping training
+    /// (only present when the run diverged)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    divergence: Option<Divergence>,
+    /// Undecoded output layer values of each validation sample,
+    /// one row per sample (only present in raw output mode)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    raw_output: Option<Array2<f64>>,
+    /// Highest raw output values of each validation sample
+    /// (only present when top-N output is enabled)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_predictions: Option<Vec<Vec<ClassProbability>>>,
+    /// Reconstruction error of autoencoder pre-training
+    /// (only present when pre-training is configured)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pretraining: Option<Pretraining>,
+    /// Metric for validation inputs corrupted with increasing noise
+    /// (only present when robustness evaluation is enabled)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    robustness: Option<Vec<NoiseLevel>>,
+    /// Temperature fitted to the validation set
+    /// (only present when calibration is enabled)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    calibration: Option<Calibration>,
+    /// Optimizer hyperparameters and schedule state
+    /// (e.g. the time step) once training finished
+    #[serde(skip_serializing_if = "Map::is_empty")]
+    optimizer_state: Map<String, Value>,
+    /// Per-phase timing breakdown of training
+    /// (only present when profiling is enabled)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    profile: Option<Profile>,
+    /// Memory used by the dataset, network,
+    /// and optimizer during training
+    #[serde(skip_serializing_if = "Option::is_none")]
+    memory: Option<MemorySer>,
+    /// CPU time and throughput of training
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timing: Option<TimingSer>,
+    /// Per-layer weight and gradient statistics
+    /// (only present when statistics are enabled)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stats: Option<TrainingStats>,
+    /// Per-layer distance between consecutive weight snapshots
+    /// (only present when snapshots are enabled)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    weight_changes: Option<WeightSnapshots>,
+    /// Per-layer weight and bias histograms recorded during training
+    /// (only present when histograms are enabled)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    weight_histograms: Option<WeightHistograms>,
+    /// Percentiles of each layer's activations for a validation batch
+    /// (only present when activation summaries are enabled)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    activations: Option<Vec<LayerActivations>>,
+    /// Neurons of each hidden layer that are inactive for every
+    /// validation sample (only present when detection is enabled)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dead_neurons: Option<Vec<LayerDeadNeurons>>,
+    /// Number of dead neurons re-initialized during training
+    /// (only present when any were re-initialized)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reinitialized_neurons: Option<usize>,
+    /// Training loss and metric of every epoch
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    history: Vec<EpochLog>,
 }
 
 impl TrainingResultsSer {
@@ -48,15 +326,401 @@ impl TrainingResultsSer {
             name: metric_label,
             value: metric_value,
             passed: metric_passed,
+            confidence_interval: None,
         };
         Self {
             network,
+            seed: None,
+            hyperparameters: Map::new(),
+            jitter_scale: None,
+            restarts: None,
             metric,
+            test_metric: None,
             elapsed_time,
             total_epochs,
             predicted_output,
+            confusion_matrix: None,
+            divergence: None,
+            raw_output: None,
+            top_predictions: None,
+            pretraining: None,
+            robustness: None,
+            optimizer_state: Map::new(),
+            calibration: None,
+            profile: None,
+            memory: None,
+            timing: None,
+            stats: None,
+            weight_changes: None,
+            weight_histograms: None,
+            activations: None,
+            dead_neurons: None,
+            reinitialized_neurons: None,
+            history: vec![],
+        }
+    }
+
+    /// Attach a per-phase timing breakdown to the results
+    ///
+    /// # Arguments
+    ///
+    /// * `profile` - Timing breakdown recorded during training
+    pub fn with_profile(mut self, profile: Option<Profile>) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Attach the estimated memory usage of the training run to the results
+    ///
+    /// # Arguments
+    ///
+    /// * `memory` - Estimated memory used during training
+    pub fn with_memory(mut self, memory: MemorySer) -> Self {
+        self.memory = Some(memory);
+        self
+    }
+
+    /// Attach the CPU time and throughput of the training run to the results
+    ///
+    /// # Arguments
+    ///
+    /// * `timing` - CPU time and throughput measured during training
+    pub fn with_timing(mut self, timing: TimingSer) -> Self {
+        self.timing = Some(timing);
+        self
+    }
+
+    /// Attach the highest raw output values of each validation sample
+    ///
+    /// # Arguments
+    ///
+    /// * `top_predictions` - Top-N classes and raw outputs of each sample
+    pub fn with_top_predictions(
+        mut self,
+        top_predictions: Option<Vec<Vec<ClassProbability>>>,
+    ) -> Self {
+        self.top_predictions = top_predictions;
+        self
+    }
+
+    /// Attach the confusion matrix of the validation set's predictions
+    ///
+    /// # Arguments
+    ///
+    /// * `confusion_matrix` - Counts of each actual vs. predicted class
+    pub fn with_confusion_matrix(mut self, confusion_matrix: Option<ConfusionMatrix>) -> Self {
+        self.confusion_matrix = confusion_matrix;
+        self
+    }
+
+    /// Attach the epoch at which the run was stopped as diverged
+    ///
+    /// # Arguments
+    ///
+    /// * `divergence` - Epoch whose training loss exploded
+    pub fn with_divergence(mut self, divergence: Option<Divergence>) -> Self {
+        self.divergence = divergence;
+        self
+    }
+
+    /// Attach the undecoded output layer values of each validation sample
+    ///
+    /// # Arguments
+    ///
+    /// * `raw_output` - Output layer values with one row per sample
+    pub fn with_raw_output(mut self, raw_output: Option<Array2<f64>>) -> Self {
+        self.raw_output = raw_output;
+        self
+    }
+
+    /// Attach the reconstruction error of autoencoder pre-training
+    ///
+    /// # Arguments
+    ///
+    /// * `pretraining` - Reconstruction error before and after pre-training
+    pub fn with_pretraining(mut self, pretraining: Option<Pretraining>) -> Self {
+        self.pretraining = pretraining;
+        self
+    }
+
+    /// Attach the metric of each noise level the validation inputs were corrupted with
+    ///
+    /// # Arguments
+    ///
+    /// * `robustness` - Metric score at each noise level
+    pub fn with_robustness(mut self, robustness: Option<Vec<NoiseLevel>>) -> Self {
+        self.robustness = robustness;
+        self
+    }
+
+    /// Attach the optimizer's hyperparameters and schedule state after training
+    ///
+    /// # Arguments
+    ///
+    /// * `optimizer_state` - Hyperparameters reported by the trained optimizer
+    pub fn with_optimizer_state(mut self, optimizer_state: Map<String, Value>) -> Self {
+        self.optimizer_state = optimizer_state;
+        self
+    }
+
+    /// Attach the temperature fitted to the validation set after training
+    ///
+    /// # Arguments
+    ///
+    /// * `calibration` - Fitted temperature and the NLL before and after scaling
+    pub fn with_calibration(mut self, calibration: Option<Calibration>) -> Self {
+        self.calibration = calibration;
+        self
+    }
+
+    /// Attach the seed the run's network was created with
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - Seed of the run's random number generator
+    pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Attach the exact hyperparameters the run started from
+    ///
+    /// # Arguments
+    ///
+    /// * `hyperparameters` - Resolved optimizer config of the run
+    /// * `jitter_scale` - Random factor the learning rate was scaled by (if jittered)
+    pub fn with_hyperparameters(
+        mut self,
+        hyperparameters: Map<String, Value>,
+        jitter_scale: Option<f64>,
+    ) -> Self {
+        self.hyperparameters = hyperparameters;
+        self.jitter_scale = jitter_scale;
+        self
+    }
+
+    /// Fully-resolved optimizer hyperparameters the run started from
+    pub fn hyperparameters(&self) -> &Map<String, Value> {
+        &self.hyperparameters
+    }
+
+    /// Attach the outcome of every random initialization trained within the run
+    ///
+    /// # Arguments
+    ///
+    /// * `restarts` - Every initialization, and which one was kept
+    pub fn with_restarts(mut self, restarts: Option<RestartsSer>) -> Self {
+        self.restarts = restarts;
+        self
+    }
+
+    /// Attach the metric of the hold-out test set, which is named
+    /// the same as the validation metric
+    ///
+    /// # Arguments
+    ///
+    /// * `test_metric` - Score, and whether or not the score passed
+    pub fn with_test_metric(mut self, test_metric: Option<(f32, bool)>) -> Self {
+        self.test_metric = test_metric.map(|(value, passed)| MetricSer {
+            name: self.metric.name.clone(),
+            value,
+            passed,
+            confidence_interval: None,
+        });
+        self
+    }
+
+    /// Attach the bootstrap confidence interval of the validation metric
+    ///
+    /// # Arguments
+    ///
+    /// * `confidence_interval` - Confidence level and bounds of the score
+    pub fn with_confidence_interval(
+        mut self,
+        confidence_interval: Option<ConfidenceInterval>,
+    ) -> Self {
+        self.metric.confidence_interval = confidence_interval;
+        self
+    }
+
+    /// Score, and whether or not the score passed,
+    /// for the hold-out test set (if the data has one)
+    pub fn test_metric(&self) -> Option<(f32, bool)> {
+        self.test_metric
+            .as_ref()
+            .map(|test_metric| (test_metric.value, test_metric.passed))
+    }
+
+    /// Attach the per-layer statistics recorded during training
+    ///
+    /// # Arguments
+    ///
+    /// * `stats` - Weight and gradient statistics of each recorded epoch
+    pub fn with_stats(mut self, stats: Option<TrainingStats>) -> Self {
+        self.stats = stats;
+        self
+    }
+
+    /// Attach the distances between weight snapshots taken during training
+    ///
+    /// # Arguments
+    ///
+    /// * `weight_changes` - Per-layer distance between consecutive snapshots
+    pub fn with_weight_changes(mut self, weight_changes: Option<WeightSnapshots>) -> Self {
+        self.weight_changes = weight_changes;
+        self
+    }
+    /// Attach the per-layer weight histograms recorded during training
+    ///
+    /// # Arguments
+    ///
+    /// * `weight_histograms` - Histograms of each layer, in order of epoch
+    pub fn with_weight_histograms(mut self, weight_histograms: Option<WeightHistograms>) -> Self {
+        self.weight_histograms = weight_histograms;
+        self
+    }
+
+    /// Attach the activation percentiles of each layer after training
+    ///
+    /// # Arguments
+    ///
+    /// * `activations` - Distribution summary of each layer's activations
+    pub fn with_activations(mut self, activations: Option<Vec<LayerActivations>>) -> Self {
+        self.activations = activations;
+        self
+    }
+
+    /// Attach the dead neurons of each hidden layer after training
+    ///
+    /// # Arguments
+    ///
+    /// * `dead_neurons` - Count and indices of each layer's dead neurons
+    pub fn with_dead_neurons(mut self, dead_neurons: Option<Vec<LayerDeadNeurons>>) -> Self {
+        self.dead_neurons = dead_neurons;
+        self
+    }
+
+    /// Attach the number of dead neurons re-initialized during training
+    ///
+    /// # Arguments
+    ///
+    /// * `reinitialized_neurons` - Number of re-initialized neurons
+    pub fn with_reinitialized_neurons(mut self, reinitialized_neurons: Option<usize>) -> Self {
+        self.reinitialized_neurons = reinitialized_neurons;
+        self
+    }
+
+    /// Attach the loss and metric recorded at the end of every epoch
+    ///
+    /// # Arguments
+    ///
+    /// * `history` - Summary of every epoch, in order
+    pub fn with_history(mut self, history: Vec<EpochLog>) -> Self {
+        self.history = history;
+        self
+    }
+
+    /// Remove and return the recorded weight histograms (if histograms were enabled)
+    pub fn take_weight_histograms(&mut self) -> Option<WeightHistograms> {
+        self.weight_histograms.take()
+    }
+
+    /// Trained network
+    pub fn network(&self) -> &Perceptron {
+        &self.network
+    }
+
+    /// Name, score, and whether or not the score passed for the metric
+    pub fn metric(&self) -> (&str, f32, bool) {
+        (&self.metric.name, self.metric.value, self.metric.passed)
+    }
+
+    /// Whether or not training was stopped because the loss exploded
+    pub fn diverged(&self) -> bool {
+        self.divergence.is_some()
+    }
+
+    /// Predicted values for the validation set inputs
+    pub fn predicted_output(&self) -> &Array2<f64> {
+        &self.predicted_output
+    }
+
+    /// Seed the run's network was created with (if seeded)
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// Number of epochs the run trained for
+    pub fn total_epochs(&self) -> usize {
+        self.total_epochs
+    }
+
+    /// Time it took for training to complete (in seconds)
+    pub fn elapsed_time(&self) -> f32 {
+        self.elapsed_time
+    }
+}
+
+/// Serialized results of combining every run's network into an ensemble
+#[derive(Serialize, Debug)]
+pub struct EnsembleSer {
+    /// Method used to combine the member predictions
+    combine: CombineDe,
+    /// Metric for the ensemble's combined prediction
+    metric: MetricSer,
+    /// Combined prediction from feeding validation
+    /// set inputs into every member
+    predicted_output: Array2<f64>,
+    /// Training results of the network that learned to combine
+    /// the member outputs (only present for stacking)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stacking: Option<TrainingResultsSer>,
+}
+
+impl EnsembleSer {
+    /// # Arguments
+    ///
+    /// * `combine` - Method used to combine the member predictions
+    /// * `metric_label` - Name of the metric
+    /// * `metric_value` - Metric score of the combined prediction
+    /// * `metric_passed` - Whether or not the score is a "passing" score
+    /// * `predicted_output` - Combined prediction for the validation set
+    pub fn new(
+        combine: CombineDe,
+        metric_label: String,
+        metric_value: f32,
+        metric_passed: bool,
+        predicted_output: Array2<f64>,
+    ) -> Self {
+        let metric: MetricSer = MetricSer {
+            name: metric_label,
+            value: metric_value,
+            passed: metric_passed,
+            confidence_interval: None,
+        };
+        Self {
+            combine,
+            metric,
+            predicted_output,
+            stacking: None,
         }
     }
+
+    /// Attach the training results of the stacking network
+    ///
+    /// # Arguments
+    ///
+    /// * `stacking` - Results of training the stacking network
+    pub fn with_stacking(mut self, stacking: TrainingResultsSer) -> Self {
+        self.stacking = Some(stacking);
+        self
+    }
+
+    /// Name, score, and whether or not the score passed
+    /// for the metric of the combined prediction
+    pub fn metric(&self) -> (&str, f32, bool) {
+        (&self.metric.name, self.metric.value, self.metric.passed)
+    }
 }
 
 #[derive(Serialize, Debug)]
@@ -72,6 +736,20 @@ pub struct ThreadedResultsSer {
     validation_outputs: Array2<f64>,
     /// Size of minibatches (if applicable)
     batch_size: Option<usize>,
+    /// Fully-resolved optimizer config every run started from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    optimizer: Option<Map<String, Value>>,
+    /// Combined results when each run is a member of an ensemble
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ensemble: Option<EnsembleSer>,
+    /// Benchmarks of each candidate batch size
+    /// (only present in automatic batch size mode)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    batch_tuning: Option<BatchSizeTuning>,
+    /// Whether or not training was cancelled before
+    /// every run finished (only present when cancelled)
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    cancelled: bool,
 }
 
 impl ThreadedResultsSer {
@@ -86,6 +764,479 @@ impl ThreadedResultsSer {
             validation_inputs,
             validation_outputs,
             batch_size,
+            optimizer: None,
+            ensemble: None,
+            batch_tuning: None,
+            cancelled: false,
+        }
+    }
+
+    /// Attach the combined results of an ensemble
+    ///
+    /// # Arguments
+    ///
+    /// * `ensemble` - Results of combining every run's network
+    pub fn with_ensemble(mut self, ensemble: EnsembleSer) -> Self {
+        self.ensemble = Some(ensemble);
+        self
+    }
+
+    /// Attach the fully-resolved optimizer config every run started from
+    ///
+    /// # Arguments
+    ///
+    /// * `optimizer` - Configured name and keys plus resolved hyperparameters
+    pub fn with_optimizer(mut self, optimizer: Map<String, Value>) -> Self {
+        self.optimizer = Some(optimizer);
+        self
+    }
+
+    /// Attach the record of how the batch size was chosen
+    ///
+    /// # Arguments
+    ///
+    /// * `batch_tuning` - Benchmarks of each candidate batch size
+    pub fn with_batch_tuning(mut self, batch_tuning: Option<BatchSizeTuning>) -> Self {
+        self.batch_tuning = batch_tuning;
+        self
+    }
+
+    /// Mark whether or not training was cancelled before every run finished
+    ///
+    /// # Arguments
+    ///
+    /// * `cancelled` - Whether or not cancellation was requested
+    pub fn with_cancelled(mut self, cancelled: bool) -> Self {
+        self.cancelled = cancelled;
+        self
+    }
+
+    /// Whether or not training was cancelled before every run finished
+    pub fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    /// Name, score, and whether or not the score
+    /// passed for the metric of each run
+    pub fn metrics(&self) -> Vec<(&str, f32, bool)> {
+        self.all_results
+            .iter()
+            .map(|results| results.metric())
+            .collect()
+    }
+
+    /// Results of each run, in order of the run IDs
+    pub fn runs(&self) -> &[TrainingResultsSer] {
+        &self.all_results
+    }
+
+    /// Remove and return the weight histograms of every run that recorded
+    /// them, along with the run's ID (e.g. to save them to separate files)
+    pub fn take_weight_histograms(&mut self) -> Vec<(usize, WeightHistograms)> {
+        self.all_results
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(id, results)| Some((id, results.take_weight_histograms()?)))
+            .collect()
+    }
+
+    /// Trained network from each run, in order of the run IDs
+    pub fn networks(&self) -> Vec<&Perceptron> {
+        self.all_results
+            .iter()
+            .map(|results| results.network())
+            .collect()
+    }
+
+    /// Mean training loss and validation metric of every run at each epoch
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - Name of the training setup in plot legends
+    pub fn learning_curve(&self, label: String) -> LearningCurve {
+        let runs: Vec<Vec<(usize, f64, f32)>> = self
+            .all_results
+            .iter()
+            .map(|results| {
+                results
+                    .history
+                    .iter()
+                    .map(|epoch_log| (epoch_log.epoch, epoch_log.loss, epoch_log.metric))
+                    .collect()
+            })
+            .collect();
+        LearningCurve::mean(label, &runs)
+    }
+
+    /// Aligned table of each run's worker thread, epochs, elapsed time,
+    /// and metric score, followed by aggregate stats over every run
+    pub fn summary_table(&self) -> String {
+        let metric_label: &str = self
+            .all_results
+            .first()
+            .map_or("Metric", |results| &results.metric.name);
+        let header: [String; 6] = [
+            "Run".to_string(),
+            "Thread".to_string(),
+            "Epochs".to_string(),
+            "Time (s)".to_string(),
+            metric_label.to_string(),
+            "Result".to_string(),
+        ];
+        let rows: Vec<[String; 6]> = self
+            .all_results
+            .iter()
+            .enumerate()
+            .map(|(run, results)| {
+                let thread: String = match results.timing.as_ref().and_then(|timing| timing.thread)
+                {
+                    Some(thread) => thread.to_string(),
+                    None => "-".to_string(),
+                };
+                [
+                    run.to_string(),
+                    thread,
+                    results.total_epochs.to_string(),
+                    format!("{:.3}", results.elapsed_time),
+                    match results.metric.confidence_interval {
+                        Some(interval) => format!(
+                            "{:.4} [{:.4}, {:.4}]",
+                            results.metric.value, interval.lower, interval.upper
+                        ),
+                        None => format!("{:.4}", results.metric.value),
+                    },
+                    if results.diverged() {
+                        "diverged"
+                    } else if results.metric.passed {
+                        "pass"
+                    } else {
+                        "fail"
+                    }
+                    .to_string(),
+                ]
+            })
+            .collect();
+
+        // Every column is padded to its widest value
+        let mut widths: [usize; 6] = [0; 6];
+        for row in rows.iter().chain([&header]) {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        let format_row = |row: &[String; 6]| -> String {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(widths)
+                .map(|(cell, width)| format!("{cell:>width$}"))
+                .collect();
+            cells.join("  ")
+        };
+
+        let mut table: String = format_row(&header);
+        table.push('\n');
+        table.push_str(&"-".repeat(widths.iter().sum::<usize>() + 2 * (widths.len() - 1)));
+        table.push('\n');
+        for row in &rows {
+            table.push_str(&format_row(row));
+            table.push('\n');
+        }
+
+        // Aggregate stats over every run
+        let runs: usize = self.all_results.len();
+        if runs > 0 {
+            let values: Vec<f32> = self
+                .all_results
+                .iter()
+                .map(|results| results.metric.value)
+                .collect();
+            let mean: f32 = values.iter().sum::<f32>() / runs as f32;
+            let std_dev: f32 = (values
+                .iter()
+                .map(|value| (value - mean).powi(2))
+                .sum::<f32>()
+                / runs as f32)
+                .sqrt();
+            let min: f32 = values.iter().cloned().fold(f32::INFINITY, f32::min);
+            let max: f32 = values.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+            let passed: usize = self
+                .metrics()
+                .iter()
+                .filter(|(_, _, passed)| *passed)
+                .count();
+            let mean_epochs: f32 = self
+                .all_results
+                .iter()
+                .map(|results| results.total_epochs as f32)
+                .sum::<f32>()
+                / runs as f32;
+            let total_time: f32 = self
+                .all_results
+                .iter()
+                .map(|results| results.elapsed_time)
+                .sum();
+
+            table.push_str(&format!(
+                "\n{metric_label}: mean {mean:.4}, std {std_dev:.4}, min {min:.4}, max {max:.4}\n"
+            ));
+            table.push_str(&format!(
+                "Passed: {passed}/{runs}, mean epochs: {mean_epochs:.1}, total training time: {total_time:.3}s\n"
+            ));
+            let diverged: usize = self
+                .all_results
+                .iter()
+                .filter(|results| results.diverged())
+                .count();
+            if diverged > 0 {
+                table.push_str(&format!("Diverged: {diverged}/{runs}\n"));
+            }
+        }
+        let test_values: Vec<f32> = self
+            .all_results
+            .iter()
+            .filter_map(|results| results.test_metric().map(|(value, _)| value))
+            .collect();
+        if !test_values.is_empty() {
+            let mean: f32 = test_values.iter().sum::<f32>() / test_values.len() as f32;
+            let min: f32 = test_values.iter().cloned().fold(f32::INFINITY, f32::min);
+            let max: f32 = test_values
+                .iter()
+                .cloned()
+                .fold(f32::NEG_INFINITY, f32::max);
+            table.push_str(&format!(
+                "Test {metric_label}: mean {mean:.4}, min {min:.4}, max {max:.4}\n"
+            ));
+        }
+        if let Some(ensemble) = &self.ensemble {
+            let (label, value, passed) = ensemble.metric();
+            let result: &str = if passed { "pass" } else { "fail" };
+            table.push_str(&format!("Ensemble {label}: {value:.4} ({result})\n"));
+        }
+        table
+    }
+}
+
+/// Serialized summary of a single trial of a hyperparameter sweep
+#[derive(Serialize, Debug)]
+pub struct TrialSummarySer {
+    /// Index of the trial (matches its archive directory)
+    trial: usize,
+    /// Value assigned to each swept parameter, by path
+    parameters: Map<String, Value>,
+    /// Name of the metric
+    metric: String,
+    /// Mean metric score over every run of the trial
+    mean_value: f32,
+    /// Number of runs with a passing metric score
+    passed_runs: usize,
+    /// Number of runs stopped because the training loss exploded
+    diverged_runs: usize,
+    /// Total number of runs
+    runs: usize,
+    /// Number of trainable values (weights and biases) of the trial's network
+    parameter_count: usize,
+}
+
+impl TrialSummarySer {
+    /// # Arguments
+    ///
+    /// * `trial` - Index of the trial
+    /// * `parameters` - Value assigned to each swept parameter
+    /// * `results` - Training results of every run of the trial
+    pub fn new(trial: usize, parameters: Map<String, Value>, results: &ThreadedResultsSer) -> Self {
+        let metrics: Vec<(&str, f32, bool)> = results.metrics();
+        let runs: usize = metrics.len();
+        let metric: String = metrics
+            .first()
+            .map(|(label, _, _)| label.to_string())
+            .unwrap_or_default();
+        let mean_value: f32 =
+            metrics.iter().map(|(_, value, _)| value).sum::<f32>() / runs.max(1) as f32;
+        let passed_runs: usize = metrics.iter().filter(|(_, _, passed)| *passed).count();
+        let diverged_runs: usize = results
+            .runs()
+            .iter()
+            .filter(|results| results.diverged())
+            .count();
+        let parameter_count: usize = results
+            .networks()
+            .first()
+            .map_or(0, |network| network.num_parameters());
+
+        Self {
+            trial,
+            parameters,
+            metric,
+            mean_value,
+            passed_runs,
+            diverged_runs,
+            runs,
+            parameter_count,
+        }
+    }
+
+    /// Index of the trial
+    pub fn trial(&self) -> usize {
+        self.trial
+    }
+
+    /// Mean metric score over every run of the trial
+    pub fn mean_value(&self) -> f32 {
+        self.mean_value
+    }
+
+    /// Name of the metric the runs were scored with
+    pub fn metric(&self) -> &str {
+        &self.metric
+    }
+
+    /// Number of runs whose metric score passed
+    pub fn passed_runs(&self) -> usize {
+        self.passed_runs
+    }
+
+    /// Total number of runs
+    pub fn runs(&self) -> usize {
+        self.runs
+    }
+
+    /// Number of trainable values of the trial's network
+    pub fn parameter_count(&self) -> usize {
+        self.parameter_count
+    }
+
+    /// Value assigned to each swept parameter, by path
+    pub fn parameters(&self) -> &Map<String, Value> {
+        &self.parameters
+    }
+}
+
+/// Serialized output of running a trained network on new inputs
+#[derive(Serialize, Debug)]
+pub struct PredictionsSer {
+    /// Decoded prediction of each sample, one row per sample
+    predictions: Array2<f64>,
+    /// Undecoded output layer values of each sample, one row per sample
+    outputs: Array2<f64>,
+}
+
+impl PredictionsSer {
+    /// # Arguments
+    ///
+    /// * `predictions` - Decoded predictions, one row per sample
+    /// * `outputs` - Undecoded output layer values, one row per sample
+    pub fn new(predictions: Array2<f64>, outputs: Array2<f64>) -> Self {
+        Self {
+            predictions,
+            outputs,
         }
     }
 }
+
+/// Serialized results of a single output head of a multi-head network
+#[derive(Serialize, Debug)]
+pub struct HeadResultsSer {
+    /// Name of the head
+    name: String,
+    /// Trained layers of the head
+    network: Perceptron,
+    /// Data for the head's metric on the validation set
+    metric: MetricSer,
+    /// Predicted values from feeding validation
+    /// set inputs into the trunk and head
+    predicted_output: Array2<f64>,
+}
+
+impl HeadResultsSer {
+    /// # Arguments
+    ///
+    /// * `name` - Name of the head
+    /// * `network` - Trained layers of the head
+    /// * `metric_label` - Name of the head's metric
+    /// * `metric_value` - Metric score of the head's prediction
+    /// * `metric_passed` - Whether or not the score is a "passing" score
+    /// * `predicted_output` - Head's prediction for the validation set
+    pub fn new(
+        name: String,
+        network: Perceptron,
+        metric_label: String,
+        metric_value: f32,
+        metric_passed: bool,
+        predicted_output: Array2<f64>,
+    ) -> Self {
+        let metric: MetricSer = MetricSer {
+            name: metric_label,
+            value: metric_value,
+            passed: metric_passed,
+            confidence_interval: None,
+        };
+        Self {
+            name,
+            network,
+            metric,
+            predicted_output,
+        }
+    }
+
+    /// Name of the head
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Name, score, and whether or not the score passed for the head's metric
+    pub fn metric(&self) -> (&str, f32, bool) {
+        (&self.metric.name, self.metric.value, self.metric.passed)
+    }
+}
+
+/// Serialized results of a single training run of a multi-head network
+#[derive(Serialize, Debug)]
+pub struct MultiHeadResultsSer {
+    /// Trained layers shared by every head
+    trunk: Perceptron,
+    /// Results of each head, in order of their output columns
+    heads: Vec<HeadResultsSer>,
+    /// Seed of the run's random number generator
+    seed: u64,
+    /// Time it took for training to complete
+    /// (in seconds)
+    elapsed_time: f32,
+    /// Total number of iterations until every head
+    /// was considered fully trained
+    total_epochs: usize,
+}
+
+impl MultiHeadResultsSer {
+    /// # Arguments
+    ///
+    /// * `trunk` - Trained layers shared by every head
+    /// * `heads` - Results of each head
+    /// * `seed` - Seed of the run's random number generator
+    /// * `elapsed_time` - Training time (in seconds)
+    /// * `total_epochs` - Number of epochs trained
+    pub fn new(
+        trunk: Perceptron,
+        heads: Vec<HeadResultsSer>,
+        seed: u64,
+        elapsed_time: f32,
+        total_epochs: usize,
+    ) -> Self {
+        Self {
+            trunk,
+            heads,
+            seed,
+            elapsed_time,
+            total_epochs,
+        }
+    }
+
+    /// Results of each head, in order of their output columns
+    pub fn heads(&self) -> &[HeadResultsSer] {
+        &self.heads
+    }
+
+    /// Total number of epochs trained
+    pub fn total_epochs(&self) -> usize {
+        self.total_epochs
+    }
+}
@@ -0,0 +1,128 @@
+use crate::error::OpenPbError;
+use ndarray::{Array2, Axis};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// Maximum number of row/column indices listed in a single message
+const MAX_LISTED: usize = 10;
+
+/// Fail if any cell of a matrix is NaN or infinite
+///
+/// # Arguments
+///
+/// * `label` - Name of the matrix for the error message (e.g. "training inputs")
+/// * `values` - Matrix with one sample per row
+pub fn check_finite(label: &str, values: &Array2<f64>) -> Result<(), OpenPbError> {
+    // Only the first few cells are described, the rest are just counted
+    let mut cells: Vec<String> = vec![];
+    let mut count: usize = 0;
+    for ((row, col), value) in values.indexed_iter() {
+        if !value.is_finite() {
+            if cells.len() < MAX_LISTED {
+                cells.push(format!("row {row}, column {col} ({value})"));
+            }
+            count += 1;
+        }
+    }
+    if count == 0 {
+        return Ok(());
+    }
+    Err(format!(
+        "{count} non-finite values in {label}: {}",
+        list(&cells, count)
+    )
+    .into())
+}
+
+/// Warning naming every column that has the same value in every row,
+/// since those columns can't help the network tell samples apart
+///
+/// # Arguments
+///
+/// * `label` - Name of the matrix for the warning message
+/// * `values` - Matrix with one sample per row
+pub fn constant_columns(label: &str, values: &Array2<f64>) -> Option<String> {
+    if values.nrows() < 2 {
+        return None;
+    }
+    let columns: Vec<usize> = values
+        .axis_iter(Axis(1))
+        .enumerate()
+        .filter(|(_, column)| column.iter().all(|value| *value == column[0]))
+        .map(|(col, _)| col)
+        .collect();
+    if columns.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "{} constant columns in {label}: {}",
+        columns.len(),
+        list(&columns, columns.len())
+    ))
+}
+
+/// Warning naming every row that's an exact copy of an earlier row
+///
+/// # Arguments
+///
+/// * `label` - Name of the matrix for the warning message
+/// * `values` - Matrix with one sample per row
+pub fn duplicate_rows(label: &str, values: &Array2<f64>) -> Option<String> {
+    // Rows are compared bit-for-bit, so -0.0 and 0.0 are treated as different.
+    // Only a hash of each distinct row is kept, rows with the same
+    // hash are compared with the original rows to rule out collisions
+    let mut first_seen: HashMap<u64, Vec<usize>> = HashMap::new();
+    let mut duplicates: Vec<String> = vec![];
+    let mut count: usize = 0;
+    for (row, sample) in values.axis_iter(Axis(0)).enumerate() {
+        let mut hasher: DefaultHasher = DefaultHasher::new();
+        for value in sample.iter() {
+            value.to_bits().hash(&mut hasher);
+        }
+        let candidates: &mut Vec<usize> = first_seen.entry(hasher.finish()).or_default();
+        let original: Option<usize> = candidates.iter().copied().find(|&original| {
+            values
+                .row(original)
+                .iter()
+                .zip(sample.iter())
+                .all(|(a, b)| a.to_bits() == b.to_bits())
+        });
+        match original {
+            Some(original) => {
+                if duplicates.len() < MAX_LISTED {
+                    duplicates.push(format!("{row} (same as {original})"));
+                }
+                count += 1;
+            }
+            None => candidates.push(row),
+        }
+    }
+    if count == 0 {
+        return None;
+    }
+    Some(format!(
+        "{count} duplicate rows in {label}: {}",
+        list(&duplicates, count)
+    ))
+}
+
+/// Comma-separated list of (at most `MAX_LISTED`) items
+///
+/// # Arguments
+///
+/// * `items` - Items to be listed
+/// * `total` - Number of items there are, including any that weren't collected
+fn list<T: ToString>(items: &[T], total: usize) -> String {
+    let mut listed: String = items
+        .iter()
+        .take(MAX_LISTED)
+        .map(ToString::to_string)
+        .collect::<Vec<String>>()
+        .join(", ");
+    let shown: usize = items.len().min(MAX_LISTED);
+    if total > shown {
+        listed.push_str(&format!(", ... ({} more)", total - shown));
+    }
+    listed
+}
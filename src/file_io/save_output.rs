@@ -1,33 +1,164 @@
-//use crate::nn::perceptron::Perceptron;
-use super::results_ser::ThreadedResultsSer;
-use crate::args::Args;
+use super::bundle::{self, ExperimentBundle};
+use super::checksum;
+use super::json_de::DataDe;
+use super::output_format::OutputFormat;
+use super::results_ser::{MultiHeadResultsSer, ThreadedResultsSer};
+use crate::error::OpenPbError;
+use crate::nn::lr_finder::LrFinderResults;
 use chrono::{DateTime, Utc};
+use serde::Serialize;
 use std::fs::{self, File};
 use std::io::prelude::*;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// Path that refers to stdin (for input) or stdout (for output)
+pub const STDIO_PATH: &str = "-";
+
+/// Template of the file results are saved to when no output path is given
+pub const DEFAULT_OUTPUT_TEMPLATE: &str = "output/{timestamp}.json";
+
+/// Values substituted into an output file name template
+#[derive(Debug, Clone, Copy)]
+pub struct OutputNames<'a> {
+    /// Network config file name (without its directory or extension)
+    pub network_name: &'a str,
+
+    /// Seed of the first training run (optional)
+    pub seed: Option<u64>,
+}
+
+/// Fill in an output file name template. Supported placeholders are
+/// `{date}` (YYYY-MM-DD), `{time}` (HHMMSS), `{timestamp}` (DDMMYYHHMMSS),
+/// `{network_name}`, and `{seed}` ("unseeded" for unseeded runs)
+///
+/// # Arguments
+///
+/// * `template` - File name template, e.g. `runs/{date}/{network_name}_{seed}.json`
+/// * `names` - Network name and seed of the training job
+pub fn render_output_template(template: &str, names: &OutputNames) -> Result<String, OpenPbError> {
+    let now: DateTime<Utc> = Utc::now();
+    let mut path: String = String::with_capacity(template.len());
+    let mut rest: &str = template;
+
+    while let Some(start) = rest.find('{') {
+        path.push_str(&rest[..start]);
+        let end: usize = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => {
+                return Err(
+                    format!("Unclosed placeholder in output template \"{template}\"").into(),
+                )
+            }
+        };
+        let value: String = match &rest[start + 1..end] {
+            "date" => now.format("%Y-%m-%d").to_string(),
+            "time" => now.format("%H%M%S").to_string(),
+            "timestamp" => now.format("%d%m%y%H%M%S").to_string(),
+            "network_name" => names.network_name.to_string(),
+            "seed" => match names.seed {
+                Some(seed) => seed.to_string(),
+                None => "unseeded".to_string(),
+            },
+            placeholder => {
+                return Err(format!(
+                    "Unknown placeholder {{{placeholder}}} in output template \"{template}\""
+                )
+                .into())
+            }
+        };
+        path.push_str(&value);
+        rest = &rest[end + 1..];
+    }
+    path.push_str(rest);
+    Ok(path)
+}
+
+/// Path results are saved to: the given output path (which may be
+/// `"-"` for stdout), or the filled in output file name template. Binary
+/// formats replace the extension of the filled in template with their own
+///
+/// # Arguments
+///
+/// * `output` - Path of the output file (optional)
+/// * `template` - File name template used when no path is given
+///   (`DEFAULT_OUTPUT_TEMPLATE` when `None`)
+/// * `names` - Network name and seed of the training job
+/// * `format` - Encoding of the results file
+pub fn output_path(
+    output: Option<String>,
+    template: Option<&str>,
+    names: &OutputNames,
+    format: OutputFormat,
+) -> Result<String, OpenPbError> {
+    match output {
+        Some(output_path) => Ok(output_path),
+        None => {
+            let path: String =
+                render_output_template(template.unwrap_or(DEFAULT_OUTPUT_TEMPLATE), names)?;
+            match format {
+                OutputFormat::Json => Ok(path),
+                _ => Ok(Path::new(&path)
+                    .with_extension(format.extension())
+                    .to_string_lossy()
+                    .to_string()),
+            }
+        }
+    }
+}
 
 /// Save network values to file
 ///
 /// # Arguments
 ///
-/// * `args` - Command line arguments
-/// * `network` - Trained network to be serialized
-pub fn save_to_dir(args: Args, threaded_results: ThreadedResultsSer) -> Result<(), String> {
-    let filepath: String = if let Some(output_path) = args.output {
-        output_path
-    } else {
-        let now: DateTime<Utc> = Utc::now();
-        format!("output/{}.json", now.format("%d%m%y%H%M%S"))
-    };
+/// * `filepath` - Path of the output file (see `output_path`).
+///   When `"-"`, results are written to stdout
+/// * `threaded_results` - Training results to be serialized
+/// * `format` - Encoding of the results file
+pub fn save_to_dir(
+    filepath: String,
+    threaded_results: ThreadedResultsSer,
+    format: OutputFormat,
+) -> Result<(), OpenPbError> {
+    if filepath == STDIO_PATH {
+        return write_stdout(&threaded_results, format);
+    }
     let filepath: &Path = Path::new(filepath.as_str());
 
     if let Some(parent_dir) = filepath.parent() {
         match fs::create_dir_all(parent_dir) {
             Ok(_) => {}
-            Err(err) => return Err(err.to_string()),
+            Err(source) => {
+                return Err(OpenPbError::Io {
+                    path: parent_dir.display().to_string(),
+                    source,
+                })
+            }
         }
     }
-    save_layer_values(threaded_results, filepath)
+    save_layer_values(threaded_results, filepath, format)
+}
+
+/// Save the training results of every run of a multi-head network
+///
+/// # Arguments
+///
+/// * `filepath` - Path of the output file (see `output_path`).
+///   When `"-"`, results are written to stdout
+/// * `results` - Training results of each run
+/// * `format` - Encoding of the results file
+pub fn save_multi_head(
+    filepath: String,
+    results: &[MultiHeadResultsSer],
+    format: OutputFormat,
+) -> Result<(), OpenPbError> {
+    if filepath == STDIO_PATH {
+        return write_stdout(results, format);
+    }
+    write_bytes(
+        "multi-head results",
+        &format.encode(results)?,
+        Path::new(filepath.as_str()),
+    )
 }
 
 /// Save internal values (weights and biases) from each layer of a network
@@ -35,21 +166,222 @@ pub fn save_to_dir(args: Args, threaded_results: ThreadedResultsSer) -> Result<(
 /// # Arguments
 ///
 /// * `network` - Network object to be serialized
-/// * `filepath` - JSON file to write serialized values to
-fn save_layer_values(threaded_results: ThreadedResultsSer, filepath: &Path) -> Result<(), String> {
-    println!("\nAttempting to write to {:#?}...", filepath);
+/// * `filepath` - File to write serialized values to
+/// * `format` - Encoding of the file
+fn save_layer_values(
+    threaded_results: ThreadedResultsSer,
+    filepath: &Path,
+    format: OutputFormat,
+) -> Result<(), OpenPbError> {
+    eprintln!("\nAttempting to write to {:#?}...", filepath);
+
+    let network_ser: Vec<u8> = format.encode(&threaded_results)?;
+    write_atomic(filepath, &network_ser)?;
+    eprintln!("Success!");
+    Ok(())
+}
+
+/// Write a `sha256sum`-compatible checksum of a saved file next to it
+/// (`results.json.sha256` for `results.json`), so a copied or archived
+/// results file can be checked with `sha256sum -c`
+///
+/// # Arguments
+///
+/// * `filepath` - File that has already been written
+pub fn save_checksum(filepath: &Path) -> Result<(), OpenPbError> {
+    let contents: Vec<u8> = match fs::read(filepath) {
+        Ok(contents) => contents,
+        Err(error) => return Err(format!("Failed to read file {:#?}: {error}", filepath).into()),
+    };
+    let file_name: String = match filepath.file_name() {
+        Some(file_name) => file_name.to_string_lossy().to_string(),
+        None => return Err(format!("{:#?} isn't a file", filepath).into()),
+    };
+    let mut checksum_path = filepath.as_os_str().to_owned();
+    checksum_path.push(".sha256");
+
+    let line: String = format!("{}  {file_name}\n", checksum::sha256_hex(&contents));
+    write_file("checksum", line, Path::new(&checksum_path))
+}
+
+/// Write serialized training results to stdout, so they can be piped
+/// into another program (progress messages are written to stderr)
+///
+/// # Arguments
+///
+/// * `results` - Training results to be serialized
+/// * `format` - Encoding of the results (JSON is followed by a newline)
+fn write_stdout<T: Serialize + ?Sized>(
+    results: &T,
+    format: OutputFormat,
+) -> Result<(), OpenPbError> {
+    let mut network_ser: Vec<u8> = format.encode(results)?;
+    if format == OutputFormat::Json {
+        network_ser.push(b'\n');
+    }
+    let mut stdout = std::io::stdout().lock();
+    match stdout.write_all(&network_ser).and_then(|_| stdout.flush()) {
+        Ok(_) => Ok(()),
+        Err(source) => Err(OpenPbError::Io {
+            path: "stdout".to_string(),
+            source,
+        }),
+    }
+}
+
+/// Save an entire experiment (configuration, data, trained networks, encoder
+/// state, and results) to file, as a `.tar.zst` archive when the file name
+/// ends in `.tar.zst` (or `.tzst`) and as JSON otherwise
+///
+/// # Arguments
+///
+/// * `bundle` - Experiment bundle to be serialized
+/// * `filepath` - File to write the serialized bundle to
+pub fn save_bundle(bundle: &ExperimentBundle, filepath: &str) -> Result<(), OpenPbError> {
+    if bundle::is_archive_path(filepath) {
+        let archive: Vec<u8> = bundle.to_archive()?;
+        return write_bytes("experiment bundle archive", &archive, Path::new(filepath));
+    }
+    let bundle_json: String = bundle.to_json()?;
+    write_file("experiment bundle", bundle_json, Path::new(filepath))
+}
+
+/// Save training and validation sets in the same JSON format `--data` reads
+///
+/// # Arguments
+///
+/// * `data_de` - Training and validation sets to be serialized
+/// * `filepath` - JSON file to write the serialized data to
+pub fn save_data(data_de: &DataDe, filepath: &str) -> Result<(), OpenPbError> {
+    let data_json: String = match serde_json::to_string(data_de) {
+        Ok(data_json) => data_json,
+        Err(error) => return Err(format!("Failed to serialize training data: {error}").into()),
+    };
+    write_file("training data", data_json, Path::new(filepath))
+}
+
+/// Save the recorded losses of a learning rate sweep
+///
+/// # Arguments
+///
+/// * `results` - Learning rates, losses, and suggested learning rate
+/// * `filepath` - JSON file to write the serialized sweep to
+pub fn save_lr_sweep(results: &LrFinderResults, filepath: &str) -> Result<(), OpenPbError> {
+    let sweep_json: String = match serde_json::to_string_pretty(results) {
+        Ok(sweep_json) => sweep_json,
+        Err(error) => {
+            return Err(format!("Failed to serialize learning rate sweep: {error}").into())
+        }
+    };
+    write_file("learning rate sweep", sweep_json, Path::new(filepath))
+}
 
-    let mut file = match File::create(filepath) {
-        Ok(file) => file,
-        Err(error) => return Err(format!("Failed to create file {:#?}: {error}", filepath)),
+/// Save any serializable value as pretty-printed JSON
+///
+/// # Arguments
+///
+/// * `label` - Description of the value for command line output
+/// * `value` - Value to be serialized
+/// * `filepath` - JSON file to write the serialized value to
+pub fn save_json<T: Serialize + ?Sized>(
+    label: &str,
+    value: &T,
+    filepath: &Path,
+) -> Result<(), OpenPbError> {
+    let json: String = match serde_json::to_string_pretty(value) {
+        Ok(json) => json,
+        Err(error) => return Err(format!("Failed to serialize {label}: {error}").into()),
     };
+    write_file(label, json, filepath)
+}
 
-    let network_ser = serde_json::to_string_pretty(&threaded_results).unwrap();
-    match file.write_all(network_ser.as_bytes()) {
-        Ok(_) => {
-            println!("Success!");
-            Ok(())
+/// Save plain text (e.g. markdown) to a file
+///
+/// # Arguments
+///
+/// * `label` - Description of the text for command line output
+/// * `text` - Contents of the file
+/// * `filepath` - File to write the text to
+pub fn save_text(label: &str, text: &str, filepath: &Path) -> Result<(), OpenPbError> {
+    write_file(label, text.to_string(), filepath)
+}
+
+/// Write contents to a file, creating any missing parent directories
+///
+/// # Arguments
+///
+/// * `label` - Description of the contents for command line output
+/// * `contents` - Serialized contents of the file
+/// * `filepath` - File to write the contents to
+fn write_file(label: &str, contents: String, filepath: &Path) -> Result<(), OpenPbError> {
+    write_bytes(label, contents.as_bytes(), filepath)
+}
+
+/// Same as `write_file`, but for encoded (possibly binary) contents
+///
+/// # Arguments
+///
+/// * `label` - Description of the contents for command line output
+/// * `contents` - Encoded contents of the file
+/// * `filepath` - File to write the contents to
+fn write_bytes(label: &str, contents: &[u8], filepath: &Path) -> Result<(), OpenPbError> {
+    eprintln!("\nAttempting to write {label} to {:#?}...", filepath);
+
+    if let Some(parent_dir) = filepath.parent() {
+        if let Err(source) = fs::create_dir_all(parent_dir) {
+            return Err(OpenPbError::Io {
+                path: parent_dir.display().to_string(),
+                source,
+            });
+        }
+    }
+    write_atomic(filepath, contents)?;
+    eprintln!("Success!");
+    Ok(())
+}
+
+/// Write contents to a temporary file in the same directory, flush it to
+/// disk, then rename it over the destination. A crash or full disk part
+/// way through leaves the previous file (if any) untouched instead of a
+/// truncated one
+///
+/// # Arguments
+///
+/// * `filepath` - File to write the contents to
+/// * `contents` - Bytes to write
+fn write_atomic(filepath: &Path, contents: &[u8]) -> Result<(), OpenPbError> {
+    let file_name: String = match filepath.file_name() {
+        Some(file_name) => file_name.to_string_lossy().to_string(),
+        None => return Err(format!("{:#?} isn't a file", filepath).into()),
+    };
+    let temp_path: PathBuf =
+        filepath.with_file_name(format!(".{file_name}.{}.tmp", std::process::id()));
+
+    let result: std::io::Result<()> = File::create(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(contents)?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&temp_path, filepath));
+    if let Err(source) = result {
+        let _ = fs::remove_file(&temp_path);
+        return Err(OpenPbError::Io {
+            path: filepath.display().to_string(),
+            source,
+        });
+    }
+
+    // Syncing the directory makes the rename itself durable
+    #[cfg(unix)]
+    if let Some(parent_dir) = filepath.parent() {
+        let parent_dir: &Path = if parent_dir.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            parent_dir
+        };
+        if let Ok(dir) = File::open(parent_dir) {
+            let _ = dir.sync_all();
         }
-        Err(error) => Err(error.to_string()),
     }
+    Ok(())
 }
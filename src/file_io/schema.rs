@@ -0,0 +1,98 @@
+use super::config::EXTENDS_KEY;
+use super::json_de::{DataDe, NetworkDe};
+use super::time_series::TimeSeriesDe;
+use schemars::schema::RootSchema;
+use schemars::{schema_for, JsonSchema};
+use serde_json::{json, Map, Value};
+
+/// Shape of a data JSON file with a time series instead of samples
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+struct TimeSeriesDataSchema {
+    /// Series that's split into sliding-window input/target pairs
+    time_series: TimeSeriesDe,
+}
+
+/// Either shape of a data JSON file
+#[derive(JsonSchema)]
+#[schemars(untagged)]
+#[allow(dead_code)]
+enum DataSchema {
+    /// Training, validation, and hold-out test samples
+    Samples(Box<DataDe>),
+
+    /// Time series split into windows
+    TimeSeries(TimeSeriesDataSchema),
+}
+
+/// JSON Schema of network config files, generated from `NetworkDe`. A
+/// config with an `"extends"` field is only complete once it's merged
+/// with its base config, so it's checked against a copy of the schema
+/// where no field is required
+pub fn network_schema() -> Value {
+    let mut schema: Value = json!(schema_for!(NetworkDe));
+    let properties: Value = schema["properties"].take();
+    let required: Value = schema["required"].take();
+    let mut definitions: Map<String, Value> = match schema["definitions"].take() {
+        Value::Object(definitions) => definitions,
+        _ => Map::new(),
+    };
+    let partial_definitions: Vec<(String, Value)> = definitions
+        .iter()
+        .map(|(name, definition)| (format!("Partial{name}"), partial(definition)))
+        .collect();
+    definitions.extend(partial_definitions);
+
+    let extends: Value = json!({
+        "description": "Path of a base config (relative to this file) that this config overrides",
+        "type": "string"
+    });
+    let mut partial_properties: Value = partial(&properties);
+    partial_properties[EXTENDS_KEY] = extends.clone();
+
+    json!({
+        "$schema": schema["$schema"],
+        "title": schema["title"],
+        "description": schema["description"],
+        "type": "object",
+        "properties": { EXTENDS_KEY: extends },
+        "if": { "required": [EXTENDS_KEY] },
+        "then": { "properties": partial_properties },
+        "else": { "properties": properties, "required": required },
+        "definitions": definitions
+    })
+}
+
+/// JSON Schema of data JSON files, generated from `DataDe`
+/// (or the time series wrapper around `TimeSeriesDe`)
+pub fn data_schema() -> Value {
+    let mut schema: RootSchema = schema_for!(DataSchema);
+    schema.schema.metadata().title = Some("DataDe".to_string());
+    json!(schema)
+}
+
+/// Copy of a schema where no field is required, and every definition
+/// it refers to is replaced with the matching partial definition
+///
+/// # Arguments
+///
+/// * `schema` - Schema (or part of a schema) being copied
+fn partial(schema: &Value) -> Value {
+    match schema {
+        Value::Object(object) => Value::Object(
+            object
+                .iter()
+                .filter(|(key, _)| key.as_str() != "required")
+                .map(|(key, value)| match (key.as_str(), value.as_str()) {
+                    ("$ref", Some(reference)) => (
+                        key.clone(),
+                        Value::from(reference.replace("#/definitions/", "#/definitions/Partial")),
+                    ),
+                    _ => (key.clone(), partial(value)),
+                })
+                .collect(),
+        ),
+        Value::Array(values) => Value::Array(values.iter().map(partial).collect()),
+        _ => schema.clone(),
+    }
+}
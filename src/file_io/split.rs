@@ -0,0 +1,115 @@
+use crate::error::OpenPbError;
+use ndarray::Array2;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Deserialized values representing how a dataset without a validation set
+/// is partitioned into training and validation sets in JSON
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy)]
+pub struct SplitDe {
+    /// Fraction of the samples held out for validation (between 0 and 1)
+    ratio: f64,
+
+    /// Seed of the shuffle that picks the validation samples, so
+    /// the same file is always split the same way
+    #[serde(default)]
+    seed: u64,
+
+    /// Whether or not each class (samples with identical output values)
+    /// is split separately, so both sets keep the class proportions
+    #[serde(default)]
+    stratify: bool,
+}
+
+/// Shape of the split wrapper in a data JSON file
+#[cfg(feature = "file_io")]
+#[derive(Deserialize, Debug)]
+pub(crate) struct SplitFileDe {
+    /// Split options (absent when the data isn't split). Kept
+    /// as raw JSON, so errors name the split's own fields
+    #[serde(default)]
+    pub split: Option<Value>,
+}
+
+impl SplitDe {
+    /// # Arguments
+    ///
+    /// * `ratio` - Fraction of the samples held out for validation
+    /// * `seed` - Seed of the shuffle that picks the validation samples
+    /// * `stratify` - Whether or not each class is split separately
+    pub fn new(ratio: f64, seed: u64, stratify: bool) -> Self {
+        Self {
+            ratio,
+            seed,
+            stratify,
+        }
+    }
+
+    /// # Arguments
+    ///
+    /// * `split` - The `"split"` object of a data JSON file
+    pub fn from_value(split: Value) -> Result<SplitDe, OpenPbError> {
+        match serde_json::from_value(split) {
+            Ok(split_de) => Ok(split_de),
+            Err(error) => Err(format!("Invalid split: {error}").into()),
+        }
+    }
+
+    /// Rows held out for validation, in ascending order. The rows are
+    /// picked by a seeded shuffle (of each class separately when
+    /// stratified), and every class keeps at least one training sample
+    ///
+    /// # Arguments
+    ///
+    /// * `outputs` - Unencoded output values (samples x outputs)
+    pub fn validation_rows(&self, outputs: &Array2<f64>) -> Result<Vec<usize>, OpenPbError> {
+        if !(self.ratio > 0.0 && self.ratio < 1.0) {
+            return Err(format!(
+                "Split ratio must be between 0 and 1 (exclusive), got {}",
+                self.ratio
+            )
+            .into());
+        }
+
+        // Classes are kept in order of their first sample, so the split is deterministic
+        let groups: Vec<Vec<usize>> = if self.stratify {
+            let mut groups: Vec<Vec<usize>> = vec![];
+            let mut class_groups: HashMap<Vec<u64>, usize> = HashMap::new();
+            for (row, output) in outputs.rows().into_iter().enumerate() {
+                let class: Vec<u64> = output.iter().map(|value| value.to_bits()).collect();
+                let group: usize = *class_groups.entry(class).or_insert_with(|| {
+                    groups.push(vec![]);
+                    groups.len() - 1
+                });
+                groups[group].push(row);
+            }
+            groups
+        } else {
+            vec![(0..outputs.nrows()).collect()]
+        };
+
+        let mut rng: StdRng = StdRng::seed_from_u64(self.seed);
+        let mut validation_rows: Vec<usize> = vec![];
+        for mut group in groups {
+            group.shuffle(&mut rng);
+            let count: usize = ((group.len() as f64 * self.ratio).round() as usize)
+                .min(group.len().saturating_sub(1));
+            validation_rows.extend_from_slice(&group[..count]);
+        }
+        if validation_rows.is_empty() {
+            return Err(format!(
+                "Split ratio {} holds out no validation samples from {} training samples",
+                self.ratio,
+                outputs.nrows()
+            )
+            .into());
+        }
+        validation_rows.sort_unstable();
+        Ok(validation_rows)
+    }
+}
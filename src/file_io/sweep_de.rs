@@ -0,0 +1,592 @@
+use crate::error::OpenPbError;
+use crate::nn::divergence::DivergenceConfig;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Prefix of parameter paths that refer to training options
+/// instead of the network config
+const TRAINING_PREFIX: &str = "training.";
+
+/// Parameter path of the hidden layer sizes chosen by an architecture search
+pub const ARCHITECTURE_PATH: &str = "hidden_layers";
+
+/// Parameter path of the dropout rate applied to every hidden layer
+pub const HIDDEN_DROPOUT_PATH: &str = "hidden_dropout";
+
+/// How trial configurations are chosen from the parameter space
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StrategyDe {
+    /// Every combination of parameter values (up to the trial budget)
+    Grid,
+
+    /// Independently sampled parameter values for each trial
+    Random,
+}
+
+/// Spacing of the values sampled from a numeric range
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ScaleDe {
+    /// Evenly spaced values
+    #[default]
+    Linear,
+
+    /// Values evenly spaced by order of magnitude (e.g. learning rates)
+    Log,
+}
+
+/// Deserialized numeric range of a swept parameter
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RangeDe {
+    /// Smallest value (inclusive)
+    pub min: f64,
+
+    /// Largest value (inclusive)
+    pub max: f64,
+
+    /// Spacing of the sampled values
+    #[serde(default)]
+    pub scale: ScaleDe,
+
+    /// Number of evenly spaced values used by grid search
+    #[serde(default = "default_steps")]
+    pub steps: usize,
+
+    /// Whether or not values are rounded to integers (e.g. neuron counts)
+    #[serde(default)]
+    pub integer: bool,
+}
+
+/// Default number of grid values taken from a range
+fn default_steps() -> usize {
+    3
+}
+
+/// Deserialized values representing a single swept parameter
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ParameterDe {
+    /// Dot-separated path of the value in the network config (e.g.
+    /// "optimizer.learning_rate" or "layers.0.neurons"). Paths that start
+    /// with "training." refer to training options (e.g. "training.batch_size")
+    pub path: String,
+
+    /// Explicit list of values to try
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub values: Option<Vec<Value>>,
+
+    /// Numeric range of values to try
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<RangeDe>,
+}
+
+impl ParameterDe {
+    /// Every value grid search tries for this parameter
+    pub fn grid_values(&self) -> Vec<Value> {
+        if let Some(values) = &self.values {
+            return values.clone();
+        }
+        let range: &RangeDe = match &self.range {
+            Some(range) => range,
+            None => return vec![],
+        };
+        let steps: usize = range.steps.max(1);
+        let mut values: Vec<Value> = (0..steps)
+            .map(|i| {
+                let t: f64 = if steps == 1 {
+                    0.0
+                } else {
+                    i as f64 / (steps - 1) as f64
+                };
+                range_value(range, t)
+            })
+            .collect();
+        // Rounding to integers can produce the same value more than once
+        values.dedup();
+        values
+    }
+
+    /// Randomly sample a single value for this parameter
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - Random number generator used for sampling
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Value {
+        if let Some(values) = &self.values {
+            return values[rng.gen_range(0..values.len())].clone();
+        }
+        match &self.range {
+            Some(range) => range_value(range, rng.gen::<f64>()),
+            None => Value::Null,
+        }
+    }
+}
+
+/// Value at a fraction of the way through a range
+///
+/// # Arguments
+///
+/// * `range` - Numeric range of the parameter
+/// * `t` - Fraction of the way from `min` to `max` (0 to 1)
+fn range_value(range: &RangeDe, t: f64) -> Value {
+    let value: f64 = match range.scale {
+        ScaleDe::Linear => range.min + t * (range.max - range.min),
+        ScaleDe::Log => range.min * (range.max / range.min).powf(t),
+    };
+    if range.integer {
+        Value::from(value.round() as i64)
+    } else {
+        Value::from(value)
+    }
+}
+
+/// Deserialized bounds of an architecture search over the number
+/// of hidden layers and the number of neurons in each of them
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ArchitectureDe {
+    /// Smallest number of hidden layers (inclusive)
+    #[serde(default = "default_min_layers")]
+    pub min_layers: usize,
+
+    /// Largest number of hidden layers (inclusive)
+    pub max_layers: usize,
+
+    /// Smallest number of neurons in a hidden layer (inclusive)
+    pub min_neurons: usize,
+
+    /// Largest number of neurons in a hidden layer (inclusive)
+    pub max_neurons: usize,
+
+    /// Spacing of the neuron counts grid search tries
+    #[serde(default)]
+    pub scale: ScaleDe,
+
+    /// Number of neuron counts grid search tries
+    #[serde(default = "default_steps")]
+    pub steps: usize,
+
+    /// Activation function of every hidden layer (otherwise
+    /// copied from the base config's first hidden layer)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activation: Option<String>,
+}
+
+/// Default smallest number of hidden layers
+fn default_min_layers() -> usize {
+    1
+}
+
+impl ArchitectureDe {
+    /// Range of neuron counts a hidden layer can have
+    fn neurons_range(&self) -> RangeDe {
+        RangeDe {
+            min: self.min_neurons as f64,
+            max: self.max_neurons as f64,
+            scale: self.scale,
+            steps: self.steps,
+            integer: true,
+        }
+    }
+
+    /// Every architecture grid search tries: each number of
+    /// hidden layers with every layer the same size
+    pub fn grid_values(&self) -> Vec<Value> {
+        let neurons: ParameterDe = ParameterDe {
+            path: ARCHITECTURE_PATH.to_string(),
+            values: None,
+            range: Some(self.neurons_range()),
+        };
+        let widths: Vec<Value> = neurons.grid_values();
+        (self.min_layers..=self.max_layers)
+            .flat_map(|layers| {
+                widths
+                    .iter()
+                    .map(move |width| Value::from(vec![width.clone(); layers]))
+            })
+            .collect()
+    }
+
+    /// Randomly sample an architecture, with the size
+    /// of each hidden layer sampled independently
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - Random number generator used for sampling
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Value {
+        let layers: usize = rng.gen_range(self.min_layers..=self.max_layers);
+        let range: RangeDe = self.neurons_range();
+        let widths: Vec<Value> = (0..layers)
+            .map(|_| range_value(&range, rng.gen::<f64>()))
+            .collect();
+        Value::from(widths)
+    }
+}
+
+/// Deserialized training options shared by every trial
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SweepTrainingDe {
+    /// Maximum number of epochs for each run
+    pub epochs: usize,
+
+    /// Number of runs of each trial's network setup
+    #[serde(default = "default_runs")]
+    pub runs: usize,
+
+    /// Number of worker threads in the pool used to train each run
+    #[serde(default = "default_runs")]
+    pub threads: usize,
+
+    /// IDs of the CPU cores the worker threads are pinned to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pin_cores: Option<Vec<usize>>,
+
+    /// Maximum number of samples in each minibatch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch_size: Option<usize>,
+
+    /// Whether or not training data is shuffled each epoch
+    #[serde(default)]
+    pub shuffle: bool,
+
+    /// Number of epochs between console lines with each run's
+    /// training loss and validation metric
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_every: Option<usize>,
+
+    /// Growth of the training loss that stops a run as diverged
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub divergence: Option<DivergenceConfig>,
+}
+
+/// Default number of runs and threads per trial
+fn default_runs() -> usize {
+    1
+}
+
+/// Deserialized values representing a hyperparameter sweep in JSON or YAML
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SweepDe {
+    /// Network config file every trial starts from
+    pub network: String,
+
+    /// Training and validation data file, or a list of files that are merged
+    pub data: DataFilesDe,
+
+    /// How trial configurations are chosen
+    pub strategy: StrategyDe,
+
+    /// Maximum number of trials
+    pub trials: usize,
+
+    /// Parameters that vary between trials
+    #[serde(default)]
+    pub parameters: Vec<ParameterDe>,
+
+    /// Bounds of the hidden layer sizes searched over (no
+    /// architecture search when omitted)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub architecture: Option<ArchitectureDe>,
+
+    /// Training options shared by every trial
+    pub training: SweepTrainingDe,
+
+    /// Directory where each sweep's trials are archived
+    #[serde(default = "default_run_dir")]
+    pub run_dir: String,
+
+    /// Seed of the random strategy's trial configurations (a random
+    /// seed is drawn and archived with the sweep when omitted)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+}
+
+/// Either a single data file or a list of data files
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum DataFilesDe {
+    /// Single data file
+    One(String),
+
+    /// Data files that are concatenated into a single dataset
+    Many(Vec<String>),
+}
+
+impl DataFilesDe {
+    /// Every data file, in order
+    pub fn paths(&self) -> Vec<String> {
+        match self {
+            DataFilesDe::One(path) => vec![path.clone()],
+            DataFilesDe::Many(paths) => paths.clone(),
+        }
+    }
+}
+
+/// Default directory where sweeps are archived
+fn default_run_dir() -> String {
+    "sweeps".to_string()
+}
+
+/// Network config and training options of a single trial
+#[derive(Serialize, Debug, Clone)]
+pub struct TrialDe {
+    /// Value assigned to each swept parameter, by path
+    pub parameters: Map<String, Value>,
+
+    /// Resolved network config
+    pub network: Value,
+
+    /// Resolved training options
+    pub training: SweepTrainingDe,
+}
+
+impl SweepDe {
+    /// # Arguments
+    ///
+    /// * `sweep_str` - Raw contents of the sweep config file
+    /// * `yaml` - Whether the contents are YAML (otherwise JSON)
+    pub fn from_str(sweep_str: &str, yaml: bool) -> Result<SweepDe, OpenPbError> {
+        let sweep_de: SweepDe = if yaml {
+            match serde_yaml::from_str(sweep_str) {
+                Ok(sweep_de) => sweep_de,
+                Err(error) => return Err(format!("Invalid sweep config: {error}").into()),
+            }
+        } else {
+            match serde_json::from_str(sweep_str) {
+                Ok(sweep_de) => sweep_de,
+                Err(error) => return Err(format!("Invalid sweep config: {error}").into()),
+            }
+        };
+        sweep_de.validate()?;
+        Ok(sweep_de)
+    }
+
+    /// Check that every parameter has values to try and that the
+    /// architecture bounds (if any) describe at least one network
+    pub fn validate(&self) -> Result<(), OpenPbError> {
+        for parameter in &self.parameters {
+            match (&parameter.values, &parameter.range) {
+                (Some(values), None) if !values.is_empty() => {}
+                (None, Some(range)) if range.scale == ScaleDe::Log && range.min <= 0.0 => {
+                    return Err(format!(
+                        "Log scale range for \"{}\" must be positive",
+                        parameter.path
+                    )
+                    .into())
+                }
+                (None, Some(_)) => {}
+                _ => {
+                    return Err(format!(
+                        "Parameter \"{}\" needs either a non-empty \"values\" list or a \"range\"",
+                        parameter.path
+                    )
+                    .into())
+                }
+            }
+        }
+        if let Some(architecture) = &self.architecture {
+            if architecture.min_layers > architecture.max_layers {
+                return Err("Architecture min_layers can't exceed max_layers".into());
+            }
+            if architecture.min_neurons == 0 || architecture.min_neurons > architecture.max_neurons
+            {
+                return Err(
+                    "Architecture min_neurons must be positive and can't exceed max_neurons".into(),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Every value of each swept parameter (including the architecture,
+    /// if searched) that grid search tries, by path
+    fn grid(&self) -> Vec<(String, Vec<Value>)> {
+        let mut grid: Vec<(String, Vec<Value>)> = self
+            .parameters
+            .iter()
+            .map(|parameter| (parameter.path.clone(), parameter.grid_values()))
+            .collect();
+        if let Some(architecture) = &self.architecture {
+            grid.push((ARCHITECTURE_PATH.to_string(), architecture.grid_values()));
+        }
+        grid
+    }
+
+    /// Values assigned to each parameter for every trial, limited to the trial budget
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - Random number generator used by random search
+    pub fn assignments<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec<Map<String, Value>> {
+        match self.strategy {
+            StrategyDe::Random => (0..self.trials)
+                .map(|_| {
+                    let mut assignment: Map<String, Value> = self
+                        .parameters
+                        .iter()
+                        .map(|parameter| (parameter.path.clone(), parameter.sample(rng)))
+                        .collect();
+                    if let Some(architecture) = &self.architecture {
+                        assignment.insert(ARCHITECTURE_PATH.to_string(), architecture.sample(rng));
+                    }
+                    assignment
+                })
+                .collect(),
+            StrategyDe::Grid => {
+                // Cartesian product of every parameter's grid values
+                let mut assignments: Vec<Map<String, Value>> = vec![Map::new()];
+                for (path, values) in &self.grid() {
+                    assignments = assignments
+                        .into_iter()
+                        .flat_map(|assignment| {
+                            values.iter().map(move |value| {
+                                let mut assignment = assignment.clone();
+                                assignment.insert(path.clone(), value.clone());
+                                assignment
+                            })
+                        })
+                        .collect();
+                }
+                assignments.truncate(self.trials);
+                assignments
+            }
+        }
+    }
+
+    /// Apply one set of parameter values to the base network config and training options
+    ///
+    /// # Arguments
+    ///
+    /// * `base_network` - Base network config every trial starts from
+    /// * `parameters` - Value assigned to each swept parameter, by path
+    pub fn resolve_trial(
+        &self,
+        base_network: &Value,
+        parameters: Map<String, Value>,
+    ) -> Result<TrialDe, OpenPbError> {
+        let mut network: Value = base_network.clone();
+        let mut training: Value = match serde_json::to_value(&self.training) {
+            Ok(training) => training,
+            Err(error) => return Err(error.to_string().into()),
+        };
+
+        for (path, value) in &parameters {
+            if path == ARCHITECTURE_PATH || path == HIDDEN_DROPOUT_PATH {
+                continue;
+            }
+            match path.strip_prefix(TRAINING_PREFIX) {
+                Some(training_path) => set_path(&mut training, training_path, value.clone())?,
+                None => set_path(&mut network, path, value.clone())?,
+            }
+        }
+        // Hidden layers are replaced before their dropout is set, so new layers get it too
+        if let Some(sizes) = parameters.get(ARCHITECTURE_PATH) {
+            self.set_hidden_layers(&mut network, sizes)?;
+        }
+        if let Some(rate) = parameters.get(HIDDEN_DROPOUT_PATH) {
+            set_hidden_dropout(&mut network, rate)?;
+        }
+        let training: SweepTrainingDe = match serde_json::from_value(training) {
+            Ok(training) => training,
+            Err(error) => return Err(format!("Invalid training options: {error}").into()),
+        };
+        Ok(TrialDe {
+            parameters,
+            network,
+            training,
+        })
+    }
+}
+
+impl SweepDe {
+    /// Replace the hidden layers of a network config with layers of the given
+    /// sizes. Each new layer is a copy of the config's first hidden layer (or
+    /// of the output layer's activation when there are no hidden layers)
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - Network config being modified
+    /// * `sizes` - Number of neurons in each hidden layer (in order)
+    fn set_hidden_layers(&self, network: &mut Value, sizes: &Value) -> Result<(), OpenPbError> {
+        let layers: &mut Vec<Value> = match network.get_mut("layers") {
+            Some(Value::Array(layers)) if !layers.is_empty() => layers,
+            _ => return Err("Architecture search needs a network config with layers".into()),
+        };
+        let sizes: &Vec<Value> = match sizes {
+            Value::Array(sizes) => sizes,
+            _ => {
+                return Err(
+                    format!("\"{ARCHITECTURE_PATH}\" must be a list of neuron counts").into(),
+                )
+            }
+        };
+
+        let output_layer: Value = layers[layers.len() - 1].clone();
+        let mut template: Value = if layers.len() > 1 {
+            layers[0].clone()
+        } else {
+            serde_json::json!({ "activation": output_layer["activation"].clone() })
+        };
+        if let Some(activation) = self
+            .architecture
+            .as_ref()
+            .and_then(|a| a.activation.clone())
+        {
+            template["activation"] = Value::from(activation);
+        }
+
+        let mut hidden_layers: Vec<Value> = sizes
+            .iter()
+            .map(|neurons| {
+                let mut layer: Value = template.clone();
+                layer["neurons"] = neurons.clone();
+                layer
+            })
+            .collect();
+        hidden_layers.push(output_layer);
+        *layers = hidden_layers;
+        Ok(())
+    }
+}
+
+/// Set the dropout rate of every layer of a network config except the output layer
+///
+/// # Arguments
+///
+/// * `network` - Network config being modified
+/// * `rate` - Dropout rate of each hidden layer
+fn set_hidden_dropout(network: &mut Value, rate: &Value) -> Result<(), OpenPbError> {
+    let layers: &mut Vec<Value> = match network.get_mut("layers") {
+        Some(Value::Array(layers)) => layers,
+        _ => return Err("Dropout search needs a network config with layers".into()),
+    };
+    let hidden_layers: usize = layers.len().saturating_sub(1);
+    for layer in &mut layers[..hidden_layers] {
+        layer["dropout_rate"] = rate.clone();
+    }
+    Ok(())
+}
+
+/// Overwrite the value at a dot-separated path, where numeric
+/// segments index into arrays (e.g. "layers.0.neurons")
+///
+/// # Arguments
+///
+/// * `root` - JSON value being modified
+/// * `path` - Dot-separated path of the value to overwrite
+/// * `value` - New value
+fn set_path(root: &mut Value, path: &str, value: Value) -> Result<(), OpenPbError> {
+    let mut current: &mut Value = root;
+    for segment in path.split('.') {
+        current = match current {
+            Value::Array(array) => match segment.parse::<usize>() {
+                Ok(index) if index < array.len() => &mut array[index],
+                _ => return Err(format!("Invalid array index \"{segment}\" in \"{path}\"").into()),
+            },
+            Value::Object(object) => object.entry(segment).or_insert(Value::Null),
+            _ => return Err(format!("Path \"{path}\" doesn't exist in the config").into()),
+        };
+    }
+    *current = value;
+    Ok(())
+}
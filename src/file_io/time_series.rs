@@ -0,0 +1,130 @@
+use super::json_de::DataDe;
+use crate::error::OpenPbError;
+use ndarray::Array2;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Default number of steps between the last input value and the target
+const DEFAULT_HORIZON: usize = 1;
+
+/// Default number of steps between the starts of consecutive windows
+const DEFAULT_STRIDE: usize = 1;
+
+/// Default fraction of windows (the most recent ones) used for validation
+const DEFAULT_VALIDATION_SPLIT: f64 = 0.2;
+
+/// Deserialized values representing a single time series in JSON,
+/// which is converted into sliding-window input/target pairs
+#[derive(Deserialize, JsonSchema, Debug, Clone)]
+pub struct TimeSeriesDe {
+    /// Values of the series, in chronological order
+    values: Vec<f64>,
+
+    /// Number of consecutive values in each input vector
+    window: usize,
+
+    /// Number of steps after the end of a window that its target is taken from
+    #[serde(default = "default_horizon")]
+    horizon: usize,
+
+    /// Number of steps between the starts of consecutive windows
+    #[serde(default = "default_stride")]
+    stride: usize,
+
+    /// Fraction of windows used for validation. The most recent
+    /// windows are held out, so validation never precedes training
+    #[serde(default = "default_validation_split")]
+    validation_split: f64,
+}
+
+/// Shape of the time series wrapper in a data JSON file
+#[derive(Deserialize, Debug)]
+pub(crate) struct TimeSeriesFileDe {
+    /// Time series and windowing options (absent for regular data files).
+    /// Kept as raw JSON, so errors name the time series' own fields
+    #[serde(default)]
+    pub time_series: Option<Value>,
+}
+
+#[doc(hidden)]
+fn default_horizon() -> usize {
+    DEFAULT_HORIZON
+}
+
+#[doc(hidden)]
+fn default_stride() -> usize {
+    DEFAULT_STRIDE
+}
+
+#[doc(hidden)]
+fn default_validation_split() -> f64 {
+    DEFAULT_VALIDATION_SPLIT
+}
+
+impl TimeSeriesDe {
+    /// # Arguments
+    ///
+    /// * `time_series` - The `"time_series"` object of a data JSON file
+    pub fn from_value(time_series: Value) -> Result<TimeSeriesDe, OpenPbError> {
+        match serde_json::from_value(time_series) {
+            Ok(time_series_de) => Ok(time_series_de),
+            Err(error) => Err(format!("Invalid time series: {error}").into()),
+        }
+    }
+
+    /// Convert the series into training and validation sets, where each
+    /// input row holds `window` consecutive values and its target is the
+    /// value `horizon` steps after the end of the window
+    pub fn to_data(&self) -> Result<DataDe, OpenPbError> {
+        if self.window == 0 || self.horizon == 0 || self.stride == 0 {
+            return Err("Time series window, horizon, and stride must be at least 1".into());
+        }
+        if !(0.0..1.0).contains(&self.validation_split) {
+            return Err(format!(
+                "Time series validation split must be in [0, 1), got {}",
+                self.validation_split
+            )
+            .into());
+        }
+
+        // Each sample spans its window and every step up to its target
+        let span: usize = self.window + self.horizon;
+        if self.values.len() < span {
+            return Err(format!(
+                "Time series has {} values, but a window of {} with a horizon of {} needs at least {span}",
+                self.values.len(),
+                self.window,
+                self.horizon
+            ).into());
+        }
+        let starts: Vec<usize> = (0..=self.values.len() - span)
+            .step_by(self.stride)
+            .collect();
+
+        let mut inputs: Vec<f64> = Vec::with_capacity(starts.len() * self.window);
+        let mut targets: Vec<f64> = Vec::with_capacity(starts.len());
+        for &start in &starts {
+            inputs.extend_from_slice(&self.values[start..start + self.window]);
+            targets.push(self.values[start + span - 1]);
+        }
+
+        // Most recent windows are held out for validation
+        let validation_rows: usize = (starts.len() as f64 * self.validation_split).round() as usize;
+        let train_rows: usize = starts.len() - validation_rows;
+        if train_rows == 0 {
+            return Err("Time series is too short to leave any training windows".into());
+        }
+
+        let split: usize = train_rows * self.window;
+        let matrix = |values: &[f64], cols: usize| -> Array2<f64> {
+            Array2::from_shape_vec((values.len() / cols, cols), values.to_vec()).unwrap()
+        };
+        DataDe::new(
+            matrix(&inputs[..split], self.window),
+            matrix(&targets[..train_rows], 1),
+            matrix(&inputs[split..], self.window),
+            matrix(&targets[train_rows..], 1),
+        )
+    }
+}
@@ -0,0 +1,240 @@
+use crate::error::OpenPbError;
+use crate::nn::perceptron::Perceptron;
+use ndarray::{s, Array2, Axis};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{prelude::*, BufWriter};
+use std::time::Instant;
+
+/// Default number of samples read, predicted, and written at once
+pub const DEFAULT_INFERENCE_CHUNK_SIZE: usize = 4096;
+
+/// Options for predicting every row of a (possibly very large) CSV file
+#[derive(Debug, Clone, Copy)]
+pub struct InferenceOptions {
+    /// Number of samples read, predicted, and written at once
+    pub chunk_size: usize,
+
+    /// Whether or not several chunks are predicted at the same time
+    /// (one per thread of the global thread pool)
+    pub parallel: bool,
+}
+
+/// Throughput of a bulk inference
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct InferenceReport {
+    /// Number of samples predicted
+    pub samples: usize,
+
+    /// Time spent reading, predicting, and writing (in seconds)
+    pub elapsed_time: f32,
+
+    /// Samples predicted per second
+    pub samples_per_second: f32,
+}
+
+/// Predict every row of a CSV file with a header row (one input value per
+/// column), writing each sample's decoded prediction and undecoded outputs to
+/// a CSV file (`prediction_0`, ..., `output_0`, ...). The file is streamed
+/// one chunk of samples at a time into reused buffers, so memory use doesn't
+/// grow with the number of samples
+///
+/// # Arguments
+///
+/// * `network` - Trained network
+/// * `decode` - Converts undecoded outputs (outputs x samples) into predictions stored as rows
+/// * `input_path` - CSV file with the input values
+/// * `output_path` - CSV file the predictions are written to
+/// * `options` - Chunk size and parallelism
+pub fn predict_csv(
+    network: &Perceptron,
+    decode: &dyn Fn(&Array2<f64>) -> Array2<f64>,
+    input_path: &str,
+    output_path: &str,
+    options: InferenceOptions,
+) -> Result<InferenceReport, OpenPbError> {
+    let now: Instant = Instant::now();
+    let mut reader = match csv::ReaderBuilder::new()
+        .has_headers(true)
+        .trim(csv::Trim::All)
+        .from_path(input_path)
+    {
+        Ok(reader) => reader,
+        Err(error) => return Err(format!("Failed to open {input_path}: {error}").into()),
+    };
+    let columns: usize = match reader.headers() {
+        Ok(header) => header.len(),
+        Err(error) => return Err(format!("Invalid CSV header in {input_path}: {error}").into()),
+    };
+    if columns != network.input_size() {
+        return Err(format!(
+            "Model expects {} input values, but {input_path} has {columns} columns",
+            network.input_size()
+        )
+        .into());
+    }
+    let mut writer = match File::create(output_path) {
+        Ok(file) => BufWriter::new(file),
+        Err(error) => return Err(format!("Failed to create {output_path}: {error}").into()),
+    };
+
+    // The header is written before any row is read, so an input without any
+    // samples still gives a valid (empty) predictions file. Its widths come
+    // from predicting a single blank sample
+    let probe: Array2<f64> = network.predict_raw(&Array2::zeros((columns, 1)));
+    write_header(
+        &mut writer,
+        decode(&probe).ncols(),
+        probe.nrows(),
+        output_path,
+    )?;
+
+    // One buffer per chunk that's predicted at the same time (shape: inputs x samples)
+    let chunk_size: usize = options.chunk_size.max(1);
+    let group_size: usize = if options.parallel {
+        rayon::current_num_threads()
+    } else {
+        1
+    };
+    let mut buffers: Vec<Array2<f64>> = (0..group_size)
+        .map(|_| Array2::zeros((columns, chunk_size)))
+        .collect();
+    let mut record = csv::StringRecord::new();
+    let mut samples: usize = 0;
+
+    loop {
+        // Fill every buffer (the last one read may be partially filled)
+        let mut filled: Vec<usize> = Vec::with_capacity(group_size);
+        for buffer in buffers.iter_mut() {
+            let mut rows: usize = 0;
+            while rows < chunk_size {
+                match reader.read_record(&mut record) {
+                    Ok(true) => {}
+                    Ok(false) => break,
+                    Err(error) => {
+                        return Err(format!("Invalid CSV row in {input_path}: {error}").into())
+                    }
+                }
+                // Line 1 is the header
+                let line: usize = samples + rows + 2;
+                if record.len() != columns {
+                    return Err(format!(
+                        "Line {line} of {input_path} has {} values, expected {columns}",
+                        record.len()
+                    )
+                    .into());
+                }
+                for (feature, cell) in record.iter().enumerate() {
+                    buffer[[feature, rows]] = match cell.parse() {
+                        Ok(value) => value,
+                        Err(_) => {
+                            return Err(format!(
+                                "Line {line} of {input_path} has a non-numeric value \"{cell}\""
+                            )
+                            .into())
+                        }
+                    };
+                }
+                rows += 1;
+            }
+            if rows == 0 {
+                break;
+            }
+            filled.push(rows);
+            samples += rows;
+            if rows < chunk_size {
+                break;
+            }
+        }
+        if filled.is_empty() {
+            break;
+        }
+
+        let predict = |(buffer, rows): (&Array2<f64>, &usize)| -> Array2<f64> {
+            if *rows == chunk_size {
+                network.predict_raw(buffer)
+            } else {
+                network.predict_raw(&buffer.slice(s![.., ..*rows]).to_owned())
+            }
+        };
+        let outputs: Vec<Array2<f64>> = if options.parallel {
+            buffers
+                .par_iter()
+                .zip(filled.par_iter())
+                .map(predict)
+                .collect()
+        } else {
+            buffers.iter().zip(filled.iter()).map(predict).collect()
+        };
+
+        for outputs in &outputs {
+            let predictions: Array2<f64> = decode(outputs);
+            write_rows(&mut writer, &predictions, outputs, output_path)?;
+        }
+        if filled.len() < group_size || filled.last() != Some(&chunk_size) {
+            break;
+        }
+    }
+    if let Err(error) = writer.flush() {
+        return Err(format!("Failed to write {output_path}: {error}").into());
+    }
+
+    let elapsed_time: f32 = now.elapsed().as_secs_f32();
+    Ok(InferenceReport {
+        samples,
+        elapsed_time,
+        samples_per_second: samples as f32 / elapsed_time.max(f32::EPSILON),
+    })
+}
+
+/// Write the header row of the predictions file
+///
+/// # Arguments
+///
+/// * `writer` - Predictions file
+/// * `predictions` - Number of decoded prediction values of each sample
+/// * `outputs` - Number of undecoded output values of each sample
+/// * `output_path` - Path of the predictions file (for error messages)
+fn write_header(
+    writer: &mut impl Write,
+    predictions: usize,
+    outputs: usize,
+    output_path: &str,
+) -> Result<(), OpenPbError> {
+    let header: Vec<String> = (0..predictions)
+        .map(|i| format!("prediction_{i}"))
+        .chain((0..outputs).map(|i| format!("output_{i}")))
+        .collect();
+    match writeln!(writer, "{}", header.join(",")) {
+        Ok(_) => Ok(()),
+        Err(error) => Err(format!("Failed to write {output_path}: {error}").into()),
+    }
+}
+
+/// Write one row per sample with its decoded prediction and undecoded outputs
+///
+/// # Arguments
+///
+/// * `writer` - Predictions file
+/// * `predictions` - Decoded predictions (samples x values)
+/// * `outputs` - Undecoded outputs (outputs x samples)
+/// * `output_path` - Path of the predictions file (for error messages)
+fn write_rows(
+    writer: &mut impl Write,
+    predictions: &Array2<f64>,
+    outputs: &Array2<f64>,
+    output_path: &str,
+) -> Result<(), OpenPbError> {
+    for (prediction, output) in predictions.outer_iter().zip(outputs.axis_iter(Axis(1))) {
+        let cells: Vec<String> = prediction
+            .iter()
+            .chain(output.iter())
+            .map(f64::to_string)
+            .collect();
+        if let Err(error) = writeln!(writer, "{}", cells.join(",")) {
+            return Err(format!("Failed to write {output_path}: {error}").into());
+        }
+    }
+    Ok(())
+}
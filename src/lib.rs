@@ -0,0 +1,49 @@
+//! Open Perceptron Benchmarker (OpenPB) training engine. Networks can be
+//! built and trained directly with [`Perceptron`], or deserialized from the
+//! same JSON files used by the command line application with
+//! [`NetworkDataDe`] and trained with [`trainer::train_from_json`]
+#[cfg(feature = "threads")]
+pub mod affinity;
+#[cfg(all(feature = "threads", feature = "file_io"))]
+pub mod daemon;
+#[cfg(all(feature = "threads", feature = "fetch"))]
+pub mod epoch_hook;
+pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod file_io;
+#[cfg(all(feature = "threads", feature = "file_io"))]
+pub mod inference;
+#[cfg(all(feature = "threads", feature = "file_io"))]
+pub mod model_card;
+pub mod nn;
+#[cfg(feature = "file_io")]
+pub mod online;
+#[cfg(all(feature = "threads", feature = "file_io"))]
+pub mod progress;
+#[cfg(all(feature = "threads", feature = "file_io"))]
+pub mod run_dir;
+#[cfg(all(feature = "threads", feature = "file_io"))]
+pub mod sweep;
+#[cfg(feature = "threads")]
+pub mod trainer;
+#[cfg(all(feature = "threads", feature = "file_io"))]
+pub mod tuner;
+#[cfg(all(feature = "threads", feature = "file_io"))]
+pub mod verify;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use error::OpenPbError;
+pub use file_io::json_de::NetworkDataDe;
+pub use nn::batches::{BatchConfig, Batches};
+pub use nn::builder::{NetworkBuilder, Training, TrainingSummary};
+pub use nn::cancellation::CancellationToken;
+pub use nn::data_source::{DataMetadata, DataSource};
+pub use nn::functions::activation::ActivationFn;
+pub use nn::functions::cost::Cost;
+pub use nn::functions::encoder::Encoder;
+pub use nn::functions::metric::Metric;
+pub use nn::functions::optimizer::Optimizer;
+pub use nn::layer::Layer;
+pub use nn::perceptron::Perceptron;
@@ -1,31 +1,1190 @@
 // To generate docs for this project, run command:
 // cargo doc --open --no-deps --document-private-items
 mod args;
-mod file_io;
-mod nn;
-mod trainer;
 
-use args::Args;
+use args::{Args, BatchSizeArg, Command, ConfigKind, DataFormat};
+use chrono::Utc;
 use clap::Parser;
-use file_io::json_de::NetworkDataDe;
+use ndarray::{concatenate, Array2, Axis};
+use ndarray_stats::QuantileExt;
+use openpb::affinity;
+use openpb::daemon::Daemon;
+use openpb::epoch_hook::EpochHook;
+use openpb::error::OpenPbError;
+use openpb::file_io::bundle::ExperimentBundle;
+use openpb::file_io::compare::{self as results_compare, ComparisonRow, ResultsSummaryDe, Section};
+use openpb::file_io::ensemble_de::EnsembleDe;
+use openpb::file_io::json_de::{DataDe, NetworkDe};
+use openpb::file_io::model_de::{checkpoint_from_bytes, model_from_bytes, Checkpoint};
+use openpb::file_io::multi_head_de::MultiHeadDe;
+use openpb::file_io::output_format::OutputFormat;
+use openpb::file_io::plot::{self, LearningCurve};
+use openpb::file_io::results_ser::{PredictionsSer, ThreadedResultsSer};
+use openpb::file_io::save_output::OutputNames;
+use openpb::file_io::schema as config_schema;
+use openpb::file_io::sweep_de::SweepDe;
+use openpb::file_io::{config, csv_de, idx, mnist, save_output};
+use openpb::inference::{self, InferenceOptions, InferenceReport};
+use openpb::model_card::{self, ModelCardContext};
+use openpb::nn::activations::DeadNeuronConfig;
+use openpb::nn::batches::{BatchConfig, Sampler};
+use openpb::nn::bootstrap::BootstrapConfig;
+use openpb::nn::data_source::DataSource;
+use openpb::nn::decision_boundary::{self as boundary, DecisionBoundary};
+use openpb::nn::divergence::DivergenceConfig;
+use openpb::nn::functions::encoder::Encoder;
+use openpb::nn::gradient_norms::GradientNormConfig;
+use openpb::nn::history::EpochLog;
+use openpb::nn::lr_finder::{self, LrFinderConfig, LrFinderResults};
+use openpb::nn::robustness::RobustnessConfig;
+use openpb::nn::saliency::{self as input_saliency, SampleSaliency};
+use openpb::nn::sampling::ValidationSampling;
+use openpb::nn::stats::{HistogramConfig, WeightHistograms};
+use openpb::online::{self, OnlineOptions};
+use openpb::progress::{ProgressEvent, ProgressStream};
+use openpb::run_dir::{RunDir, RunManifest};
+use openpb::sweep::SweepOutcome;
+use openpb::trainer::{self, TrainerOptions};
+use openpb::tuner::{self, SearchDe};
+use openpb::verify::{self as run_verify, RunCheck};
+use openpb::{sweep as sweep_runner, NetworkDataDe, Perceptron};
 use std::fs;
-use trainer::train_from_json;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::Arc;
 
+/// Maximum number of input vectors fed forward at once by `predict`
+const PREDICT_CHUNK_SIZE: usize = 4096;
+
+/// Converts undecoded outputs (outputs x samples) into predictions stored as rows
+type OutputDecoder = Box<dyn Fn(&Array2<f64>) -> Array2<f64>>;
+
+#[doc(hidden)]
+fn main() {
+    if let Err(error) = run() {
+        eprintln!("Error: {error}");
+        process::exit(1);
+    }
+}
+
+/// Run the subcommand (or training) chosen on the command line
 #[doc(hidden)]
-fn main() -> Result<(), String> {
+fn run() -> Result<(), OpenPbError> {
     let args = Args::parse();
+    if let Some(threads) = args.internal_threads {
+        affinity::limit_internal_threads(threads)?;
+    }
+
+    if let Some(command) = args.command {
+        return match command {
+            Command::FetchMnist {
+                cache_dir,
+                output,
+                train_samples,
+                test_samples,
+                url,
+            } => fetch_mnist(&cache_dir, &output, train_samples, test_samples, &url),
+            Command::Sweep { config } => sweep(&config),
+            Command::Search { spec } => search(&spec),
+            Command::LrFind {
+                data,
+                network,
+                output,
+                min_lr,
+                max_lr,
+                steps,
+                batch_size,
+                shuffle,
+                seed,
+            } => {
+                let batch_config = BatchConfig {
+                    batch_size,
+                    shuffle,
+                    drop_last: false,
+                    sampler: Sampler::Sequential,
+                };
+                let config = LrFinderConfig {
+                    min_lr,
+                    max_lr,
+                    steps,
+                    seed,
+                };
+                lr_find(&data, &network, output, batch_config, config)
+            }
+            Command::Predict {
+                model,
+                input,
+                inputs,
+                network,
+                raw,
+                output,
+                data,
+                chunk_size,
+                parallel,
+            } => match (data, output) {
+                (Some(data), Some(output)) => {
+                    let options = InferenceOptions {
+                        chunk_size,
+                        parallel,
+                    };
+                    predict_bulk(&model, &data, network, &output, options)
+                }
+                (_, output) => predict(&model, input, inputs, network, raw, output),
+            },
+            Command::Saliency {
+                model,
+                data,
+                samples,
+                output,
+            } => saliency(&model, &data, samples, output),
+            Command::DecisionBoundary {
+                model,
+                data,
+                network,
+                resolution,
+                output,
+            } => decision_boundary(&model, &data, network, resolution, &output),
+            Command::Compare {
+                baseline,
+                results,
+                plot,
+            } => compare(&baseline, &results, plot),
+            Command::Schema { kind, output } => schema(kind, output),
+            Command::Verify {
+                run_dir,
+                data,
+                tolerance,
+            } => verify(&run_dir, &data, tolerance),
+            Command::Online {
+                network,
+                resume,
+                output,
+                batch_size,
+                window,
+                report_every,
+                checkpoint_every,
+                seed,
+            } => {
+                let options = OnlineOptions {
+                    batch_size,
+                    window,
+                    report_every,
+                    checkpoint_every,
+                    seed,
+                };
+                online(&network, resume, &output, options)
+            }
+            Command::Daemon {
+                address,
+                workers,
+                max_threads,
+                keep_jobs,
+            } => {
+                let max_threads: usize = max_threads.unwrap_or_else(|| {
+                    std::thread::available_parallelism().map_or(1, |threads| threads.get())
+                });
+                Arc::new(Daemon::new(max_threads).with_max_finished(keep_jobs))
+                    .serve(&address, workers)
+            }
+        };
+    }
+
+    // Training arguments are required whenever no subcommand is given
+    let (network, epochs) = match (args.network, args.epochs) {
+        (Some(network), Some(epochs)) if !args.data.is_empty() || args.train_images.is_some() => {
+            (network, epochs)
+        }
+        _ => return Err("Missing required training arguments".into()),
+    };
+
+    let network_json: String = config::read_config(&network)?;
+    // Every JSON data file is concatenated into a single dataset
+    let data_de: DataDe = match args.data_format {
+        DataFormat::Json => DataDe::from_files(&args.data)?,
+        DataFormat::Csv => csv_de::data_from_csv(&args.data, args.label_column.as_deref())?,
+        DataFormat::Idx => match (&args.train_images, &args.train_labels) {
+            (Some(train_images), Some(train_labels)) => idx::data_from_idx(
+                train_images,
+                train_labels,
+                args.test_images.as_deref(),
+                args.test_labels.as_deref(),
+            )?,
+            _ => return Err("IDX data requires --train-images and --train-labels".into()),
+        },
+    };
+    // IDX files are recorded in the run manifest and model card in place of data files
+    let data_files: Vec<String> = match args.data_format {
+        DataFormat::Idx => vec![
+            args.train_images.clone(),
+            args.train_labels.clone(),
+            args.test_images.clone(),
+            args.test_labels.clone(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect(),
+        _ => args.data.clone(),
+    };
+    if args.sampler == Sampler::Weighted && data_de.sample_weights().is_none() {
+        return Err("The weighted sampler requires train_weights in the data".into());
+    }
+
+    if args.bootstrap == Some(0) {
+        return Err("Bootstrapping needs at least one resample".into());
+    }
+    if !(args.confidence > 0.0 && args.confidence < 1.0) {
+        return Err(format!(
+            "Confidence must be between 0 and 1 (exclusive), got {}",
+            args.confidence
+        )
+        .into());
+    }
+
+    let options = TrainerOptions {
+        threads: args.threads,
+        pin_cores: args.pin_cores.clone().map(|cores| cores.0),
+        runs: args.runs.unwrap_or(args.threads),
+        epochs,
+        batch_config: BatchConfig {
+            batch_size: match args.batch_size {
+                Some(BatchSizeArg::Fixed(batch_size)) => Some(batch_size),
+                _ => None,
+            },
+            shuffle: args.shuffle,
+            drop_last: args.drop_last,
+            sampler: args.sampler,
+        },
+        auto_batch_size: matches!(args.batch_size, Some(BatchSizeArg::Auto)),
+        profile: args.profile,
+        stats_interval: args.stats_every,
+        snapshot_interval: args.snapshot_every,
+        histograms: match args.histogram_every {
+            Some(interval) => Some(HistogramConfig {
+                interval,
+                bins: args.histogram_bins,
+            }),
+            None => None,
+        },
+        top_n: args.top_n,
+        activation_samples: args.activation_samples,
+        dead_neurons: match args.dead_neurons {
+            Some(threshold) => Some(DeadNeuronConfig {
+                threshold,
+                warn_every: args.dead_warn_every,
+            }),
+            None => None,
+        },
+        calibrate: args.calibrate,
+        raw_output: args.raw_output,
+        robustness: match args.noise_max {
+            Some(max_noise) => Some(RobustnessConfig {
+                max_noise,
+                steps: args.noise_steps,
+            }),
+            None => None,
+        },
+        bootstrap: match args.bootstrap {
+            Some(resamples) => Some(BootstrapConfig {
+                resamples,
+                confidence: args.confidence,
+            }),
+            None => None,
+        },
+        seed: Some(args.seed.unwrap_or_else(rand::random)),
+        restarts: args.restarts,
+        jitter: args.jitter,
+        log_every: args.log_every,
+        // A maximum norm implies tracking
+        gradient_norms: if args.gradient_norms || args.max_gradient_norm.is_some() {
+            Some(GradientNormConfig {
+                max_norm: args.max_gradient_norm,
+            })
+        } else {
+            None
+        },
+        divergence: match args.divergence_factor {
+            Some(factor) => Some(DivergenceConfig {
+                factor,
+                window: args.divergence_window,
+            }),
+            None => None,
+        },
+        validation_sampling: match args.validation_fraction {
+            Some(fraction) => Some(ValidationSampling {
+                fraction,
+                full_every: args.full_validation_every,
+            }),
+            None => None,
+        },
+        early_stopping: !args.no_early_stopping,
+        save_optimizer_state: args.save_optimizer_state,
+    };
+
+    // Values available to the output file name template
+    let network_name: String = Path::new(&network)
+        .file_stem()
+        .map_or(network.clone(), |stem| stem.to_string_lossy().to_string());
+    let names = OutputNames {
+        network_name: &network_name,
+        seed: options.seed,
+    };
+    // Template mistakes are reported before training instead of after
+    if let Some(template) = &args.output_template {
+        save_output::render_output_template(template, &names)?;
+    }
+
+    // Ensemble configs train each (differently configured) member once
+    if EnsembleDe::is_ensemble(&network_json) {
+        if args.bundle.is_some() {
+            return Err("Experiment bundles aren't supported for ensembles".into());
+        }
+        if args.run_dir.is_some() {
+            return Err("Run directories aren't supported for ensembles".into());
+        }
+        if args.progress.is_some() {
+            return Err("Progress streaming isn't supported for ensembles".into());
+        }
+        if args.epoch_hook.is_some() {
+            return Err("Epoch hooks aren't supported for ensembles".into());
+        }
+        if args.model_card {
+            return Err("Model cards aren't supported for ensembles".into());
+        }
+        if args.resume.is_some() {
+            return Err("Resuming isn't supported for ensembles".into());
+        }
+        if args.summary_only {
+            return Err("Summaries aren't supported for ensembles".into());
+        }
+        let ensemble_de: EnsembleDe = EnsembleDe::from_json(&network_json)?;
+        let threaded_results = trainer::train_ensemble(Arc::new(data_de), &ensemble_de, &options)?;
+        eprint!("\n{}", threaded_results.summary_table());
+        let output: String = save_output::output_path(
+            args.output,
+            args.output_template.as_deref(),
+            &names,
+            args.output_format,
+        )?;
+        return save_results(output, threaded_results, args.output_format, args.checksum);
+    }
+
+    // Multi-head configs train a shared trunk with one output head per task
+    if MultiHeadDe::is_multi_head(&network_json) {
+        if args.bundle.is_some() {
+            return Err("Experiment bundles aren't supported for multi-head networks".into());
+        }
+        if args.run_dir.is_some() {
+            return Err("Run directories aren't supported for multi-head networks".into());
+        }
+        if args.progress.is_some() {
+            return Err("Progress streaming isn't supported for multi-head networks".into());
+        }
+        if args.epoch_hook.is_some() {
+            return Err("Epoch hooks aren't supported for multi-head networks".into());
+        }
+        if args.model_card {
+            return Err("Model cards aren't supported for multi-head networks".into());
+        }
+        if args.resume.is_some() {
+            return Err("Resuming isn't supported for multi-head networks".into());
+        }
+        if args.summary_only {
+            return Err("Summaries aren't supported for multi-head networks".into());
+        }
+        let multi_head_de: MultiHeadDe = MultiHeadDe::from_json(&network_json)?;
+        let results = trainer::train_multi_head(Arc::new(data_de), &multi_head_de, &options)?;
+        let output: String = save_output::output_path(
+            args.output,
+            args.output_template.as_deref(),
+            &names,
+            args.output_format,
+        )?;
+        let write_checksum: bool = args.checksum && output != save_output::STDIO_PATH;
+        let results_path: PathBuf = PathBuf::from(&output);
+        save_output::save_multi_head(output, &results, args.output_format)?;
+        if write_checksum {
+            save_output::save_checksum(&results_path)?;
+        }
+        return Ok(());
+    }
+
+    let network_de: NetworkDe =
+        NetworkDe::from_json(&network_json).map_err(|error| error.in_file(&network))?;
+    let mut network_data_de: NetworkDataDe =
+        NetworkDataDe::from_config(Arc::new(data_de), network_de)?;
+    if args.summary_only {
+        return print_summary(&network_data_de, &options);
+    }
+    if let Some(checkpoint_path) = &args.resume {
+        let checkpoint: Vec<u8> = match fs::read(checkpoint_path) {
+            Ok(result) => result,
+            _ => return Err(format!("File {} missing or corrupted", checkpoint_path).into()),
+        };
+        network_data_de.resume_from(checkpoint_from_bytes(&checkpoint)?)?;
+    }
+
+    // Run tracking records provenance before training starts
+    let run_dir: Option<RunDir> = match &args.run_dir {
+        Some(base_dir) => {
+            let run_dir: RunDir = RunDir::create(
+                base_dir,
+                options.seed,
+                args.checkpoint_every,
+                args.log_every,
+            )?;
+            let manifest = RunManifest {
+                run_id: run_dir.run_id().to_string(),
+                created: Utc::now().to_rfc3339(),
+                command: std::env::args().collect(),
+                network: network.clone(),
+                data: data_files.clone(),
+                options: options.clone(),
+            };
+            run_dir.write_manifest(&manifest, &network_json)?;
+            Some(run_dir)
+        }
+        None => None,
+    };
+    let progress: Option<ProgressStream> = match &args.progress {
+        Some(address) => Some(ProgressStream::connect(address)?),
+        None => None,
+    };
+    let epoch_hook: Option<EpochHook> = args.epoch_hook.as_deref().map(EpochHook::new);
+    if let Some(progress) = &progress {
+        progress.send(&ProgressEvent::Start {
+            runs: options.runs,
+            epochs: options.epochs,
+        });
+    }
+
+    let on_epoch = |id: usize, epoch_log: &EpochLog, network: &Perceptron| -> bool {
+        if let Some(progress) = &progress {
+            progress.on_epoch(id, epoch_log, network);
+        }
+        if let Some(epoch_hook) = &epoch_hook {
+            epoch_hook.on_epoch(id, epoch_log, network);
+        }
+        match &run_dir {
+            Some(run_dir) => run_dir.on_epoch(id, epoch_log, network),
+            None => true,
+        }
+    };
+    let mut threaded_results =
+        trainer::train_from_json_with_callback(&network_data_de, &options, &on_epoch)?;
+    if let Some(progress) = &progress {
+        let metrics = threaded_results.metrics();
+        progress.send(&ProgressEvent::Finish {
+            runs: metrics.len(),
+            passed: metrics.iter().filter(|(_, _, passed)| *passed).count(),
+        });
+    }
+    eprint!("\n{}", threaded_results.summary_table());
+
+    // Histograms are moved out of the results into one file per run
+    if let Some(histogram_dir) = &args.histogram_dir {
+        let histograms: Vec<(usize, WeightHistograms)> = threaded_results.take_weight_histograms();
+        for (id, run_histograms) in &histograms {
+            let histogram_path: PathBuf = Path::new(histogram_dir).join(format!("run_{id}.json"));
+            save_output::save_json("weight histograms", run_histograms, &histogram_path)?;
+        }
+    }
+
+    if let Some(bundle_path) = &args.bundle {
+        let networks: Vec<Perceptron> = threaded_results.networks().into_iter().cloned().collect();
+        let results: serde_json::Value = match serde_json::to_value(&threaded_results) {
+            Ok(results) => results,
+            Err(error) => return Err(format!("Failed to serialize results: {error}").into()),
+        };
+        let bundle = ExperimentBundle::new(&network_data_de, networks).with_results(results);
+        save_output::save_bundle(&bundle, bundle_path)?;
+    }
+    let card: Option<String> = if args.model_card {
+        let command: Vec<String> = std::env::args().collect();
+        let context = ModelCardContext {
+            network: &network,
+            data: &data_files,
+            network_data_de: &network_data_de,
+            options: &options,
+            command: &command,
+        };
+        Some(model_card::model_card(&context, &threaded_results))
+    } else {
+        None
+    };
+
+    if let Some(run_dir) = &run_dir {
+        let results_path: String = run_dir.results_path().to_string_lossy().to_string();
+        save_output::save_json("results", &threaded_results, Path::new(&results_path))?;
+        if args.checksum {
+            save_output::save_checksum(Path::new(&results_path))?;
+        }
+        if let Some(card) = &card {
+            let card_path: PathBuf = run_dir.path().join("model_card.md");
+            save_output::save_text("model card", card, &card_path)?;
+        }
+        eprintln!(
+            "\nRun {} archived in {:#?}",
+            run_dir.run_id(),
+            run_dir.path()
+        );
+        // Results are only written once unless another output is requested
+        if args.output.is_none() && args.output_template.is_none() {
+            return Ok(());
+        }
+    }
+    // Model cards are written next to the results file (unless piped to stdout)
+    let output: String = save_output::output_path(
+        args.output,
+        args.output_template.as_deref(),
+        &names,
+        args.output_format,
+    )?;
+    if let Some(card) = &card {
+        if output != save_output::STDIO_PATH {
+            let card_path: PathBuf = Path::new(&output).with_extension("md");
+            save_output::save_text("model card", card, &card_path)?;
+        }
+    }
+    save_results(output, threaded_results, args.output_format, args.checksum)
+}
+
+/// Save training results, along with their checksum when requested
+///
+/// # Arguments
+///
+/// * `output` - Path of the results file ("-" for stdout)
+/// * `threaded_results` - Training results to be serialized
+/// * `format` - Encoding of the results file
+/// * `checksum` - Whether or not to write a checksum next to the results file
+fn save_results(
+    output: String,
+    threaded_results: ThreadedResultsSer,
+    format: OutputFormat,
+    checksum: bool,
+) -> Result<(), OpenPbError> {
+    let write_checksum: bool = checksum && output != save_output::STDIO_PATH;
+    let results_path: PathBuf = PathBuf::from(&output);
+    save_output::save_to_dir(output, threaded_results, format)?;
+    if write_checksum {
+        save_output::save_checksum(&results_path)?;
+    }
+    Ok(())
+}
+
+/// Download (or reuse cached) MNIST files and write them as a training data JSON file
+///
+/// # Arguments
+///
+/// * `cache_dir` - Directory where the downloaded IDX files are cached
+/// * `output` - JSON file where the converted data is stored
+/// * `train_samples` - Maximum number of training samples (optional)
+/// * `test_samples` - Maximum number of validation samples (optional)
+/// * `url` - URL of the directory with the gzipped IDX files
+#[doc(hidden)]
+fn fetch_mnist(
+    cache_dir: &str,
+    output: &str,
+    train_samples: Option<usize>,
+    test_samples: Option<usize>,
+    url: &str,
+) -> Result<(), OpenPbError> {
+    let cache_dir: &Path = Path::new(cache_dir);
+    mnist::fetch_mnist(cache_dir, url)?;
+
+    let data_de: DataDe = mnist::load_mnist(cache_dir, train_samples, test_samples)?;
+    save_output::save_data(&data_de, output)
+}
+
+/// Sweep learning rates for a freshly initialized network and report the loss curve
+///
+/// # Arguments
+///
+/// * `data` - JSON file with training and validation sets
+/// * `network` - JSON file with network structure and hyperparameters
+/// * `output` - JSON file where the recorded sweep is stored (optional)
+/// * `batch_config` - Minibatch size and shuffling options
+/// * `config` - Learning rate range and number of steps
+#[doc(hidden)]
+fn lr_find(
+    data: &str,
+    network: &str,
+    output: Option<String>,
+    batch_config: BatchConfig,
+    config: LrFinderConfig,
+) -> Result<(), OpenPbError> {
+    let network_json: String = config::read_config(network)?;
+    let network_de: NetworkDe =
+        NetworkDe::from_json(&network_json).map_err(|error| error.in_file(network))?;
+    let data_de: DataDe = DataDe::from_files(&[data.to_string()])?;
+    let mut network_data_de: NetworkDataDe =
+        NetworkDataDe::from_config(Arc::new(data_de), network_de)?;
+    let mut perceptron: Perceptron = match config.seed {
+        Some(seed) => network_data_de.create_seeded_network(seed)?,
+        None => network_data_de.create_network()?,
+    };
+
+    let results: LrFinderResults = lr_finder::find_learning_rate(
+        &mut perceptron,
+        network_data_de.data.as_ref(),
+        network_data_de.optimizer.as_mut(),
+        network_data_de.cost.as_ref(),
+        network_data_de.encoder.as_ref(),
+        batch_config,
+        config,
+    )?;
+
+    println!("Loss vs. learning rate (log scale):\n");
+    print!("{}", lr_finder::plot(&results, 60, 15));
+    match results.suggested_lr {
+        Some(learning_rate) => println!("\nSuggested learning rate: {learning_rate:.2e}"),
+        None => println!("\nNot enough steps were recorded to suggest a learning rate"),
+    }
+
+    match output {
+        Some(output) => save_output::save_lr_sweep(&results, &output),
+        None => Ok(()),
+    }
+}
+
+/// Run every trial of a hyperparameter sweep
+///
+/// # Arguments
+///
+/// * `config` - JSON or YAML file with the sweep config
+#[doc(hidden)]
+fn sweep(config: &str) -> Result<(), OpenPbError> {
+    let sweep_str: String = match fs::read_to_string(config) {
+        Ok(result) => result,
+        _ => return Err(format!("File {} missing or corrupted", config).into()),
+    };
+    let yaml: bool = config.ends_with(".yaml") || config.ends_with(".yml");
+    let sweep_de: SweepDe = SweepDe::from_str(&sweep_str, yaml)?;
+
+    let outcome: SweepOutcome = sweep_runner::run_sweep(&sweep_de)?;
+    println!("\nSweep archived in {:#?}", outcome.sweep_dir);
+    Ok(())
+}
+
+/// Train every configuration of a hyperparameter search and rank them
+///
+/// # Arguments
+///
+/// * `spec` - JSON or YAML file with the search spec
+#[doc(hidden)]
+fn search(spec: &str) -> Result<(), OpenPbError> {
+    let search_str: String = match fs::read_to_string(spec) {
+        Ok(result) => result,
+        _ => return Err(format!("File {} missing or corrupted", spec).into()),
+    };
+    let yaml: bool = spec.ends_with(".yaml") || spec.ends_with(".yml");
+    let search_de: SearchDe = SearchDe::from_str(&search_str, yaml)?;
+
+    let search_dir: PathBuf = tuner::run_search(&search_de)?;
+    println!("\nSearch archived in {:#?}", search_dir);
+    Ok(())
+}
+
+/// Learn from NDJSON samples streamed to stdin until it's closed
+///
+/// # Arguments
+///
+/// * `network` - JSON file with network structure and hyperparameters
+/// * `resume` - Results, network, or checkpoint file to continue learning from (optional)
+/// * `output` - JSON file where the network and optimizer state are checkpointed
+/// * `options` - Batch size, window size, and report and checkpoint intervals
+#[doc(hidden)]
+fn online(
+    network: &str,
+    resume: Option<String>,
+    output: &str,
+    options: OnlineOptions,
+) -> Result<(), OpenPbError> {
+    let network_json: String = config::read_config(network)?;
+    let checkpoint: Option<Checkpoint> = match resume {
+        Some(checkpoint_path) => match fs::read(&checkpoint_path) {
+            Ok(checkpoint) => Some(checkpoint_from_bytes(&checkpoint)?),
+            _ => return Err(format!("File {} missing or corrupted", checkpoint_path).into()),
+        },
+        None => None,
+    };
+    let output_path: &Path = Path::new(output);
+    online::learn_stream(
+        io::stdin().lock(),
+        &network_json,
+        checkpoint,
+        output_path,
+        options,
+    )?;
+    // Stdout is reserved for the NDJSON reports
+    eprintln!("Online checkpoint saved to {:#?}", output_path);
+    Ok(())
+}
 
-    let network_json: String = match fs::read_to_string(&args.network) {
+/// Predict and print the output of a trained network for a single
+/// sample, or for every sample of an input file
+///
+/// # Arguments
+///
+/// * `model` - Model or results JSON file with the trained network
+/// * `input` - Comma-separated input values (read from stdin when `None`)
+/// * `inputs` - JSON file with one row of input values per sample (optional)
+/// * `network` - Network config whose encoder decodes the output (optional)
+/// * `raw` - Whether or not to only print the undecoded outputs as JSON
+/// * `output` - JSON file the predictions are written to instead of stdout (optional)
+#[doc(hidden)]
+fn predict(
+    model: &str,
+    input: Option<String>,
+    inputs: Option<String>,
+    network: Option<String>,
+    raw: bool,
+    output: Option<String>,
+) -> Result<(), OpenPbError> {
+    let model_file: Vec<u8> = match fs::read(model) {
         Ok(result) => result,
-        _ => return Err(format!("File {} missing or corrupted", args.network)),
+        _ => return Err(format!("File {} missing or corrupted", model).into()),
+    };
+    let perceptron: Perceptron = model_from_bytes(&model_file)?;
+
+    let rows: Vec<Vec<f64>> = match (input, inputs) {
+        (_, Some(inputs)) => {
+            let inputs_json: String = match fs::read_to_string(&inputs) {
+                Ok(result) => result,
+                _ => return Err(format!("File {} missing or corrupted", inputs).into()),
+            };
+            match serde_json::from_str(&inputs_json) {
+                Ok(rows) => rows,
+                Err(error) => {
+                    return Err(
+                        format!("Expected a JSON array of input rows in {inputs}: {error}").into(),
+                    )
+                }
+            }
+        }
+        (Some(input), None) => vec![parse_input(&input)?],
+        (None, None) => {
+            let mut stdin_json = String::new();
+            if let Err(error) = io::stdin().read_to_string(&mut stdin_json) {
+                return Err(format!("Failed to read stdin: {error}").into());
+            }
+            match serde_json::from_str(&stdin_json) {
+                Ok(values) => vec![values],
+                Err(error) => {
+                    return Err(format!("Expected a JSON array of numbers: {error}").into())
+                }
+            }
+        }
     };
-    let data_json: String = match fs::read_to_string(&args.data) {
+    for (sample, values) in rows.iter().enumerate() {
+        if values.len() != perceptron.input_size() {
+            return Err(format!(
+                "Model expects {} input values, got {} for sample {sample}",
+                perceptron.input_size(),
+                values.len()
+            )
+            .into());
+        }
+    }
+
+    // Network expects each input vector as a column
+    let samples: usize = rows.len();
+    let values: Vec<f64> = rows.into_iter().flatten().collect();
+    let inputs: Array2<f64> = Array2::from_shape_vec((samples, perceptron.input_size()), values)
+        .unwrap()
+        .reversed_axes();
+    // Large input files are fed forward in chunks to bound memory
+    let outputs: Array2<f64> = perceptron.predict_chunked(&inputs, PREDICT_CHUNK_SIZE);
+
+    let has_encoder: bool = network.is_some();
+    let decoded: Array2<f64> = output_decoder(network)?(&outputs);
+
+    if let Some(output) = output {
+        let predictions = PredictionsSer::new(decoded, outputs.reversed_axes());
+        return save_output::save_json("predictions", &predictions, Path::new(&output));
+    }
+
+    // Raw outputs are left for the caller to threshold
+    if raw {
+        let values: Vec<Vec<f64>> = outputs
+            .axis_iter(Axis(1))
+            .map(|sample| sample.to_vec())
+            .collect();
+        let values_json = if samples == 1 {
+            serde_json::to_string(&values[0])
+        } else {
+            serde_json::to_string(&values)
+        };
+        return match values_json {
+            Ok(values_json) => {
+                println!("{values_json}");
+                Ok(())
+            }
+            Err(error) => Err(format!("Failed to serialize outputs: {error}").into()),
+        };
+    }
+
+    let label: &str = if has_encoder { "Prediction" } else { "Class" };
+    let predictions: Vec<String> = decoded
+        .rows()
+        .into_iter()
+        .map(|row| {
+            let values: Vec<String> = row.iter().map(f64::to_string).collect();
+            format!("{label}: {}", values.join(", "))
+        })
+        .collect();
+
+    if samples == 1 {
+        println!("{}", predictions[0]);
+        println!("Outputs:");
+        for (class, value) in outputs.index_axis(Axis(1), 0).iter().enumerate() {
+            println!("  {class}: {value:.6}");
+        }
+    } else {
+        for (sample, prediction) in predictions.iter().enumerate() {
+            println!("{sample}: {prediction}");
+        }
+    }
+    Ok(())
+}
+
+/// Parse a comma-separated list of input values
+///
+/// # Arguments
+///
+/// * `input` - Comma-separated input values (e.g. "5.1,3.5,1.4,0.2")
+#[doc(hidden)]
+fn parse_input(input: &str) -> Result<Vec<f64>, OpenPbError> {
+    input
+        .split(',')
+        .map(|value| match value.trim().parse::<f64>() {
+            Ok(value) => Ok(value),
+            Err(_) => Err(format!("Invalid input value \"{}\"", value.trim()).into()),
+        })
+        .collect()
+}
+
+/// Predict every row of a CSV file in chunks, write the predictions
+/// to a CSV file, and report the throughput
+///
+/// # Arguments
+///
+/// * `model` - Model or results JSON file with the trained network
+/// * `data` - CSV file with a header row and one input value per column
+/// * `network` - Network config whose encoder decodes the outputs (optional)
+/// * `output` - CSV file where the predictions are stored
+/// * `options` - Chunk size and parallelism
+#[doc(hidden)]
+fn predict_bulk(
+    model: &str,
+    data: &str,
+    network: Option<String>,
+    output: &str,
+    options: InferenceOptions,
+) -> Result<(), OpenPbError> {
+    let model_file: Vec<u8> = match fs::read(model) {
+        Ok(result) => result,
+        _ => return Err(format!("File {} missing or corrupted", model).into()),
+    };
+    let perceptron: Perceptron = model_from_bytes(&model_file)?;
+    let decoder: OutputDecoder = output_decoder(network)?;
+
+    let report: InferenceReport =
+        inference::predict_csv(&perceptron, decoder.as_ref(), data, output, options)?;
+    eprintln!(
+        "Predicted {} samples in {:.3}s ({:.0} samples/s), saved to {output}",
+        report.samples, report.elapsed_time, report.samples_per_second
+    );
+    Ok(())
+}
+
+/// Function that decodes undecoded outputs (outputs x samples) into
+/// predictions stored as rows: the network config's encoder if one is
+/// given, otherwise the index of each sample's highest output
+///
+/// # Arguments
+///
+/// * `network` - Network config whose encoder decodes the outputs (optional)
+#[doc(hidden)]
+fn output_decoder(network: Option<String>) -> Result<OutputDecoder, OpenPbError> {
+    match network {
+        Some(network) => {
+            let network_json: String = config::read_config(&network)?;
+            let encoder: Box<dyn Encoder> = NetworkDe::from_json(&network_json)
+                .map_err(|error| error.in_file(&network))?
+                .encoder()?;
+            Ok(Box::new(move |outputs| encoder.decode(outputs)))
+        }
+        None => Ok(Box::new(|outputs| {
+            outputs
+                .map_axis(Axis(0), |sample| {
+                    sample.argmax().map_or(0, |class| class) as f64
+                })
+                .insert_axis(Axis(1))
+        })),
+    }
+}
+
+/// Predict a trained network over a grid covering the input range of a
+/// 2-feature dataset, and save the grid for plotting decision boundaries
+///
+/// # Arguments
+///
+/// * `model` - Model or results JSON file with the trained network
+/// * `data` - Data file whose training and validation inputs the grid covers
+/// * `network` - Network config whose encoder decodes the outputs (optional)
+/// * `resolution` - Number of grid points along each axis
+/// * `output` - CSV (when it ends in .csv) or JSON file where the grid is stored
+#[doc(hidden)]
+fn decision_boundary(
+    model: &str,
+    data: &str,
+    network: Option<String>,
+    resolution: usize,
+    output: &str,
+) -> Result<(), OpenPbError> {
+    let model_file: Vec<u8> = match fs::read(model) {
         Ok(result) => result,
-        _ => return Err(format!("File {} missing or corrupted", args.data)),
+        _ => return Err(format!("File {} missing or corrupted", model).into()),
     };
+    let perceptron: Perceptron = model_from_bytes(&model_file)?;
+    let data_de: DataDe = DataDe::from_files(&[data.to_string()])?;
+
+    // Grid covers both the training and validation inputs
+    let (training_inputs, _) = data_de.training_set();
+    let (validation_inputs, _) = data_de.validation_set();
+    let inputs: Array2<f64> =
+        match concatenate(Axis(1), &[training_inputs.view(), validation_inputs.view()]) {
+            Ok(inputs) => inputs,
+            Err(_) => training_inputs,
+        };
+    let decode = output_decoder(network)?;
+    let boundary: DecisionBoundary =
+        boundary::decision_boundary(&perceptron, &inputs, resolution, &decode)?;
+
+    let output_path: &Path = Path::new(output);
+    match output_path
+        .extension()
+        .and_then(|extension| extension.to_str())
+    {
+        Some("csv") => save_output::save_text("decision boundary", &boundary.to_csv(), output_path),
+        _ => save_output::save_json("decision boundary", &boundary, output_path),
+    }
+}
+
+/// Compute and report input-gradient magnitudes for validation samples
+///
+/// # Arguments
+///
+/// * `model` - Model or results JSON file with the trained network
+/// * `data` - JSON file whose validation set inputs are explained
+/// * `samples` - Indices of the validation samples (every sample when empty)
+/// * `output` - JSON file where the gradient magnitudes are stored (optional)
+#[doc(hidden)]
+fn saliency(
+    model: &str,
+    data: &str,
+    samples: Vec<usize>,
+    output: Option<String>,
+) -> Result<(), OpenPbError> {
+    let model_file: Vec<u8> = match fs::read(model) {
+        Ok(result) => result,
+        _ => return Err(format!("File {} missing or corrupted", model).into()),
+    };
+    let data_json: String = match fs::read_to_string(data) {
+        Ok(result) => result,
+        _ => return Err(format!("File {} missing or corrupted", data).into()),
+    };
+    let network: Perceptron = model_from_bytes(&model_file)?;
+    let data_de: DataDe = DataDe::from_json(&data_json)?;
+
+    let (validation_inputs, _) = data_de.validation_set();
+    let samples: Vec<usize> = if samples.is_empty() {
+        (0..validation_inputs.ncols()).collect()
+    } else {
+        samples
+    };
+    let results: Vec<SampleSaliency> =
+        input_saliency::saliency(&network, &validation_inputs, &samples)?;
+
+    for result in &results {
+        println!(
+            "Sample {} (class {}): most salient inputs {:?}",
+            result.sample,
+            result.class,
+            result.most_salient(5)
+        );
+    }
+    match output {
+        Some(output) => save_output::save_json("saliency", &results, Path::new(&output)),
+        None => Ok(()),
+    }
+}
+
+/// Train a run directory's runs again and report whether each run's
+/// metric score matches the recorded one (an error when any doesn't)
+///
+/// # Arguments
+///
+/// * `run_dir` - Run directory with the manifest, network config, and results
+/// * `data` - Data files to train on, defaults to the ones in the manifest when empty
+/// * `tolerance` - Largest difference between the scores that counts as a match
+#[doc(hidden)]
+fn verify(run_dir: &str, data: &[String], tolerance: f32) -> Result<(), OpenPbError> {
+    let checks: Vec<RunCheck> = run_verify::verify_run_dir(Path::new(run_dir), data, tolerance)?;
+    println!("Run  Seed                  Recorded    Reproduced  Epochs     Result");
+    for check in &checks {
+        let seed: String = check.seed.map_or("-".to_string(), |seed| seed.to_string());
+        println!(
+            "{:<4} {:<21} {:<11} {:<11} {:<10} {}",
+            check.run,
+            seed,
+            check.recorded,
+            check.reproduced,
+            format!("{}/{}", check.recorded_epochs, check.reproduced_epochs),
+            if check.matches { "match" } else { "MISMATCH" }
+        );
+    }
+
+    let mismatches: usize = checks.iter().filter(|check| !check.matches).count();
+    if mismatches > 0 {
+        return Err(format!(
+            "{mismatches}/{} runs didn't reproduce within {tolerance}",
+            checks.len()
+        )
+        .into());
+    }
+    println!("\nAll {} runs reproduced within {tolerance}", checks.len());
+    Ok(())
+}
+
+/// Print the network's layers, parameter counts, and estimated FLOPs per
+/// forward pass for the configured batch size, without training
+///
+/// # Arguments
+///
+/// * `network_data_de` - Network config and training data
+/// * `options` - Training options (for the batch size)
+#[doc(hidden)]
+fn print_summary(
+    network_data_de: &NetworkDataDe,
+    options: &TrainerOptions,
+) -> Result<(), OpenPbError> {
+    let network: Perceptron = network_data_de.create_network()?;
+    let train_samples: usize = network_data_de.data.metadata().train_samples;
+    let batch_size: usize = options.batch_config.samples_per_batch(train_samples);
+    print!("{}", network.summary(batch_size));
+    if options.auto_batch_size {
+        println!("Batch size is chosen when training starts, so a full-batch pass is shown");
+    }
+    Ok(())
+}
+
+/// Print the JSON Schema of a config file format, or save it to a file
+///
+/// # Arguments
+///
+/// * `kind` - Config file format
+/// * `output` - JSON file to save the schema to (optional)
+#[doc(hidden)]
+fn schema(kind: ConfigKind, output: Option<String>) -> Result<(), OpenPbError> {
+    let schema = match kind {
+        ConfigKind::Network => config_schema::network_schema(),
+        ConfigKind::Data => config_schema::data_schema(),
+    };
+    if let Some(output) = output {
+        return save_output::save_json("schema", &schema, Path::new(&output));
+    }
+    match serde_json::to_string_pretty(&schema) {
+        Ok(schema_json) => {
+            println!("{schema_json}");
+            Ok(())
+        }
+        Err(error) => Err(format!("Failed to serialize schema: {error}").into()),
+    }
+}
+
+/// Print a section-by-section comparison of two results files, and
+/// optionally overlay their learning curves in an SVG plot
+///
+/// # Arguments
+///
+/// * `baseline` - Results JSON file of the baseline benchmark
+/// * `results` - Results JSON file of the new benchmark
+/// * `plot_path` - SVG file where both benchmarks' learning curves are overlaid (optional)
+#[doc(hidden)]
+fn compare(baseline: &str, results: &str, plot_path: Option<String>) -> Result<(), OpenPbError> {
+    let mut summaries: Vec<ResultsSummaryDe> = Vec::with_capacity(2);
+    for path in [baseline, results] {
+        let results_file: Vec<u8> = match fs::read(path) {
+            Ok(result) => result,
+            _ => return Err(format!("File {} missing or corrupted", path).into()),
+        };
+        summaries.push(ResultsSummaryDe::from_bytes(&results_file)?);
+    }
+    let rows: Vec<ComparisonRow> = results_compare::compare(&summaries[0], &summaries[1]);
+
+    // Every column is padded to its widest value
+    let name_width: usize = rows.iter().map(|row| row.name.len()).max().unwrap_or(0);
+    let before_width: usize = rows
+        .iter()
+        .map(|row| row.before.len())
+        .chain([baseline.len()])
+        .max()
+        .unwrap_or(0);
+    let after_width: usize = rows
+        .iter()
+        .map(|row| row.after.len())
+        .chain([results.len()])
+        .max()
+        .unwrap_or(0);
+
+    println!(
+        "  {:name_width$}  {:before_width$}  {results}",
+        "", baseline
+    );
+    let mut section: Option<Section> = None;
+    for row in &rows {
+        if section != Some(row.section) {
+            println!("{}", row.section.label());
+            section = Some(row.section);
+        }
+        let marker: &str = if row.regression {
+            "!"
+        } else if row.changed() {
+            "*"
+        } else {
+            " "
+        };
+        let change: String = match row.change {
+            Some(change) if row.changed() => format!("{:+.1}%", change * 100.0),
+            _ => String::new(),
+        };
+        let regression: &str = if row.regression { "  REGRESSION" } else { "" };
+        let line: String = format!(
+            "{marker} {:name_width$}  {:before_width$}  {:after_width$}  {change}{regression}",
+            row.name, row.before, row.after
+        );
+        println!("{}", line.trim_end());
+    }
+
+    let regressions: usize = rows.iter().filter(|row| row.regression).count();
+    println!("\n{regressions} regression(s) found");
 
-    match NetworkDataDe::from_json(&data_json, &network_json) {
-        Ok(network_data_de) => train_from_json(network_data_de, args),
-        Err(error) => Err(error),
+    if let Some(plot_path) = plot_path {
+        let curves: Vec<LearningCurve> = [baseline, results]
+            .iter()
+            .zip(&summaries)
+            .map(|(path, summary)| {
+                let label: String = match summary.optimizer() {
+                    Some(optimizer) => format!("{path} ({})", plot::optimizer_label(optimizer)),
+                    None => path.to_string(),
+                };
+                summary.learning_curve(label)
+            })
+            .collect();
+        let svg: String = plot::learning_curves_svg(&curves, summaries[0].metric_label());
+        save_output::save_text("comparison plot", &svg, Path::new(&plot_path))?;
     }
+    Ok(())
 }
@@ -0,0 +1,220 @@
+use crate::file_io::json_de::NetworkDataDe;
+use crate::file_io::results_ser::ThreadedResultsSer;
+use crate::nn::data_source::DataMetadata;
+use crate::trainer::TrainerOptions;
+use chrono::Utc;
+use serde_json::Value;
+use std::path::Path;
+
+/// Everything a model card describes, besides the training results
+pub struct ModelCardContext<'a> {
+    /// Network config file
+    pub network: &'a str,
+
+    /// Data files, in the order they were merged
+    pub data: &'a [String],
+
+    /// Deserialized network config and data
+    pub network_data_de: &'a NetworkDataDe,
+
+    /// Training options, including the seed of the first run
+    pub options: &'a TrainerOptions,
+
+    /// Full command line training was started with
+    pub command: &'a [String],
+}
+
+/// Markdown "model card" that documents a benchmark: the architecture,
+/// dataset, hyperparameters, per-run metric results, training time,
+/// and how to reproduce it
+///
+/// # Arguments
+///
+/// * `context` - Configs, data, and options training was started with
+/// * `results` - Results of every training run
+pub fn model_card(context: &ModelCardContext, results: &ThreadedResultsSer) -> String {
+    let title: &str = Path::new(context.network)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(context.network);
+    let mut card: String = format!("# Model Card: {title}\n\n");
+    card.push_str(&format!("Generated {}\n", Utc::now().to_rfc3339()));
+
+    card.push_str("\n## Architecture\n\n");
+    if let Some(network) = results.networks().first() {
+        let train_samples: usize = context.network_data_de.data.metadata().train_samples;
+        let batch_size: usize = context
+            .options
+            .batch_config
+            .samples_per_batch(train_samples);
+        card.push_str(&format!("```\n{}```\n\n", network.summary(batch_size)));
+    }
+    card.push_str(&table(
+        &["Component", "Value"],
+        &[
+            vec![
+                "Cost".to_string(),
+                context.network_data_de.cost_name().to_string(),
+            ],
+            vec![
+                "Metric".to_string(),
+                context.network_data_de.metric.label().to_string(),
+            ],
+        ],
+    ));
+
+    let metadata: DataMetadata = context.network_data_de.data.metadata();
+    card.push_str("\n## Dataset\n\n");
+    card.push_str(&table(
+        &["Property", "Value"],
+        &[
+            vec!["Files".to_string(), code_list(context.data)],
+            vec![
+                "Training samples".to_string(),
+                metadata.train_samples.to_string(),
+            ],
+            vec![
+                "Validation samples".to_string(),
+                metadata.validation_samples.to_string(),
+            ],
+            vec![
+                "Test samples".to_string(),
+                metadata.test_samples.to_string(),
+            ],
+            vec!["Input size".to_string(), metadata.input_size.to_string()],
+            vec!["Output size".to_string(), metadata.output_size.to_string()],
+        ],
+    ));
+
+    let options: &TrainerOptions = context.options;
+    let mut hyperparameters: Vec<Vec<String>> = vec![
+        vec!["Max epochs".to_string(), options.epochs.to_string()],
+        vec!["Runs".to_string(), options.runs.to_string()],
+        vec!["Restarts".to_string(), options.restarts.to_string()],
+        vec![
+            "Batch size".to_string(),
+            match options.batch_config.batch_size {
+                Some(batch_size) => batch_size.to_string(),
+                None => "full batch".to_string(),
+            },
+        ],
+        vec![
+            "Early stopping".to_string(),
+            options.early_stopping.to_string(),
+        ],
+        vec![
+            "Shuffle".to_string(),
+            options.batch_config.shuffle.to_string(),
+        ],
+        vec![
+            "Seed".to_string(),
+            match options.seed {
+                Some(seed) => seed.to_string(),
+                None => "unseeded".to_string(),
+            },
+        ],
+    ];
+    for (key, value) in context.network_data_de.optimizer_config() {
+        let value: String = match value {
+            Value::String(value) => value,
+            value => value.to_string(),
+        };
+        hyperparameters.push(vec![format!("Optimizer {key}"), value]);
+    }
+    card.push_str("\n## Hyperparameters\n\n");
+    card.push_str(&table(&["Parameter", "Value"], &hyperparameters));
+
+    let metric_label: &str = context.network_data_de.metric.label();
+    let rows: Vec<Vec<String>> = results
+        .runs()
+        .iter()
+        .enumerate()
+        .map(|(run, run_results)| {
+            let (_, value, passed) = run_results.metric();
+            vec![
+                run.to_string(),
+                run_results
+                    .seed()
+                    .map_or("-".to_string(), |seed| seed.to_string()),
+                run_results.total_epochs().to_string(),
+                format!("{:.3}", run_results.elapsed_time()),
+                format!("{value:.4}"),
+                run_results
+                    .test_metric()
+                    .map_or("-".to_string(), |(value, _)| format!("{value:.4}")),
+                if passed { "pass" } else { "fail" }.to_string(),
+            ]
+        })
+        .collect();
+    let test_label: String = format!("Test {metric_label}");
+    card.push_str("\n## Results\n\n");
+    card.push_str(&table(
+        &[
+            "Run",
+            "Seed",
+            "Epochs",
+            "Time (s)",
+            metric_label,
+            &test_label,
+            "Result",
+        ],
+        &rows,
+    ));
+
+    let metrics: Vec<(&str, f32, bool)> = results.metrics();
+    if !metrics.is_empty() {
+        let runs: f32 = metrics.len() as f32;
+        let mean: f32 = metrics.iter().map(|(_, value, _)| value).sum::<f32>() / runs;
+        let std_dev: f32 = (metrics
+            .iter()
+            .map(|(_, value, _)| (value - mean).powi(2))
+            .sum::<f32>()
+            / runs)
+            .sqrt();
+        let passed: usize = metrics.iter().filter(|(_, _, passed)| *passed).count();
+        let total_time: f32 = results
+            .runs()
+            .iter()
+            .map(|run_results| run_results.elapsed_time())
+            .sum();
+        card.push_str(&format!(
+            "\n{metric_label}: {mean:.4} ± {std_dev:.4} over {} runs ({passed} passed)\n",
+            metrics.len()
+        ));
+        card.push_str("\n## Training Time\n\n");
+        card.push_str(&format!(
+            "Total training time was {total_time:.3}s ({:.3}s per run) with {} worker thread(s)\n",
+            total_time / runs,
+            options.threads
+        ));
+    }
+
+    card.push_str("\n## Reproducing\n\n");
+    card.push_str(&format!("```\n{}\n```\n", context.command.join(" ")));
+    card
+}
+
+/// Markdown table with a header row
+///
+/// # Arguments
+///
+/// * `header` - Label of each column
+/// * `rows` - Cells of each row
+fn table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut table: String = format!("| {} |\n", header.join(" | "));
+    table.push_str(&format!("|{}\n", " --- |".repeat(header.len())));
+    for row in rows {
+        table.push_str(&format!("| {} |\n", row.join(" | ")));
+    }
+    table
+}
+
+/// Comma-separated list of inline code spans
+///
+/// # Arguments
+///
+/// * `values` - Values to list
+fn code_list(values: &[String]) -> String {
+    let values: Vec<String> = values.iter().map(|value| format!("`{value}`")).collect();
+    values.join(", ")
+}
@@ -0,0 +1,216 @@
+use super::perceptron::{Perceptron, VALIDATION_CHUNK_SIZE};
+use ndarray::{s, Array1, Array2, Axis};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Distribution summary of a single Layer's outputs (activations)
+/// over a batch of validation samples
+#[derive(Serialize, Debug, Clone)]
+pub struct LayerActivations {
+    /// Index of the Layer (0 is the first hidden layer)
+    pub layer: usize,
+
+    /// Smallest activation
+    pub min: f64,
+
+    /// 5th percentile
+    pub p5: f64,
+
+    /// 25th percentile
+    pub p25: f64,
+
+    /// Median
+    pub p50: f64,
+
+    /// 75th percentile
+    pub p75: f64,
+
+    /// 95th percentile
+    pub p95: f64,
+
+    /// Largest activation
+    pub max: f64,
+
+    /// Fraction of activations that are exactly zero
+    /// (e.g. inactive ReLU units)
+    pub zero_fraction: f64,
+}
+
+impl LayerActivations {
+    /// # Arguments
+    ///
+    /// * `layer` - Index of the Layer
+    /// * `activations` - Outputs of the Layer (neurons x samples)
+    pub fn new(layer: usize, activations: &Array2<f64>) -> Self {
+        let mut values: Vec<f64> = activations.iter().copied().collect();
+        values.sort_by(f64::total_cmp);
+        let zeros: usize = values.iter().filter(|value| **value == 0.0).count();
+
+        Self {
+            layer,
+            min: percentile(&values, 0.0),
+            p5: percentile(&values, 5.0),
+            p25: percentile(&values, 25.0),
+            p50: percentile(&values, 50.0),
+            p75: percentile(&values, 75.0),
+            p95: percentile(&values, 95.0),
+            max: percentile(&values, 100.0),
+            zero_fraction: zeros as f64 / values.len().max(1) as f64,
+        }
+    }
+}
+
+/// Summarize the activations of every Layer for (up to) the first
+/// `batch_size` validation samples
+///
+/// # Arguments
+///
+/// * `network` - Trained network
+/// * `inputs` - Validation input vectors (features x samples)
+/// * `batch_size` - Maximum number of samples fed forward
+pub fn activation_summaries(
+    network: &Perceptron,
+    inputs: &Array2<f64>,
+    batch_size: usize,
+) -> Vec<LayerActivations> {
+    let samples: usize = inputs.ncols().min(batch_size.max(1));
+    let batch: Array2<f64> = inputs.slice(s![.., ..samples]).to_owned();
+    network
+        .forward_with_activations(&batch)
+        .iter()
+        .enumerate()
+        .map(|(layer, activations)| LayerActivations::new(layer, activations))
+        .collect()
+}
+
+/// Linearly interpolated percentile of sorted values (0 when empty)
+///
+/// # Arguments
+///
+/// * `sorted` - Values in ascending order
+/// * `percent` - Percentile between 0 and 100
+fn percentile(sorted: &[f64], percent: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank: f64 = percent / 100.0 * (sorted.len() - 1) as f64;
+    let lower: usize = rank.floor() as usize;
+    let upper: usize = rank.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
+/// Options for detecting dead neurons in hidden layers
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct DeadNeuronConfig {
+    /// Largest absolute activation a neuron can have across the
+    /// whole validation set and still be considered dead
+    pub threshold: f64,
+
+    /// Number of epochs between warnings about dead neurons during
+    /// training (only checked after training when `None`)
+    pub warn_every: Option<usize>,
+}
+
+/// Per-layer option for re-randomizing the incoming weights of
+/// dead neurons during training, so they can start learning again
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy)]
+pub struct DeadNeuronReinit {
+    /// Number of epochs between checks for dead neurons
+    pub every: usize,
+
+    /// Largest absolute activation a neuron can have across
+    /// the whole training set and still be considered dead
+    #[serde(default = "default_dead_threshold")]
+    pub threshold: f64,
+}
+
+/// Default threshold of `DeadNeuronReinit`
+fn default_dead_threshold() -> f64 {
+    1e-6
+}
+
+/// Dead neurons of a single hidden Layer
+#[derive(Serialize, Debug, Clone)]
+pub struct LayerDeadNeurons {
+    /// Index of the Layer (0 is the first hidden layer)
+    pub layer: usize,
+
+    /// Number of neurons in the Layer
+    pub neurons: usize,
+
+    /// Number of dead neurons
+    pub dead: usize,
+
+    /// Index of each dead neuron within the Layer
+    pub indices: Vec<usize>,
+}
+
+/// Find the neurons of every hidden Layer whose activations stay within
+/// `threshold` of zero for every validation sample. The output layer is
+/// skipped, since its values are predictions rather than features
+///
+/// # Arguments
+///
+/// * `network` - Network to analyze
+/// * `inputs` - Validation input vectors (features x samples)
+/// * `threshold` - Largest absolute activation of a dead neuron
+pub fn dead_neurons(
+    network: &Perceptron,
+    inputs: &Array2<f64>,
+    threshold: f64,
+) -> Vec<LayerDeadNeurons> {
+    max_activations(network, inputs)
+        .iter()
+        .enumerate()
+        .map(|(layer, max)| {
+            let indices: Vec<usize> = max
+                .iter()
+                .enumerate()
+                .filter(|(_, value)| **value <= threshold)
+                .map(|(neuron, _)| neuron)
+                .collect();
+            LayerDeadNeurons {
+                layer,
+                neurons: max.len(),
+                dead: indices.len(),
+                indices,
+            }
+        })
+        .collect()
+}
+
+/// Largest absolute activation of each neuron of every hidden Layer
+/// over all of the given samples
+///
+/// # Arguments
+///
+/// * `network` - Network to analyze
+/// * `inputs` - Input vectors (features x samples)
+pub fn max_activations(network: &Perceptron, inputs: &Array2<f64>) -> Vec<Array1<f64>> {
+    // Accumulated in chunks so large sets aren't fed forward all at once
+    let mut max_activations: Vec<Array1<f64>> = vec![];
+    let samples: usize = inputs.ncols();
+    for start in (0..samples).step_by(VALIDATION_CHUNK_SIZE) {
+        let end: usize = (start + VALIDATION_CHUNK_SIZE).min(samples);
+        let chunk: Array2<f64> = inputs.slice(s![.., start..end]).to_owned();
+        let layer_outputs: Vec<Array2<f64>> = network.forward_with_activations(&chunk);
+        let hidden_outputs: &[Array2<f64>] = match layer_outputs.split_last() {
+            Some((_, hidden_outputs)) => hidden_outputs,
+            None => &[],
+        };
+
+        for (layer, outputs) in hidden_outputs.iter().enumerate() {
+            let chunk_max: Array1<f64> = outputs.map_axis(Axis(1), |neuron| {
+                neuron
+                    .iter()
+                    .fold(0.0, |max: f64, value| max.max(value.abs()))
+            });
+            match max_activations.get_mut(layer) {
+                Some(max) => max.zip_mut_with(&chunk_max, |max, value| *max = max.max(*value)),
+                None => max_activations.push(chunk_max),
+            }
+        }
+    }
+
+    max_activations
+}
@@ -0,0 +1,41 @@
+use super::layer::Layer;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Options for keeping a Polyak (tau-averaged) copy of a
+/// Network's weights and biases during training
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy)]
+pub struct AveragingConfig {
+    /// Fraction of the way the averaged weights move towards
+    /// the trained weights after every minibatch
+    pub tau: f64,
+
+    /// Whether or not the early stopping metric is checked
+    /// against the averaged weights' predictions
+    #[serde(default = "default_enabled")]
+    pub evaluate: bool,
+
+    /// Whether or not the averaged weights replace the
+    /// trained weights once training has finished
+    #[serde(default = "default_enabled")]
+    pub save: bool,
+}
+
+#[doc(hidden)]
+fn default_enabled() -> bool {
+    true
+}
+
+/// Move every averaged Layer's weights and biases towards
+/// those of the matching trained Layer
+///
+/// # Arguments
+///
+/// * `averaged` - Averaged copies of the Network's layers
+/// * `layers` - Layers being trained
+/// * `tau` - Fraction of the way the averaged values move
+pub fn update(averaged: &mut [Layer], layers: &[Layer], tau: f64) {
+    for (averaged_layer, layer) in averaged.iter_mut().zip(layers) {
+        averaged_layer.blend(layer, tau);
+    }
+}
@@ -0,0 +1,410 @@
+use crate::error::OpenPbError;
+use ndarray::{concatenate, s, Array2, Axis};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+/// Fraction of the running mean and variance that's
+/// replaced by each training minibatch's statistics
+pub const BATCH_NORM_MOMENTUM: f64 = 0.1;
+
+/// Added to each variance before its square root is taken,
+/// so neurons with a constant activation aren't divided by zero
+pub const BATCH_NORM_EPSILON: f64 = 1e-5;
+
+/// Fewest samples a minibatch needs to be normalized by its own
+/// statistics (a single sample has no variance, so it would normalize
+/// every activation to zero)
+pub const MIN_BATCH_NORM_SAMPLES: usize = 2;
+
+/// Check that minibatches are big enough for batch normalization
+///
+/// # Arguments
+///
+/// * `batch_size` - Number of samples in each training minibatch
+pub fn check_batch_size(batch_size: usize) -> Result<(), OpenPbError> {
+    if batch_size < MIN_BATCH_NORM_SAMPLES {
+        return Err(format!(
+            "Batch normalization needs at least {MIN_BATCH_NORM_SAMPLES} samples per minibatch, got {batch_size}"
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Batch normalization of a Layer's activation values. During training,
+/// each neuron's activations are normalized by the minibatch's mean and
+/// variance, then scaled and shifted by the learnable gamma and beta. The
+/// running mean and variance tracked during training are used in their
+/// place for predictions (all vectors have the shape: neurons x 1)
+#[derive(Clone, Debug)]
+pub struct BatchNorm {
+    /// Learnable scale of each neuron's normalized activations
+    gamma: Array2<f64>,
+
+    /// Learnable shift of each neuron's normalized activations
+    beta: Array2<f64>,
+
+    /// Mean activation of each neuron, tracked during training
+    running_mean: Array2<f64>,
+
+    /// Variance of each neuron's activations, tracked during training
+    running_variance: Array2<f64>,
+
+    /// Normalized activations of the latest training
+    /// minibatch (shape: neurons x samples)
+    normalized: Array2<f64>,
+
+    /// Inverse standard deviation of each neuron's
+    /// activations in the latest training minibatch
+    inverse_std: Array2<f64>,
+
+    /// Gradient of the cost with respect to gamma from the latest backprop step
+    gamma_gradient: Array2<f64>,
+
+    /// Gradient of the cost with respect to beta from the latest backprop step
+    beta_gradient: Array2<f64>,
+}
+
+impl BatchNorm {
+    /// Batch normalization that starts out as the identity
+    /// (gamma of 1, beta of 0, and a running variance of 1)
+    ///
+    /// # Arguments
+    ///
+    /// * `neurons` - Number of neurons in the Layer
+    pub fn new(neurons: usize) -> BatchNorm {
+        BatchNorm::from_parameters(
+            Array2::ones((neurons, 1)),
+            Array2::zeros((neurons, 1)),
+            Array2::zeros((neurons, 1)),
+            Array2::ones((neurons, 1)),
+        )
+    }
+
+    /// Reconstruct previously trained batch normalization
+    ///
+    /// # Arguments
+    ///
+    /// * `gamma` - Learnable scale of each neuron (shape: neurons x 1)
+    /// * `beta` - Learnable shift of each neuron (shape: neurons x 1)
+    /// * `running_mean` - Mean activation of each neuron (shape: neurons x 1)
+    /// * `running_variance` - Variance of each neuron's activations (shape: neurons x 1)
+    pub fn from_parameters(
+        gamma: Array2<f64>,
+        beta: Array2<f64>,
+        running_mean: Array2<f64>,
+        running_variance: Array2<f64>,
+    ) -> BatchNorm {
+        let neurons: usize = gamma.nrows();
+        BatchNorm {
+            gamma,
+            beta,
+            running_mean,
+            running_variance,
+            normalized: Array2::zeros((neurons, 0)),
+            inverse_std: Array2::zeros((neurons, 1)),
+            gamma_gradient: Array2::zeros((neurons, 1)),
+            beta_gradient: Array2::zeros((neurons, 1)),
+        }
+    }
+
+    /// Number of neurons the parameters are shaped for
+    pub fn neurons(&self) -> usize {
+        self.gamma.nrows()
+    }
+
+    /// Number of trainable values (gamma and beta)
+    pub fn num_parameters(&self) -> usize {
+        self.gamma.len() + self.beta.len()
+    }
+
+    /// Normalize a training minibatch's activations in place with the
+    /// minibatch's own statistics, which are blended into the running
+    /// mean and variance. The values needed by backprop are saved
+    ///
+    /// # Arguments
+    ///
+    /// * `activations` - Activation values (shape: neurons x samples)
+    pub fn normalize(&mut self, activations: &mut Array2<f64>) {
+        let samples: usize = activations.ncols();
+        if samples == 0 {
+            return;
+        }
+        let mean: Array2<f64> = activations.sum_axis(Axis(1)).insert_axis(Axis(1)) / samples as f64;
+        let variance: Array2<f64> = (&*activations - &mean)
+            .mapv(|value| value * value)
+            .sum_axis(Axis(1))
+            .insert_axis(Axis(1))
+            / samples as f64;
+
+        self.running_mean *= 1.0 - BATCH_NORM_MOMENTUM;
+        self.running_mean.scaled_add(BATCH_NORM_MOMENTUM, &mean);
+        self.running_variance *= 1.0 - BATCH_NORM_MOMENTUM;
+        self.running_variance
+            .scaled_add(BATCH_NORM_MOMENTUM, &variance);
+
+        self.inverse_std = variance.mapv(|variance| 1.0 / (variance + BATCH_NORM_EPSILON).sqrt());
+        *activations -= &mean;
+        *activations *= &self.inverse_std;
+        self.normalized = activations.clone();
+
+        *activations *= &self.gamma;
+        *activations += &self.beta;
+    }
+
+    /// Normalize activations with the running mean and variance.
+    /// This function is meant to get predictions from a fully-trained network
+    ///
+    /// # Arguments
+    ///
+    /// * `activations` - Activation values (shape: neurons x samples)
+    pub fn predict(&self, activations: &Array2<f64>) -> Array2<f64> {
+        let scale: Array2<f64> = self.prediction_scale();
+        (activations - &self.running_mean) * &scale + &self.beta
+    }
+
+    /// Convert the gradient of the cost with respect to the normalized
+    /// outputs of the latest training minibatch into the gradient with
+    /// respect to the activation values (in place), and save the gamma
+    /// and beta gradients for the next update
+    ///
+    /// # Arguments
+    ///
+    /// * `deltas` - Gradient with respect to the normalized outputs (shape: neurons x samples)
+    pub fn back_prop(&mut self, deltas: &mut Array2<f64>) {
+        if deltas.dim() != self.normalized.dim() || deltas.ncols() == 0 {
+            return;
+        }
+        let samples: f64 = deltas.ncols() as f64;
+        self.beta_gradient = deltas.sum_axis(Axis(1)).insert_axis(Axis(1));
+        self.gamma_gradient = (&*deltas * &self.normalized)
+            .sum_axis(Axis(1))
+            .insert_axis(Axis(1));
+
+        // d_activations = gamma * inverse_std / samples
+        //     * (samples * deltas - sum(deltas) - normalized * sum(deltas * normalized))
+        let scale: Array2<f64> = &self.gamma * &self.inverse_std / samples;
+        let correction: Array2<f64> = &self.normalized * &self.gamma_gradient + &self.beta_gradient;
+        *deltas *= samples;
+        *deltas -= &correction;
+        *deltas *= &scale;
+    }
+
+    /// Gradient with respect to the activation values, given the gradient
+    /// with respect to the normalized outputs, when the running statistics
+    /// are used (i.e. during prediction). Internal values aren't saved
+    ///
+    /// # Arguments
+    ///
+    /// * `deltas` - Gradient with respect to the normalized outputs (shape: neurons x samples)
+    pub fn input_gradient(&self, deltas: &Array2<f64>) -> Array2<f64> {
+        deltas * &self.prediction_scale()
+    }
+
+    /// Gradient of the cost with respect to gamma (first column) and beta
+    /// (second column), summed over the latest training minibatch, so the
+    /// optimizer can step both like any other parameters (shape: neurons x 2).
+    /// Returns `None` before the first training minibatch
+    pub fn gradient(&self) -> Option<Array2<f64>> {
+        if self.normalized.ncols() == 0 {
+            return None;
+        }
+        Some(concatenate![
+            Axis(1),
+            self.gamma_gradient,
+            self.beta_gradient
+        ])
+    }
+
+    /// Adjust gamma and beta by a step computed by the optimizer from
+    /// `gradient`, averaged over the samples of the latest training minibatch
+    ///
+    /// # Arguments
+    ///
+    /// * `step` - Change in gamma (first column) and beta (second column)
+    pub fn step(&mut self, step: &Array2<f64>) {
+        let samples: usize = self.normalized.ncols();
+        if samples == 0 {
+            return;
+        }
+        let scale: f64 = 1.0 / samples as f64;
+        self.gamma.scaled_add(-scale, &step.slice(s![.., 0..1]));
+        self.beta.scaled_add(-scale, &step.slice(s![.., 1..2]));
+    }
+
+    /// Copy the parameters and running statistics of trained
+    /// batch normalization with the same number of neurons
+    ///
+    /// # Arguments
+    ///
+    /// * `trained` - Batch normalization whose values are copied
+    pub fn load_parameters(&mut self, trained: &BatchNorm) {
+        self.gamma.assign(&trained.gamma);
+        self.beta.assign(&trained.beta);
+        self.running_mean.assign(&trained.running_mean);
+        self.running_variance.assign(&trained.running_variance);
+    }
+
+    /// Move the parameters and running statistics a fraction of
+    /// the way towards another's (Polyak averaging)
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - Batch normalization with the same number of neurons
+    /// * `tau` - Fraction of the way the values move
+    pub fn blend(&mut self, source: &BatchNorm, tau: f64) {
+        let blend = |value: &mut f64, source: &f64| *value += tau * (source - *value);
+        self.gamma.zip_mut_with(&source.gamma, blend);
+        self.beta.zip_mut_with(&source.beta, blend);
+        self.running_mean.zip_mut_with(&source.running_mean, blend);
+        self.running_variance
+            .zip_mut_with(&source.running_variance, blend);
+    }
+
+    /// Scale applied to each neuron's centered activations during prediction
+    fn prediction_scale(&self) -> Array2<f64> {
+        &self.gamma
+            / &self
+                .running_variance
+                .mapv(|variance| (variance + BATCH_NORM_EPSILON).sqrt())
+    }
+}
+
+impl Serialize for BatchNorm {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // Only the values needed for predictions (or resuming training)
+        let mut s = serializer.serialize_struct("BatchNorm", 4)?;
+        s.serialize_field("gamma", &self.gamma)?;
+        s.serialize_field("beta", &self.beta)?;
+        s.serialize_field("running_mean", &self.running_mean)?;
+        s.serialize_field("running_variance", &self.running_variance)?;
+        s.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    /// Step size of the central differences
+    const H: f64 = 1e-6;
+
+    /// Batch normalization with a non-trivial scale and shift
+    fn batch_norm(gamma: Array2<f64>, beta: Array2<f64>) -> BatchNorm {
+        BatchNorm::from_parameters(gamma, beta, Array2::zeros((2, 1)), Array2::ones((2, 1)))
+    }
+
+    fn gamma() -> Array2<f64> {
+        array![[1.5], [0.5]]
+    }
+
+    fn beta() -> Array2<f64> {
+        array![[0.2], [-0.3]]
+    }
+
+    fn activations() -> Array2<f64> {
+        array![[0.3, -1.2, 2.0, 0.7], [1.0, 0.4, -0.6, 0.1]]
+    }
+
+    /// Weights of the loss `sum(weights * normalized outputs)`,
+    /// whose gradient with respect to the outputs is the weights
+    fn loss_weights() -> Array2<f64> {
+        array![[0.5, -1.0, 0.25, 2.0], [1.5, 0.3, -0.7, 0.9]]
+    }
+
+    fn loss(mut batch_norm: BatchNorm, activations: &Array2<f64>) -> f64 {
+        let mut outputs: Array2<f64> = activations.clone();
+        batch_norm.normalize(&mut outputs);
+        (&outputs * &loss_weights()).sum()
+    }
+
+    fn assert_close(analytic: f64, numeric: f64, label: &str) {
+        assert!(
+            (analytic - numeric).abs() < 1e-5,
+            "{}: backprop gave {}, finite differences gave {}",
+            label,
+            analytic,
+            numeric
+        );
+    }
+
+    #[test]
+    fn normalize_gives_zero_mean_and_unit_variance() {
+        let mut batch_norm: BatchNorm = BatchNorm::new(2);
+        let mut outputs: Array2<f64> = activations();
+        batch_norm.normalize(&mut outputs);
+        for row in outputs.rows() {
+            let mean: f64 = row.mean().unwrap();
+            let variance: f64 = row.mapv(|value| (value - mean).powi(2)).mean().unwrap();
+            assert!(mean.abs() < 1e-12);
+            assert!((variance - 1.0).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn activation_gradient_matches_finite_differences() {
+        let mut trained: BatchNorm = batch_norm(gamma(), beta());
+        let mut outputs: Array2<f64> = activations();
+        trained.normalize(&mut outputs);
+        let mut deltas: Array2<f64> = loss_weights();
+        trained.back_prop(&mut deltas);
+
+        for ((i, j), &analytic) in deltas.indexed_iter() {
+            let (mut plus, mut minus) = (activations(), activations());
+            plus[[i, j]] += H;
+            minus[[i, j]] -= H;
+            let numeric: f64 = (loss(batch_norm(gamma(), beta()), &plus)
+                - loss(batch_norm(gamma(), beta()), &minus))
+                / (2.0 * H);
+            assert_close(analytic, numeric, &format!("activation [{i}, {j}]"));
+        }
+    }
+
+    #[test]
+    fn gamma_and_beta_gradients_match_finite_differences() {
+        let mut trained: BatchNorm = batch_norm(gamma(), beta());
+        let mut outputs: Array2<f64> = activations();
+        trained.normalize(&mut outputs);
+        trained.back_prop(&mut loss_weights());
+        let gradient: Array2<f64> = trained.gradient().unwrap();
+        assert_eq!(gradient.dim(), (2, 2));
+
+        for i in 0..2 {
+            let (mut plus, mut minus) = (gamma(), gamma());
+            plus[[i, 0]] += H;
+            minus[[i, 0]] -= H;
+            let numeric: f64 = (loss(batch_norm(plus, beta()), &activations())
+                - loss(batch_norm(minus, beta()), &activations()))
+                / (2.0 * H);
+            assert_close(gradient[[i, 0]], numeric, &format!("gamma [{i}]"));
+
+            let (mut plus, mut minus) = (beta(), beta());
+            plus[[i, 0]] += H;
+            minus[[i, 0]] -= H;
+            let numeric: f64 = (loss(batch_norm(gamma(), plus), &activations())
+                - loss(batch_norm(gamma(), minus), &activations()))
+                / (2.0 * H);
+            assert_close(gradient[[i, 1]], numeric, &format!("beta [{i}]"));
+        }
+    }
+
+    #[test]
+    fn step_is_averaged_over_the_minibatch() {
+        let mut trained: BatchNorm = batch_norm(gamma(), beta());
+        assert!(trained.gradient().is_none());
+        let mut outputs: Array2<f64> = activations();
+        trained.normalize(&mut outputs);
+        trained.step(&array![[1.0, 2.0], [4.0, 8.0]]);
+        assert_eq!(trained.gamma, array![[1.25], [-0.5]]);
+        assert_eq!(trained.beta, array![[-0.3], [-2.3]]);
+    }
+
+    #[test]
+    fn single_sample_minibatches_are_rejected() {
+        assert!(check_batch_size(1).is_err());
+        assert!(check_batch_size(MIN_BATCH_NORM_SAMPLES).is_ok());
+    }
+}
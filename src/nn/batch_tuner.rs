@@ -0,0 +1,133 @@
+use super::batches::{BatchConfig, Batches, Sampler};
+use super::data_source::{self, DataSource};
+use super::functions::cost::Cost;
+use super::functions::encoder::Encoder;
+use super::functions::optimizer::Optimizer;
+use super::perceptron::Perceptron;
+use ndarray::Array2;
+use rand::rngs::StdRng;
+use serde::Serialize;
+use std::time::Instant;
+
+/// Batch sizes benchmarked in automatic mode (the full
+/// training set is always benchmarked as well)
+pub const AUTO_BATCH_CANDIDATES: [usize; 5] = [16, 32, 64, 128, 256];
+
+/// Number of epochs each candidate batch size is trained for
+pub const AUTO_BATCH_EPOCHS: usize = 3;
+
+/// Benchmark results for a single candidate batch size
+#[derive(Serialize, Debug, Clone)]
+pub struct BatchCandidate {
+    /// Number of samples in each minibatch
+    pub batch_size: usize,
+
+    /// Mean wall-clock time of a single epoch (always 0 on wasm32)
+    pub seconds_per_epoch: f64,
+
+    /// Change in training loss per second of training (per epoch
+    /// on wasm32, where time can't be measured; more negative is better)
+    pub loss_slope: f64,
+}
+
+/// Record of how the batch size was chosen in automatic mode
+#[derive(Serialize, Debug, Clone)]
+pub struct BatchSizeTuning {
+    /// Benchmark results of every candidate
+    pub candidates: Vec<BatchCandidate>,
+
+    /// Batch size used for the full run
+    pub chosen: usize,
+}
+
+/// Train a copy of the network for a few epochs with each candidate batch
+/// size and choose the one that lowers the training loss the fastest
+///
+/// # Arguments
+///
+/// * `network` - Freshly initialized network (every candidate starts from a copy)
+/// * `data` - Source of training data
+/// * `optimizer` - Optimization method (every candidate starts from a copy)
+/// * `cost` - Loss function that's compared between candidates
+/// * `encoder` - Transforms output values to/from the network's output format
+/// * `shuffle` - Whether or not training data is shuffled each epoch
+/// * `epochs` - Number of epochs each candidate is trained for
+/// * `rng` - Random number generator the training data is shuffled with
+#[allow(clippy::too_many_arguments)]
+pub fn tune_batch_size(
+    network: &Perceptron,
+    data: &dyn DataSource,
+    optimizer: &dyn Optimizer,
+    cost: &dyn Cost,
+    encoder: &dyn Encoder,
+    shuffle: bool,
+    epochs: usize,
+    rng: &mut StdRng,
+) -> BatchSizeTuning {
+    let (training_inputs, training_outputs) = data.training_set();
+    let input_rows: usize = training_inputs.nrows();
+    let train_samples: usize = training_inputs.ncols();
+    let expected: Array2<f64> = data_source::expected_outputs(data, encoder, &training_outputs);
+
+    // Candidates larger than the training set are the same as a single full batch
+    let mut batch_sizes: Vec<usize> = AUTO_BATCH_CANDIDATES
+        .iter()
+        .copied()
+        .filter(|&batch_size| batch_size < train_samples)
+        .collect();
+    batch_sizes.push(train_samples.max(1));
+
+    let initial_loss: f64 = cost.call(&network.predict_raw(&training_inputs), &expected);
+
+    let candidates: Vec<BatchCandidate> = batch_sizes
+        .into_iter()
+        .map(|batch_size| {
+            let mut network: Perceptron = network.clone();
+            let mut optimizer: Box<dyn Optimizer> = optimizer.clone_box();
+            let batch_config = BatchConfig {
+                batch_size: Some(batch_size),
+                shuffle,
+                drop_last: false,
+                sampler: Sampler::Sequential,
+            };
+
+            // Instant isn't available on wasm32-unknown-unknown, where
+            // candidates are compared by their loss change per epoch instead
+            let start: Option<Instant> = if cfg!(target_arch = "wasm32") {
+                None
+            } else {
+                Some(Instant::now())
+            };
+            for _ in 0..epochs.max(1) {
+                let batches = Batches::new(&training_inputs, &expected, batch_config, rng);
+                for (batch_inputs, batch_expected) in batches {
+                    network.train_batch(
+                        &batch_inputs,
+                        &batch_expected,
+                        optimizer.as_mut(),
+                        cost,
+                        input_rows,
+                    );
+                }
+            }
+            let elapsed: Option<f64> =
+                start.map(|start| start.elapsed().as_secs_f64().max(f64::EPSILON));
+
+            let final_loss: f64 = cost.call(&network.predict_raw(&training_inputs), &expected);
+            BatchCandidate {
+                batch_size,
+                seconds_per_epoch: elapsed.map_or(0.0, |elapsed| elapsed / epochs.max(1) as f64),
+                loss_slope: (final_loss - initial_loss) / elapsed.unwrap_or(epochs.max(1) as f64),
+            }
+        })
+        .collect();
+
+    // Diverging candidates (NaN loss) are never chosen
+    let chosen: usize = candidates
+        .iter()
+        .filter(|candidate| candidate.loss_slope.is_finite())
+        .min_by(|a, b| a.loss_slope.total_cmp(&b.loss_slope))
+        .map_or(train_samples, |candidate| candidate.batch_size);
+
+    BatchSizeTuning { candidates, chosen }
+}
@@ -0,0 +1,230 @@
+use crate::error::OpenPbError;
+use ndarray::{Array2, Axis};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How the samples of each epoch's minibatches are chosen
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Sampler {
+    /// Every sample is visited once, in order (or shuffled)
+    #[default]
+    Sequential,
+
+    /// Samples are drawn with replacement, with probability inversely
+    /// proportional to the frequency of their class (output values)
+    Balanced,
+
+    /// Samples are drawn with replacement, with probability
+    /// proportional to the per-sample weights of the training data
+    Weighted,
+}
+
+impl Sampler {
+    /// # Arguments
+    ///
+    /// * `name` - "sequential", "balanced", or "weighted"
+    pub fn from_name(name: &str) -> Result<Sampler, OpenPbError> {
+        match name.to_lowercase().as_str() {
+            "sequential" => Ok(Sampler::Sequential),
+            "balanced" => Ok(Sampler::Balanced),
+            "weighted" => Ok(Sampler::Weighted),
+            _ => Err(format!(
+                "Unknown sampler \"{name}\", expected sequential, balanced, or weighted"
+            )
+            .into()),
+        }
+    }
+}
+
+/// Options that control how a dataset is split into minibatches
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct BatchConfig {
+    /// Maximum number of samples in each batch. When `None`,
+    /// the entire dataset is used as a single batch
+    pub batch_size: Option<usize>,
+
+    /// When 'true', samples are shuffled before being split into batches
+    pub shuffle: bool,
+
+    /// When 'true', the final batch is skipped if it's
+    /// smaller than the requested batch size
+    pub drop_last: bool,
+
+    /// How samples are chosen for each epoch's batches
+    pub sampler: Sampler,
+}
+
+impl BatchConfig {
+    /// Number of samples in each full batch of a training set
+    ///
+    /// # Arguments
+    ///
+    /// * `train_samples` - Number of samples in the training set
+    pub fn samples_per_batch(&self, train_samples: usize) -> usize {
+        match self.batch_size {
+            Some(batch_size) => batch_size.min(train_samples),
+            None => train_samples,
+        }
+    }
+}
+
+/// Iterator over minibatches of a dataset. Both matrices are expected
+/// to store each sample as a column, so the number of columns of the
+/// inputs must match the number of columns of the targets
+pub struct Batches<'a> {
+    /// Input vectors (shape: features x samples)
+    inputs: &'a Array2<f64>,
+
+    /// Target vectors (shape: outputs x samples)
+    targets: &'a Array2<f64>,
+
+    /// Order in which samples are visited
+    indices: Vec<usize>,
+
+    /// Number of samples in each batch
+    batch_size: usize,
+
+    /// Whether or not an incomplete final batch is skipped
+    drop_last: bool,
+
+    /// Index (into `indices`) of the next batch's first sample
+    position: usize,
+}
+
+impl<'a> Batches<'a> {
+    /// # Arguments
+    ///
+    /// * `inputs` - Input vectors (shape: features x samples)
+    /// * `targets` - Target vectors (shape: outputs x samples)
+    /// * `config` - Batch size, shuffling, and drop-last options
+    /// * `rng` - Random number generator used for shuffling
+    pub fn new<R: Rng + ?Sized>(
+        inputs: &'a Array2<f64>,
+        targets: &'a Array2<f64>,
+        config: BatchConfig,
+        rng: &mut R,
+    ) -> Self {
+        assert_eq!(
+            inputs.ncols(),
+            targets.ncols(),
+            "Number of input samples != number of target samples"
+        );
+        let samples: usize = inputs.ncols();
+
+        let mut indices: Vec<usize> = (0..samples).collect();
+        if config.shuffle {
+            indices.shuffle(rng);
+        }
+        // Batch size is clamped so an empty or oversized
+        // batch size still produces a valid iterator
+        let batch_size: usize = config.samples_per_batch(samples).clamp(1, samples.max(1));
+
+        Self {
+            inputs,
+            targets,
+            indices,
+            batch_size,
+            drop_last: config.drop_last,
+            position: 0,
+        }
+    }
+
+    /// Same as `new`, but each epoch draws as many samples as the
+    /// dataset has (with replacement) with probability proportional
+    /// to their weights, instead of visiting every sample once
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - Input vectors (shape: features x samples)
+    /// * `targets` - Target vectors (shape: outputs x samples)
+    /// * `config` - Batch size and drop-last options (shuffling is implied)
+    /// * `weights` - Relative sampling weight of each sample
+    /// * `rng` - Random number generator used for sampling
+    pub fn weighted<R: Rng + ?Sized>(
+        inputs: &'a Array2<f64>,
+        targets: &'a Array2<f64>,
+        config: BatchConfig,
+        weights: &[f64],
+        rng: &mut R,
+    ) -> Self {
+        let mut batches: Batches = Batches::new(
+            inputs,
+            targets,
+            BatchConfig {
+                shuffle: false,
+                ..config
+            },
+            rng,
+        );
+        // Invalid (e.g. all zero) weights fall back to sequential batches
+        if let Ok(distribution) = WeightedIndex::new(weights) {
+            let samples: usize = batches.indices.len();
+            batches.indices = (0..samples).map(|_| distribution.sample(rng)).collect();
+        }
+        batches
+    }
+
+    /// Number of batches that are left in the iterator
+    fn remaining(&self) -> usize {
+        let remaining_samples: usize = self.indices.len() - self.position;
+        if self.drop_last {
+            remaining_samples / self.batch_size
+        } else {
+            remaining_samples.div_ceil(self.batch_size)
+        }
+    }
+}
+
+impl<'a> Iterator for Batches<'a> {
+    /// Input and target vectors for a single batch
+    type Item = (Array2<f64>, Array2<f64>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining() == 0 {
+            return None;
+        }
+        let end: usize = (self.position + self.batch_size).min(self.indices.len());
+        let batch_indices: &[usize] = &self.indices[self.position..end];
+        self.position = end;
+
+        Some((
+            self.inputs.select(Axis(1), batch_indices),
+            self.targets.select(Axis(1), batch_indices),
+        ))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining: usize = self.remaining();
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a> ExactSizeIterator for Batches<'a> {}
+
+/// Sampling weight of each sample that's inversely proportional to the
+/// number of samples in its class, so every class is drawn equally often.
+/// Samples with identical output values are considered the same class
+///
+/// # Arguments
+///
+/// * `outputs` - Unencoded output values (samples x outputs)
+pub fn inverse_class_frequency(outputs: &Array2<f64>) -> Vec<f64> {
+    let classes: Vec<Vec<u64>> = outputs
+        .rows()
+        .into_iter()
+        .map(|row| row.iter().map(|value| value.to_bits()).collect())
+        .collect();
+
+    let mut counts: HashMap<&[u64], usize> = HashMap::new();
+    for class in &classes {
+        *counts.entry(class.as_slice()).or_insert(0) += 1;
+    }
+    classes
+        .iter()
+        .map(|class| 1.0 / counts[class.as_slice()] as f64)
+        .collect()
+}
@@ -0,0 +1,198 @@
+use super::functions::metric::Metric;
+use ndarray::{Array2, Axis};
+use rand::rngs::StdRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Default number of bootstrap resamples of the validation set
+pub const DEFAULT_BOOTSTRAP_RESAMPLES: usize = 1000;
+
+/// Default confidence level of bootstrap intervals
+pub const DEFAULT_CONFIDENCE: f64 = 0.95;
+
+/// How the validation predictions are resampled
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct BootstrapConfig {
+    /// Number of resamples the metric is computed for
+    pub resamples: usize,
+
+    /// Probability that the interval contains the metric's
+    /// true value (between 0 and 1, exclusive)
+    pub confidence: f64,
+}
+
+/// Range of plausible metric scores, estimated by bootstrapping
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct ConfidenceInterval {
+    /// Confidence level of the interval
+    pub confidence: f64,
+
+    /// Lower bound of the metric score
+    pub lower: f32,
+
+    /// Upper bound of the metric score
+    pub upper: f32,
+
+    /// Number of resamples the interval was estimated from
+    pub resamples: usize,
+}
+
+/// Estimate a confidence interval for a metric with the percentile
+/// bootstrap: the validation samples are resampled with replacement,
+/// every resample is scored with the metric, and the interval spans
+/// the middle `confidence` fraction of the scores. Small validation
+/// sets give wide intervals, which is the point
+///
+/// # Arguments
+///
+/// * `predictions` - Decoded predictions of the validation set (samples x outputs)
+/// * `outputs` - Validation output values (samples x outputs)
+/// * `metric` - Method used to score the predictions
+/// * `config` - Number of resamples and confidence level
+/// * `rng` - Random number generator the resamples are drawn with
+///
+/// Returns `None` when there are fewer than two samples, or
+/// when no resample has a finite score
+pub fn confidence_interval(
+    predictions: &Array2<f64>,
+    outputs: &Array2<f64>,
+    metric: &dyn Metric,
+    config: BootstrapConfig,
+    rng: &mut StdRng,
+) -> Option<ConfidenceInterval> {
+    let samples: usize = predictions.nrows();
+    if samples < 2 || config.resamples == 0 {
+        return None;
+    }
+
+    let mut scores: Vec<f32> = (0..config.resamples)
+        .map(|_| {
+            let rows: Vec<usize> = (0..samples).map(|_| rng.gen_range(0..samples)).collect();
+            metric.value(
+                &predictions.select(Axis(0), &rows),
+                &outputs.select(Axis(0), &rows),
+            )
+        })
+        .filter(|score| score.is_finite())
+        .collect();
+    if scores.is_empty() {
+        return None;
+    }
+    scores.sort_by(f32::total_cmp);
+
+    let tail: f64 = (1.0 - config.confidence) / 2.0;
+    let percentile =
+        |fraction: f64| -> f32 { scores[((scores.len() - 1) as f64 * fraction).round() as usize] };
+    Some(ConfidenceInterval {
+        confidence: config.confidence,
+        lower: percentile(tail),
+        upper: percentile(1.0 - tail),
+        resamples: scores.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nn::functions::metric::Accuracy;
+    use rand::SeedableRng;
+    use serde_json::Map;
+
+    /// Class predictions of 10 samples, of which the first `correct` are right
+    fn predictions(correct: usize) -> (Array2<f64>, Array2<f64>) {
+        let outputs: Array2<f64> = Array2::from_shape_fn((10, 1), |(i, _)| (i % 3) as f64);
+        let predictions: Array2<f64> =
+            Array2::from_shape_fn(
+                (10, 1),
+                |(i, _)| if i < correct { (i % 3) as f64 } else { -1.0 },
+            );
+        (predictions, outputs)
+    }
+
+    fn interval(
+        predictions: &Array2<f64>,
+        outputs: &Array2<f64>,
+        config: BootstrapConfig,
+        seed: u64,
+    ) -> Option<ConfidenceInterval> {
+        let accuracy = Accuracy::new(&Map::new());
+        let mut rng: StdRng = StdRng::seed_from_u64(seed);
+        confidence_interval(predictions, outputs, &accuracy, config, &mut rng)
+    }
+
+    const CONFIG: BootstrapConfig = BootstrapConfig {
+        resamples: 500,
+        confidence: 0.9,
+    };
+
+    #[test]
+    fn perfect_predictions_give_a_point_interval() {
+        let (predictions, outputs) = predictions(10);
+        let interval = interval(&predictions, &outputs, CONFIG, 0).unwrap();
+        assert_eq!((interval.lower, interval.upper), (1.0, 1.0));
+        assert_eq!(interval.resamples, CONFIG.resamples);
+    }
+
+    #[test]
+    fn interval_brackets_the_observed_score() {
+        let (predictions, outputs) = predictions(5);
+        let interval = interval(&predictions, &outputs, CONFIG, 0).unwrap();
+        assert!(0.0 <= interval.lower && interval.lower < 0.5);
+        assert!(0.5 < interval.upper && interval.upper <= 1.0);
+    }
+
+    #[test]
+    fn wider_confidence_gives_a_wider_interval() {
+        let (predictions, outputs) = predictions(5);
+        let narrow = interval(
+            &predictions,
+            &outputs,
+            BootstrapConfig {
+                confidence: 0.5,
+                ..CONFIG
+            },
+            0,
+        )
+        .unwrap();
+        let wide = interval(&predictions, &outputs, CONFIG, 0).unwrap();
+        assert!(wide.lower <= narrow.lower && narrow.upper <= wide.upper);
+
+        // Without any confidence, both bounds are the median score
+        let median = interval(
+            &predictions,
+            &outputs,
+            BootstrapConfig {
+                confidence: 0.0,
+                ..CONFIG
+            },
+            0,
+        )
+        .unwrap();
+        assert_eq!(median.lower, median.upper);
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_interval() {
+        let (predictions, outputs) = predictions(7);
+        let first = interval(&predictions, &outputs, CONFIG, 42).unwrap();
+        let second = interval(&predictions, &outputs, CONFIG, 42).unwrap();
+        assert_eq!((first.lower, first.upper), (second.lower, second.upper));
+    }
+
+    #[test]
+    fn too_few_samples_or_resamples_give_no_interval() {
+        let (predictions, outputs) = predictions(10);
+        let one_sample = interval(
+            &predictions.slice(ndarray::s![..1, ..]).to_owned(),
+            &outputs.slice(ndarray::s![..1, ..]).to_owned(),
+            CONFIG,
+            0,
+        );
+        assert!(one_sample.is_none());
+        let no_resamples = BootstrapConfig {
+            resamples: 0,
+            ..CONFIG
+        };
+        assert!(interval(&predictions, &outputs, no_resamples, 0).is_none());
+    }
+}
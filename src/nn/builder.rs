@@ -0,0 +1,464 @@
+use crate::error::OpenPbError;
+use crate::nn::batches::{BatchConfig, Sampler};
+use crate::nn::cancellation::CancellationToken;
+use crate::nn::data_source::{self, DataMetadata, DataSource};
+use crate::nn::functions::activation::ActivationFn;
+use crate::nn::functions::cost::{Cost, MSE};
+use crate::nn::functions::encoder::{Encoder, Identity};
+use crate::nn::functions::metric::Metric;
+use crate::nn::functions::optimizer::{Adam, Optimizer, DEFAULT_BETA1, DEFAULT_BETA2};
+use crate::nn::history::EpochLog;
+use crate::nn::initializer::Initializer;
+use crate::nn::perceptron::Perceptron;
+use crate::nn::regularization::Regularization;
+use ndarray::Array2;
+use serde_json::Map;
+
+/// Default number of epochs used by `Training`
+pub const DEFAULT_EPOCHS: usize = 1000;
+
+/// Default learning rate of the Adam optimizer used by `Training`
+pub const DEFAULT_LEARNING_RATE: f64 = 0.001;
+
+/// Function called at the end of every epoch by `Training`
+type EpochFn<'a> = dyn FnMut(&EpochLog, &Perceptron) -> bool + 'a;
+
+/// Settings of a single Layer, kept until the Network is built
+struct LayerSpec {
+    /// Number of neurons in the Layer
+    neurons: usize,
+
+    /// Function that determines the activation of individual neurons
+    activation_fn: Box<dyn ActivationFn>,
+
+    /// Optional rate for randomly excluding neurons during each training cycle
+    dropout: Option<f32>,
+
+    /// Scheme the initial weights are drawn with (picked from the activation when `None`)
+    initializer: Option<Initializer>,
+
+    /// Whether or not the activation values are batch normalized
+    batch_norm: bool,
+}
+
+/// Builder-style alternative to adding Layers to a `Perceptron` one at a
+/// time. Layers are listed in order (hidden layers first, output layer
+/// last), and the size of the first Layer's input vectors is taken from
+/// the dataset the Network is built for:
+///
+/// ```ignore
+/// let mut network = NetworkBuilder::new()
+///     .seed(42)
+///     .layer(16, ReLU)
+///     .dropout(0.1)
+///     .layer(1, Sigmoid)
+///     .build(&data)?;
+/// ```
+#[derive(Default)]
+pub struct NetworkBuilder {
+    /// Seed of the Network's random number generator (random when `None`)
+    seed: Option<u64>,
+
+    /// Settings of every Layer, in order
+    layers: Vec<LayerSpec>,
+
+    /// Rate at which input features are dropped during training
+    input_dropout: Option<f32>,
+
+    /// L1/L2 penalties applied to every Layer's weights
+    regularization: Option<Regularization>,
+
+    /// Whether or not every Layer's dropout stream is derived from its index
+    independent_dropout: bool,
+}
+
+impl NetworkBuilder {
+    pub fn new() -> NetworkBuilder {
+        NetworkBuilder::default()
+    }
+
+    /// Seed the Network's random number generator, so weight
+    /// initialization and dropout are reproducible
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - Seed of the random number generator
+    pub fn seed(mut self, seed: u64) -> NetworkBuilder {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Add a Layer after every previously added Layer
+    ///
+    /// # Arguments
+    ///
+    /// * `neurons` - Number of neurons in the Layer
+    /// * `activation_fn` - Function that determines the activation of individual neurons
+    pub fn layer(
+        mut self,
+        neurons: usize,
+        activation_fn: impl ActivationFn + 'static,
+    ) -> NetworkBuilder {
+        self.layers.push(LayerSpec {
+            neurons,
+            activation_fn: Box::new(activation_fn),
+            dropout: None,
+            initializer: None,
+            batch_norm: false,
+        });
+        self
+    }
+
+    /// Set the dropout rate of the most recently added Layer
+    ///
+    /// # Arguments
+    ///
+    /// * `rate` - Rate for randomly excluding neurons during each training cycle
+    pub fn dropout(mut self, rate: f32) -> NetworkBuilder {
+        if let Some(layer) = self.layers.last_mut() {
+            layer.dropout = Some(rate);
+        }
+        self
+    }
+
+    /// Set the scheme the initial weights of the most recently added Layer are drawn with
+    ///
+    /// # Arguments
+    ///
+    /// * `initializer` - Initialization scheme of the Layer's weights
+    pub fn initializer(mut self, initializer: Initializer) -> NetworkBuilder {
+        if let Some(layer) = self.layers.last_mut() {
+            layer.initializer = Some(initializer);
+        }
+        self
+    }
+
+    /// Batch normalize the activation values of the most recently added Layer
+    pub fn batch_norm(mut self) -> NetworkBuilder {
+        if let Some(layer) = self.layers.last_mut() {
+            layer.batch_norm = true;
+        }
+        self
+    }
+
+    /// Randomly drop input features during training
+    ///
+    /// # Arguments
+    ///
+    /// * `rate` - Rate at which input features are dropped, in [0, 1)
+    pub fn input_dropout(mut self, rate: f32) -> NetworkBuilder {
+        self.input_dropout = Some(rate);
+        self
+    }
+
+    /// Penalize large weights during training
+    ///
+    /// # Arguments
+    ///
+    /// * `regularization` - L1/L2 penalties applied to every Layer's weights
+    pub fn regularization(mut self, regularization: Regularization) -> NetworkBuilder {
+        self.regularization = Some(regularization);
+        self
+    }
+
+    /// Derive every Layer's dropout stream from the seed and the Layer's
+    /// index, so adding or removing a Layer doesn't change the others' dropout
+    pub fn independent_dropout(mut self) -> NetworkBuilder {
+        self.independent_dropout = true;
+        self
+    }
+
+    /// Build the Network, sizing the first Layer's inputs for the given dataset
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Dataset the Network will be trained on
+    pub fn build(self, data: &dyn DataSource) -> Result<Perceptron, OpenPbError> {
+        if self.layers.is_empty() {
+            return Err("A network needs at least one layer".into());
+        }
+        if self.layers.iter().any(|layer| layer.neurons == 0) {
+            return Err("Every layer needs at least one neuron".into());
+        }
+        for rate in self.layers.iter().filter_map(|layer| layer.dropout) {
+            if !(0.0..1.0).contains(&rate) {
+                return Err(format!("Dropout rate {rate} isn't in [0, 1)").into());
+            }
+        }
+
+        let metadata: DataMetadata = data.metadata();
+        let mut network: Perceptron = match self.seed {
+            Some(seed) => Perceptron::with_seed(seed),
+            None => Perceptron::new(),
+        };
+        let mut input_shape: Option<(usize, usize)> =
+            Some((metadata.input_size, metadata.train_samples));
+        for (index, layer) in self.layers.into_iter().enumerate() {
+            network.add_initialized_layer(
+                layer.neurons,
+                input_shape.take(),
+                layer.activation_fn,
+                layer.dropout,
+                layer.initializer,
+            );
+            if layer.batch_norm {
+                network.enable_batch_norm(index);
+            }
+        }
+
+        if let Some(rate) = self.input_dropout {
+            if !(0.0..1.0).contains(&rate) {
+                return Err(format!("Input dropout rate {rate} isn't in [0, 1)").into());
+            }
+            network.enable_input_dropout(rate);
+        }
+        if let Some(regularization) = self.regularization {
+            if regularization.l1 < 0.0 || regularization.l2 < 0.0 {
+                return Err("Regularization penalties can't be negative".into());
+            }
+            network.enable_regularization(regularization);
+        }
+        if self.independent_dropout {
+            network.enable_independent_dropout();
+        }
+        Ok(network)
+    }
+}
+
+/// Outcome of `Training::fit`
+#[derive(Debug, Clone)]
+pub struct TrainingSummary {
+    /// Number of epochs that were trained
+    pub epochs: usize,
+
+    /// Name of the metric
+    pub metric_label: String,
+
+    /// Metric value of the validation set
+    pub metric_value: f32,
+
+    /// Whether or not the validation set satisfied the metric
+    pub metric_passed: bool,
+}
+
+/// Builder-style alternative to `Perceptron::fit`. Everything except the
+/// dataset and the metric has a default (Adam with a learning rate of
+/// `DEFAULT_LEARNING_RATE`, mean squared error, no output encoding,
+/// `DEFAULT_EPOCHS` epochs, and full-batch training):
+///
+/// ```ignore
+/// let mut training = Training::new(&data, Accuracy::new(&Map::new()))
+///     .optimizer(SGD::new(0.1, 0.9))
+///     .epochs(500)
+///     .batch_size(32)
+///     .shuffle(true);
+/// let summary = training.fit(&mut network)?;
+/// let predictions = network.predict(&inputs, training.encoder_ref());
+/// ```
+pub struct Training<'a> {
+    /// Source of the training and validation sets
+    data: &'a dyn DataSource,
+
+    /// Early stopping and evaluation metric
+    metric: Box<dyn Metric>,
+
+    /// Method used to adjust the weights after each batch
+    optimizer: Box<dyn Optimizer>,
+
+    /// Loss function that is minimized
+    cost: Box<dyn Cost>,
+
+    /// Method for encoding the output values
+    encoder: Box<dyn Encoder>,
+
+    /// Maximum number of epochs
+    epochs: usize,
+
+    /// How the training set is split into minibatches
+    batch_config: BatchConfig,
+
+    /// Called at the end of every epoch, training stops when it returns `false`
+    on_epoch: Option<Box<EpochFn<'a>>>,
+
+    /// Token that stops training early when cancelled
+    token: Option<CancellationToken>,
+}
+
+impl<'a> Training<'a> {
+    /// # Arguments
+    ///
+    /// * `data` - Source of the training and validation sets
+    /// * `metric` - Early stopping and evaluation metric
+    pub fn new(data: &'a dyn DataSource, metric: impl Metric + 'static) -> Training<'a> {
+        Training {
+            data,
+            metric: Box::new(metric),
+            optimizer: Box::new(Adam::new(
+                DEFAULT_LEARNING_RATE,
+                DEFAULT_BETA1,
+                DEFAULT_BETA2,
+            )),
+            cost: Box::new(MSE),
+            encoder: Box::new(Identity::new(&Map::new())),
+            epochs: DEFAULT_EPOCHS,
+            batch_config: BatchConfig::default(),
+            on_epoch: None,
+            token: None,
+        }
+    }
+
+    /// # Arguments
+    ///
+    /// * `optimizer` - Method used to adjust the weights after each batch
+    pub fn optimizer(mut self, optimizer: impl Optimizer + 'static) -> Training<'a> {
+        self.optimizer = Box::new(optimizer);
+        self
+    }
+
+    /// # Arguments
+    ///
+    /// * `cost` - Loss function that is minimized
+    pub fn cost(mut self, cost: impl Cost + 'static) -> Training<'a> {
+        self.cost = Box::new(cost);
+        self
+    }
+
+    /// # Arguments
+    ///
+    /// * `encoder` - Method for encoding the output values
+    pub fn encoder(mut self, encoder: impl Encoder + 'static) -> Training<'a> {
+        self.encoder = Box::new(encoder);
+        self
+    }
+
+    /// # Arguments
+    ///
+    /// * `epochs` - Maximum number of epochs
+    pub fn epochs(mut self, epochs: usize) -> Training<'a> {
+        self.epochs = epochs;
+        self
+    }
+
+    /// # Arguments
+    ///
+    /// * `batch_size` - Maximum number of samples in each minibatch
+    pub fn batch_size(mut self, batch_size: usize) -> Training<'a> {
+        self.batch_config.batch_size = Some(batch_size);
+        self
+    }
+
+    /// # Arguments
+    ///
+    /// * `shuffle` - Whether or not samples are shuffled before being split into batches
+    pub fn shuffle(mut self, shuffle: bool) -> Training<'a> {
+        self.batch_config.shuffle = shuffle;
+        self
+    }
+
+    /// # Arguments
+    ///
+    /// * `drop_last` - Whether or not a final batch smaller than the batch size is skipped
+    pub fn drop_last(mut self, drop_last: bool) -> Training<'a> {
+        self.batch_config.drop_last = drop_last;
+        self
+    }
+
+    /// # Arguments
+    ///
+    /// * `sampler` - How samples are chosen for each epoch's batches
+    pub fn sampler(mut self, sampler: Sampler) -> Training<'a> {
+        self.batch_config.sampler = sampler;
+        self
+    }
+
+    /// Call a function at the end of every epoch
+    ///
+    /// # Arguments
+    ///
+    /// * `on_epoch` - Receives the epoch's summary and the Network,
+    ///   training stops when it returns `false`
+    pub fn on_epoch(
+        mut self,
+        on_epoch: impl FnMut(&EpochLog, &Perceptron) -> bool + 'a,
+    ) -> Training<'a> {
+        self.on_epoch = Some(Box::new(on_epoch));
+        self
+    }
+
+    /// # Arguments
+    ///
+    /// * `token` - Token that stops training at the end of the current epoch when cancelled
+    pub fn cancellation(mut self, token: CancellationToken) -> Training<'a> {
+        self.token = Some(token);
+        self
+    }
+
+    /// Encoder fit to the training outputs, used to decode the Network's predictions
+    pub fn encoder_ref(&self) -> &dyn Encoder {
+        self.encoder.as_ref()
+    }
+
+    /// Optimizer, including any state accumulated during training
+    pub fn optimizer_ref(&self) -> &dyn Optimizer {
+        self.optimizer.as_ref()
+    }
+
+    /// Train the Network, then evaluate the metric on the validation set.
+    /// Calling `fit` again continues training with the same optimizer state
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - Network built for the same dataset
+    pub fn fit(&mut self, network: &mut Perceptron) -> Result<TrainingSummary, OpenPbError> {
+        let metadata: DataMetadata = self.data.metadata();
+        if network.input_size() != metadata.input_size {
+            return Err(format!(
+                "Network expects {} inputs, but the data has {}",
+                network.input_size(),
+                metadata.input_size
+            )
+            .into());
+        }
+
+        let (validation_inputs, validation_outputs) = self.data.validation_set();
+        let output_size: usize = data_source::prepare_encoder(self.data, self.encoder.as_mut())?;
+        if network.output_size() != output_size {
+            return Err(format!(
+                "Network outputs {} values, but the encoded outputs have {output_size}",
+                network.output_size()
+            )
+            .into());
+        }
+
+        let token: Option<CancellationToken> = self.token.clone();
+        let on_epoch = &mut self.on_epoch;
+        let epochs: usize = network.fit_with_callback(
+            self.data,
+            self.optimizer.as_mut(),
+            self.metric.as_ref(),
+            self.cost.as_ref(),
+            self.encoder.as_ref(),
+            self.epochs,
+            self.batch_config,
+            &mut |epoch_log: &EpochLog, network: &Perceptron| {
+                let keep_going: bool = match on_epoch {
+                    Some(on_epoch) => on_epoch(epoch_log, network),
+                    None => true,
+                };
+                keep_going && !token.as_ref().is_some_and(CancellationToken::is_cancelled)
+            },
+        );
+        if let Some(explosion) = network.take_gradient_explosion() {
+            return Err(explosion.to_string().into());
+        }
+
+        let predicted_output: Array2<f64> =
+            network.predict(&validation_inputs, self.encoder.as_ref());
+        Ok(TrainingSummary {
+            epochs,
+            metric_label: self.metric.label().to_string(),
+            metric_value: self.metric.value(&predicted_output, &validation_outputs),
+            metric_passed: self.metric.check(&predicted_output, &validation_outputs),
+        })
+    }
+}
@@ -0,0 +1,101 @@
+use super::functions::numeric::{self, DEFAULT_EPSILON};
+use super::perceptron::Perceptron;
+use ndarray::{Array2, ArrayView1, Axis};
+use ndarray_stats::QuantileExt;
+use serde::Serialize;
+
+/// Smallest temperature considered during calibration
+const MIN_TEMPERATURE: f64 = 0.05;
+
+/// Largest temperature considered during calibration
+const MAX_TEMPERATURE: f64 = 20.0;
+
+/// Number of golden-section search iterations
+const SEARCH_ITERATIONS: usize = 60;
+
+/// Result of fitting a temperature to the validation set
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct Calibration {
+    /// Value the output layer's logits are divided by
+    pub temperature: f64,
+
+    /// Mean negative log-likelihood of the validation set before calibration
+    pub nll_before: f64,
+
+    /// Mean negative log-likelihood of the validation set after calibration
+    pub nll_after: f64,
+}
+
+/// Fit a single temperature that minimizes the negative log-likelihood of the
+/// given samples when the output layer's logits are divided by it. Meant for
+/// classification networks, where the temperature changes how confident the
+/// predicted probabilities are without changing the predicted classes
+///
+/// # Arguments
+///
+/// * `network` - Trained network (it isn't modified)
+/// * `inputs` - Matrix of input vectors (validation set)
+/// * `expected` - Encoded outputs, one row per sample (e.g. one-hot vectors)
+pub fn fit_temperature(
+    network: &Perceptron,
+    inputs: &Array2<f64>,
+    expected: &Array2<f64>,
+) -> Calibration {
+    let logits: Array2<f64> = network.predict_logits(inputs);
+    // Index of the correct class of each sample
+    let targets: Vec<usize> = expected
+        .axis_iter(Axis(0))
+        .map(|row| row.argmax().unwrap_or_default())
+        .collect();
+    let nll = |temperature: f64| -> f64 {
+        negative_log_likelihood(&network.activate_logits(&logits, temperature), &targets)
+    };
+
+    // Golden-section search over log temperature (NLL is unimodal in temperature)
+    let ratio: f64 = (5.0_f64.sqrt() - 1.0) / 2.0;
+    let (mut low, mut high): (f64, f64) = (MIN_TEMPERATURE.ln(), MAX_TEMPERATURE.ln());
+    for _ in 0..SEARCH_ITERATIONS {
+        let left: f64 = high - ratio * (high - low);
+        let right: f64 = low + ratio * (high - low);
+        if nll(left.exp()) < nll(right.exp()) {
+            high = right;
+        } else {
+            low = left;
+        }
+    }
+    let mut temperature: f64 = ((low + high) / 2.0).exp();
+
+    // Scale-invariant outputs (e.g. normalized ReLU outputs) can't be
+    // improved by a temperature, so they're left unscaled
+    let nll_before: f64 = nll(1.0);
+    let mut nll_after: f64 = nll(temperature);
+    if nll_after.is_nan() || nll_after >= nll_before {
+        temperature = 1.0;
+        nll_after = nll_before;
+    }
+
+    Calibration {
+        temperature,
+        nll_before,
+        nll_after,
+    }
+}
+
+/// Mean negative log-likelihood of the correct classes, where each
+/// sample's outputs are normalized to sum to 1
+///
+/// # Arguments
+///
+/// * `outputs` - Network output vectors, one per column
+/// * `targets` - Index of the correct class of each sample
+fn negative_log_likelihood(outputs: &Array2<f64>, targets: &[usize]) -> f64 {
+    let total: f64 = outputs
+        .axis_iter(Axis(1))
+        .zip(targets.iter())
+        .map(|(sample, &target): (ArrayView1<f64>, &usize)| {
+            let probability: f64 = sample[target] / sample.sum().max(DEFAULT_EPSILON);
+            -numeric::safe_ln(probability, DEFAULT_EPSILON)
+        })
+        .sum();
+    total / targets.len().max(1) as f64
+}
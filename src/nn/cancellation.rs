@@ -0,0 +1,27 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Shared flag that asks training to stop at the end of the current
+/// epoch. Clones share the same flag, so one can be kept by a GUI or
+/// server while another is handed to the training code
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    /// Whether or not cancellation has been requested
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    /// Ask every training loop holding this token (or a clone of it) to stop
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether or not cancellation has been requested
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
@@ -0,0 +1,62 @@
+use ndarray::Array2;
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+/// Largest number of distinct classes a confusion matrix is computed for
+pub const MAX_CONFUSION_CLASSES: usize = 256;
+
+/// Counts of each (actual, predicted) pair of class labels
+#[derive(Serialize, Debug, Clone)]
+pub struct ConfusionMatrix {
+    /// Every class label seen in the actual or predicted values, in ascending order
+    pub labels: Vec<i64>,
+
+    /// Number of samples of each actual class (row) that were
+    /// predicted as each class (column), in the order of `labels`
+    pub counts: Array2<u64>,
+}
+
+impl ConfusionMatrix {
+    /// Compare decoded predictions to the expected values. Only single-column
+    /// outputs made of whole numbers (class labels) are compared, so `None`
+    /// is returned for regression outputs, multi-column outputs, or more
+    /// than `MAX_CONFUSION_CLASSES` distinct labels
+    ///
+    /// # Arguments
+    ///
+    /// * `predicted` - Decoded predictions, one row per sample
+    /// * `expected` - Expected (decoded) outputs, one row per sample
+    pub fn new(predicted: &Array2<f64>, expected: &Array2<f64>) -> Option<ConfusionMatrix> {
+        if predicted.ncols() != 1 || expected.ncols() != 1 || predicted.nrows() != expected.nrows()
+        {
+            return None;
+        }
+        if predicted.is_empty()
+            || predicted
+                .iter()
+                .chain(expected.iter())
+                .any(|value| !value.is_finite() || value.fract() != 0.0)
+        {
+            return None;
+        }
+
+        let labels: BTreeSet<i64> = predicted
+            .iter()
+            .chain(expected.iter())
+            .map(|&value| value as i64)
+            .collect();
+        if labels.len() > MAX_CONFUSION_CLASSES {
+            return None;
+        }
+        let labels: Vec<i64> = labels.into_iter().collect();
+
+        let mut counts: Array2<u64> = Array2::zeros((labels.len(), labels.len()));
+        for (&actual, &prediction) in expected.iter().zip(predicted.iter()) {
+            // Every label is in the list, so the searches can't fail
+            let row: usize = labels.binary_search(&(actual as i64)).unwrap();
+            let col: usize = labels.binary_search(&(prediction as i64)).unwrap();
+            counts[[row, col]] += 1;
+        }
+        Some(ConfusionMatrix { labels, counts })
+    }
+}
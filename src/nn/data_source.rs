@@ -0,0 +1,116 @@
+use super::functions::encoder::Encoder;
+use crate::error::OpenPbError;
+use ndarray::{s, Array2};
+use serde::Serialize;
+
+/// Shape information about a dataset
+#[derive(Serialize, Debug, Clone)]
+pub struct DataMetadata {
+    /// Number of samples in the training set
+    pub train_samples: usize,
+
+    /// Number of samples in the validation set
+    pub validation_samples: usize,
+
+    /// Number of samples in the hold-out test set
+    pub test_samples: usize,
+
+    /// Size of each input vector
+    pub input_size: usize,
+
+    /// Number of columns in each (unencoded) output vector
+    pub output_size: usize,
+}
+
+/// Source of training and validation data for a Network. Sets are
+/// returned in the layout used by `Perceptron::fit`: input vectors are
+/// stored as columns (features x samples), and unencoded output values
+/// are stored as rows (samples x outputs)
+pub trait DataSource: Sync + Send {
+    /// Shape information about the dataset
+    fn metadata(&self) -> DataMetadata;
+
+    /// Input and output vectors used to train the Network
+    fn training_set(&self) -> (Array2<f64>, Array2<f64>);
+
+    /// Input and output vectors used to validate the Network
+    fn validation_set(&self) -> (Array2<f64>, Array2<f64>);
+
+    /// Hold-out test vectors, evaluated exactly once after training
+    /// so they never influence early stopping (`None` when absent)
+    fn test_set(&self) -> Option<(Array2<f64>, Array2<f64>)> {
+        None
+    }
+
+    /// Relative sampling weight of each training sample, used by the
+    /// weighted minibatch sampler (`None` when the data has no weights)
+    fn sample_weights(&self) -> Option<Vec<f64>> {
+        None
+    }
+
+    /// Whether or not the training outputs are soft targets: probability
+    /// distributions already in the Network's output format (e.g. from
+    /// distillation or label smoothing), which skip the encoder. Validation
+    /// and test outputs are always human-readable values (e.g. class labels)
+    fn soft_targets(&self) -> bool {
+        false
+    }
+}
+
+/// Check that an encoder can represent a data source's outputs, then
+/// fit it to the training outputs. Soft targets are never encoded, so
+/// they're only checked against the width of the encoded validation outputs
+///
+/// # Arguments
+///
+/// * `data` - Source of training and validation data
+/// * `encoder` - Transforms output values to/from the Network's output format
+///
+/// Returns the size of each encoded output vector, which
+/// the Network's output layer must have as many neurons as
+pub fn prepare_encoder(
+    data: &dyn DataSource,
+    encoder: &mut dyn Encoder,
+) -> Result<usize, OpenPbError> {
+    let (_, training_outputs) = data.training_set();
+    let (_, validation_outputs) = data.validation_set();
+    encoder.check("validation outputs", &validation_outputs)?;
+    if !data.soft_targets() {
+        encoder.check("training outputs", &training_outputs)?;
+        // Encoders with state (e.g. target scaling) are fit to the training outputs
+        encoder.fit(&training_outputs);
+    }
+
+    // A single sample is enough to find the encoded width
+    let first_row: usize = validation_outputs.nrows().min(1);
+    let encoded_cols: usize = encoder
+        .encode(&validation_outputs.slice(s![..first_row, ..]).to_owned())
+        .ncols();
+    if data.soft_targets() && training_outputs.ncols() != encoded_cols {
+        return Err(format!(
+            "Soft targets have {} columns, but the encoder outputs {encoded_cols}",
+            training_outputs.ncols()
+        )
+        .into());
+    }
+    Ok(encoded_cols)
+}
+
+/// Expected Network output for every training sample, stored as
+/// columns (outputs x samples). Soft targets are used as they are
+///
+/// # Arguments
+///
+/// * `data` - Source of the training outputs
+/// * `encoder` - Transforms output values to the Network's output format
+/// * `training_outputs` - Training outputs of `data` (samples x outputs)
+pub fn expected_outputs(
+    data: &dyn DataSource,
+    encoder: &dyn Encoder,
+    training_outputs: &Array2<f64>,
+) -> Array2<f64> {
+    if data.soft_targets() {
+        return training_outputs.t().to_owned();
+    }
+    encoder.encode(training_outputs).t().to_owned()
+}
@@ -0,0 +1,163 @@
+use super::perceptron::{Perceptron, VALIDATION_CHUNK_SIZE};
+use crate::error::OpenPbError;
+use ndarray::{Array2, Axis};
+use serde::Serialize;
+
+/// Fraction of each feature's range added on both sides of the grid,
+/// so the boundaries around the outermost samples are visible
+pub const BOUNDARY_MARGIN: f64 = 0.1;
+
+/// Prediction of the network at a single grid point
+#[derive(Serialize, Debug, Clone)]
+pub struct GridPoint {
+    /// Value of the first input feature
+    pub x: f64,
+
+    /// Value of the second input feature
+    pub y: f64,
+
+    /// Decoded prediction
+    pub prediction: Vec<f64>,
+
+    /// Undecoded output layer values
+    pub outputs: Vec<f64>,
+}
+
+/// Predictions of a network over a regular grid covering the
+/// input range of a dataset with two input features
+#[derive(Serialize, Debug, Clone)]
+pub struct DecisionBoundary {
+    /// Smallest and largest value of the first input feature
+    pub x_range: (f64, f64),
+
+    /// Smallest and largest value of the second input feature
+    pub y_range: (f64, f64),
+
+    /// Number of grid points along each axis
+    pub resolution: usize,
+
+    /// Every grid point, row by row (`y` changes slowest)
+    pub points: Vec<GridPoint>,
+}
+
+impl DecisionBoundary {
+    /// Grid points as CSV, with a header row. Each prediction and
+    /// output value gets its own column (`prediction_0`, `output_0`, ...)
+    pub fn to_csv(&self) -> String {
+        let predictions: usize = self
+            .points
+            .first()
+            .map_or(0, |point| point.prediction.len());
+        let outputs: usize = self.points.first().map_or(0, |point| point.outputs.len());
+
+        let mut header: Vec<String> = vec!["x".to_string(), "y".to_string()];
+        header.extend((0..predictions).map(|i| format!("prediction_{i}")));
+        header.extend((0..outputs).map(|i| format!("output_{i}")));
+
+        let mut lines: Vec<String> = vec![header.join(",")];
+        for point in &self.points {
+            let cells: Vec<String> = [point.x, point.y]
+                .iter()
+                .chain(&point.prediction)
+                .chain(&point.outputs)
+                .map(f64::to_string)
+                .collect();
+            lines.push(cells.join(","));
+        }
+        lines.join("\n") + "\n"
+    }
+}
+
+/// Smallest and largest value of a feature, widened by
+/// `BOUNDARY_MARGIN` of the range on both sides
+///
+/// # Arguments
+///
+/// * `values` - Every value of the feature
+fn padded_range<'a>(values: impl Iterator<Item = &'a f64>) -> (f64, f64) {
+    let (min, max) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &value| {
+        (min.min(value), max.max(value))
+    });
+    // Constant features still get a grid of width 1
+    let margin: f64 = if max > min {
+        (max - min) * BOUNDARY_MARGIN
+    } else {
+        0.5
+    };
+    (min - margin, max + margin)
+}
+
+/// Evenly spaced values from `range.0` to `range.1` (inclusive)
+///
+/// # Arguments
+///
+/// * `range` - Smallest and largest value
+/// * `resolution` - Number of values
+fn linspace(range: (f64, f64), resolution: usize) -> Vec<f64> {
+    let step: f64 = (range.1 - range.0) / (resolution - 1) as f64;
+    (0..resolution).map(|i| range.0 + step * i as f64).collect()
+}
+
+/// Predict every point of a `resolution x resolution` grid that covers
+/// the range of the given input vectors
+///
+/// # Arguments
+///
+/// * `network` - Trained network with two inputs
+/// * `inputs` - Input vectors whose range the grid covers (2 x samples)
+/// * `resolution` - Number of grid points along each axis (at least 2)
+/// * `decode` - Converts undecoded outputs (outputs x samples) into
+///   predictions (samples x values)
+pub fn decision_boundary(
+    network: &Perceptron,
+    inputs: &Array2<f64>,
+    resolution: usize,
+    decode: &dyn Fn(&Array2<f64>) -> Array2<f64>,
+) -> Result<DecisionBoundary, OpenPbError> {
+    if inputs.nrows() != 2 || network.input_size() != 2 {
+        return Err(format!(
+            "Decision boundaries need exactly 2 input features (the data has {}, the model expects {})",
+            inputs.nrows(),
+            network.input_size()
+        ).into());
+    }
+    if inputs.ncols() == 0 {
+        return Err("Data has no input vectors to cover".into());
+    }
+    if resolution < 2 {
+        return Err("Grid resolution must be at least 2".into());
+    }
+
+    let x_range: (f64, f64) = padded_range(inputs.row(0).iter());
+    let y_range: (f64, f64) = padded_range(inputs.row(1).iter());
+    let xs: Vec<f64> = linspace(x_range, resolution);
+    let ys: Vec<f64> = linspace(y_range, resolution);
+
+    // One column per grid point, row by row
+    let grid: Array2<f64> =
+        Array2::from_shape_fn((2, resolution * resolution), |(row, col)| match row {
+            0 => xs[col % resolution],
+            _ => ys[col / resolution],
+        });
+
+    let outputs: Array2<f64> = network.predict_chunked(&grid, VALIDATION_CHUNK_SIZE);
+    let predictions: Array2<f64> = decode(&outputs);
+    let points: Vec<GridPoint> = grid
+        .axis_iter(Axis(1))
+        .zip(predictions.axis_iter(Axis(0)))
+        .zip(outputs.axis_iter(Axis(1)))
+        .map(|((point, prediction), outputs)| GridPoint {
+            x: point[0],
+            y: point[1],
+            prediction: prediction.to_vec(),
+            outputs: outputs.to_vec(),
+        })
+        .collect();
+
+    Ok(DecisionBoundary {
+        x_range,
+        y_range,
+        resolution,
+        points,
+    })
+}
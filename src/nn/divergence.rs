@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Default number of previous epochs the training loss is compared against
+pub const DEFAULT_DIVERGENCE_WINDOW: usize = 5;
+
+/// Options for stopping runs whose training loss explodes
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct DivergenceConfig {
+    /// Largest factor the training loss can grow by over
+    /// the window before the run is considered diverged
+    pub factor: f64,
+
+    /// Number of previous epochs the training loss is compared against
+    #[serde(default = "default_window")]
+    pub window: usize,
+}
+
+/// Default number of previous epochs in the window
+fn default_window() -> usize {
+    DEFAULT_DIVERGENCE_WINDOW
+}
+
+/// Epoch whose training loss grew by more than the configured
+/// factor, at which point the run's training was stopped
+#[derive(Serialize, Debug, Clone)]
+pub struct Divergence {
+    /// Epoch training was stopped at (starting at 1)
+    pub epoch: usize,
+
+    /// Mean training loss of the epoch
+    pub loss: f64,
+
+    /// Lowest mean training loss of the previous epochs within the window
+    pub baseline_loss: f64,
+
+    /// Configured growth factor
+    pub factor: f64,
+
+    /// Configured number of previous epochs
+    pub window: usize,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Training loss {:.4e} at epoch {} grew by more than a factor of {} over the \
+             previous {} epochs (lowest {:.4e}); training stopped as diverged",
+            self.loss, self.epoch, self.factor, self.window, self.baseline_loss
+        )
+    }
+}
+
+/// Training loss of the latest epochs, and the epoch
+/// at which the run was found to diverge (if any)
+#[derive(Debug, Clone)]
+pub struct DivergenceGuard {
+    /// Growth factor and window size
+    config: DivergenceConfig,
+
+    /// Mean training loss of each epoch within the window (oldest first)
+    losses: VecDeque<f64>,
+
+    /// Epoch whose loss exceeded the allowed growth
+    divergence: Option<Divergence>,
+}
+
+impl DivergenceGuard {
+    /// # Arguments
+    ///
+    /// * `config` - Growth factor and window size
+    pub fn new(config: DivergenceConfig) -> Self {
+        Self {
+            config,
+            losses: VecDeque::with_capacity(config.window.max(1)),
+            divergence: None,
+        }
+    }
+
+    /// Record the mean training loss of an epoch
+    ///
+    /// # Arguments
+    ///
+    /// * `epoch` - Epoch that has just finished (starting at 1)
+    /// * `loss` - Mean training loss of the epoch
+    ///
+    /// Returns whether or not training has diverged
+    pub fn record(&mut self, epoch: usize, loss: f64) -> bool {
+        let baseline_loss: f64 = self.losses.iter().cloned().fold(f64::INFINITY, f64::min);
+
+        // Losses that overflowed always count as diverged
+        if !loss.is_finite()
+            || (baseline_loss.is_finite() && loss > baseline_loss * self.config.factor)
+        {
+            self.divergence = Some(Divergence {
+                epoch,
+                loss,
+                baseline_loss,
+                factor: self.config.factor,
+                window: self.config.window,
+            });
+            return true;
+        }
+
+        if self.losses.len() >= self.config.window.max(1) {
+            self.losses.pop_front();
+        }
+        self.losses.push_back(loss);
+        false
+    }
+
+    /// Forget the losses of a previous call to `fit`
+    pub fn reset(&mut self) {
+        self.losses.clear();
+    }
+
+    /// Remove and return the epoch at which training diverged (if any)
+    pub fn take_divergence(&mut self) -> Option<Divergence> {
+        self.divergence.take()
+    }
+}
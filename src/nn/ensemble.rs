@@ -0,0 +1,42 @@
+use ndarray::{Array2, Zip};
+
+/// Combine decoded predictions by majority vote. Ties go
+/// to the value predicted by the earliest member
+///
+/// # Arguments
+///
+/// * `predictions` - Decoded prediction of each member (all the same shape)
+pub fn vote(predictions: &[Array2<f64>]) -> Array2<f64> {
+    let mut voted: Array2<f64> = predictions[0].clone();
+    for ((row, col), value) in voted.indexed_iter_mut() {
+        // Number of votes for each distinct predicted value
+        let mut counts: Vec<(f64, usize)> = vec![];
+        for prediction in predictions {
+            let candidate: f64 = prediction[[row, col]];
+            match counts.iter_mut().find(|(v, _)| *v == candidate) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((candidate, 1)),
+            }
+        }
+        // Earliest value wins ties since `max_by_key` keeps the last maximum
+        if let Some((winner, _)) = counts.iter().rev().max_by_key(|(_, count)| *count) {
+            *value = *winner;
+        }
+    }
+    voted
+}
+
+/// Combine raw network outputs by taking their element-wise mean
+///
+/// # Arguments
+///
+/// * `outputs` - Raw output of each member (all the same shape)
+pub fn average(outputs: &[Array2<f64>]) -> Array2<f64> {
+    let mut sum: Array2<f64> = Array2::zeros(outputs[0].raw_dim());
+    for output in outputs {
+        Zip::from(&mut sum)
+            .and(output)
+            .for_each(|sum, &x| *sum += x);
+    }
+    sum / outputs.len() as f64
+}
@@ -1,9 +1,13 @@
+use super::numeric;
 use crate::dyn_clone;
-use ndarray::{Array1, Array2, Axis};
+use ndarray::{Array2, Axis, Zip};
 
 /// Neuron activation function used for feed forward
 /// and backprop methods in Network training
 pub trait ActivationFn: DynClone + Sync + Send {
+    /// Name of the activation function, as written in the network JSON
+    fn name(&self) -> &str;
+
     /// Call the activation function with a set of inputs
     ///
     /// # Arguments
@@ -17,6 +21,60 @@ pub trait ActivationFn: DynClone + Sync + Send {
     ///
     /// * `x` - Row vector of input values
     fn prime(&self, x: &Array2<f64>) -> Array2<f64>;
+
+    /// Same as `call`, but the result is written into a
+    /// preallocated buffer of the same shape as `x`
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - Row vector of input values
+    /// * `out` - Buffer that receives the activated values
+    fn call_into(&self, x: &Array2<f64>, out: &mut Array2<f64>) {
+        out.assign(&self.call(x));
+    }
+
+    /// Same as `prime`, but the result is written into a
+    /// preallocated buffer of the same shape as `x`
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - Row vector of input values
+    /// * `out` - Buffer that receives the derivative values
+    fn prime_into(&self, x: &Array2<f64>, out: &mut Array2<f64>) {
+        out.assign(&self.prime(x));
+    }
+
+    /// Backpropagate a gradient with respect to the function's outputs
+    /// to its inputs, in place. Element-wise functions multiply it by
+    /// `prime`, functions where each output depends on every input of
+    /// the same vector (e.g. softmax) apply their full Jacobian
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - Row vector of input values
+    /// * `gradient` - Gradient with respect to the outputs, replaced
+    ///   by the gradient with respect to `x`
+    /// * `primes` - Buffer of the same shape as `x` for intermediate values
+    fn back_prop_into(
+        &self,
+        x: &Array2<f64>,
+        gradient: &mut Array2<f64>,
+        primes: &mut Array2<f64>,
+    ) {
+        self.prime_into(x, primes);
+        *gradient *= &*primes;
+    }
+
+    /// Shape parameter of the activation function (e.g. ELU's alpha), saved
+    /// alongside its name so trained layers can be reconstructed
+    fn alpha(&self) -> Option<f64> {
+        None
+    }
+}
+
+/// Apply an element-wise function to `x`, writing the results into `out`
+fn map_into(x: &Array2<f64>, out: &mut Array2<f64>, f: impl Fn(f64) -> f64) {
+    Zip::from(out).and(x).for_each(|out, &x| *out = f(x));
 }
 dyn_clone!(ActivationFn);
 
@@ -28,16 +86,30 @@ fn __sigmoid(x: f64) -> f64 {
     1.0 / (1.0 + f64::exp(-x))
 }
 
+fn __sigmoid_prime(x: f64) -> f64 {
+    let sig: f64 = __sigmoid(x);
+    sig * (1.0 - sig)
+}
+
 impl ActivationFn for Sigmoid {
+    fn name(&self) -> &str {
+        "sigmoid"
+    }
+
     fn call(&self, x: &Array2<f64>) -> Array2<f64> {
-        x.mapv(|x| __sigmoid(x))
+        x.mapv(__sigmoid)
     }
 
     fn prime(&self, x: &Array2<f64>) -> Array2<f64> {
-        x.mapv(|x| {
-            let sig: f64 = __sigmoid(x);
-            sig * (1.0 - sig)
-        })
+        x.mapv(__sigmoid_prime)
+    }
+
+    fn call_into(&self, x: &Array2<f64>, out: &mut Array2<f64>) {
+        map_into(x, out, __sigmoid);
+    }
+
+    fn prime_into(&self, x: &Array2<f64>, out: &mut Array2<f64>) {
+        map_into(x, out, __sigmoid_prime);
     }
 }
 
@@ -46,6 +118,10 @@ impl ActivationFn for Sigmoid {
 pub struct ReLU;
 
 impl ActivationFn for ReLU {
+    fn name(&self) -> &str {
+        "relu"
+    }
+
     fn call(&self, x: &Array2<f64>) -> Array2<f64> {
         x.mapv(|x| if x > 0.0 { x } else { 0.0 })
     }
@@ -53,6 +129,14 @@ impl ActivationFn for ReLU {
     fn prime(&self, x: &Array2<f64>) -> Array2<f64> {
         x.mapv(|x| if x > 0.0 { 1.0 } else { 0.0 })
     }
+
+    fn call_into(&self, x: &Array2<f64>, out: &mut Array2<f64>) {
+        map_into(x, out, |x| if x > 0.0 { x } else { 0.0 });
+    }
+
+    fn prime_into(&self, x: &Array2<f64>, out: &mut Array2<f64>) {
+        map_into(x, out, |x| if x > 0.0 { 1.0 } else { 0.0 });
+    }
 }
 
 /// "Leaky" Rectified Linear Unit activation function
@@ -60,6 +144,10 @@ impl ActivationFn for ReLU {
 pub struct LeakyReLU;
 
 impl ActivationFn for LeakyReLU {
+    fn name(&self) -> &str {
+        "leaky_relu"
+    }
+
     fn call(&self, x: &Array2<f64>) -> Array2<f64> {
         x.mapv(|x| if x > 0.0 { x } else { 0.01 * x })
     }
@@ -67,6 +155,165 @@ impl ActivationFn for LeakyReLU {
     fn prime(&self, x: &Array2<f64>) -> Array2<f64> {
         x.mapv(|x| if x > 0.0 { 1.0 } else { 0.01 })
     }
+
+    fn call_into(&self, x: &Array2<f64>, out: &mut Array2<f64>) {
+        map_into(x, out, |x| if x > 0.0 { x } else { 0.01 * x });
+    }
+
+    fn prime_into(&self, x: &Array2<f64>, out: &mut Array2<f64>) {
+        map_into(x, out, |x| if x > 0.0 { 1.0 } else { 0.01 });
+    }
+}
+
+/// Linear (identity) activation function, used
+/// for unbounded regression outputs
+#[derive(Clone)]
+pub struct Linear;
+
+impl ActivationFn for Linear {
+    fn name(&self) -> &str {
+        "linear"
+    }
+
+    fn call(&self, x: &Array2<f64>) -> Array2<f64> {
+        x.to_owned()
+    }
+
+    fn prime(&self, x: &Array2<f64>) -> Array2<f64> {
+        Array2::ones(x.raw_dim())
+    }
+
+    fn call_into(&self, x: &Array2<f64>, out: &mut Array2<f64>) {
+        out.assign(x);
+    }
+
+    fn prime_into(&self, _x: &Array2<f64>, out: &mut Array2<f64>) {
+        out.fill(1.0);
+    }
+}
+
+/// Hyperbolic tangent activation function
+#[derive(Clone)]
+pub struct Tanh;
+
+fn __tanh_prime(x: f64) -> f64 {
+    let tanh: f64 = x.tanh();
+    1.0 - tanh * tanh
+}
+
+impl ActivationFn for Tanh {
+    fn name(&self) -> &str {
+        "tanh"
+    }
+
+    fn call(&self, x: &Array2<f64>) -> Array2<f64> {
+        x.mapv(f64::tanh)
+    }
+
+    fn prime(&self, x: &Array2<f64>) -> Array2<f64> {
+        x.mapv(__tanh_prime)
+    }
+
+    fn call_into(&self, x: &Array2<f64>, out: &mut Array2<f64>) {
+        map_into(x, out, f64::tanh);
+    }
+
+    fn prime_into(&self, x: &Array2<f64>, out: &mut Array2<f64>) {
+        map_into(x, out, __tanh_prime);
+    }
+}
+
+/// Exponential Linear Unit activation function
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone)]
+pub struct ELU {
+    /// Value that negative inputs saturate to (as `-alpha`)
+    pub alpha: f64,
+}
+
+impl Default for ELU {
+    fn default() -> Self {
+        Self { alpha: 1.0 }
+    }
+}
+
+impl ELU {
+    fn elu(&self, x: f64) -> f64 {
+        if x > 0.0 {
+            x
+        } else {
+            self.alpha * x.exp_m1()
+        }
+    }
+
+    fn elu_prime(&self, x: f64) -> f64 {
+        if x > 0.0 {
+            1.0
+        } else {
+            self.alpha * x.exp()
+        }
+    }
+}
+
+impl ActivationFn for ELU {
+    fn name(&self) -> &str {
+        "elu"
+    }
+
+    fn call(&self, x: &Array2<f64>) -> Array2<f64> {
+        x.mapv(|x| self.elu(x))
+    }
+
+    fn prime(&self, x: &Array2<f64>) -> Array2<f64> {
+        x.mapv(|x| self.elu_prime(x))
+    }
+
+    fn call_into(&self, x: &Array2<f64>, out: &mut Array2<f64>) {
+        map_into(x, out, |x| self.elu(x));
+    }
+
+    fn prime_into(&self, x: &Array2<f64>, out: &mut Array2<f64>) {
+        map_into(x, out, |x| self.elu_prime(x));
+    }
+
+    fn alpha(&self) -> Option<f64> {
+        Some(self.alpha)
+    }
+}
+
+/// Swish (or SiLU, Sigmoid Linear Unit) activation function
+#[derive(Clone)]
+pub struct Swish;
+
+fn __swish(x: f64) -> f64 {
+    x * __sigmoid(x)
+}
+
+fn __swish_prime(x: f64) -> f64 {
+    let sig: f64 = __sigmoid(x);
+    sig + x * sig * (1.0 - sig)
+}
+
+impl ActivationFn for Swish {
+    fn name(&self) -> &str {
+        "swish"
+    }
+
+    fn call(&self, x: &Array2<f64>) -> Array2<f64> {
+        x.mapv(__swish)
+    }
+
+    fn prime(&self, x: &Array2<f64>) -> Array2<f64> {
+        x.mapv(__swish_prime)
+    }
+
+    fn call_into(&self, x: &Array2<f64>, out: &mut Array2<f64>) {
+        map_into(x, out, __swish);
+    }
+
+    fn prime_into(&self, x: &Array2<f64>, out: &mut Array2<f64>) {
+        map_into(x, out, __swish_prime);
+    }
 }
 
 /// Softmax activation function
@@ -74,16 +321,109 @@ impl ActivationFn for LeakyReLU {
 pub struct Softmax;
 
 impl ActivationFn for Softmax {
+    fn name(&self) -> &str {
+        "softmax"
+    }
+
     fn call(&self, x: &Array2<f64>) -> Array2<f64> {
-        let a: Array2<f64> = x.mapv(|a| a.exp());
-        let sum: Array1<f64> = a.sum_axis(Axis(0));
-        a / sum
+        numeric::stable_softmax(x)
     }
 
     fn prime(&self, x: &Array2<f64>) -> Array2<f64> {
+        // Only the diagonal of each sample's Jacobian (s_i - s_i * s_i),
+        // backprop goes through `back_prop_into` for the full Jacobian
         let sm: Array2<f64> = self.call(x);
-        let si_sj: Array2<f64> = -&sm * &sm;
-        let diag: Array1<f64> = sm.diag().to_owned();
-        diag + si_sj
+        &sm - &(&sm * &sm)
+    }
+
+    fn back_prop_into(
+        &self,
+        x: &Array2<f64>,
+        gradient: &mut Array2<f64>,
+        primes: &mut Array2<f64>,
+    ) {
+        // Jacobian-vector product of each sample: s_i * (g_i - sum_j(g_j * s_j))
+        self.call_into(x, primes);
+        let weighted: Array2<f64> = (&*gradient * &*primes)
+            .sum_axis(Axis(0))
+            .insert_axis(Axis(0));
+        *gradient -= &weighted;
+        *gradient *= &*primes;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    /// Gradient of `sum(upstream * f(x))` with respect to `x`, by central differences
+    fn numeric_gradient(
+        activation_fn: &dyn ActivationFn,
+        x: &Array2<f64>,
+        upstream: &Array2<f64>,
+    ) -> Array2<f64> {
+        let h: f64 = 1e-6;
+        let mut gradient: Array2<f64> = Array2::zeros(x.dim());
+        for ((i, j), value) in gradient.indexed_iter_mut() {
+            let (mut plus, mut minus) = (x.clone(), x.clone());
+            plus[[i, j]] += h;
+            minus[[i, j]] -= h;
+            *value = ((&activation_fn.call(&plus) * upstream).sum()
+                - (&activation_fn.call(&minus) * upstream).sum())
+                / (2.0 * h);
+        }
+        gradient
+    }
+
+    /// Check `back_prop_into` against finite differences, with 3 samples
+    /// of 3 values that are away from any kinks (e.g. ReLU's at zero)
+    fn assert_back_prop_matches(activation_fn: &dyn ActivationFn) {
+        let x: Array2<f64> = array![[0.5, -1.0, 2.0], [1.5, 0.2, -0.3], [-0.7, 0.9, 0.1]];
+        let upstream: Array2<f64> = array![[1.0, -0.5, 0.3], [0.2, 0.8, -1.2], [-0.4, 0.6, 0.9]];
+        let mut gradient: Array2<f64> = upstream.clone();
+        let mut primes: Array2<f64> = Array2::zeros(x.dim());
+        activation_fn.back_prop_into(&x, &mut gradient, &mut primes);
+
+        let expected: Array2<f64> = numeric_gradient(activation_fn, &x, &upstream);
+        for ((index, analytic), numeric) in gradient.indexed_iter().zip(expected.iter()) {
+            assert!(
+                (analytic - numeric).abs() < 1e-6,
+                "{} at {:?}: backprop gave {}, finite differences gave {}",
+                activation_fn.name(),
+                index,
+                analytic,
+                numeric
+            );
+        }
+    }
+
+    #[test]
+    fn softmax_back_prop_applies_the_full_jacobian() {
+        assert_back_prop_matches(&Softmax);
+    }
+
+    #[test]
+    fn element_wise_back_prop_matches_finite_differences() {
+        let activation_fns: Vec<Box<dyn ActivationFn>> = vec![
+            Box::new(Sigmoid),
+            Box::new(ReLU),
+            Box::new(Tanh),
+            Box::new(ELU::default()),
+            Box::new(Swish),
+            Box::new(Linear),
+        ];
+        for activation_fn in &activation_fns {
+            assert_back_prop_matches(activation_fn.as_ref());
+        }
+    }
+
+    #[test]
+    fn softmax_outputs_are_distributions() {
+        let x: Array2<f64> = array![[1000.0, -3.0], [1001.0, 0.0], [999.0, 3.0]];
+        for column in Softmax.call(&x).columns() {
+            assert!((column.sum() - 1.0).abs() < 1e-12);
+            assert!(column.iter().all(|p| p.is_finite() && *p >= 0.0));
+        }
     }
 }
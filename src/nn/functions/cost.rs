@@ -1,8 +1,17 @@
+use super::numeric;
 use crate::dyn_clone;
-use ndarray::Array2;
+use ndarray::{Array2, Zip};
 
 /// Cost or loss function to determine the Network's error
 pub trait Cost: DynClone + Sync + Send {
+    /// Mean loss over every sample
+    ///
+    /// # Arguments
+    ///
+    /// * `actual` - Actual values
+    /// * `expected` - Expected values
+    fn call(&self, actual: &Array2<f64>, expected: &Array2<f64>) -> f64;
+
     /// First derivative of the cost function. Used in Network backprop
     ///
     /// # Arguments
@@ -10,15 +19,212 @@ pub trait Cost: DynClone + Sync + Send {
     /// * `actual` - Actual values
     /// * `expected` - Expected values
     fn prime(&self, actual: &Array2<f64>, expected: &Array2<f64>) -> Array2<f64>;
+
+    /// Derivative of the cost with respect to the output layer's logits,
+    /// for output activations where the combined derivative is simpler
+    /// (and more stable) than `prime` times the activation's derivative.
+    /// `None` when there's no combined form, so `prime` is used instead
+    ///
+    /// # Arguments
+    ///
+    /// * `actual` - Actual values
+    /// * `expected` - Expected values
+    /// * `activation` - Name of the output layer's activation function
+    fn logit_delta(
+        &self,
+        _actual: &Array2<f64>,
+        _expected: &Array2<f64>,
+        _activation: &str,
+    ) -> Option<Array2<f64>> {
+        None
+    }
 }
 dyn_clone!(Cost);
 
 /// Mean Squared Error loss function
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Clone)]
 pub struct MSE;
 
 impl Cost for MSE {
+    fn call(&self, actual: &Array2<f64>, expected: &Array2<f64>) -> f64 {
+        (actual - expected)
+            .mapv(|error| error * error)
+            .mean()
+            .unwrap_or_default()
+    }
+
     fn prime(&self, actual: &Array2<f64>, expected: &Array2<f64>) -> Array2<f64> {
         actual - expected
     }
 }
+
+/// Cross-entropy loss function for classification. Outputs are treated as
+/// probabilities: categorical cross-entropy when each output vector is a
+/// probability distribution (softmax outputs), otherwise binary
+/// cross-entropy of each output neuron (e.g. sigmoid outputs)
+#[derive(Clone)]
+pub struct CrossEntropy {
+    /// Distance from 0 and 1 that probabilities are clamped to before taking a log
+    pub epsilon: f64,
+
+    /// Whether or not each output vector is a probability distribution
+    pub categorical: bool,
+}
+
+impl Default for CrossEntropy {
+    fn default() -> Self {
+        Self {
+            epsilon: numeric::DEFAULT_EPSILON,
+            categorical: false,
+        }
+    }
+}
+
+impl CrossEntropy {
+    /// Cross-entropy of an output layer with the given activation function,
+    /// which is categorical for softmax and binary for anything else
+    ///
+    /// # Arguments
+    ///
+    /// * `activation` - Name of the output layer's activation function
+    pub fn for_activation(activation: &str) -> Self {
+        Self {
+            categorical: activation == "softmax",
+            ..Self::default()
+        }
+    }
+}
+
+impl Cost for CrossEntropy {
+    fn call(&self, actual: &Array2<f64>, expected: &Array2<f64>) -> f64 {
+        let samples: f64 = actual.ncols().max(1) as f64;
+        let probabilities: Array2<f64> = numeric::clamp_probabilities(actual, self.epsilon);
+        let log_likelihood: Array2<f64> = if self.categorical {
+            Zip::from(&probabilities)
+                .and(expected)
+                .map_collect(|&p, &y| y * p.ln())
+        } else {
+            Zip::from(&probabilities)
+                .and(expected)
+                .map_collect(|&p, &y| y * p.ln() + (1.0 - y) * (1.0 - p).ln())
+        };
+        -log_likelihood.sum() / samples
+    }
+
+    fn prime(&self, actual: &Array2<f64>, expected: &Array2<f64>) -> Array2<f64> {
+        let probabilities: Array2<f64> = numeric::clamp_probabilities(actual, self.epsilon);
+        if self.categorical {
+            Zip::from(&probabilities)
+                .and(expected)
+                .map_collect(|&p, &y| -y / p)
+        } else {
+            Zip::from(&probabilities)
+                .and(expected)
+                .map_collect(|&p, &y| (p - y) / (p * (1.0 - p)))
+        }
+    }
+
+    fn logit_delta(
+        &self,
+        actual: &Array2<f64>,
+        expected: &Array2<f64>,
+        activation: &str,
+    ) -> Option<Array2<f64>> {
+        // The activation's derivative cancels out, leaving the error itself
+        match activation {
+            "softmax" | "sigmoid" => Some(actual - expected),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nn::functions::activation::{ActivationFn, Sigmoid, Softmax};
+    use ndarray::array;
+
+    /// Logits of 3 output neurons for 2 samples
+    fn logits() -> Array2<f64> {
+        array![[0.5, -1.0], [1.5, 0.2], [-0.7, 0.9]]
+    }
+
+    /// Gradient of the summed (not averaged) loss with respect to the logits,
+    /// by central differences, which `logit_delta` is expected to match
+    fn numeric_logit_delta(
+        cost: &dyn Cost,
+        activation_fn: &dyn ActivationFn,
+        expected: &Array2<f64>,
+    ) -> Array2<f64> {
+        let h: f64 = 1e-6;
+        let samples: f64 = expected.ncols() as f64;
+        let mut delta: Array2<f64> = Array2::zeros(expected.dim());
+        for ((i, j), value) in delta.indexed_iter_mut() {
+            let (mut plus, mut minus) = (logits(), logits());
+            plus[[i, j]] += h;
+            minus[[i, j]] -= h;
+            *value = (cost.call(&activation_fn.call(&plus), expected)
+                - cost.call(&activation_fn.call(&minus), expected))
+                * samples
+                / (2.0 * h);
+        }
+        delta
+    }
+
+    fn assert_close(actual: &Array2<f64>, expected: &Array2<f64>) {
+        for (actual, expected) in actual.iter().zip(expected.iter()) {
+            assert!(
+                (actual - expected).abs() < 1e-6,
+                "got {}, expected {}",
+                actual,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn categorical_cross_entropy_logit_delta_matches_finite_differences() {
+        let expected: Array2<f64> = array![[0.0, 1.0], [1.0, 0.0], [0.0, 0.0]];
+        let cost: CrossEntropy = CrossEntropy::for_activation("softmax");
+        assert!(cost.categorical);
+        let actual: Array2<f64> = Softmax.call(&logits());
+        let delta: Array2<f64> = cost.logit_delta(&actual, &expected, "softmax").unwrap();
+        assert_close(&delta, &numeric_logit_delta(&cost, &Softmax, &expected));
+
+        // Going through `prime` and softmax's full Jacobian gives the same delta
+        let mut chained: Array2<f64> = cost.prime(&actual, &expected);
+        let mut primes: Array2<f64> = Array2::zeros(actual.dim());
+        Softmax.back_prop_into(&logits(), &mut chained, &mut primes);
+        assert_close(&chained, &delta);
+    }
+
+    #[test]
+    fn binary_cross_entropy_logit_delta_matches_finite_differences() {
+        let expected: Array2<f64> = array![[0.0, 1.0], [1.0, 1.0], [0.0, 0.0]];
+        let cost: CrossEntropy = CrossEntropy::for_activation("sigmoid");
+        assert!(!cost.categorical);
+        let actual: Array2<f64> = Sigmoid.call(&logits());
+        let delta: Array2<f64> = cost.logit_delta(&actual, &expected, "sigmoid").unwrap();
+        assert_close(&delta, &numeric_logit_delta(&cost, &Sigmoid, &expected));
+
+        let mut chained: Array2<f64> = cost.prime(&actual, &expected);
+        let mut primes: Array2<f64> = Array2::zeros(actual.dim());
+        Sigmoid.back_prop_into(&logits(), &mut chained, &mut primes);
+        assert_close(&chained, &delta);
+    }
+
+    #[test]
+    fn cross_entropy_has_no_combined_delta_for_other_activations() {
+        let cost: CrossEntropy = CrossEntropy::default();
+        assert!(cost.logit_delta(&logits(), &logits(), "relu").is_none());
+    }
+
+    #[test]
+    fn mse_prime_is_the_error() {
+        let actual: Array2<f64> = array![[1.0, 2.0]];
+        let expected: Array2<f64> = array![[0.5, 3.0]];
+        assert_eq!(MSE.call(&actual, &expected), 0.625);
+        assert_eq!(MSE.prime(&actual, &expected), array![[0.5, -1.0]]);
+    }
+}
@@ -1,7 +1,8 @@
 use crate::dyn_clone;
-use ndarray::{Array2, Axis};
+use crate::error::OpenPbError;
+use ndarray::{Array1, Array2, Axis};
 use ndarray_stats::QuantileExt;
-use serde_json::{Map, Value};
+use serde_json::{json, Map, Value};
 
 /// Transform outputs to/from human-readable values
 pub trait Encoder: DynClone + Sync + Send {
@@ -20,6 +21,40 @@ pub trait Encoder: DynClone + Sync + Send {
     ///
     /// * `y` - Raw (encoded) network output vectors
     fn decode(&self, y: &Array2<f64>) -> Array2<f64>;
+
+    /// Fit any encoder state (e.g. target scaling) to
+    /// the training outputs before training begins
+    ///
+    /// # Arguments
+    ///
+    /// * `y` - Human-readable (decoded) training output vectors
+    fn fit(&mut self, _y: &Array2<f64>) {}
+
+    /// Check that every output value can be encoded, naming
+    /// the offending rows when they can't
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - Name of the outputs for the error message (e.g. "training outputs")
+    /// * `y` - Human-readable (decoded) output vectors
+    fn check(&self, _label: &str, _y: &Array2<f64>) -> Result<(), OpenPbError> {
+        Ok(())
+    }
+
+    /// Values learned by `fit` (e.g. target means), so the encoder
+    /// can decode outputs later without the training data
+    fn state(&self) -> Map<String, Value> {
+        Map::new()
+    }
+
+    /// Restore values saved by `state`. Missing values are left as they are
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - Values previously returned by `state`
+    fn load_state(&mut self, _state: &Map<String, Value>) -> Result<(), OpenPbError> {
+        Ok(())
+    }
 }
 dyn_clone!(Encoder);
 
@@ -36,9 +71,12 @@ impl OneHot {
     /// # Arguments
     ///
     /// * `params` - JSON object with initialization parameters.
-    /// Allowed keys: "max"
+    ///   Allowed keys: "max"
     pub fn new(params: &Map<String, Value>) -> Self {
-        let max: usize = params["max"].as_u64().unwrap_or_default() as usize;
+        let max: usize = params
+            .get("max")
+            .and_then(Value::as_u64)
+            .unwrap_or_default() as usize;
         Self { max }
     }
 }
@@ -63,10 +101,143 @@ impl Encoder for OneHot {
         let mut decoded: Vec<[f64; 1]> = vec![[0.0]; stride];
 
         for (i, row) in y.axis_iter(Axis(0)).enumerate() {
-            // Get index with maximum value
-            let argmax = row.argmax().unwrap() as f64;
+            // Get index with maximum value. Outputs of a diverged network can
+            // be NaN, which have no maximum, so they decode to a NaN class
+            // that never matches a label
+            let argmax: f64 = match row.argmax() {
+                Ok(argmax) => argmax as f64,
+                Err(_) => f64::NAN,
+            };
             decoded[i] = [argmax];
         }
         Array2::from(decoded)
     }
+
+    fn check(&self, label: &str, y: &Array2<f64>) -> Result<(), OpenPbError> {
+        // Labels must be whole numbers between 0 and `max`
+        let rows: Vec<String> = y
+            .column(0)
+            .iter()
+            .enumerate()
+            .filter(|(_, &label)| label < 0.0 || label > self.max as f64 || label.fract() != 0.0)
+            .map(|(row, label)| format!("row {row} ({label})"))
+            .collect();
+        if rows.is_empty() {
+            return Ok(());
+        }
+        let listed: Vec<String> = rows.iter().take(10).cloned().collect();
+        Err(format!(
+            "{} class labels in {label} aren't integers from 0 to the one_hot \"max\" ({}): {}{}",
+            rows.len(),
+            self.max,
+            listed.join(", "),
+            if rows.len() > listed.len() {
+                ", ..."
+            } else {
+                ""
+            }
+        )
+        .into())
+    }
+}
+
+/// Identity encoding for regression: outputs are passed through as-is,
+/// optionally standardized to zero mean and unit variance per column
+#[derive(Clone)]
+pub struct Identity {
+    /// Whether or not targets are standardized during training
+    scale: bool,
+
+    /// Per-column mean of the training targets
+    mean: Array1<f64>,
+
+    /// Per-column standard deviation of the training targets
+    std: Array1<f64>,
+}
+
+impl Identity {
+    /// # Arguments
+    ///
+    /// * `params` - JSON object with initialization parameters.
+    ///   Allowed keys: "scale"
+    pub fn new(params: &Map<String, Value>) -> Self {
+        let scale: bool = params
+            .get("scale")
+            .and_then(|scale| scale.as_bool())
+            .unwrap_or_default();
+        Self {
+            scale,
+            mean: Array1::zeros(0),
+            std: Array1::zeros(0),
+        }
+    }
+
+    /// True if scaling is enabled and has been fit to the training targets
+    fn is_scaled(&self, cols: usize) -> bool {
+        self.scale && self.mean.len() == cols
+    }
+}
+
+impl Encoder for Identity {
+    fn encode(&self, y: &Array2<f64>) -> Array2<f64> {
+        if self.is_scaled(y.ncols()) {
+            (y - &self.mean) / &self.std
+        } else {
+            y.to_owned()
+        }
+    }
+
+    fn decode(&self, y: &Array2<f64>) -> Array2<f64> {
+        // Raw network output stores each sample as a column
+        let y: Array2<f64> = y.t().to_owned();
+        if self.is_scaled(y.ncols()) {
+            y * &self.std + &self.mean
+        } else {
+            y
+        }
+    }
+
+    fn fit(&mut self, y: &Array2<f64>) {
+        if !self.scale || y.nrows() == 0 {
+            return;
+        }
+        self.mean = y.mean_axis(Axis(0)).unwrap();
+        // Constant columns are left unscaled to avoid dividing by zero
+        self.std = y
+            .std_axis(Axis(0), 0.0)
+            .mapv(|std| if std > f64::EPSILON { std } else { 1.0 });
+    }
+    fn state(&self) -> Map<String, Value> {
+        let mut state: Map<String, Value> = Map::new();
+        if self.scale && !self.mean.is_empty() {
+            state.insert("mean".into(), json!(self.mean.to_vec()));
+            state.insert("std".into(), json!(self.std.to_vec()));
+        }
+        state
+    }
+
+    fn load_state(&mut self, state: &Map<String, Value>) -> Result<(), OpenPbError> {
+        let (mean, std) = match (state.get("mean"), state.get("std")) {
+            (Some(mean), Some(std)) => (mean.clone(), std.clone()),
+            _ => return Ok(()),
+        };
+        let (mean, std): (Vec<f64>, Vec<f64>) =
+            match (serde_json::from_value(mean), serde_json::from_value(std)) {
+                (Ok(mean), Ok(std)) => (mean, std),
+                (Err(error), _) | (_, Err(error)) => {
+                    return Err(format!("Invalid encoder state: {error}").into())
+                }
+            };
+        if mean.len() != std.len() {
+            return Err(format!(
+                "Invalid encoder state: {} means but {} standard deviations",
+                mean.len(),
+                std.len()
+            )
+            .into());
+        }
+        self.mean = Array1::from(mean);
+        self.std = Array1::from(std);
+        Ok(())
+    }
 }
@@ -10,7 +10,7 @@ pub trait Metric: DynClone + Sync + Send {
 
     /// Metric score from comparing expected and actual values
     ///
-    /// # Argumentsd
+    /// # Arguments
     ///
     /// * `actual` - Actual values
     /// * `expected` - Expected values
@@ -23,11 +23,17 @@ pub trait Metric: DynClone + Sync + Send {
     /// * `actual` - Actual values
     /// * `expected` - Expected values
     fn check(&self, actual: &Array2<f64>, expected: &Array2<f64>) -> bool;
+
+    /// Returns true if lower scores are better (e.g. error metrics)
+    fn lower_is_better(&self) -> bool {
+        false
+    }
 }
 dyn_clone!(Metric);
 
-/// Metric that is satisfied when a certain percentage
-/// of all expected and actual output values are equal
+/// Metric that is satisfied when a certain percentage of samples are
+/// predicted correctly. Values are compared row by row, so a sample
+/// only counts as correct when its whole decoded output row matches
 #[derive(Clone)]
 pub struct Accuracy {
     /// Minimum passing accuracy score
@@ -38,9 +44,12 @@ impl Accuracy {
     /// # Arguments
     ///
     /// * `params` - JSON object with initialization parameters.
-    /// Allowed keys: "min"
+    ///   Allowed keys: "min"
     pub fn new(params: &Map<String, Value>) -> Self {
-        let min: f64 = params["min"].as_f64().unwrap_or(1.0);
+        let min: f64 = params
+            .get("min")
+            .and_then(|min| min.as_f64())
+            .unwrap_or(1.0);
         Self { min: min as f32 }
     }
 }
@@ -51,14 +60,108 @@ impl Metric for Accuracy {
     }
 
     fn value(&self, actual: &Array2<f64>, expected: &Array2<f64>) -> f32 {
-        let equality: Vec<usize> = actual
+        let correct: usize = actual
+            .rows()
+            .into_iter()
+            .zip(expected.rows())
+            .filter(|(actual_row, expected_row)| actual_row == expected_row)
+            .count();
+        let len = expected.nrows().max(1) as f32;
+        correct as f32 / len
+    }
+
+    fn check(&self, actual: &Array2<f64>, expected: &Array2<f64>) -> bool {
+        self.value(actual, expected) >= self.min
+    }
+}
+
+/// Root mean squared error between expected and actual values.
+/// Satisfied when the error is at or below a maximum value
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone)]
+pub struct RMSE {
+    /// Maximum passing error
+    max: f32,
+}
+
+impl RMSE {
+    /// # Arguments
+    ///
+    /// * `params` - JSON object with initialization parameters.
+    ///   Allowed keys: "max"
+    pub fn new(params: &Map<String, Value>) -> Self {
+        let max: f64 = params
+            .get("max")
+            .and_then(|max| max.as_f64())
+            .unwrap_or_default();
+        Self { max: max as f32 }
+    }
+}
+
+impl Metric for RMSE {
+    fn label(&self) -> &str {
+        "RMSE"
+    }
+
+    fn value(&self, actual: &Array2<f64>, expected: &Array2<f64>) -> f32 {
+        let squared_error: f64 = actual
+            .iter()
+            .zip(expected)
+            .map(|(a, e)| (a - e).powi(2))
+            .sum();
+        let len = actual.len().max(1) as f64;
+        (squared_error / len).sqrt() as f32
+    }
+
+    fn check(&self, actual: &Array2<f64>, expected: &Array2<f64>) -> bool {
+        self.value(actual, expected) <= self.max
+    }
+
+    fn lower_is_better(&self) -> bool {
+        true
+    }
+}
+
+/// Coefficient of determination (R²) of the actual values.
+/// Satisfied when the score is at or above a minimum value
+#[derive(Clone)]
+pub struct RSquared {
+    /// Minimum passing score
+    min: f32,
+}
+
+impl RSquared {
+    /// # Arguments
+    ///
+    /// * `params` - JSON object with initialization parameters.
+    ///   Allowed keys: "min"
+    pub fn new(params: &Map<String, Value>) -> Self {
+        let min: f64 = params
+            .get("min")
+            .and_then(|min| min.as_f64())
+            .unwrap_or(1.0);
+        Self { min: min as f32 }
+    }
+}
+
+impl Metric for RSquared {
+    fn label(&self) -> &str {
+        "R²"
+    }
+
+    fn value(&self, actual: &Array2<f64>, expected: &Array2<f64>) -> f32 {
+        let mean: f64 = expected.mean().unwrap_or_default();
+        let residual: f64 = actual
             .iter()
             .zip(expected)
-            .map(|a: (&f64, &f64)| (a.0 == a.1) as usize)
-            .collect();
-        let len = equality.len() as f32;
-        let sum = equality.into_iter().sum::<usize>() as f32;
-        sum / len
+            .map(|(a, e)| (e - a).powi(2))
+            .sum();
+        let total: f64 = expected.iter().map(|e| (e - mean).powi(2)).sum();
+        if total == 0.0 {
+            // Constant targets: only a perfect fit scores 1
+            return if residual == 0.0 { 1.0 } else { 0.0 };
+        }
+        (1.0 - residual / total) as f32
     }
 
     fn check(&self, actual: &Array2<f64>, expected: &Array2<f64>) -> bool {
@@ -2,5 +2,6 @@ pub mod activation;
 pub mod cost;
 pub mod encoder;
 pub mod metric;
+pub mod numeric;
 pub mod optimizer;
 mod util;
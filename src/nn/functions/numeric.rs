@@ -0,0 +1,51 @@
+use ndarray::{Array2, Axis};
+
+/// Default distance from 0 and 1 that probabilities are clamped to
+/// before taking a log, so saturated outputs don't produce ±inf
+pub const DEFAULT_EPSILON: f64 = 1e-12;
+
+/// Clamp a probability into `[epsilon, 1 - epsilon]`
+///
+/// # Arguments
+///
+/// * `probability` - Value to clamp
+/// * `epsilon` - Smallest distance from 0 and 1
+pub fn clamp_probability(probability: f64, epsilon: f64) -> f64 {
+    probability.clamp(epsilon, 1.0 - epsilon)
+}
+
+/// Clamp every value of a matrix of probabilities into `[epsilon, 1 - epsilon]`
+///
+/// # Arguments
+///
+/// * `probabilities` - Values to clamp
+/// * `epsilon` - Smallest distance from 0 and 1
+pub fn clamp_probabilities(probabilities: &Array2<f64>, epsilon: f64) -> Array2<f64> {
+    probabilities.mapv(|probability| clamp_probability(probability, epsilon))
+}
+
+/// Natural log that never returns -inf (or NaN for values below zero),
+/// since values are raised to `epsilon` first
+///
+/// # Arguments
+///
+/// * `value` - Value to take the log of
+/// * `epsilon` - Smallest value the log is taken of
+pub fn safe_ln(value: f64, epsilon: f64) -> f64 {
+    value.max(epsilon).ln()
+}
+
+/// Softmax of each column that doesn't overflow for large inputs,
+/// since each column's maximum is subtracted before exponentiating
+///
+/// # Arguments
+///
+/// * `x` - Input vectors, one per column
+pub fn stable_softmax(x: &Array2<f64>) -> Array2<f64> {
+    let max: Array2<f64> = x
+        .fold_axis(Axis(0), f64::NEG_INFINITY, |&max, &value| max.max(value))
+        .insert_axis(Axis(0));
+    let exp: Array2<f64> = (x - &max).mapv(f64::exp);
+    let sum: Array2<f64> = exp.sum_axis(Axis(0)).insert_axis(Axis(0));
+    exp / sum
+}
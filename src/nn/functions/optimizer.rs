@@ -1,6 +1,9 @@
 use crate::dyn_clone;
+use crate::error::OpenPbError;
 use crate::nn::layer::Layer;
 use ndarray::Array2;
+use serde_json::{json, Map, Value};
+use std::convert::TryFrom;
 
 /// Default momentum constant
 pub const DEFAULT_BETA1: f64 = 0.9;
@@ -8,21 +11,21 @@ pub const DEFAULT_BETA1: f64 = 0.9;
 /// Default secondary momentum constant
 pub const DEFAULT_BETA2: f64 = 0.999;
 
+/// Default decay rate of RMSProp's running average of squared gradients
+pub const DEFAULT_RMSPROP_DECAY: f64 = 0.9;
+
+/// Default AdamW weight decay (fraction of each weight removed
+/// per training step, scaled by the learning rate)
+pub const DEFAULT_WEIGHT_DECAY: f64 = 0.01;
+
+/// Added to the denominator of adaptive learning rates to avoid dividing by zero
+const ADAPTIVE_EPSILON: f64 = 1e-7;
+
 /// Wrapper for updating a network with any given
-/// optimization function using online training
-pub fn optimize(optimizer: &mut dyn Optimizer, layers: &mut Vec<Layer>, input_rows: usize) {
-    let deltas: Vec<Array2<f64>> = layers
-        .iter()
-        .enumerate()
-        .map(|layer: (usize, &Layer)| {
-            let deltas: &Array2<f64> = match &layer.1.deltas {
-                Some(deltas) => deltas,
-                None => panic!("Deltas not calculated for layer {}", layer.0),
-            };
-            deltas.clone()
-        })
-        .collect();
-    optimizer.update(layers, &deltas, input_rows);
+/// optimization function using online training. Each
+/// layer's deltas are read directly from its backprop buffer
+pub fn optimize(optimizer: &mut dyn Optimizer, layers: &mut [Layer], input_rows: usize) {
+    optimizer.update(layers, input_rows);
 }
 
 /// Optimizer functions that's used to determine how a Network's weights should be
@@ -34,11 +37,248 @@ pub trait Optimizer: DynClone + Sync + Send {
     /// # Arguments
     ///
     /// * `layers` - Layers of the network to apply gradient descent to
-    fn update(&mut self, layers: &mut Vec<Layer>, deltas: &Vec<Array2<f64>>, input_rows: usize);
+    ///   (deltas are assumed to have already been computed)
+    /// * `input_rows` - Number of rows in the training input set
+    fn update(&mut self, layers: &mut [Layer], input_rows: usize);
+
+    /// Current step size when adjusting weights during gradient descent
+    fn learning_rate(&self) -> f64;
+
+    /// Change the step size used by subsequent training steps
+    ///
+    /// # Arguments
+    ///
+    /// * `learning_rate` - New step size
+    fn set_learning_rate(&mut self, learning_rate: f64);
+
+    /// Number of values stored as internal state between
+    /// training steps (e.g. momentum vectors)
+    fn state_len(&self) -> usize {
+        0
+    }
+
+    /// Fully-resolved hyperparameters (including defaults) and any
+    /// schedule state (e.g. the current time step), keyed the same
+    /// way as the "optimizer" object of a network config
+    fn hyperparameters(&self) -> Map<String, Value> {
+        let mut hyperparameters: Map<String, Value> = Map::new();
+        hyperparameters.insert("learning_rate".into(), self.learning_rate().into());
+        hyperparameters
+    }
+
+    /// Internal values kept between training steps (e.g. momentum
+    /// vectors and the time step), so training can be resumed later
+    fn state(&self) -> Map<String, Value> {
+        Map::new()
+    }
+
+    /// Restore internal values saved by `state`. Missing values are
+    /// left as they are (e.g. momentum vectors are created lazily)
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - Values previously returned by `state`
+    fn load_state(&mut self, _state: &Map<String, Value>) -> Result<(), OpenPbError> {
+        Ok(())
+    }
+}
+
+/// Per-layer state matrices saved under the given key
+/// (`None` when the key is missing)
+///
+/// # Arguments
+///
+/// * `state` - Values previously returned by `Optimizer::state`
+/// * `key` - Name of the saved matrices
+fn load_matrices(
+    state: &Map<String, Value>,
+    key: &str,
+) -> Result<Option<Vec<Array2<f64>>>, OpenPbError> {
+    match state.get(key) {
+        Some(value) => match serde_json::from_value(value.clone()) {
+            Ok(matrices) => Ok(Some(matrices)),
+            Err(error) => Err(format!("Invalid optimizer state \"{key}\": {error}").into()),
+        },
+        None => Ok(None),
+    }
+}
+
+/// Total number of values in a set of per-layer state matrices
+fn matrices_len(matrices: &[Array2<f64>]) -> usize {
+    matrices.iter().map(Array2::len).sum()
+}
+
+/// Save per-layer state matrices under the given key, unless there
+/// are none (e.g. batch normalization state of a network without it)
+///
+/// # Arguments
+///
+/// * `state` - Values returned by `Optimizer::state`
+/// * `key` - Name of the matrices
+/// * `matrices` - Per-layer state matrices
+fn save_matrices(state: &mut Map<String, Value>, key: &str, matrices: &[Array2<f64>]) {
+    if !matrices.is_empty() {
+        state.insert(key.into(), json!(matrices));
+    }
+}
+
+/// State matrix of a Layer's batch normalization parameters, created
+/// with zeros when it doesn't exist yet. Earlier layers without batch
+/// normalization get empty placeholders, so every Layer's state stays
+/// at the Layer's index
+///
+/// # Arguments
+///
+/// * `states` - Per-layer state matrices
+/// * `i` - Index of the Layer
+/// * `dim` - Shape of the batch normalization parameters
+fn batch_norm_state(
+    states: &mut Vec<Array2<f64>>,
+    i: usize,
+    dim: (usize, usize),
+) -> &mut Array2<f64> {
+    while states.len() < i {
+        states.push(Array2::zeros((0, 0)));
+    }
+    if states.len() == i {
+        states.push(Array2::zeros(dim));
+    }
+    &mut states[i]
+}
+
+/// Step of classical momentum: the moment accumulates each scaled gradient
+///
+/// # Arguments
+///
+/// * `moment` - Momentum of the parameters (updated in place)
+/// * `gradient` - Gradient of the cost with respect to the parameters
+/// * `learning_rate` - Step size
+/// * `gamma` - Momentum constant
+fn momentum_step(
+    moment: &mut Array2<f64>,
+    gradient: &Array2<f64>,
+    learning_rate: f64,
+    gamma: f64,
+) -> Array2<f64> {
+    let delta: Array2<f64> = learning_rate * gradient;
+    let step: Array2<f64> = (gamma * &*moment) + &delta;
+    moment.assign(&step);
+    step
+}
+
+/// Time step as an exponent of the momentum constants. Powers of
+/// constants below 1 are 0 long before the exponent stops fitting
+///
+/// # Arguments
+///
+/// * `time_step` - Current step in the training process
+fn exponent(time_step: u64) -> i32 {
+    i32::try_from(time_step).unwrap_or(i32::MAX)
+}
+
+/// Step of Adam: the moment is scaled by the root of the velocity
+/// (running average of squared gradients), both bias-corrected
+///
+/// # Arguments
+///
+/// * `moment` - Running average of the gradients (updated in place)
+/// * `velocity` - Running average of the squared gradients (updated in place)
+/// * `gradient` - Gradient of the cost with respect to the parameters
+/// * `learning_rate` - Step size
+/// * `gamma` - Momentum constant
+/// * `beta` - Secondary momentum constant
+/// * `time_step` - Current step in the training process (starting at 1)
+fn adam_step(
+    moment: &mut Array2<f64>,
+    velocity: &mut Array2<f64>,
+    gradient: &Array2<f64>,
+    learning_rate: f64,
+    gamma: f64,
+    beta: f64,
+    time_step: u64,
+) -> Array2<f64> {
+    // Initial momentum calculation
+    let next_moment: Array2<f64> = (&*moment * gamma) + (gradient * (1. - gamma));
+
+    // Initial velocity calculation
+    let next_velocity: Array2<f64> = {
+        let grad_squared = gradient.mapv(|el| el * el);
+        (&*velocity * beta) + (grad_squared * (1. - beta))
+    };
+
+    // Save momentum and velocity values for future passes
+    moment.assign(&next_moment);
+    velocity.assign(&next_velocity);
+
+    // Adjust momentum inversely relative to the number of training cycles
+    let moment_bar: Array2<f64> = {
+        let beta1_t = 1. - gamma.powi(exponent(time_step));
+        moment.mapv(|el| el / beta1_t)
+    };
+
+    // Adjust velocity inversely relative to the number of training cycles
+    let velocity_sqrt: Array2<f64> = {
+        let beta2_t = 1. - beta.powi(exponent(time_step));
+        let velocity_bar: Array2<f64> = velocity.mapv(|el| el / beta2_t);
+
+        velocity_bar.mapv(|el| f64::sqrt(el) + ADAPTIVE_EPSILON)
+    };
+
+    // Calculate final momentum w.r.t. velocity
+    (moment_bar * learning_rate) / velocity_sqrt
+}
+
+/// Step of RMSProp: the gradient is scaled by the root of
+/// a running average of squared gradients
+///
+/// # Arguments
+///
+/// * `velocity` - Running average of the squared gradients (updated in place)
+/// * `gradient` - Gradient of the cost with respect to the parameters
+/// * `learning_rate` - Step size
+/// * `decay` - Decay rate of the running average
+fn rms_prop_step(
+    velocity: &mut Array2<f64>,
+    gradient: &Array2<f64>,
+    learning_rate: f64,
+    decay: f64,
+) -> Array2<f64> {
+    // Running average of squared gradients
+    let next_velocity: Array2<f64> = {
+        let grad_squared = gradient.mapv(|el| el * el);
+        (&*velocity * decay) + (grad_squared * (1. - decay))
+    };
+    velocity.assign(&next_velocity);
+
+    let velocity_sqrt: Array2<f64> = next_velocity.mapv(|el| f64::sqrt(el) + ADAPTIVE_EPSILON);
+    (gradient * learning_rate) / velocity_sqrt
+}
+
+/// Step of AdaGrad: the gradient is scaled by the root
+/// of the sum of every squared gradient so far
+///
+/// # Arguments
+///
+/// * `accumulator` - Sum of the squared gradients (updated in place)
+/// * `gradient` - Gradient of the cost with respect to the parameters
+/// * `learning_rate` - Step size
+fn ada_grad_step(
+    accumulator: &mut Array2<f64>,
+    gradient: &Array2<f64>,
+    learning_rate: f64,
+) -> Array2<f64> {
+    // Sum of every squared gradient so far
+    let next_accumulator: Array2<f64> = &*accumulator + gradient.mapv(|el| el * el);
+    accumulator.assign(&next_accumulator);
+
+    let accumulator_sqrt: Array2<f64> =
+        next_accumulator.mapv(|el| f64::sqrt(el) + ADAPTIVE_EPSILON);
+    (gradient * learning_rate) / accumulator_sqrt
 }
 dyn_clone!(Optimizer);
 
 /// Stochastic Gradient Descent with momentum
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Clone)]
 pub struct SGD {
     /// The step size when adjusting weights for each call of gradient descent
@@ -50,6 +290,9 @@ pub struct SGD {
 
     /// Set of moment values for use in classical momentum
     moments: Vec<Array2<f64>>,
+
+    /// Moment values of each Layer's batch normalization parameters
+    batch_norm_moments: Vec<Array2<f64>>,
 }
 
 impl SGD {
@@ -62,41 +305,87 @@ impl SGD {
             learning_rate,
             gamma,
             moments: vec![],
+            batch_norm_moments: vec![],
         }
     }
 }
 
 impl Optimizer for SGD {
-    fn update(&mut self, layers: &mut Vec<Layer>, deltas: &Vec<Array2<f64>>, input_rows: usize) {
+    fn update(&mut self, layers: &mut [Layer], input_rows: usize) {
         for (i, layer) in layers.iter_mut().enumerate() {
             // Convert activation (z) deltas from initial back-prop run
             // into weight and bias deltas
-            let delta_weights: Array2<f64> = self.learning_rate * deltas[i].dot(&layer.inputs.t());
-            let delta_biases: Array2<f64> = self.learning_rate * &deltas[i];
+            let gradient: Array2<f64> = layer.deltas.dot(&layer.inputs.t());
+            let delta_biases: Array2<f64> = self.learning_rate * &layer.deltas;
 
             // Create momentum vectors if they don't already exist
             if self.moments.len() <= i {
-                self.moments.push(Array2::zeros(delta_weights.dim()));
+                self.moments.push(Array2::zeros(gradient.dim()));
             }
 
-            // Apply momentum to weight deltas
-            let moment: Array2<f64> = {
-                let prev_moment: Array2<f64> = self.moments[i].clone();
-                (self.gamma * prev_moment) + &delta_weights
-            };
+            // Apply momentum to weight deltas (saved for future passes)
+            let moment: Array2<f64> = momentum_step(
+                &mut self.moments[i],
+                &gradient,
+                self.learning_rate,
+                self.gamma,
+            );
 
             // Apply deltas to layer
             layer.update(&moment, &delta_biases, input_rows);
-            // Save momentum values for future passes
-            self.moments[i].assign(&moment);
+
+            // Batch normalization's scale and shift take the same kind of step
+            if let Some(gradient) = layer.batch_norm_gradient() {
+                let moment_state: &mut Array2<f64> =
+                    batch_norm_state(&mut self.batch_norm_moments, i, gradient.dim());
+                let moment: Array2<f64> =
+                    momentum_step(moment_state, &gradient, self.learning_rate, self.gamma);
+                layer.step_batch_norm(&moment);
+            }
+        }
+    }
+
+    fn learning_rate(&self) -> f64 {
+        self.learning_rate
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.learning_rate = learning_rate;
+    }
+
+    fn state_len(&self) -> usize {
+        matrices_len(&self.moments) + matrices_len(&self.batch_norm_moments)
+    }
+
+    fn hyperparameters(&self) -> Map<String, Value> {
+        let mut hyperparameters: Map<String, Value> = Map::new();
+        hyperparameters.insert("learning_rate".into(), self.learning_rate.into());
+        hyperparameters.insert("beta1".into(), self.gamma.into());
+        hyperparameters
+    }
+
+    fn state(&self) -> Map<String, Value> {
+        let mut state: Map<String, Value> = Map::new();
+        state.insert("moments".into(), json!(self.moments));
+        save_matrices(&mut state, "batch_norm_moments", &self.batch_norm_moments);
+        state
+    }
+
+    fn load_state(&mut self, state: &Map<String, Value>) -> Result<(), OpenPbError> {
+        if let Some(moments) = load_matrices(state, "moments")? {
+            self.moments = moments;
         }
+        if let Some(moments) = load_matrices(state, "batch_norm_moments")? {
+            self.batch_norm_moments = moments;
+        }
+        Ok(())
     }
 }
 
 #[derive(Clone)]
 pub struct Adam {
     /// Current step in the training process
-    time_step: u16,
+    time_step: u64,
 
     /// The step size when adjusting weights during gradient descent
     learning_rate: f64,
@@ -114,6 +403,12 @@ pub struct Adam {
 
     /// Set of moment values for use in classical momentum
     moments: Vec<Array2<f64>>,
+
+    /// Velocity values of each Layer's batch normalization parameters
+    batch_norm_velocities: Vec<Array2<f64>>,
+
+    /// Moment values of each Layer's batch normalization parameters
+    batch_norm_moments: Vec<Array2<f64>>,
 }
 
 impl Adam {
@@ -129,19 +424,21 @@ impl Adam {
             beta,
             velocities: vec![],
             moments: vec![],
+            batch_norm_velocities: vec![],
+            batch_norm_moments: vec![],
         }
     }
 }
 
 impl Optimizer for Adam {
-    fn update(&mut self, layers: &mut Vec<Layer>, deltas: &Vec<Array2<f64>>, input_rows: usize) {
+    fn update(&mut self, layers: &mut [Layer], input_rows: usize) {
         self.time_step += 1;
 
         for (i, layer) in layers.iter_mut().enumerate() {
             // Convert activation (z) deltas from initial back-prop run
             // into weight and bias deltas
-            let delta_weights: Array2<f64> = deltas[i].dot(&layer.inputs.t());
-            let delta_biases: Array2<f64> = self.learning_rate * &deltas[i];
+            let delta_weights: Array2<f64> = layer.deltas.dot(&layer.inputs.t());
+            let delta_biases: Array2<f64> = self.learning_rate * &layer.deltas;
 
             // Create velocity vectors if they don't already exist
             if self.velocities.len() <= i {
@@ -153,37 +450,505 @@ impl Optimizer for Adam {
                 self.moments.push(Array2::zeros(delta_weights.dim()));
             }
 
-            // Initial momentum calculation
-            let moment: Array2<f64> =
-                (&self.moments[i] * self.gamma) + (&delta_weights * (1. - self.gamma));
+            // Momentum w.r.t. velocity (both saved for future passes)
+            let moment_adj: Array2<f64> = adam_step(
+                &mut self.moments[i],
+                &mut self.velocities[i],
+                &delta_weights,
+                self.learning_rate,
+                self.gamma,
+                self.beta,
+                self.time_step,
+            );
+            layer.update(&moment_adj, &delta_biases, input_rows);
 
-            // Initial velocity calculation
-            let velocity: Array2<f64> = {
-                let grad_squared = delta_weights.mapv(|el| el * el);
-                (&self.velocities[i] * self.beta) + (grad_squared * (1. - self.beta))
-            };
+            // Batch normalization's scale and shift take the same kind of step
+            if let Some(gradient) = layer.batch_norm_gradient() {
+                let moment_adj: Array2<f64> = adam_step(
+                    batch_norm_state(&mut self.batch_norm_moments, i, gradient.dim()),
+                    batch_norm_state(&mut self.batch_norm_velocities, i, gradient.dim()),
+                    &gradient,
+                    self.learning_rate,
+                    self.gamma,
+                    self.beta,
+                    self.time_step,
+                );
+                layer.step_batch_norm(&moment_adj);
+            }
+        }
+    }
 
-            // Save momentum and velocity values for future passes
-            self.moments[i].assign(&moment);
-            self.velocities[i].assign(&velocity);
+    fn learning_rate(&self) -> f64 {
+        self.learning_rate
+    }
 
-            // Adjust momentum inversely relative to the number of training cycles
-            let moment_bar: Array2<f64> = {
-                let beta1_t = 1. - self.gamma.powi(self.time_step as i32);
-                self.moments[i].mapv(|el| el / beta1_t)
-            };
+    fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.learning_rate = learning_rate;
+    }
+
+    fn state_len(&self) -> usize {
+        matrices_len(&self.moments)
+            + matrices_len(&self.velocities)
+            + matrices_len(&self.batch_norm_moments)
+            + matrices_len(&self.batch_norm_velocities)
+    }
 
-            // Adjust velocity inversely relative to the number of training cycles
-            let velocity_sqrt: Array2<f64> = {
-                let beta2_t = 1. - self.beta.powi(self.time_step as i32);
-                let velocity_bar: Array2<f64> = self.velocities[i].mapv(|el| el / beta2_t);
+    fn hyperparameters(&self) -> Map<String, Value> {
+        let mut hyperparameters: Map<String, Value> = Map::new();
+        hyperparameters.insert("learning_rate".into(), self.learning_rate.into());
+        hyperparameters.insert("beta1".into(), self.gamma.into());
+        hyperparameters.insert("beta2".into(), self.beta.into());
+        hyperparameters.insert("time_step".into(), self.time_step.into());
+        hyperparameters
+    }
+
+    fn state(&self) -> Map<String, Value> {
+        let mut state: Map<String, Value> = Map::new();
+        state.insert("time_step".into(), self.time_step.into());
+        state.insert("moments".into(), json!(self.moments));
+        state.insert("velocities".into(), json!(self.velocities));
+        save_matrices(&mut state, "batch_norm_moments", &self.batch_norm_moments);
+        save_matrices(
+            &mut state,
+            "batch_norm_velocities",
+            &self.batch_norm_velocities,
+        );
+        state
+    }
 
-                velocity_bar.mapv(|el| f64::sqrt(el) + 1e-7)
+    fn load_state(&mut self, state: &Map<String, Value>) -> Result<(), OpenPbError> {
+        if let Some(time_step) = state.get("time_step") {
+            self.time_step = match time_step.as_u64() {
+                Some(time_step) => time_step,
+                None => return Err("Invalid optimizer state \"time_step\"".into()),
             };
+        }
+        if let Some(moments) = load_matrices(state, "moments")? {
+            self.moments = moments;
+        }
+        if let Some(velocities) = load_matrices(state, "velocities")? {
+            self.velocities = velocities;
+        }
+        if let Some(moments) = load_matrices(state, "batch_norm_moments")? {
+            self.batch_norm_moments = moments;
+        }
+        if let Some(velocities) = load_matrices(state, "batch_norm_velocities")? {
+            self.batch_norm_velocities = velocities;
+        }
+        Ok(())
+    }
+}
+
+/// Root Mean Square Propagation: each weight's step is scaled by a
+/// running average of its recent squared gradients
+#[derive(Clone)]
+pub struct RMSProp {
+    /// The step size when adjusting weights during gradient descent
+    learning_rate: f64,
+
+    /// Decay rate of the running average, typically set
+    /// to 0.9 (`DEFAULT_RMSPROP_DECAY`)
+    decay: f64,
+
+    /// Set of running averages of squared gradients
+    velocities: Vec<Array2<f64>>,
+
+    /// Running averages of each Layer's squared batch normalization gradients
+    batch_norm_velocities: Vec<Array2<f64>>,
+}
+
+impl RMSProp {
+    /// # Arguments
+    ///
+    /// * `learning_rate` - The step size when adjusting weights during gradient descent
+    /// * `decay` - Decay rate of the running average of squared gradients
+    pub fn new(learning_rate: f64, decay: f64) -> RMSProp {
+        RMSProp {
+            learning_rate,
+            decay,
+            velocities: vec![],
+            batch_norm_velocities: vec![],
+        }
+    }
+}
+
+impl Optimizer for RMSProp {
+    fn update(&mut self, layers: &mut [Layer], input_rows: usize) {
+        for (i, layer) in layers.iter_mut().enumerate() {
+            // Convert activation (z) deltas from initial back-prop run
+            // into weight and bias deltas
+            let delta_weights: Array2<f64> = layer.deltas.dot(&layer.inputs.t());
+            let delta_biases: Array2<f64> = self.learning_rate * &layer.deltas;
 
-            // Calculate final momentum w.r.t. velocity
-            let moment_adj: Array2<f64> = (moment_bar * self.learning_rate) / velocity_sqrt;
-            layer.update(&moment_adj, &delta_biases, input_rows)
+            // Create velocity vectors if they don't already exist
+            if self.velocities.len() <= i {
+                self.velocities.push(Array2::zeros(delta_weights.dim()));
+            }
+
+            // Scaled by the running average of squared gradients
+            let delta_adj: Array2<f64> = rms_prop_step(
+                &mut self.velocities[i],
+                &delta_weights,
+                self.learning_rate,
+                self.decay,
+            );
+            layer.update(&delta_adj, &delta_biases, input_rows);
+
+            // Batch normalization's scale and shift take the same kind of step
+            if let Some(gradient) = layer.batch_norm_gradient() {
+                let velocity: &mut Array2<f64> =
+                    batch_norm_state(&mut self.batch_norm_velocities, i, gradient.dim());
+                let delta_adj: Array2<f64> =
+                    rms_prop_step(velocity, &gradient, self.learning_rate, self.decay);
+                layer.step_batch_norm(&delta_adj);
+            }
+        }
+    }
+
+    fn learning_rate(&self) -> f64 {
+        self.learning_rate
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.learning_rate = learning_rate;
+    }
+
+    fn state_len(&self) -> usize {
+        matrices_len(&self.velocities) + matrices_len(&self.batch_norm_velocities)
+    }
+
+    fn hyperparameters(&self) -> Map<String, Value> {
+        let mut hyperparameters: Map<String, Value> = Map::new();
+        hyperparameters.insert("learning_rate".into(), self.learning_rate.into());
+        hyperparameters.insert("decay".into(), self.decay.into());
+        hyperparameters
+    }
+
+    fn state(&self) -> Map<String, Value> {
+        let mut state: Map<String, Value> = Map::new();
+        state.insert("velocities".into(), json!(self.velocities));
+        save_matrices(
+            &mut state,
+            "batch_norm_velocities",
+            &self.batch_norm_velocities,
+        );
+        state
+    }
+
+    fn load_state(&mut self, state: &Map<String, Value>) -> Result<(), OpenPbError> {
+        if let Some(velocities) = load_matrices(state, "velocities")? {
+            self.velocities = velocities;
+        }
+        if let Some(velocities) = load_matrices(state, "batch_norm_velocities")? {
+            self.batch_norm_velocities = velocities;
+        }
+        Ok(())
+    }
+}
+
+/// Adaptive Gradient: each weight's step is scaled by
+/// the sum of all of its past squared gradients
+#[derive(Clone)]
+pub struct AdaGrad {
+    /// The step size when adjusting weights during gradient descent
+    learning_rate: f64,
+
+    /// Set of sums of squared gradients
+    accumulators: Vec<Array2<f64>>,
+
+    /// Sums of each Layer's squared batch normalization gradients
+    batch_norm_accumulators: Vec<Array2<f64>>,
+}
+
+impl AdaGrad {
+    /// # Arguments
+    ///
+    /// * `learning_rate` - The step size when adjusting weights during gradient descent
+    pub fn new(learning_rate: f64) -> AdaGrad {
+        AdaGrad {
+            learning_rate,
+            accumulators: vec![],
+            batch_norm_accumulators: vec![],
+        }
+    }
+}
+
+impl Optimizer for AdaGrad {
+    fn update(&mut self, layers: &mut [Layer], input_rows: usize) {
+        for (i, layer) in layers.iter_mut().enumerate() {
+            // Convert activation (z) deltas from initial back-prop run
+            // into weight and bias deltas
+            let delta_weights: Array2<f64> = layer.deltas.dot(&layer.inputs.t());
+            let delta_biases: Array2<f64> = self.learning_rate * &layer.deltas;
+
+            // Create accumulator vectors if they don't already exist
+            if self.accumulators.len() <= i {
+                self.accumulators.push(Array2::zeros(delta_weights.dim()));
+            }
+
+            // Scaled by the sum of every squared gradient so far
+            let delta_adj: Array2<f64> = ada_grad_step(
+                &mut self.accumulators[i],
+                &delta_weights,
+                self.learning_rate,
+            );
+            layer.update(&delta_adj, &delta_biases, input_rows);
+
+            // Batch normalization's scale and shift take the same kind of step
+            if let Some(gradient) = layer.batch_norm_gradient() {
+                let accumulator: &mut Array2<f64> =
+                    batch_norm_state(&mut self.batch_norm_accumulators, i, gradient.dim());
+                let delta_adj: Array2<f64> =
+                    ada_grad_step(accumulator, &gradient, self.learning_rate);
+                layer.step_batch_norm(&delta_adj);
+            }
+        }
+    }
+
+    fn learning_rate(&self) -> f64 {
+        self.learning_rate
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.learning_rate = learning_rate;
+    }
+
+    fn state_len(&self) -> usize {
+        matrices_len(&self.accumulators) + matrices_len(&self.batch_norm_accumulators)
+    }
+
+    fn state(&self) -> Map<String, Value> {
+        let mut state: Map<String, Value> = Map::new();
+        state.insert("accumulators".into(), json!(self.accumulators));
+        save_matrices(
+            &mut state,
+            "batch_norm_accumulators",
+            &self.batch_norm_accumulators,
+        );
+        state
+    }
+
+    fn load_state(&mut self, state: &Map<String, Value>) -> Result<(), OpenPbError> {
+        if let Some(accumulators) = load_matrices(state, "accumulators")? {
+            self.accumulators = accumulators;
+        }
+        if let Some(accumulators) = load_matrices(state, "batch_norm_accumulators")? {
+            self.batch_norm_accumulators = accumulators;
         }
+        Ok(())
+    }
+}
+
+/// Adam with decoupled weight decay: every weight shrinks by a fixed
+/// fraction of itself each step, independently of the adaptive gradient step
+#[derive(Clone)]
+pub struct AdamW {
+    /// Adam optimizer that computes the gradient step
+    adam: Adam,
+
+    /// Fraction of each weight removed per step (scaled by the learning rate)
+    weight_decay: f64,
+}
+
+impl AdamW {
+    /// # Arguments
+    ///
+    /// * `learning_rate` - The step size when adjusting weights during gradient descent
+    /// * `gamma` - Momentum constant
+    /// * `beta` - Secondary momentum constant
+    /// * `weight_decay` - Fraction of each weight removed per step
+    pub fn new(learning_rate: f64, gamma: f64, beta: f64, weight_decay: f64) -> AdamW {
+        AdamW {
+            adam: Adam::new(learning_rate, gamma, beta),
+            weight_decay,
+        }
+    }
+}
+
+impl Optimizer for AdamW {
+    fn update(&mut self, layers: &mut [Layer], input_rows: usize) {
+        // Weights decay before (and separately from) the gradient step
+        let decay: f64 = self.adam.learning_rate * self.weight_decay;
+        for layer in layers.iter_mut() {
+            layer.decay_weights(decay);
+        }
+        self.adam.update(layers, input_rows);
+    }
+
+    fn learning_rate(&self) -> f64 {
+        self.adam.learning_rate()
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.adam.set_learning_rate(learning_rate);
+    }
+
+    fn state_len(&self) -> usize {
+        self.adam.state_len()
+    }
+
+    fn hyperparameters(&self) -> Map<String, Value> {
+        let mut hyperparameters: Map<String, Value> = self.adam.hyperparameters();
+        hyperparameters.insert("weight_decay".into(), self.weight_decay.into());
+        hyperparameters
+    }
+
+    fn state(&self) -> Map<String, Value> {
+        self.adam.state()
+    }
+
+    fn load_state(&mut self, state: &Map<String, Value>) -> Result<(), OpenPbError> {
+        self.adam.load_state(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nn::batch_norm::BatchNorm;
+    use crate::nn::functions::activation::Linear;
+    use ndarray::array;
+
+    const LEARNING_RATE: f64 = 0.1;
+
+    /// Linear layer with 2 inputs and 1 neuron, whose weight gradient
+    /// after `backprop` is [0.5, -1.0] for a single sample
+    fn layer(weights: Array2<f64>) -> Layer {
+        Layer::from_parameters(weights, array![[0.0]], Box::new(Linear))
+    }
+
+    /// Set the layer's inputs and deltas as a backprop step would
+    fn backprop(layer: &mut Layer) {
+        layer.inputs = array![[1.0], [-2.0]];
+        layer.deltas = array![[0.5]];
+    }
+
+    /// Weights of a layer after each of the given number of identical steps
+    fn train(optimizer: &mut dyn Optimizer, steps: usize) -> Vec<Array2<f64>> {
+        let mut layers: Vec<Layer> = vec![layer(array![[0.0, 0.0]])];
+        (0..steps)
+            .map(|_| {
+                backprop(&mut layers[0]);
+                optimizer.update(&mut layers, 1);
+                layers[0].snapshot().weights
+            })
+            .collect()
+    }
+
+    fn assert_close(actual: &Array2<f64>, expected: &Array2<f64>) {
+        for (actual, expected) in actual.iter().zip(expected.iter()) {
+            assert!(
+                (actual - expected).abs() < 1e-6,
+                "got {}, expected {}",
+                actual,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn sgd_steps_down_the_gradient_with_momentum() {
+        let weights: Vec<Array2<f64>> = train(&mut SGD::new(LEARNING_RATE, 0.9), 2);
+        assert_close(&weights[0], &array![[-0.05, 0.1]]);
+        // The second step adds the first one's momentum: 0.9 * 0.05 + 0.05
+        assert_close(&weights[1], &array![[-0.145, 0.29]]);
+    }
+
+    #[test]
+    fn adaptive_optimizers_take_unit_first_steps() {
+        // Each first step is the learning rate times the gradient's sign,
+        // scaled by the running average's bias for RMSProp
+        let rms_prop_scale: f64 = LEARNING_RATE / (1.0 - DEFAULT_RMSPROP_DECAY).sqrt();
+        let cases: Vec<(Box<dyn Optimizer>, f64)> = vec![
+            (
+                Box::new(Adam::new(LEARNING_RATE, DEFAULT_BETA1, DEFAULT_BETA2)),
+                LEARNING_RATE,
+            ),
+            (
+                Box::new(RMSProp::new(LEARNING_RATE, DEFAULT_RMSPROP_DECAY)),
+                rms_prop_scale,
+            ),
+            (Box::new(AdaGrad::new(LEARNING_RATE)), LEARNING_RATE),
+        ];
+        for (mut optimizer, scale) in cases {
+            let weights: Vec<Array2<f64>> = train(optimizer.as_mut(), 1);
+            assert_close(&weights[0], &array![[-scale, scale]]);
+        }
+    }
+
+    #[test]
+    fn adam_keeps_stepping_past_u16_time_steps() {
+        // Training takes one step per minibatch, so long runs (or online
+        // learning one sample at a time) go past 65,535 steps
+        let steps: usize = u16::MAX as usize + 10;
+        let mut adam = Adam::new(LEARNING_RATE, DEFAULT_BETA1, DEFAULT_BETA2);
+        let mut adam_w = AdamW::new(LEARNING_RATE, DEFAULT_BETA1, DEFAULT_BETA2, 0.01);
+        let optimizers: [&mut dyn Optimizer; 2] = [&mut adam, &mut adam_w];
+        for optimizer in optimizers {
+            let mut layers: Vec<Layer> = vec![layer(array![[0.0, 0.0]])];
+            for _ in 0..steps {
+                backprop(&mut layers[0]);
+                optimizer.update(&mut layers, 1);
+            }
+            assert!(layers[0].snapshot().weights.iter().all(|w| w.is_finite()));
+            assert_eq!(optimizer.hyperparameters()["time_step"], steps as u64);
+        }
+    }
+
+    #[test]
+    fn ada_grad_steps_shrink_as_gradients_accumulate() {
+        let weights: Vec<Array2<f64>> = train(&mut AdaGrad::new(LEARNING_RATE), 2);
+        let total: f64 = LEARNING_RATE * (1.0 + 1.0 / 2f64.sqrt());
+        assert_close(&weights[1], &array![[-total, total]]);
+    }
+
+    #[test]
+    fn adam_w_decays_weights_without_a_gradient() {
+        let mut optimizer = AdamW::new(LEARNING_RATE, DEFAULT_BETA1, DEFAULT_BETA2, 0.5);
+        let mut layers: Vec<Layer> = vec![layer(array![[1.0, -2.0]])];
+        layers[0].inputs = array![[1.0], [-2.0]];
+        layers[0].deltas = array![[0.0]];
+        optimizer.update(&mut layers, 1);
+        assert_close(&layers[0].snapshot().weights, &array![[0.95, -1.9]]);
+    }
+
+    #[test]
+    fn batch_norm_parameters_are_stepped_by_the_optimizer() {
+        let mut optimizer = Adam::new(LEARNING_RATE, DEFAULT_BETA1, DEFAULT_BETA2);
+        let mut layers: Vec<Layer> = vec![layer(array![[0.0, 0.0]]), layer(array![[1.0, -1.0]])];
+        layers[1].enable_batch_norm(BatchNorm::new(1));
+        let inputs: Array2<f64> = array![[1.0, 2.0, 0.5], [-1.0, 0.0, 3.0]];
+        layers[0].feed_forward(&inputs);
+        layers[1].feed_forward(&inputs);
+        for layer in layers.iter_mut() {
+            layer.back_prop_with_deltas(&array![[1.0, -0.5, 0.25]]);
+        }
+        optimizer.update(&mut layers, 3);
+
+        // Only the layer with batch normalization has state for it,
+        // while the first layer keeps an empty placeholder
+        assert_eq!(optimizer.batch_norm_moments[0].len(), 0);
+        assert_eq!(optimizer.batch_norm_moments[1].dim(), (1, 2));
+        let gradient: Array2<f64> = layers[1].batch_norm_gradient().unwrap();
+        assert_eq!(optimizer.state_len(), 2 * 2 + 2 * 2 + 2 * gradient.len());
+
+        // State saved with batch normalization is restored by a fresh optimizer
+        let mut restored = Adam::new(LEARNING_RATE, DEFAULT_BETA1, DEFAULT_BETA2);
+        restored.load_state(&optimizer.state()).unwrap();
+        assert_eq!(restored.state_len(), optimizer.state_len());
+        assert_eq!(
+            restored.batch_norm_velocities,
+            optimizer.batch_norm_velocities
+        );
+    }
+
+    #[test]
+    fn batch_norm_state_is_only_saved_when_used() {
+        let mut optimizer = RMSProp::new(LEARNING_RATE, DEFAULT_RMSPROP_DECAY);
+        train(&mut optimizer, 1);
+        let state: Map<String, Value> = optimizer.state();
+        assert!(state.contains_key("velocities"));
+        assert!(!state.contains_key("batch_norm_velocities"));
+        assert_eq!(optimizer.hyperparameters()["decay"], DEFAULT_RMSPROP_DECAY);
     }
 }
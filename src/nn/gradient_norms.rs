@@ -0,0 +1,140 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Options for tracking gradient norms during training
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct GradientNormConfig {
+    /// Largest global gradient norm a training step can have before
+    /// training halts with an error (never halts when `None`)
+    pub max_norm: Option<f64>,
+}
+
+/// Training step whose global gradient norm exceeded the configured maximum
+#[derive(Serialize, Debug, Clone)]
+pub struct GradientExplosion {
+    /// Epoch the step was part of (starting at 1)
+    pub epoch: usize,
+
+    /// Step (minibatch) within the epoch (starting at 1)
+    pub step: usize,
+
+    /// Global L2 norm of the step's gradients
+    pub norm: f64,
+
+    /// Configured maximum norm
+    pub max_norm: f64,
+
+    /// L2 norm of each Layer's gradients during the step
+    pub layer_norms: Vec<f64>,
+}
+
+impl fmt::Display for GradientExplosion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let layer_norms: Vec<String> = self
+            .layer_norms
+            .iter()
+            .map(|norm| format!("{norm:.4e}"))
+            .collect();
+        write!(
+            f,
+            "Gradient norm {:.4e} exceeded the maximum of {} at epoch {}, step {} \
+             (layer norms: [{}]); training is likely diverging, try a lower learning rate",
+            self.norm,
+            self.max_norm,
+            self.epoch,
+            self.step,
+            layer_norms.join(", ")
+        )
+    }
+}
+
+/// Largest gradient norms of each training step within the current
+/// epoch, and the first step that exceeded the maximum norm (if any)
+#[derive(Debug, Clone)]
+pub struct GradientNorms {
+    /// Largest global norm a step can have before training halts
+    max_norm: Option<f64>,
+
+    /// Largest global norm of the current epoch's steps
+    epoch_norm: f64,
+
+    /// Largest norm of each Layer over the current epoch's steps
+    epoch_layer_norms: Vec<f64>,
+
+    /// First step whose global norm exceeded the maximum
+    explosion: Option<GradientExplosion>,
+}
+
+impl GradientNorms {
+    /// # Arguments
+    ///
+    /// * `config` - Largest global norm before training halts
+    pub fn new(config: GradientNormConfig) -> Self {
+        Self {
+            max_norm: config.max_norm,
+            epoch_norm: 0.0,
+            epoch_layer_norms: vec![],
+            explosion: None,
+        }
+    }
+
+    /// Reset the largest norms at the start of an epoch
+    pub fn start_epoch(&mut self) {
+        self.epoch_norm = 0.0;
+        self.epoch_layer_norms.clear();
+    }
+
+    /// Record the gradient norms of a single training step
+    ///
+    /// # Arguments
+    ///
+    /// * `epoch` - Epoch the step is part of (starting at 1)
+    /// * `step` - Step within the epoch (starting at 1)
+    /// * `layer_norms` - L2 norm of each Layer's gradients
+    ///
+    /// Returns whether or not the global norm exceeded the maximum
+    pub fn record(&mut self, epoch: usize, step: usize, layer_norms: Vec<f64>) -> bool {
+        let norm: f64 = layer_norms
+            .iter()
+            .map(|norm| norm * norm)
+            .sum::<f64>()
+            .sqrt();
+        self.epoch_norm = self.epoch_norm.max(norm);
+        if self.epoch_layer_norms.len() < layer_norms.len() {
+            self.epoch_layer_norms.resize(layer_norms.len(), 0.0);
+        }
+        for (epoch_layer_norm, layer_norm) in self.epoch_layer_norms.iter_mut().zip(&layer_norms) {
+            *epoch_layer_norm = epoch_layer_norm.max(*layer_norm);
+        }
+
+        // NaN norms (from overflowing values) always halt training
+        match self.max_norm {
+            Some(max_norm) if norm > max_norm || norm.is_nan() => {
+                self.explosion = Some(GradientExplosion {
+                    epoch,
+                    step,
+                    norm,
+                    max_norm,
+                    layer_norms,
+                });
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Largest global norm of the current epoch's steps
+    pub fn epoch_norm(&self) -> f64 {
+        self.epoch_norm
+    }
+
+    /// Largest norm of each Layer over the current epoch's steps
+    pub fn epoch_layer_norms(&self) -> &[f64] {
+        &self.epoch_layer_norms
+    }
+
+    /// Remove and return the step that exceeded the maximum norm (if any)
+    pub fn take_explosion(&mut self) -> Option<GradientExplosion> {
+        self.explosion.take()
+    }
+}
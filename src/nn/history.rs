@@ -0,0 +1,32 @@
+use serde::Serialize;
+
+/// Summary of the Network's progress at the end of a single epoch
+#[derive(Serialize, Debug, Clone)]
+pub struct EpochLog {
+    /// Epoch that has just finished (starting at 1)
+    pub epoch: usize,
+
+    /// Mean training loss (cost) over the epoch's minibatches, each
+    /// computed before the minibatch's update
+    pub loss: f64,
+
+    /// L1/L2 regularization penalty of the weights at the end of the epoch
+    /// (0 without regularization), reported separately from `loss`
+    pub regularization: f64,
+
+    /// Value of the early stopping metric on the validation set
+    pub metric: f32,
+
+    /// Time since training started (in seconds)
+    pub elapsed_time: f32,
+
+    /// Largest global L2 norm of the epoch's gradient steps
+    /// (only present when gradient norms are tracked)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gradient_norm: Option<f64>,
+
+    /// Largest L2 norm of each Layer's gradients over the epoch's
+    /// steps (only present when gradient norms are tracked)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layer_gradient_norms: Option<Vec<f64>>,
+}
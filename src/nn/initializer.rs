@@ -0,0 +1,91 @@
+use ndarray::Array2;
+use ndarray_rand::rand_distr::Normal;
+use ndarray_rand::RandomExt;
+use rand::distributions::Uniform;
+use rand::rngs::StdRng;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Scheme used to draw a Layer's initial weights and biases
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Initializer {
+    /// Weights and biases drawn uniformly from [-0.5, 0.5), with
+    /// weights scaled down by the square root of the input samples
+    Uniform,
+
+    /// Weights drawn from a normal distribution with a variance of
+    /// 2 / fan-in, suited to ReLU-family activations (biases start at 0)
+    #[serde(alias = "kaiming")]
+    He,
+
+    /// Weights drawn uniformly with a variance of 2 / (fan-in + fan-out),
+    /// suited to sigmoid and tanh activations (biases start at 0)
+    #[serde(alias = "glorot")]
+    Xavier,
+}
+
+impl Initializer {
+    /// Default scheme for an activation function: He for the
+    /// ReLU family, and Xavier for everything else
+    ///
+    /// # Arguments
+    ///
+    /// * `activation` - Name of the activation function
+    pub fn for_activation(activation: &str) -> Initializer {
+        match activation {
+            "relu" | "leaky_relu" | "elu" | "swish" => Initializer::He,
+            _ => Initializer::Xavier,
+        }
+    }
+
+    /// Name of the scheme, as written in the network JSON
+    pub fn name(&self) -> &str {
+        match self {
+            Initializer::Uniform => "uniform",
+            Initializer::He => "he",
+            Initializer::Xavier => "xavier",
+        }
+    }
+
+    /// Draw the initial weights and biases of a Layer
+    ///
+    /// # Arguments
+    ///
+    /// * `neurons` - Number of neurons (fan-out)
+    /// * `input_shape` - Size of the input vector (fan-in) and number of samples
+    /// * `rng` - Random number generator the values are drawn from
+    pub fn initialize(
+        &self,
+        neurons: usize,
+        input_shape: (usize, usize),
+        rng: &mut StdRng,
+    ) -> (Array2<f64>, Array2<f64>) {
+        let fan_in: f64 = input_shape.0.max(1) as f64;
+        let fan_out: f64 = neurons.max(1) as f64;
+        let weights_shape: (usize, usize) = (neurons, input_shape.0);
+
+        match self {
+            Initializer::Uniform => {
+                let distribution: Uniform<f64> = Uniform::new(-0.5, 0.5);
+                let weights: Array2<f64> = Array2::random_using(weights_shape, distribution, rng);
+                // Scaling the weights by the sqrt of the number of nodes
+                // helps to reduce the problem of disappearing gradient
+                let weights: Array2<f64> = weights / f64::sqrt(input_shape.1 as f64);
+                let biases: Array2<f64> = Array2::random_using((neurons, 1), distribution, rng);
+                (weights, biases)
+            }
+            Initializer::He => {
+                let distribution: Normal<f64> = Normal::new(0.0, (2.0 / fan_in).sqrt()).unwrap();
+                let weights: Array2<f64> = Array2::random_using(weights_shape, distribution, rng);
+                (weights, Array2::zeros((neurons, 1)))
+            }
+            Initializer::Xavier => {
+                let limit: f64 = (6.0 / (fan_in + fan_out)).sqrt();
+                let distribution: Uniform<f64> = Uniform::new(-limit, limit);
+                let weights: Array2<f64> = Array2::random_using(weights_shape, distribution, rng);
+                (weights, Array2::zeros((neurons, 1)))
+            }
+        }
+    }
+}
@@ -1,6 +1,11 @@
+use super::activations::DeadNeuronReinit;
+use super::batch_norm::BatchNorm;
 use super::functions::activation::ActivationFn;
-use ndarray::{Array1, Array2, Axis};
-use ndarray_rand::RandomExt;
+use super::initializer::Initializer;
+use super::regularization::Regularization;
+use super::stats::{self, Histogram, LayerHistogram, LayerSnapshot, LayerStats, ValueStats};
+use ndarray::linalg::general_mat_mul;
+use ndarray::{Array2, Axis};
 use rand::distributions::{Distribution, Uniform};
 use rand::prelude::*;
 use serde::ser::{Serialize, SerializeStruct, Serializer};
@@ -11,7 +16,7 @@ pub struct Layer {
     /// Delta values computed using the first derivative of
     /// the Layer's activation function during backprop. Used
     /// to compute the gradient during the update stage
-    pub deltas: Option<Array2<f64>>,
+    pub deltas: Array2<f64>,
 
     /// Input vector recorded during the feed-forward process
     pub inputs: Array2<f64>,
@@ -27,7 +32,15 @@ pub struct Layer {
     biases: Array2<f64>,
 
     /// Activation values: (weights dot inputs) + biases
-    activations: Option<Array2<f64>>,
+    activations: Array2<f64>,
+
+    /// Outputs of the activation function recorded
+    /// during the feed-forward process
+    outputs: Array2<f64>,
+
+    /// First derivative of the activation function w.r.t.
+    /// the activation values, computed during backprop
+    primes: Array2<f64>,
 
     /// Function that determines the activation of individual neurons
     activation_fn: Box<dyn ActivationFn>,
@@ -38,6 +51,21 @@ pub struct Layer {
     /// Row indices of neurons that have been dropped out
     /// temporarily during training
     dropped_neurons: Vec<usize>,
+
+    /// Random number generator that chooses dropped neurons
+    rng: StdRng,
+
+    /// Scheme the initial weights and biases were drawn with
+    /// (unknown for Layers reconstructed from trained parameters)
+    initializer: Option<Initializer>,
+
+    /// How often dead neurons are re-initialized during
+    /// training (never when `None`)
+    dead_reinit: Option<DeadNeuronReinit>,
+
+    /// Normalization of the activation values before the
+    /// activation function is applied (disabled when `None`)
+    batch_norm: Option<BatchNorm>,
 }
 
 impl Layer {
@@ -53,89 +81,336 @@ impl Layer {
         activation_fn: Box<dyn ActivationFn>,
         dropout: Option<f32>,
     ) -> Layer {
-        // Weights and biases are initialized randomly
-        // in the range [-0.5, 0.5)
-        let distribution: Uniform<f64> = Uniform::new(-0.5, 0.5);
-
-        // Create weights matrix
-        let weights: Array2<f64> = Array2::random((neurons, input_shape.0), distribution);
-        // Scaling the weights by the sqrt of the number of nodes
-        // helps to reduce the problem of disappearing gradient
-        let weights: Array2<f64> = weights / f64::sqrt(input_shape.1 as f64);
+        let mut rng: StdRng = StdRng::from_entropy();
+        Layer::with_rng(neurons, input_shape, activation_fn, dropout, None, &mut rng)
+    }
 
-        // Create biases matrix
-        let biases: Array2<f64> = Array2::random((neurons, 1), distribution);
+    /// Same as `new`, but the initial weights and the Layer's dropout
+    /// stream are drawn from the given random number generator, so
+    /// seeded networks are reproducible
+    ///
+    /// # Arguments
+    ///
+    /// * `neurons` - Number of neurons, determines how many weights/biases are present
+    /// * `inputs` - Size of expected input vector
+    /// * `activation_fn` - Function that determines the activation of individual neurons
+    /// * `dropout` - Optional rate for randomly excluding neurons during each training cycle
+    /// * `initializer` - Scheme the initial weights are drawn with, chosen
+    ///   from the activation function when `None` (He for the ReLU family,
+    ///   Xavier otherwise)
+    /// * `rng` - Random number generator used for initialization
+    pub fn with_rng(
+        neurons: usize,
+        input_shape: (usize, usize),
+        activation_fn: Box<dyn ActivationFn>,
+        dropout: Option<f32>,
+        initializer: Option<Initializer>,
+        rng: &mut StdRng,
+    ) -> Layer {
+        let initializer: Initializer =
+            initializer.unwrap_or_else(|| Initializer::for_activation(activation_fn.name()));
+        let (weights, biases) = initializer.initialize(neurons, input_shape, rng);
 
         // Stored inputs initialized to zero
         let inputs: Array2<f64> = Array2::zeros(input_shape);
 
+        // Feed forward and backprop buffers are allocated once here and
+        // reused on every training cycle (shape: neurons x samples)
+        let buffer_shape: (usize, usize) = (neurons, input_shape.1);
+
         Layer {
-            deltas: None,
+            deltas: Array2::zeros(buffer_shape),
             inputs,
             neurons,
             weights,
             biases,
-            activations: None,
+            activations: Array2::zeros(buffer_shape),
+            outputs: Array2::zeros(buffer_shape),
+            primes: Array2::zeros(buffer_shape),
             activation_fn,
             dropout,
             dropped_neurons: vec![],
+            rng: StdRng::seed_from_u64(rng.gen()),
+            initializer: Some(initializer),
+            dead_reinit: None,
+            batch_norm: None,
         }
     }
 
-    /// Feedforward step for an individual Layer. Used for predicting outputs from a given input
+    /// Feedforward step for an individual Layer. Used for predicting outputs from a given input.
+    /// Results are written into the Layer's preallocated buffers
     ///
     /// # Arguments
     ///
     /// * `inputs` - Matrix of input vectors (outputs from previous layer)
-    pub fn feed_forward(&mut self, inputs: &Array2<f64>) -> Array2<f64> {
-        let activations: Array2<f64> = self.weights.dot(inputs) + &self.biases;
-        let outputs: Array2<f64> = self.activation_fn.call(&activations);
+    pub fn feed_forward(&mut self, inputs: &Array2<f64>) -> &Array2<f64> {
+        let buffer_shape: (usize, usize) = (self.neurons, inputs.ncols());
+        resize_buffer(&mut self.activations, buffer_shape);
+        resize_buffer(&mut self.outputs, buffer_shape);
+        resize_buffer(&mut self.inputs, inputs.dim());
 
-        self.inputs = inputs.clone();
-        self.activations = Some(activations);
+        // activations = (weights dot inputs) + biases
+        general_mat_mul(1.0, &self.weights, inputs, 0.0, &mut self.activations);
+        self.activations += &self.biases;
+        if let Some(batch_norm) = &mut self.batch_norm {
+            batch_norm.normalize(&mut self.activations);
+        }
+        self.activation_fn
+            .call_into(&self.activations, &mut self.outputs);
 
-        match self.dropout {
-            Some(dropout) => {
-                self.dropped_neurons.clear();
-                self.map_output_to_dropout(outputs, dropout)
-            }
-            None => outputs,
+        self.inputs.assign(inputs);
+
+        if let Some(dropout) = self.dropout {
+            self.dropped_neurons.clear();
+            self.map_output_to_dropout(dropout);
+        }
+        &self.outputs
+    }
+
+    /// Reconstruct a previously trained Layer from its weights and biases.
+    /// Dropout is disabled, and buffers are allocated on the first feedforward pass
+    ///
+    /// # Arguments
+    ///
+    /// * `weights` - Matrix of weights (shape: neurons x inputs)
+    /// * `biases` - Vector of bias offsets (shape: neurons x 1)
+    /// * `activation_fn` - Function that determines the activation of individual neurons
+    pub fn from_parameters(
+        weights: Array2<f64>,
+        biases: Array2<f64>,
+        activation_fn: Box<dyn ActivationFn>,
+    ) -> Layer {
+        let neurons: usize = weights.nrows();
+        let buffer_shape: (usize, usize) = (neurons, 0);
+
+        Layer {
+            deltas: Array2::zeros(buffer_shape),
+            inputs: Array2::zeros((weights.ncols(), 0)),
+            neurons,
+            weights,
+            biases,
+            activations: Array2::zeros(buffer_shape),
+            outputs: Array2::zeros(buffer_shape),
+            primes: Array2::zeros(buffer_shape),
+            activation_fn,
+            dropout: None,
+            dropped_neurons: vec![],
+            rng: StdRng::from_entropy(),
+            initializer: None,
+            dead_reinit: None,
+            batch_norm: None,
         }
     }
 
+    /// Replace the Layer's weights and biases with those of a trained
+    /// Layer of the same shape (e.g. to resume training)
+    ///
+    /// # Arguments
+    ///
+    /// * `trained` - Layer whose weights and biases are copied
+    pub fn load_parameters(&mut self, trained: &Layer) {
+        self.weights.assign(&trained.weights);
+        self.biases.assign(&trained.biases);
+        if let (Some(batch_norm), Some(trained)) = (&mut self.batch_norm, &trained.batch_norm) {
+            batch_norm.load_parameters(trained);
+        }
+    }
+
+    /// Normalize the Layer's activation values (before the activation
+    /// function is applied) with learnable scale and shift parameters
+    ///
+    /// # Arguments
+    ///
+    /// * `batch_norm` - New or previously trained batch normalization,
+    ///   shaped for the Layer's number of neurons
+    pub fn enable_batch_norm(&mut self, batch_norm: BatchNorm) {
+        self.batch_norm = Some(batch_norm);
+    }
+
+    /// Restart the stream that chooses dropped neurons from a new seed
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - Seed of the Layer's dropout stream
+    pub fn reseed_dropout(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    /// Batch normalization of the activation values (if enabled)
+    pub fn batch_norm(&self) -> Option<&BatchNorm> {
+        self.batch_norm.as_ref()
+    }
+
+    /// Periodically re-initialize the Layer's dead neurons during training
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Epochs between checks and the dead activation threshold
+    pub fn enable_dead_reinit(&mut self, config: DeadNeuronReinit) {
+        self.dead_reinit = Some(config);
+    }
+
+    /// How often dead neurons are re-initialized (if enabled)
+    pub fn dead_reinit(&self) -> Option<DeadNeuronReinit> {
+        self.dead_reinit
+    }
+
+    /// Draw fresh incoming weights and biases for the given neurons, using
+    /// the Layer's initialization scheme (picked from the activation
+    /// function when unknown). Every other neuron is left untouched
+    ///
+    /// # Arguments
+    ///
+    /// * `indices` - Row indices of the neurons to re-initialize
+    /// * `rng` - Random number generator the values are drawn from
+    pub fn reinitialize_neurons(&mut self, indices: &[usize], rng: &mut StdRng) {
+        if indices.is_empty() {
+            return;
+        }
+        let initializer: Initializer = self
+            .initializer
+            .unwrap_or_else(|| Initializer::for_activation(self.activation_fn.name()));
+        let input_shape: (usize, usize) = (self.weights.ncols(), self.inputs.ncols().max(1));
+        let (weights, biases) = initializer.initialize(self.neurons, input_shape, rng);
+        for &neuron in indices {
+            self.weights.row_mut(neuron).assign(&weights.row(neuron));
+            self.biases.row_mut(neuron).assign(&biases.row(neuron));
+        }
+    }
+
+    /// Shape of the Layer's weights matrix (neurons x inputs)
+    pub fn shape(&self) -> (usize, usize) {
+        self.weights.dim()
+    }
+
+    /// Name of the Layer's activation function
+    pub fn activation_name(&self) -> &str {
+        self.activation_fn.name()
+    }
+
+    /// Size of the Layer's expected input vector
+    pub fn input_size(&self) -> usize {
+        self.weights.ncols()
+    }
+
+    /// Number of trainable values (weights, biases, and
+    /// any batch normalization parameters)
+    pub fn num_parameters(&self) -> usize {
+        let batch_norm_parameters: usize = self
+            .batch_norm
+            .as_ref()
+            .map_or(0, BatchNorm::num_parameters);
+        self.weights.len() + self.biases.len() + batch_norm_parameters
+    }
+
+    /// Estimated floating point operations needed to feed one sample forward:
+    /// a multiply and an add for each weight, one operation for each bias
+    /// and activation, and four more per neuron when batch normalized
+    pub fn forward_flops(&self) -> usize {
+        let (neurons, inputs) = self.shape();
+        let batch_norm_flops: usize = if self.batch_norm.is_some() {
+            4 * neurons
+        } else {
+            0
+        };
+        2 * neurons * inputs + 2 * neurons + batch_norm_flops
+    }
+
+    /// Number of values held in the Layer's feed forward and backprop buffers
+    pub fn buffer_len(&self) -> usize {
+        self.deltas.len()
+            + self.inputs.len()
+            + self.activations.len()
+            + self.outputs.len()
+            + self.primes.len()
+    }
+
+    /// Output values from the latest feedforward pass
+    pub fn outputs(&self) -> &Array2<f64> {
+        &self.outputs
+    }
+
     /// Same as `feed_forward`, but dropout isn't applied and internal values aren't
     /// saved. This function is meant to get predictions from a fully-trained network
     ///
     /// # Arguments
     ///
     /// * `inputs` - Matrix of input vectors (outputs from previous layer)
-    pub fn predict(&mut self, inputs: &Array2<f64>) -> Array2<f64> {
+    pub fn predict(&self, inputs: &Array2<f64>) -> Array2<f64> {
+        self.activation_fn.call(&self.predict_logits(inputs))
+    }
+
+    /// Same as `predict`, but the activation function isn't applied
+    /// (batch normalization uses the running statistics)
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - Matrix of input vectors (outputs from previous layer)
+    pub fn predict_logits(&self, inputs: &Array2<f64>) -> Array2<f64> {
         let activations: Array2<f64> = self.weights.dot(inputs) + &self.biases;
-        self.activation_fn.call(&activations)
+        match &self.batch_norm {
+            Some(batch_norm) => batch_norm.predict(&activations),
+            None => activations,
+        }
+    }
+
+    /// Gradient of a value with respect to the Layer's inputs, given that value's
+    /// gradient with respect to the Layer's outputs. Internal values aren't saved
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - Matrix of input vectors (outputs from previous layer)
+    /// * `output_gradient` - Gradient with respect to each of the Layer's outputs
+    /// * `temperature` - Value the logits are divided by before activation (see `activate_scaled`)
+    pub fn input_gradient(
+        &self,
+        inputs: &Array2<f64>,
+        output_gradient: &Array2<f64>,
+        temperature: f64,
+    ) -> Array2<f64> {
+        let logits: Array2<f64> = self.predict_logits(inputs) / temperature;
+        let mut deltas: Array2<f64> = output_gradient / temperature;
+        let mut primes: Array2<f64> = Array2::zeros(logits.raw_dim());
+        self.activation_fn
+            .back_prop_into(&logits, &mut deltas, &mut primes);
+        if let Some(batch_norm) = &self.batch_norm {
+            deltas = batch_norm.input_gradient(&deltas);
+        }
+        self.weights.t().dot(&deltas)
+    }
+
+    /// Apply the activation function to logits divided by a temperature
+    ///
+    /// # Arguments
+    ///
+    /// * `logits` - Matrix of activation values (output of `predict_logits`)
+    /// * `temperature` - Value each logit is divided by (1.0 has no effect)
+    pub fn activate_scaled(&self, logits: &Array2<f64>, temperature: f64) -> Array2<f64> {
+        self.activation_fn.call(&(logits / temperature))
     }
 
     /// Randomly choose dropped neurons for the current training cycle and
-    /// change the respective output vectors to zeroed vectors of the same size
+    /// change the respective output vectors to zeroed vectors of the same size.
+    /// Outputs of the remaining neurons are scaled by 1 / (1 - dropout)
+    /// (inverted dropout), so their expected value matches prediction,
+    /// where no neurons are dropped
     ///
     /// # Arguments
     ///
-    /// * `outputs` - Matrix of output vectors from last feedforward pass for
-    /// the current layer
     /// * `dropout` - Rate at which neurons are dropped during training
-    fn map_output_to_dropout(&mut self, mut outputs: Array2<f64>, dropout: f32) -> Array2<f64> {
+    fn map_output_to_dropout(&mut self, dropout: f32) {
         let range: Uniform<f32> = Uniform::new(0.0, 1.0);
-        let zeros: Array1<f64> = Array1::zeros(outputs.ncols());
-
-        let mut rng = thread_rng();
+        let scale: f64 = dropout_scale(dropout);
 
-        for (i, mut row) in outputs.axis_iter_mut(Axis(0)).enumerate() {
-            let sample: f32 = range.sample(&mut rng);
+        for (i, mut row) in self.outputs.axis_iter_mut(Axis(0)).enumerate() {
+            let sample: f32 = range.sample(&mut self.rng);
             if sample < dropout {
                 self.dropped_neurons.push(i);
-                row.assign(&zeros);
+                row.fill(0.0);
+            } else {
+                row *= scale;
             }
         }
-        outputs
     }
 
     /// Backpropogation step where the deltas for each layer are calculated
@@ -143,18 +418,20 @@ impl Layer {
     ///
     /// # Arguments
     ///
-    /// * `actual` - The predicted output produced by the network
-    /// * `target` - The expected output value
     /// * `attached_layer` - The next layer in the network
-    /// * `cost` - The cost or loss function associated with the
-    /// training setup
     pub fn back_prop(&mut self, attached_layer: &Layer) {
-        let attached_deltas: &Array2<f64> = match &attached_layer.deltas {
-            Some(attached_deltas) => attached_deltas,
-            None => panic!("Deltas not calculated for attached layer"),
-        };
-        let next_deltas: Array2<f64> = attached_layer.weights.t().dot(attached_deltas);
-        self.back_prop_with_deltas(&next_deltas);
+        let buffer_shape: (usize, usize) = (self.neurons, attached_layer.deltas.ncols());
+        resize_buffer(&mut self.deltas, buffer_shape);
+
+        // deltas = (attached weights)^T dot (attached deltas)
+        general_mat_mul(
+            1.0,
+            &attached_layer.weights.t(),
+            &attached_layer.deltas,
+            0.0,
+            &mut self.deltas,
+        );
+        self.apply_primes();
     }
 
     /// Computes current layer's delta values from attached layer's deltas
@@ -162,35 +439,174 @@ impl Layer {
     /// # Arguments
     ///
     /// * `attached_deltas` - Attached layer's deltas (assumed to have
-    /// already been computed)
+    ///   already been computed)
     pub fn back_prop_with_deltas(&mut self, attached_deltas: &Array2<f64>) {
-        let activations: &Array2<f64> = match &self.activations {
-            Some(activations) => activations,
-            None => panic!("Error: back prop run before feed forward"),
-        };
-        let deltas: Array2<f64> = self.activation_fn.prime(activations) * attached_deltas;
-        self.deltas = Some(deltas);
+        resize_buffer(&mut self.deltas, attached_deltas.dim());
+        self.deltas.assign(attached_deltas);
+        self.apply_primes();
+    }
+
+    /// Same as `back_prop_with_deltas`, but the deltas are already taken
+    /// with respect to the Layer's logits, so the derivative of the
+    /// activation function isn't applied
+    ///
+    /// # Arguments
+    ///
+    /// * `logit_deltas` - Gradient of the cost with respect to the Layer's logits
+    pub fn back_prop_with_logit_deltas(&mut self, logit_deltas: &Array2<f64>) {
+        resize_buffer(&mut self.deltas, logit_deltas.dim());
+        self.deltas.assign(logit_deltas);
+        self.drop_deltas();
+        self.normalize_deltas();
+    }
+
+    /// Gradient of the cost with respect to the Layer's inputs for the most
+    /// recent backprop step, i.e. the deltas the previous layer would receive
+    pub fn input_deltas(&self) -> Array2<f64> {
+        self.weights.t().dot(&self.deltas)
+    }
+
+    /// Backpropagates the current deltas through the derivative of the
+    /// activation function, then removes the deltas of dropped neurons
+    fn apply_primes(&mut self) {
+        resize_buffer(&mut self.primes, self.activations.dim());
+        self.activation_fn
+            .back_prop_into(&self.activations, &mut self.deltas, &mut self.primes);
+
         self.drop_deltas();
+        self.normalize_deltas();
     }
 
-    /// Remove deltas relative to which neurons have been dropped
-    /// during the latest training cycle
+    /// Remove deltas relative to which neurons have been dropped during
+    /// the latest training cycle, and scale the rest like their outputs
     fn drop_deltas(&mut self) {
-        let deltas: &mut Array2<f64> = match &mut self.deltas {
-            Some(deltas) => deltas,
-            None => panic!("Can't drop deltas if deltas haven't been calculated"),
-        };
-        match self.dropout {
-            Some(_) => {
-                let zeros: Array1<f64> = Array1::zeros(deltas.ncols());
-                for dropped_neuron in self.dropped_neurons.iter() {
-                    deltas.row_mut(*dropped_neuron).assign(&zeros);
-                }
+        if let Some(dropout) = self.dropout {
+            self.deltas *= dropout_scale(dropout);
+            for dropped_neuron in self.dropped_neurons.iter() {
+                self.deltas.row_mut(*dropped_neuron).fill(0.0);
             }
-            None => {}
         }
     }
 
+    /// Convert the deltas (taken with respect to the normalized activation
+    /// values) into deltas with respect to the activation values, when
+    /// batch normalization is enabled
+    fn normalize_deltas(&mut self) {
+        if let Some(batch_norm) = &mut self.batch_norm {
+            batch_norm.back_prop(&mut self.deltas);
+        }
+    }
+
+    /// Gradient of the batch normalization parameters (gamma and beta,
+    /// shape: neurons x 2) from the most recent backprop step, or `None`
+    /// when batch normalization isn't enabled
+    pub fn batch_norm_gradient(&self) -> Option<Array2<f64>> {
+        self.batch_norm.as_ref().and_then(BatchNorm::gradient)
+    }
+
+    /// Adjusts the batch normalization parameters (if enabled) based on
+    /// the step calculated by the optimizer from `batch_norm_gradient`
+    ///
+    /// # Arguments
+    ///
+    /// * `step` - Change in gamma (first column) and beta (second column)
+    pub fn step_batch_norm(&mut self, step: &Array2<f64>) {
+        if let Some(batch_norm) = &mut self.batch_norm {
+            batch_norm.step(step);
+        }
+    }
+
+    /// Combined L2 norm of the weight and bias gradients
+    /// from the most recent backprop step
+    pub fn gradient_norm(&self) -> f64 {
+        let weight_gradient: Array2<f64> = self.deltas.dot(&self.inputs.t());
+        let bias_gradient: Array2<f64> = self.deltas.sum_axis(Axis(1)).insert_axis(Axis(1));
+        (stats::l2_norm(&weight_gradient).powi(2) + stats::l2_norm(&bias_gradient).powi(2)).sqrt()
+    }
+
+    /// Weight statistics and the gradient norms of the most recent minibatch
+    pub fn stats(&self) -> LayerStats {
+        // Same (unscaled) gradients the optimizers compute from the deltas
+        let weight_gradient: Array2<f64> = self.deltas.dot(&self.inputs.t());
+        let bias_gradient: Array2<f64> = self.deltas.sum_axis(Axis(1)).insert_axis(Axis(1));
+
+        LayerStats {
+            weights: ValueStats::new(&self.weights),
+            biases: ValueStats::new(&self.biases),
+            weight_gradient_norm: stats::l2_norm(&weight_gradient),
+            bias_gradient_norm: stats::l2_norm(&bias_gradient),
+        }
+    }
+
+    /// Copy of the current weights and biases
+    pub fn snapshot(&self) -> LayerSnapshot {
+        LayerSnapshot {
+            weights: self.weights.clone(),
+            biases: self.biases.clone(),
+        }
+    }
+    /// Histograms of the current weights and biases
+    ///
+    /// # Arguments
+    ///
+    /// * `bins` - Number of bins of each histogram
+    pub fn histogram(&self, bins: usize) -> LayerHistogram {
+        LayerHistogram {
+            weights: Histogram::new(&self.weights, bins),
+            biases: Histogram::new(&self.biases, bins),
+        }
+    }
+
+    /// Move the weights and biases a fraction of the way
+    /// towards another Layer's (Polyak averaging)
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - Layer with the same shape whose values are blended in
+    /// * `tau` - Fraction of the way the values move
+    pub fn blend(&mut self, source: &Layer, tau: f64) {
+        self.weights.zip_mut_with(&source.weights, |value, source| {
+            *value += tau * (source - *value)
+        });
+        self.biases.zip_mut_with(&source.biases, |value, source| {
+            *value += tau * (source - *value)
+        });
+        if let (Some(batch_norm), Some(source)) = (&mut self.batch_norm, &source.batch_norm) {
+            batch_norm.blend(source, tau);
+        }
+    }
+
+    /// Take a gradient step on the L1 and L2 penalties of the weights.
+    /// Biases aren't regularized
+    ///
+    /// # Arguments
+    ///
+    /// * `regularization` - Strength of the L1 and L2 penalties
+    /// * `learning_rate` - Step size of the optimizer
+    pub fn regularize(&mut self, regularization: &Regularization, learning_rate: f64) {
+        let gradient: Array2<f64> = regularization.gradient(&self.weights);
+        self.weights.scaled_add(-learning_rate, &gradient);
+    }
+
+    /// Penalty of the current weights
+    ///
+    /// # Arguments
+    ///
+    /// * `regularization` - Strength of the L1 and L2 penalties
+    pub fn regularization_penalty(&self, regularization: &Regularization) -> f64 {
+        regularization.penalty(&self.weights)
+    }
+
+    /// Shrink every weight by a fraction of itself (decoupled weight decay).
+    /// Biases are left as they are
+    ///
+    /// # Arguments
+    ///
+    /// * `decay` - Fraction of each weight that's removed
+    pub fn decay_weights(&mut self, decay: f64) {
+        self.weights.mapv_inplace(|weight| weight * (1.0 - decay));
+    }
+
     /// Adjusts the weights and biases based on deltas calculated during gradient descent
     ///
     /// # Arguments
@@ -214,16 +630,56 @@ impl Layer {
     }
 }
 
+/// Reallocate a buffer only when its shape doesn't match the expected
+/// shape (e.g. when the final minibatch of an epoch is smaller)
+///
+/// # Arguments
+///
+/// * `buffer` - Preallocated buffer to be resized
+/// * `shape` - Expected shape of the buffer
+fn resize_buffer(buffer: &mut Array2<f64>, shape: (usize, usize)) {
+    if buffer.dim() != shape {
+        *buffer = Array2::zeros(shape);
+    }
+}
+
+/// Factor the values that survive dropout are scaled by during training
+///
+/// # Arguments
+///
+/// * `rate` - Rate at which values are dropped (in [0, 1))
+pub(crate) fn dropout_scale(rate: f32) -> f64 {
+    1.0 / (1.0 - rate as f64)
+}
+
 impl Serialize for Layer {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        let mut s = serializer.serialize_struct("Layer", 2)?;
+        // Binary formats write the number of fields up front
+        let fields: usize = 3
+            + self.activation_fn.alpha().is_some() as usize
+            + self.batch_norm.is_some() as usize
+            + self.initializer.is_some() as usize;
+        let mut s = serializer.serialize_struct("Layer", fields)?;
 
-        // Only weights and biases are serialized
+        // Only weights, biases, and the activation function's
+        // name are serialized (enough to reconstruct the layer
+        // for predictions)
         s.serialize_field("weights", &self.weights)?;
         s.serialize_field("biases", &self.biases)?;
+        s.serialize_field("activation", self.activation_fn.name())?;
+        if let Some(alpha) = self.activation_fn.alpha() {
+            s.serialize_field("alpha", &alpha)?;
+        }
+        if let Some(batch_norm) = &self.batch_norm {
+            s.serialize_field("batch_norm", batch_norm)?;
+        }
+        // Metadata about how the layer was initialized
+        if let Some(initializer) = &self.initializer {
+            s.serialize_field("initializer", initializer.name())?;
+        }
         s.end()
     }
 }
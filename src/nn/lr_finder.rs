@@ -0,0 +1,221 @@
+use super::batches::{BatchConfig, Batches};
+use super::data_source::{self, DataSource};
+use super::functions::cost::Cost;
+use super::functions::encoder::Encoder;
+use super::functions::optimizer::Optimizer;
+use super::perceptron::Perceptron;
+use crate::error::OpenPbError;
+use ndarray::Array2;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::Serialize;
+
+/// Smoothing factor for the exponential moving average of the loss
+const LOSS_SMOOTHING: f64 = 0.98;
+
+/// Sweep stops once the smoothed loss exceeds the best loss by this factor
+const DIVERGENCE_FACTOR: f64 = 4.0;
+
+/// Range and length of a learning rate sweep
+#[derive(Debug, Clone, Copy)]
+pub struct LrFinderConfig {
+    /// Learning rate of the first training step
+    pub min_lr: f64,
+
+    /// Learning rate of the last training step
+    pub max_lr: f64,
+
+    /// Number of training steps (minibatches) in the sweep
+    pub steps: usize,
+
+    /// Seed of the minibatch shuffling (unseeded when `None`)
+    pub seed: Option<u64>,
+}
+
+impl Default for LrFinderConfig {
+    fn default() -> Self {
+        Self {
+            min_lr: 1e-6,
+            max_lr: 10.0,
+            steps: 100,
+            seed: None,
+        }
+    }
+}
+
+/// Recorded loss for every learning rate in the sweep
+#[derive(Serialize, Debug)]
+pub struct LrFinderResults {
+    /// Learning rate used for each training step
+    pub learning_rates: Vec<f64>,
+
+    /// Smoothed training loss before each training step
+    pub losses: Vec<f64>,
+
+    /// Learning rate where the loss decreased the fastest
+    pub suggested_lr: Option<f64>,
+}
+
+/// Train for a short number of steps while increasing the learning rate
+/// exponentially from `min_lr` to `max_lr`, recording the loss at each step.
+/// The sweep stops early if the loss diverges
+///
+/// # Arguments
+///
+/// * `network` - Freshly initialized network (its weights are modified by the sweep)
+/// * `data` - Source of training data
+/// * `optimizer` - Optimization method (its learning rate is overwritten each step)
+/// * `cost` - Loss function that's recorded at each step
+/// * `encoder` - Transforms output values to/from the network's output format
+/// * `batch_config` - Minibatch size, shuffling, and drop-last options
+/// * `config` - Learning rate range, number of steps, and seed
+pub fn find_learning_rate(
+    network: &mut Perceptron,
+    data: &dyn DataSource,
+    optimizer: &mut dyn Optimizer,
+    cost: &dyn Cost,
+    encoder: &dyn Encoder,
+    batch_config: BatchConfig,
+    config: LrFinderConfig,
+) -> Result<LrFinderResults, OpenPbError> {
+    // Learning rates grow geometrically, so the range must be positive and increasing
+    if !(config.min_lr > 0.0 && config.min_lr.is_finite()) {
+        return Err(format!(
+            "Minimum learning rate must be positive, got {}",
+            config.min_lr
+        )
+        .into());
+    }
+    if !(config.max_lr > config.min_lr && config.max_lr.is_finite()) {
+        return Err(format!(
+            "Maximum learning rate must be greater than the minimum ({}), got {}",
+            config.min_lr, config.max_lr
+        )
+        .into());
+    }
+
+    let (training_inputs, training_outputs) = data.training_set();
+    let input_rows: usize = training_inputs.nrows();
+    let expected: Array2<f64> = data_source::expected_outputs(data, encoder, &training_outputs);
+
+    // Each step multiplies the learning rate by the same factor
+    let steps: usize = config.steps.max(2);
+    let growth: f64 = (config.max_lr / config.min_lr).powf(1.0 / (steps - 1) as f64);
+
+    let mut rng: StdRng = match config.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let mut batches = Batches::new(&training_inputs, &expected, batch_config, &mut rng);
+
+    let mut learning_rates: Vec<f64> = Vec::with_capacity(steps);
+    let mut losses: Vec<f64> = Vec::with_capacity(steps);
+    let mut average_loss: f64 = 0.0;
+    let mut best_loss: f64 = f64::INFINITY;
+
+    for step in 0..steps {
+        // Start another pass over the training set when the batches run out
+        let (batch_inputs, batch_expected) = match batches.next() {
+            Some(batch) => batch,
+            None => {
+                batches = Batches::new(&training_inputs, &expected, batch_config, &mut rng);
+                match batches.next() {
+                    Some(batch) => batch,
+                    None => break,
+                }
+            }
+        };
+
+        let learning_rate: f64 = config.min_lr * growth.powi(step as i32);
+        optimizer.set_learning_rate(learning_rate);
+
+        // Bias-corrected exponential moving average of the loss
+        let loss: f64 = cost.call(&network.predict_raw(&batch_inputs), &batch_expected);
+        average_loss = LOSS_SMOOTHING * average_loss + (1.0 - LOSS_SMOOTHING) * loss;
+        let smoothed_loss: f64 = average_loss / (1.0 - LOSS_SMOOTHING.powi(step as i32 + 1));
+
+        if !smoothed_loss.is_finite() || smoothed_loss > DIVERGENCE_FACTOR * best_loss {
+            break;
+        }
+        best_loss = best_loss.min(smoothed_loss);
+        learning_rates.push(learning_rate);
+        losses.push(smoothed_loss);
+
+        network.train_batch(&batch_inputs, &batch_expected, optimizer, cost, input_rows);
+    }
+
+    let suggested_lr: Option<f64> = steepest_descent(&learning_rates, &losses);
+    Ok(LrFinderResults {
+        learning_rates,
+        losses,
+        suggested_lr,
+    })
+}
+
+/// Learning rate where the loss has the most negative slope
+/// with respect to the (log scale) learning rate
+///
+/// # Arguments
+///
+/// * `learning_rates` - Learning rate of each step
+/// * `losses` - Smoothed loss of each step
+fn steepest_descent(learning_rates: &[f64], losses: &[f64]) -> Option<f64> {
+    (1..losses.len())
+        .map(|i| {
+            let slope: f64 =
+                (losses[i] - losses[i - 1]) / (learning_rates[i].ln() - learning_rates[i - 1].ln());
+            (i, slope)
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(i, _)| learning_rates[i])
+}
+
+/// Render the loss vs. learning rate curve as a text plot
+/// with a log scale x-axis
+///
+/// # Arguments
+///
+/// * `results` - Recorded sweep
+/// * `width` - Number of columns in the plot area
+/// * `height` - Number of rows in the plot area
+pub fn plot(results: &LrFinderResults, width: usize, height: usize) -> String {
+    if results.losses.is_empty() || width == 0 || height == 0 {
+        return String::from("(no losses recorded)\n");
+    }
+    let min_loss: f64 = results.losses.iter().copied().fold(f64::INFINITY, f64::min);
+    let max_loss: f64 = results
+        .losses
+        .iter()
+        .copied()
+        .fold(f64::NEG_INFINITY, f64::max);
+    let loss_range: f64 = (max_loss - min_loss).max(f64::EPSILON);
+
+    // Steps are exponentially spaced, so step index is already a log scale
+    let mut grid: Vec<Vec<char>> = vec![vec![' '; width]; height];
+    let last_step: usize = (results.losses.len() - 1).max(1);
+    for (step, loss) in results.losses.iter().enumerate() {
+        let col: usize = step * (width - 1) / last_step;
+        let row: usize = ((max_loss - loss) / loss_range * (height - 1) as f64).round() as usize;
+        grid[row][col] = '*';
+    }
+
+    let mut plot = String::new();
+    for (i, row) in grid.iter().enumerate() {
+        let label: String = match i {
+            0 => format!("{max_loss:>10.4}"),
+            _ if i == height - 1 => format!("{min_loss:>10.4}"),
+            _ => " ".repeat(10),
+        };
+        plot.push_str(&format!("{label} |{}\n", row.iter().collect::<String>()));
+    }
+    let first_lr: f64 = results.learning_rates[0];
+    let last_lr: f64 = results.learning_rates[results.learning_rates.len() - 1];
+    plot.push_str(&format!("{} +{}\n", " ".repeat(10), "-".repeat(width)));
+    plot.push_str(&format!(
+        "{} {first_lr:<e$.1e}{last_lr:>e2$.1e}\n",
+        " ".repeat(10),
+        e = width / 2,
+        e2 = width - width / 2
+    ));
+    plot
+}
@@ -1,3 +1,31 @@
+pub mod activations;
+pub mod averaging;
+pub mod batch_norm;
+pub mod batch_tuner;
+pub mod batches;
+pub mod bootstrap;
+pub mod builder;
+pub mod calibration;
+pub mod cancellation;
+pub mod confusion;
+pub mod data_source;
+pub mod decision_boundary;
+pub mod divergence;
+pub mod ensemble;
 pub mod functions;
+pub mod gradient_norms;
+pub mod history;
+pub mod initializer;
 pub mod layer;
+pub mod lr_finder;
+pub mod multi_head;
 pub mod perceptron;
+pub mod pretrain;
+pub mod profiler;
+pub mod regularization;
+pub mod robustness;
+pub mod saliency;
+pub mod sampling;
+pub mod stats;
+pub mod summary;
+pub mod top_n;
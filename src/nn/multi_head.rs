@@ -0,0 +1,297 @@
+use super::batches::{BatchConfig, Batches, Sampler};
+use super::data_source::{DataMetadata, DataSource};
+use super::functions::cost::Cost;
+use super::functions::encoder::Encoder;
+use super::functions::metric::Metric;
+use super::functions::optimizer::Optimizer;
+use super::perceptron::{Perceptron, VALIDATION_CHUNK_SIZE};
+use ndarray::{concatenate, s, Array2, ArrayView2, Axis};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// Output columns of a DataSource that are predicted by a single head.
+/// Input vectors are passed through unchanged
+pub struct HeadData {
+    /// Source of every head's output values
+    data: Arc<dyn DataSource>,
+
+    /// Output columns predicted by the head
+    columns: Range<usize>,
+}
+
+impl HeadData {
+    /// # Arguments
+    ///
+    /// * `data` - Source of every head's output values
+    /// * `columns` - Output columns predicted by the head
+    pub fn new(data: Arc<dyn DataSource>, columns: Range<usize>) -> Self {
+        Self { data, columns }
+    }
+
+    /// Only the head's columns of a (samples x outputs) matrix
+    ///
+    /// # Arguments
+    ///
+    /// * `outputs` - Output values of every head
+    fn select(&self, outputs: &Array2<f64>) -> Array2<f64> {
+        outputs.slice(s![.., self.columns.clone()]).to_owned()
+    }
+}
+
+impl DataSource for HeadData {
+    fn metadata(&self) -> DataMetadata {
+        DataMetadata {
+            output_size: self.columns.len(),
+            ..self.data.metadata()
+        }
+    }
+
+    fn training_set(&self) -> (Array2<f64>, Array2<f64>) {
+        let (inputs, outputs) = self.data.training_set();
+        (inputs, self.select(&outputs))
+    }
+
+    fn validation_set(&self) -> (Array2<f64>, Array2<f64>) {
+        let (inputs, outputs) = self.data.validation_set();
+        (inputs, self.select(&outputs))
+    }
+
+    fn test_set(&self) -> Option<(Array2<f64>, Array2<f64>)> {
+        self.data
+            .test_set()
+            .map(|(inputs, outputs)| (inputs, self.select(&outputs)))
+    }
+
+    fn sample_weights(&self) -> Option<Vec<f64>> {
+        self.data.sample_weights()
+    }
+}
+
+/// Output head of a multi-head network, fed by the outputs of the shared trunk
+#[derive(Clone)]
+pub struct Head {
+    /// Name of the head (e.g. the task it predicts)
+    pub name: String,
+
+    /// Layers of the head (the first Layer's inputs are the trunk's outputs)
+    pub network: Perceptron,
+
+    /// Loss function used to compute the head's output deltas
+    pub cost: Box<dyn Cost>,
+
+    /// Decides when the head is performing 'good enough'
+    pub metric: Box<dyn Metric>,
+
+    /// Transforms the head's output values to/from its output format
+    pub encoder: Box<dyn Encoder>,
+
+    /// Optimization method used to update the head's Layers
+    pub optimizer: Box<dyn Optimizer>,
+
+    /// Output columns (of the data's output values) predicted by the head
+    pub columns: Range<usize>,
+}
+
+/// Network with several output heads branching from shared Layers (the
+/// trunk). Each head has its own cost, and the gradients of every head
+/// are summed before being backpropagated through the trunk
+#[derive(Clone)]
+pub struct MultiHeadPerceptron {
+    /// Layers shared by every head
+    trunk: Perceptron,
+
+    /// Optimization method used to update the trunk's Layers
+    optimizer: Box<dyn Optimizer>,
+
+    /// Output heads, in order of their output columns
+    heads: Vec<Head>,
+
+    /// Whether or not training stops once every head's metric is satisfied
+    early_stopping: bool,
+
+    /// Random number generator used for shuffling
+    rng: StdRng,
+}
+
+impl MultiHeadPerceptron {
+    /// # Arguments
+    ///
+    /// * `trunk` - Layers shared by every head
+    /// * `optimizer` - Optimization method used to update the trunk's Layers
+    /// * `heads` - Output heads, in order of their output columns
+    /// * `seed` - Seed of the random number generator used for shuffling
+    pub fn new(
+        trunk: Perceptron,
+        optimizer: Box<dyn Optimizer>,
+        heads: Vec<Head>,
+        seed: u64,
+    ) -> Self {
+        Self {
+            trunk,
+            optimizer,
+            heads,
+            early_stopping: true,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Train every epoch, even after every head's metric is satisfied
+    pub fn disable_early_stopping(&mut self) {
+        self.early_stopping = false;
+    }
+
+    /// Layers shared by every head
+    pub fn trunk(&self) -> &Perceptron {
+        &self.trunk
+    }
+
+    /// Output heads, in order of their output columns
+    pub fn heads(&self) -> &[Head] {
+        &self.heads
+    }
+
+    /// Train the trunk and every head with the training set, until
+    /// the metric of every head passes on the validation set
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Source of the input vectors and the output values of every head
+    /// * `epochs` - Maximum number of training cycles
+    /// * `batch_config` - Determines how the training set is split into
+    ///   minibatches during each training cycle
+    ///
+    /// # Returns
+    ///
+    /// The number of epochs it took for the training to complete (every metric check passed)
+    pub fn fit(
+        &mut self,
+        data: &dyn DataSource,
+        epochs: usize,
+        batch_config: BatchConfig,
+    ) -> usize {
+        let (training_inputs, training_outputs) = data.training_set();
+        let input_rows: usize = training_inputs.nrows();
+        let (validation_inputs, validation_outputs) = data.validation_set();
+
+        // Encoded outputs of every head are stacked, so each batch
+        // samples the same rows for every head
+        let encoded: Vec<Array2<f64>> = self
+            .heads
+            .iter()
+            .map(|head| {
+                let outputs: ArrayView2<f64> = training_outputs.slice(s![.., head.columns.clone()]);
+                head.encoder.encode(&outputs.to_owned()).t().to_owned()
+            })
+            .collect();
+        let views: Vec<ArrayView2<f64>> = encoded.iter().map(Array2::view).collect();
+        let expected: Array2<f64> = concatenate(Axis(0), &views).unwrap();
+
+        let sample_weights: Option<Vec<f64>> = match batch_config.sampler {
+            Sampler::Weighted => data.sample_weights(),
+            Sampler::Sequential | Sampler::Balanced => None,
+        };
+        let mut rng: StdRng = self.rng.clone();
+
+        let mut last_epoch: usize = epochs;
+        for epoch in 1..=epochs {
+            if self.early_stopping
+                && self
+                    .evaluate(&validation_inputs, &validation_outputs)
+                    .iter()
+                    .all(|(_, _, passed)| *passed)
+            {
+                last_epoch = epoch;
+                break;
+            }
+
+            let batches = match &sample_weights {
+                Some(weights) => {
+                    Batches::weighted(&training_inputs, &expected, batch_config, weights, &mut rng)
+                }
+                None => Batches::new(&training_inputs, &expected, batch_config, &mut rng),
+            };
+            for (batch_inputs, batch_expected) in batches {
+                self.train_batch(&batch_inputs, &batch_expected, input_rows);
+            }
+        }
+        self.rng = rng;
+        last_epoch
+    }
+
+    /// Performs a single step of gradient descent on one minibatch
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - Input vectors of the minibatch
+    /// * `expected` - Encoded output vectors of every head, stacked in order of the heads
+    /// * `input_rows` - Number of rows in the training input set
+    pub fn train_batch(&mut self, inputs: &Array2<f64>, expected: &Array2<f64>, input_rows: usize) {
+        let trunk_outputs: Array2<f64> = self.trunk.feed_forward(inputs).to_owned();
+
+        // Gradient of the summed cost of every head w.r.t. the trunk's outputs
+        let mut trunk_deltas: Array2<f64> = Array2::zeros(trunk_outputs.dim());
+        let mut row: usize = 0;
+        for head in self.heads.iter_mut() {
+            let rows: usize = head.network.output_size();
+            let head_expected: Array2<f64> = expected.slice(s![row..row + rows, ..]).to_owned();
+            row += rows;
+
+            head.network.feed_forward(&trunk_outputs);
+            head.network
+                .back_prop_cost(head.cost.as_ref(), &head_expected);
+            // Read before the update, which changes the head's weights
+            trunk_deltas += &head.network.input_deltas();
+            head.network.update(head.optimizer.as_mut(), input_rows);
+        }
+
+        self.trunk.back_prop(&trunk_deltas);
+        self.trunk.update(self.optimizer.as_mut(), input_rows);
+    }
+
+    /// Decoded prediction of every head for the given inputs,
+    /// in order of the heads
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - Matrix of input vectors
+    pub fn predict(&self, inputs: &Array2<f64>) -> Vec<Array2<f64>> {
+        let trunk_outputs: Array2<f64> = self.trunk.predict_chunked(inputs, VALIDATION_CHUNK_SIZE);
+        self.heads
+            .iter()
+            .map(|head| {
+                head.network.par_predict(
+                    &trunk_outputs,
+                    head.encoder.as_ref(),
+                    VALIDATION_CHUNK_SIZE,
+                )
+            })
+            .collect()
+    }
+
+    /// Metric label, value, and whether or not it passed for every head
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - Input vectors (features x samples)
+    /// * `outputs` - Output values of every head (samples x outputs)
+    pub fn evaluate(
+        &self,
+        inputs: &Array2<f64>,
+        outputs: &Array2<f64>,
+    ) -> Vec<(String, f32, bool)> {
+        self.heads
+            .iter()
+            .zip(self.predict(inputs))
+            .map(|(head, prediction)| {
+                let expected: Array2<f64> = outputs.slice(s![.., head.columns.clone()]).to_owned();
+                (
+                    head.metric.label().to_string(),
+                    head.metric.value(&prediction, &expected),
+                    head.metric.check(&prediction, &expected),
+                )
+            })
+            .collect()
+    }
+}
@@ -1,26 +1,502 @@
-use super::functions::activation::ActivationFn;
+use super::activations::{self, DeadNeuronReinit};
+use super::averaging::{self, AveragingConfig};
+use super::batch_norm::BatchNorm;
+use super::batches::{self, BatchConfig, Batches, Sampler};
+use super::cancellation::CancellationToken;
+use super::data_source::{self, DataSource};
+use super::divergence::{Divergence, DivergenceConfig, DivergenceGuard};
+use super::functions::activation::{ActivationFn, Linear};
 use super::functions::cost::Cost;
 use super::functions::encoder::Encoder;
 use super::functions::metric::Metric;
 use super::functions::optimizer::{optimize, Optimizer};
-use super::layer::Layer;
-use ndarray::{Array1, Array2, ArrayViewMut1, Axis, Slice};
-use rand::seq::SliceRandom;
+use super::gradient_norms::{GradientExplosion, GradientNormConfig, GradientNorms};
+use super::history::EpochLog;
+use super::initializer::Initializer;
+use super::layer::{self, Layer};
+use super::profiler::{Phase, Profile};
+use super::regularization::{self, Regularization};
+use super::sampling::ValidationSampling;
+use super::stats::{
+    EpochHistograms, EpochStats, HistogramConfig, TrainingStats, WeightHistograms, WeightSnapshots,
+};
+use super::summary::{LayerSummary, NetworkSummary};
+use crate::error::OpenPbError;
+use crate::phase;
+use ndarray::{concatenate, Array1, Array2, ArrayView2, Axis};
+use rand::distributions::{Distribution, Uniform};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+#[cfg(feature = "threads")]
+use rayon::prelude::*;
 use serde::ser::{Serialize, SerializeStruct, Serializer};
-use std::fmt::Debug;
+use std::fmt::{Debug, Display};
+use std::time::Instant;
 
+/// Maximum number of validation vectors fed forward on a single thread
+/// when checking the early stopping metric. Larger validation sets are
+/// split into chunks of this size and evaluated in parallel
+pub const VALIDATION_CHUNK_SIZE: usize = 512;
+
+#[derive(Clone)]
 pub struct Perceptron {
     /// Input, hidden, and output layers. Each layer is considered
     /// to be 'connected' to the next one in the list
     layers: Vec<Layer>,
+
+    /// Timing breakdown of each training phase, only
+    /// recorded when profiling has been enabled
+    profile: Option<Profile>,
+
+    /// Per-layer weight and gradient statistics, only
+    /// recorded when statistics have been enabled
+    stats: Option<TrainingStats>,
+
+    /// Per-layer distance between periodic weight snapshots,
+    /// only recorded when snapshots have been enabled
+    snapshots: Option<WeightSnapshots>,
+
+    /// Per-layer weight histograms, only recorded
+    /// when histograms have been enabled
+    histograms: Option<WeightHistograms>,
+
+    /// Largest gradient norms of each epoch, only
+    /// tracked when gradient norms have been enabled
+    gradient_norms: Option<GradientNorms>,
+
+    /// Training loss of the latest epochs, only tracked
+    /// when the divergence guard has been enabled
+    divergence_guard: Option<DivergenceGuard>,
+
+    /// L1 and L2 penalties applied to the weights after
+    /// every training step (if regularization has been enabled)
+    regularization: Option<Regularization>,
+
+    /// Polyak-averaged copy of the Network, only kept
+    /// during training when averaging has been enabled
+    averaged: Option<(AveragingConfig, Box<Perceptron>)>,
+
+    /// Value the output layer's logits are divided by during
+    /// prediction, set by temperature-scaling calibration
+    temperature: Option<f64>,
+
+    /// Subsampling of the validation set for the early stopping
+    /// metric, only used when validation sampling has been enabled
+    validation_sampling: Option<ValidationSampling>,
+
+    /// Rate at which input features are zeroed during each training
+    /// cycle, and the random number generator that chooses them
+    input_dropout: Option<(f32, StdRng)>,
+
+    /// Whether or not training stops once the metric is satisfied
+    /// (otherwise every epoch is trained)
+    early_stopping: bool,
+
+    /// Number of dead neurons that have been re-initialized during training
+    reinitialized_neurons: usize,
+
+    /// Random number generator used for weight initialization
+    /// and shuffling (seeded for reproducible training)
+    rng: StdRng,
+
+    /// Seed the random number generator was created with
+    /// (`None` when it was seeded from entropy)
+    seed: Option<u64>,
 }
 
 impl Perceptron {
-    /// # Arguments:
-    ///
-    /// * `cost` - Loss function for error reporting/backprop
     pub fn new() -> Perceptron {
-        Perceptron { layers: vec![] }
+        Perceptron {
+            layers: vec![],
+            profile: None,
+            stats: None,
+            snapshots: None,
+            histograms: None,
+            gradient_norms: None,
+            divergence_guard: None,
+            regularization: None,
+            averaged: None,
+            temperature: None,
+            input_dropout: None,
+            validation_sampling: None,
+            early_stopping: true,
+            reinitialized_neurons: 0,
+            rng: StdRng::from_entropy(),
+            seed: None,
+        }
+    }
+
+    /// Create an empty Network whose initial weights, dropout, and
+    /// shuffling are all derived from the given seed
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - Seed of the Network's random number generator
+    pub fn with_seed(seed: u64) -> Perceptron {
+        Perceptron {
+            rng: StdRng::seed_from_u64(seed),
+            seed: Some(seed),
+            ..Perceptron::new()
+        }
+    }
+
+    /// Create a Network from a list of previously built Layers
+    ///
+    /// # Arguments
+    ///
+    /// * `layers` - Input, hidden, and output layers (in order)
+    pub fn from_layers(layers: Vec<Layer>) -> Perceptron {
+        Perceptron {
+            layers,
+            profile: None,
+            stats: None,
+            snapshots: None,
+            histograms: None,
+            gradient_norms: None,
+            divergence_guard: None,
+            regularization: None,
+            averaged: None,
+            temperature: None,
+            input_dropout: None,
+            validation_sampling: None,
+            early_stopping: true,
+            reinitialized_neurons: 0,
+            rng: StdRng::from_entropy(),
+            seed: None,
+        }
+    }
+
+    /// Size of the Network's expected input vector
+    pub fn input_size(&self) -> usize {
+        self.layers.first().map_or(0, Layer::input_size)
+    }
+
+    /// Size of the Network's (raw) output vector
+    pub fn output_size(&self) -> usize {
+        self.layers.last().map_or(0, |layer| layer.neurons)
+    }
+
+    /// Record per-phase timings during all subsequent calls to `fit`
+    pub fn enable_profiling(&mut self) {
+        self.profile = Some(Profile::default());
+    }
+
+    /// Remove and return the recorded timing breakdown (if profiling was enabled)
+    pub fn take_profile(&mut self) -> Option<Profile> {
+        self.profile.take()
+    }
+
+    /// Record per-layer weight and gradient statistics every `interval`
+    /// epochs during all subsequent calls to `fit`
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - Number of epochs between recordings
+    pub fn enable_stats(&mut self, interval: usize) {
+        self.stats = Some(TrainingStats::new(interval));
+    }
+
+    /// Remove and return the recorded statistics (if statistics were enabled)
+    pub fn take_stats(&mut self) -> Option<TrainingStats> {
+        self.stats.take()
+    }
+
+    /// Take a snapshot of every Layer's weights every `interval` epochs
+    /// during all subsequent calls to `fit`, recording the distance
+    /// between consecutive snapshots
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - Number of epochs between snapshots
+    pub fn enable_snapshots(&mut self, interval: usize) {
+        self.snapshots = Some(WeightSnapshots::new(interval));
+    }
+
+    /// Remove and return the recorded snapshot distances (if snapshots were enabled)
+    pub fn take_snapshots(&mut self) -> Option<WeightSnapshots> {
+        self.snapshots.take()
+    }
+
+    /// Record histograms of every Layer's weights and biases before training
+    /// and every `config.interval` epochs during all subsequent calls to `fit`
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Number of epochs between histograms, and number of bins
+    pub fn enable_histograms(&mut self, config: HistogramConfig) {
+        self.histograms = Some(WeightHistograms::new(config));
+    }
+
+    /// Remove and return the recorded histograms (if histograms were enabled)
+    pub fn take_histograms(&mut self) -> Option<WeightHistograms> {
+        self.histograms.take()
+    }
+
+    /// Record the histograms of every Layer after the given epoch
+    ///
+    /// # Arguments
+    ///
+    /// * `epoch` - Epoch that has just finished (0 is before training)
+    fn record_histograms(&mut self, epoch: usize) {
+        if let Some(histograms) = &mut self.histograms {
+            let bins: usize = histograms.bins;
+            let layers = self
+                .layers
+                .iter()
+                .map(|layer| layer.histogram(bins))
+                .collect();
+            histograms.epochs.push(EpochHistograms { epoch, layers });
+        }
+    }
+
+    /// Track the global and per-layer gradient norms of every training step
+    /// during all subsequent calls to `fit`, halting training once a step's
+    /// global norm exceeds the configured maximum
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Largest global norm before training halts (optional)
+    pub fn enable_gradient_norms(&mut self, config: GradientNormConfig) {
+        self.gradient_norms = Some(GradientNorms::new(config));
+    }
+
+    /// Remove and return the step whose gradient norm exceeded the
+    /// maximum, if training was halted because of one
+    pub fn take_gradient_explosion(&mut self) -> Option<GradientExplosion> {
+        self.gradient_norms
+            .as_mut()
+            .and_then(GradientNorms::take_explosion)
+    }
+
+    /// Stop training during all subsequent calls to `fit` once the
+    /// mean training loss of an epoch grows by more than a factor
+    /// over the lowest loss of the previous epochs in a window
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Growth factor and window size
+    pub fn enable_divergence_guard(&mut self, config: DivergenceConfig) {
+        self.divergence_guard = Some(DivergenceGuard::new(config));
+    }
+
+    /// Remove and return the epoch whose training loss exploded,
+    /// if training was stopped because of one
+    pub fn take_divergence(&mut self) -> Option<Divergence> {
+        self.divergence_guard
+            .as_mut()
+            .and_then(DivergenceGuard::take_divergence)
+    }
+
+    /// Apply L1 and L2 weight penalties after every training step
+    /// during all subsequent calls to `fit`
+    ///
+    /// # Arguments
+    ///
+    /// * `regularization` - Strength of the L1 and L2 penalties
+    pub fn enable_regularization(&mut self, regularization: Regularization) {
+        self.regularization = Some(regularization);
+    }
+
+    /// Total L1/L2 penalty of every Layer's weights (0 without regularization)
+    pub fn regularization_penalty(&self) -> f64 {
+        match &self.regularization {
+            Some(regularization) => self
+                .layers
+                .iter()
+                .map(|layer| layer.regularization_penalty(regularization))
+                .sum(),
+            None => 0.0,
+        }
+    }
+
+    /// Randomly zero input features during every training cycle (in
+    /// addition to any dropout of the layers' own neurons)
+    ///
+    /// # Arguments
+    ///
+    /// * `rate` - Rate at which input features are dropped during training
+    pub fn enable_input_dropout(&mut self, rate: f32) {
+        let rng: StdRng = StdRng::seed_from_u64(self.rng.gen());
+        self.input_dropout = Some((rate, rng));
+    }
+
+    /// Give input dropout and every Layer's dropout their own streams, derived
+    /// from the Network's seed and each Layer's index, so adding or removing
+    /// a Layer doesn't change which neurons the other Layers drop (keeping
+    /// ablations comparable). By default, each stream is drawn from the
+    /// Network's random number generator after the previous Layer's weights.
+    /// Call after every Layer is added, and after `enable_input_dropout`
+    pub fn enable_independent_dropout(&mut self) {
+        // Unseeded Networks still get distinct streams per Layer
+        let seed: u64 = match self.seed {
+            Some(seed) => seed,
+            None => self.rng.gen(),
+        };
+        if let Some((_, rng)) = &mut self.input_dropout {
+            *rng = StdRng::seed_from_u64(dropout_stream_seed(seed, 0));
+        }
+        for (index, layer) in self.layers.iter_mut().enumerate() {
+            layer.reseed_dropout(dropout_stream_seed(seed, index as u64 + 1));
+        }
+    }
+
+    /// Rate at which input features are dropped during training (if enabled)
+    pub fn input_dropout(&self) -> Option<f32> {
+        self.input_dropout.as_ref().map(|(rate, _)| *rate)
+    }
+
+    /// Check the early stopping metric against a random subsample of the
+    /// validation set during all subsequent calls to `fit`, with a full
+    /// evaluation every few epochs (and before stopping early)
+    ///
+    /// # Arguments
+    ///
+    /// * `sampling` - Subsample fraction and epochs between full evaluations
+    pub fn enable_validation_sampling(&mut self, sampling: ValidationSampling) {
+        self.validation_sampling = Some(sampling);
+    }
+
+    /// Periodically re-initialize the dead neurons of a hidden Layer during
+    /// all subsequent calls to `fit`. Ignored for the output layer
+    ///
+    /// # Arguments
+    ///
+    /// * `layer` - Index of the Layer (0 is the first hidden layer)
+    /// * `config` - Epochs between checks and the dead activation threshold
+    pub fn enable_dead_reinit(&mut self, layer: usize, config: DeadNeuronReinit) {
+        if let Some(layer) = self.layers.get_mut(layer) {
+            layer.enable_dead_reinit(config);
+        }
+    }
+
+    /// Normalize the activation values of a Layer with batch normalization,
+    /// whose scale and shift are learned during all subsequent calls to `fit`
+    ///
+    /// # Arguments
+    ///
+    /// * `layer` - Index of the Layer (0 is the first hidden layer)
+    pub fn enable_batch_norm(&mut self, layer: usize) {
+        if let Some(layer) = self.layers.get_mut(layer) {
+            layer.enable_batch_norm(BatchNorm::new(layer.neurons));
+        }
+    }
+
+    /// Whether or not any Layer normalizes its activation values
+    pub fn has_batch_norm(&self) -> bool {
+        self.layers.iter().any(|layer| layer.batch_norm().is_some())
+    }
+
+    /// Number of dead neurons that have been re-initialized during training
+    pub fn reinitialized_neurons(&self) -> usize {
+        self.reinitialized_neurons
+    }
+
+    /// Train for the maximum number of epochs during all subsequent calls
+    /// to `fit`, even when the metric is satisfied (it's still reported)
+    pub fn disable_early_stopping(&mut self) {
+        self.early_stopping = false;
+    }
+
+    /// Keep a Polyak-averaged copy of the Network's current weights
+    /// and biases, which is updated after every minibatch
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Averaging rate, and whether the averaged copy is
+    ///   used for the metric check and kept after training
+    pub fn enable_averaging(&mut self, config: AveragingConfig) {
+        let averaged = Perceptron::from_layers(self.layers.clone());
+        self.averaged = Some((config, Box::new(averaged)));
+    }
+
+    /// Stop averaging, replacing the trained weights and biases with
+    /// the averaged ones when the averaging config saves them
+    pub fn finish_averaging(&mut self) {
+        if let Some((config, averaged)) = self.averaged.take() {
+            if config.save {
+                self.layers = averaged.layers;
+            }
+        }
+    }
+
+    /// Network whose predictions are checked against the early stopping
+    /// metric: the averaged copy when it's evaluated, otherwise itself
+    fn evaluation_network(&self) -> &Perceptron {
+        match &self.averaged {
+            Some((config, averaged)) if config.evaluate => averaged,
+            _ => self,
+        }
+    }
+
+    /// Calibrated temperature of the output layer (if calibration was performed)
+    pub fn temperature(&self) -> Option<f64> {
+        self.temperature
+    }
+
+    /// Divide the output layer's logits by a temperature during all subsequent predictions
+    ///
+    /// # Arguments
+    ///
+    /// * `temperature` - Calibrated temperature (`None` removes the scaling)
+    pub fn set_temperature(&mut self, temperature: Option<f64>) {
+        self.temperature = temperature;
+    }
+
+    /// Total number of trainable values (weights and biases) in the Network
+    pub fn num_parameters(&self) -> usize {
+        self.layers.iter().map(Layer::num_parameters).sum()
+    }
+
+    /// Shape of each Layer's weights matrix (neurons x inputs)
+    pub fn layer_shapes(&self) -> Vec<(usize, usize)> {
+        self.layers.iter().map(Layer::shape).collect()
+    }
+
+    /// Architecture of the Network, with the estimated floating point
+    /// operations of each Layer and of a forward pass over a full batch
+    ///
+    /// # Arguments
+    ///
+    /// * `batch_size` - Number of samples in each forward pass
+    pub fn summary(&self, batch_size: usize) -> NetworkSummary {
+        let layers: Vec<LayerSummary> = self
+            .layers
+            .iter()
+            .map(|layer| LayerSummary {
+                inputs: layer.input_size(),
+                neurons: layer.neurons,
+                activation: layer.activation_name().to_string(),
+                batch_norm: layer.batch_norm().is_some(),
+                parameters: layer.num_parameters(),
+                flops_per_sample: layer.forward_flops(),
+            })
+            .collect();
+        let flops_per_sample: usize = layers.iter().map(|layer| layer.flops_per_sample).sum();
+        NetworkSummary {
+            layers,
+            input_dropout: self.input_dropout(),
+            parameters: self.num_parameters(),
+            batch_size,
+            flops_per_sample,
+            flops_per_batch: flops_per_sample * batch_size,
+        }
+    }
+
+    /// Total number of values held in each Layer's
+    /// feed forward and backprop buffers
+    pub fn buffer_len(&self) -> usize {
+        self.layers.iter().map(Layer::buffer_len).sum()
+    }
+
+    /// Finish timing a phase of the training loop
+    ///
+    /// # Arguments
+    ///
+    /// * `phase` - Phase that has just finished
+    fn end_phase(&mut self, phase: Phase) {
+        if let Some(profile) = &mut self.profile {
+            profile.record(phase);
+        }
     }
 
     /// Creates a new layer and adds it to the Network. Used only for the
@@ -29,18 +505,26 @@ impl Perceptron {
     /// # Arguments
     ///
     /// * `neurons` - Number of neurons, determines how many weights/biases
-    /// are present in the new Layer
+    ///   are present in the new Layer
     /// * `inputs` - Size of expected the Layer's input vector
     /// * `activation_fn` - Function that determines the activation of individual neurons
+    /// * `initializer` - Scheme the initial weights are drawn with (optional)
     fn add_input_layer(
         &mut self,
         neurons: usize,
         input_shape: (usize, usize),
         activation_fn: Box<dyn ActivationFn>,
         dropout: Option<f32>,
+        initializer: Option<Initializer>,
     ) {
-        self.layers
-            .push(Layer::new(neurons, input_shape, activation_fn, dropout));
+        self.layers.push(Layer::with_rng(
+            neurons,
+            input_shape,
+            activation_fn,
+            dropout,
+            initializer,
+            &mut self.rng,
+        ));
     }
 
     /// Same as `add_input_layer`, but used for any other layer after. The number of
@@ -49,23 +533,27 @@ impl Perceptron {
     /// # Arguments
     ///
     /// * `neurons` - Number of neurons, determines how many weights/biases
-    /// are present in the new Layer
+    ///   are present in the new Layer
     /// * `activation_fn` - Function that determines the activation of individual neurons
+    /// * `initializer` - Scheme the initial weights are drawn with (optional)
     fn add_hidden_layer(
         &mut self,
         neurons: usize,
         activation_fn: Box<dyn ActivationFn>,
         dropout: Option<f32>,
+        initializer: Option<Initializer>,
     ) {
         let prev_layer: &mut Layer = self.layers.last_mut().unwrap();
         let prev_neurons: usize = prev_layer.neurons;
         let prev_inputs: usize = prev_layer.inputs.ncols();
 
-        self.layers.push(Layer::new(
+        self.layers.push(Layer::with_rng(
             neurons,
             (prev_neurons, prev_inputs),
             activation_fn,
             dropout,
+            initializer,
+            &mut self.rng,
         ));
     }
 
@@ -75,7 +563,7 @@ impl Perceptron {
     /// # Arguments
     ///
     /// * `neurons` - Number of neurons, determines how many weights/biases
-    /// are present in the new Layer
+    ///   are present in the new Layer
     /// * `inputs` (optional) - Size of expected the Layer's input vector
     /// * `activation_fn` - Function that determines the activation of individual neurons
     pub fn add_layer(
@@ -84,101 +572,290 @@ impl Perceptron {
         input_shape: Option<(usize, usize)>,
         activation_fn: Box<dyn ActivationFn>,
         dropout: Option<f32>,
+    ) {
+        self.add_initialized_layer(neurons, input_shape, activation_fn, dropout, None);
+    }
+
+    /// Same as `add_layer`, but the scheme used to draw the Layer's initial
+    /// weights can be chosen instead of being picked from its activation
+    ///
+    /// # Arguments
+    ///
+    /// * `neurons` - Number of neurons, determines how many weights/biases
+    ///   are present in the new Layer
+    /// * `inputs` (optional) - Size of expected the Layer's input vector
+    /// * `activation_fn` - Function that determines the activation of individual neurons
+    /// * `dropout` - Optional rate for randomly excluding neurons during each training cycle
+    /// * `initializer` - Scheme the initial weights are drawn with, chosen
+    ///   from the activation function when `None`
+    pub fn add_initialized_layer(
+        &mut self,
+        neurons: usize,
+        input_shape: Option<(usize, usize)>,
+        activation_fn: Box<dyn ActivationFn>,
+        dropout: Option<f32>,
+        initializer: Option<Initializer>,
     ) {
         match input_shape {
-            Some(input_shape) => self.add_input_layer(neurons, input_shape, activation_fn, dropout),
-            _ => self.add_hidden_layer(neurons, activation_fn, dropout),
+            Some(input_shape) => {
+                self.add_input_layer(neurons, input_shape, activation_fn, dropout, initializer)
+            }
+            _ => self.add_hidden_layer(neurons, activation_fn, dropout, initializer),
+        }
+    }
+
+    /// Autoencoder made of a copy of every layer except the output layer,
+    /// followed by a new linear decoder layer that reconstructs the input
+    /// vector. Returns `None` when the Network has no hidden layers
+    pub fn autoencoder(&self) -> Option<Perceptron> {
+        if self.layers.len() < 2 {
+            return None;
+        }
+        let mut autoencoder = Perceptron {
+            layers: self.layers[..self.layers.len() - 1].to_vec(),
+            rng: self.rng.clone(),
+            seed: self.seed,
+            ..Perceptron::new()
+        };
+        autoencoder.add_hidden_layer(self.input_size(), Box::new(Linear), None, None);
+        Some(autoencoder)
+    }
+
+    /// Replace the Network's hidden layers with the trained encoder
+    /// layers (every layer except the decoder) of an autoencoder
+    ///
+    /// # Arguments
+    ///
+    /// * `autoencoder` - Trained autoencoder created by `autoencoder`
+    pub fn load_encoder(&mut self, mut autoencoder: Perceptron) {
+        autoencoder.layers.pop();
+        for (layer, encoder_layer) in self.layers.iter_mut().zip(autoencoder.layers) {
+            *layer = encoder_layer;
+        }
+    }
+
+    /// Replace the weights and biases of every Layer with those of a
+    /// previously trained Network with the same layer shapes, so training
+    /// continues from them instead of from random initial values
+    ///
+    /// # Arguments
+    ///
+    /// * `trained` - Network whose weights and biases are copied
+    pub fn load_parameters(&mut self, trained: &Perceptron) -> Result<(), OpenPbError> {
+        let shapes: Vec<(usize, usize)> = self.layer_shapes();
+        let trained_shapes: Vec<(usize, usize)> = trained.layer_shapes();
+        if shapes != trained_shapes {
+            return Err(format!(
+                "Trained layer shapes {trained_shapes:?} don't match the network's {shapes:?}"
+            )
+            .into());
+        }
+        for (layer, trained_layer) in self.layers.iter_mut().zip(&trained.layers) {
+            layer.load_parameters(trained_layer);
         }
+        Ok(())
     }
 
     /// Trains the entire Network for a specified number of cycles. Training is
     /// stopped when the given metric is satisfied based on the input/output
-    /// sets provided
+    /// sets provided. Each cycle (epoch) is a full pass over the training set,
+    /// with one optimizer step per minibatch
     ///
     /// # Arguments
     ///
-    /// * `training_set` - Set of all input and output vectors to train the network on
-    /// * `validation_set` - Set of all input and output vectors to validate if the
-    /// network has been sufficiently trained
+    /// * `data` - Source of the input and output vectors to train the network on,
+    ///   and to validate if the network has been sufficiently trained
     /// * `optimizer` - Optimization method used when performing gradient descent
     /// * `metric` - Decides when the Network is performing 'good enough'
-    /// on the provided validation data
-    /// * `cost` -
-    /// * `encoder` -
-    /// * `epochs` - Maximum number of training cycles
-    /// * `shuffle` - When 'true', training inputs are shuffled at the start of
-    /// each training cycle
+    ///   on the provided validation data
+    /// * `cost` - Loss function used to compute the output layer's deltas
+    /// * `encoder` - Transforms output values to/from the network's output format
+    /// * `epochs` - Maximum number of training cycles (full passes over the training set)
+    /// * `batch_config` - Determines how the training set is split into
+    ///   minibatches during each training cycle
     ///
     /// # Returns
     ///
     /// The number of epochs it took for the training to complete (metric check passed)
+    #[allow(clippy::too_many_arguments)]
     pub fn fit(
         &mut self,
-        training_set: &(Array2<f64>, Array2<f64>),
-        validation_set: &(Array2<f64>, Array2<f64>),
+        data: &dyn DataSource,
         optimizer: &mut dyn Optimizer,
         metric: &dyn Metric,
         cost: &dyn Cost,
         encoder: &dyn Encoder,
         epochs: usize,
-        shuffle: bool,
-        batch_size: Option<usize>,
+        batch_config: BatchConfig,
+    ) -> usize {
+        self.fit_with_callback(
+            data,
+            optimizer,
+            metric,
+            cost,
+            encoder,
+            epochs,
+            batch_config,
+            &mut |_, _| true,
+        )
+    }
+
+    /// Same as `fit`, but training stops cleanly at the end of the current
+    /// epoch once `token` is cancelled (e.g. from a GUI or server thread).
+    /// Nothing is trained when the token is already cancelled
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Source of the input and output vectors to train the network on,
+    ///   and to validate if the network has been sufficiently trained
+    /// * `optimizer` - Optimization method used when performing gradient descent
+    /// * `metric` - Decides when the Network is performing 'good enough'
+    ///   on the provided validation data
+    /// * `cost` - Loss function used to compute the output layer's deltas
+    /// * `encoder` - Transforms output values to/from the network's output format
+    /// * `epochs` - Maximum number of training cycles
+    /// * `batch_config` - Determines how the training set is split into
+    ///   minibatches during each training cycle
+    /// * `token` - Checked at the end of every epoch
+    ///
+    /// # Returns
+    ///
+    /// The number of epochs trained before the metric check passed or training was cancelled
+    #[allow(clippy::too_many_arguments)]
+    pub fn fit_cancellable(
+        &mut self,
+        data: &dyn DataSource,
+        optimizer: &mut dyn Optimizer,
+        metric: &dyn Metric,
+        cost: &dyn Cost,
+        encoder: &dyn Encoder,
+        epochs: usize,
+        batch_config: BatchConfig,
+        token: &CancellationToken,
+    ) -> usize {
+        if token.is_cancelled() {
+            return 0;
+        }
+        self.fit_with_callback(
+            data,
+            optimizer,
+            metric,
+            cost,
+            encoder,
+            epochs,
+            batch_config,
+            &mut |_, _| !token.is_cancelled(),
+        )
+    }
+
+    /// Same as `fit`, but `on_epoch` is called with a summary of the
+    /// Network's progress at the end of every epoch. Training stops
+    /// early when `on_epoch` returns `false`
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Source of the input and output vectors to train the network on,
+    ///   and to validate if the network has been sufficiently trained
+    /// * `optimizer` - Optimization method used when performing gradient descent
+    /// * `metric` - Decides when the Network is performing 'good enough'
+    ///   on the provided validation data
+    /// * `cost` - Loss function used to compute the output layer's deltas
+    /// * `encoder` - Transforms output values to/from the network's output format
+    /// * `epochs` - Maximum number of training cycles
+    /// * `batch_config` - Determines how the training set is split into
+    ///   minibatches during each training cycle
+    /// * `on_epoch` - Called with the epoch's summary and the Network itself
+    ///
+    /// # Returns
+    ///
+    /// The number of epochs it took for the training to complete (metric check passed)
+    #[allow(clippy::too_many_arguments)]
+    pub fn fit_with_callback(
+        &mut self,
+        data: &dyn DataSource,
+        optimizer: &mut dyn Optimizer,
+        metric: &dyn Metric,
+        cost: &dyn Cost,
+        encoder: &dyn Encoder,
+        epochs: usize,
+        batch_config: BatchConfig,
+        on_epoch: &mut dyn FnMut(&EpochLog, &Perceptron) -> bool,
     ) -> usize {
         // Keep track of which iteration training ended on
         // (default is the maximum number of epochs)
         let mut last_epoch: usize = epochs;
-
-        // Rows and columns of full training input set
-        let input_rows: usize = training_set.0.nrows();
-        let input_cols: usize = training_set.0.ncols();
+        if let Some(divergence_guard) = &mut self.divergence_guard {
+            divergence_guard.reset();
+        }
 
         // Split training set
-        let mut training_inputs: Array2<f64> = training_set.0.clone();
-        let mut training_outputs: Array2<f64> = training_set.1.clone();
+        let (training_inputs, training_outputs) = data.training_set();
+        // Rows of full training input set
+        let input_rows: usize = training_inputs.nrows();
 
         // Split validation set
-        let validation_inputs: &Array2<f64> = &validation_set.0;
-        let validation_outputs: &Array2<f64> = &validation_set.1;
+        let (validation_inputs, validation_outputs) = data.validation_set();
 
         // Encode training set output values to match
-        // the network's output format
-        let mut expected: Array2<f64> = encoder.encode(&training_outputs).t().to_owned();
+        // the network's output format (soft targets already do)
+        let expected: Array2<f64> = data_source::expected_outputs(data, encoder, &training_outputs);
 
-        // Initiate RNG
-        let mut rng = rand::thread_rng();
+        // Weighted samplers draw batches in proportion to each sample's weight
+        // (data without weights falls back to sequential batches)
+        let sample_weights: Option<Vec<f64>> = match batch_config.sampler {
+            Sampler::Sequential => None,
+            Sampler::Balanced => Some(batches::inverse_class_frequency(&training_outputs)),
+            Sampler::Weighted => data.sample_weights(),
+        };
 
-        // Starting index of batch, if applicable
-        let mut batch_start: usize = 0;
+        // Shuffling uses the Network's own (possibly seeded) RNG
+        let mut rng: StdRng = self.rng.clone();
+        // Instant isn't available on wasm32-unknown-unknown, where elapsed time is always 0
+        let start: Option<Instant> = if cfg!(target_arch = "wasm32") {
+            None
+        } else {
+            Some(Instant::now())
+        };
 
-        for epoch in 1..=epochs {
-            if shuffle {
-                // Assumes each input vector has a single corresponding output vector
-                // (number of columns of the training inputs should be
-                // equal to the number of rows of the outputs after transposing)
-                let mut indices: Vec<usize> = (0..training_inputs.ncols()).collect();
-                indices.shuffle(&mut rng);
-
-                self.shuffle_on_axis(&mut training_inputs, &indices, Axis(1));
-                self.shuffle_on_axis(&mut training_outputs, &indices, Axis(0));
+        // Initial snapshot that the first recorded change is measured from
+        if let Some(snapshots) = &mut self.snapshots {
+            if !snapshots.has_snapshot() {
+                snapshots.record(0, self.layers.iter().map(Layer::snapshot).collect());
             }
+        }
+        if matches!(&self.histograms, Some(histograms) if histograms.epochs.is_empty()) {
+            self.record_histograms(0);
+        }
 
-            if let Some(batch_size) = batch_size {
-                // Create minibatches by slicing training sets
-                training_inputs = self.batch(&training_set.0, batch_start, batch_size, Axis(1));
-                training_outputs = self.batch(&training_set.1, batch_start, batch_size, Axis(0));
-
-                // Re-evaluate expected values for minibatch
-                expected = encoder.encode(&training_outputs).t().to_owned();
+        // Each epoch's validation prediction is reused by the next epoch's early stopping
+        // check. Sampled outputs are only present when a subsample was predicted
+        let phase: Phase = phase!(self.profile, "validation");
+        let (mut prediction, mut sampled_outputs) = self.predict_validation(
+            &validation_inputs,
+            &validation_outputs,
+            encoder,
+            0,
+            &mut rng,
+        );
+        self.end_phase(phase);
 
-                // Increment batch start index
-                batch_start += batch_size;
-                if batch_start > input_cols {
-                    batch_start = 0;
-                }
-            }
+        for epoch in 1..=epochs {
             // Check network prediction against validation set
-            let prediction: Array2<f64> = self.predict(validation_inputs, encoder);
-            let early_stop: bool = metric.check(&prediction, validation_outputs);
+            let phase: Phase = phase!(self.profile, "validation");
+            let expected_outputs: &Array2<f64> =
+                sampled_outputs.as_ref().unwrap_or(&validation_outputs);
+            let mut early_stop: bool =
+                self.early_stopping && metric.check(&prediction, expected_outputs);
+            // Passing a subsample isn't enough, the full set is checked before stopping
+            if early_stop && sampled_outputs.is_some() {
+                let full_prediction: Array2<f64> = self.evaluation_network().par_predict(
+                    &validation_inputs,
+                    encoder,
+                    VALIDATION_CHUNK_SIZE,
+                );
+                early_stop = metric.check(&full_prediction, &validation_outputs);
+            }
+            self.end_phase(phase);
 
             // Stop training if early stopping metric criteria has been met
             if early_stop {
@@ -186,48 +863,221 @@ impl Perceptron {
                 break;
             }
 
-            let actual: Array2<f64> = self.feed_forward(&training_inputs);
-            let delta: Array2<f64> = cost.prime(&actual, &expected);
-            self.back_prop(&delta);
+            let phase: Phase = phase!(self.profile, "batch");
+            let batches = match &sample_weights {
+                Some(weights) => {
+                    Batches::weighted(&training_inputs, &expected, batch_config, weights, &mut rng)
+                }
+                None => Batches::new(&training_inputs, &expected, batch_config, &mut rng),
+            };
+            self.end_phase(phase);
+
+            // Each minibatch's mean loss is weighted by its number of samples
+            let mut loss_sum: f64 = 0.0;
+            let mut samples: usize = 0;
+            let mut diverged: bool = false;
+            if let Some(gradient_norms) = &mut self.gradient_norms {
+                gradient_norms.start_epoch();
+            }
+            for (step, (batch_inputs, batch_expected)) in batches.enumerate() {
+                let batch_loss: f64 =
+                    self.train_batch(&batch_inputs, &batch_expected, optimizer, cost, input_rows);
+                loss_sum += batch_loss * batch_expected.ncols() as f64;
+                samples += batch_expected.ncols();
 
-            // Update network weights/biases using
-            // the given Optimizer
-            optimize(optimizer, &mut self.layers, input_rows);
+                // Backprop buffers still hold the step's gradients after the update
+                if self.gradient_norms.is_some() {
+                    let layer_norms: Vec<f64> =
+                        self.layers.iter().map(Layer::gradient_norm).collect();
+                    if let Some(gradient_norms) = &mut self.gradient_norms {
+                        diverged = gradient_norms.record(epoch, step + 1, layer_norms);
+                    }
+                }
+                if diverged {
+                    break;
+                }
+            }
+            // Training halts right away, the caller reports the exploding step
+            if diverged {
+                last_epoch = epoch;
+                break;
+            }
+            // Exploding losses also stop training, the caller reports the epoch
+            let loss: f64 = loss_sum / samples.max(1) as f64;
+            if let Some(divergence_guard) = &mut self.divergence_guard {
+                if divergence_guard.record(epoch, loss) {
+                    last_epoch = epoch;
+                    break;
+                }
+            }
+            self.reinit_dead_neurons(epoch, &training_inputs, &mut rng);
+
+            if let Some(stats) = &mut self.stats {
+                if stats.is_due(epoch) {
+                    let layers = self.layers.iter().map(Layer::stats).collect();
+                    stats.epochs.push(EpochStats { epoch, layers });
+                }
+            }
+            if let Some(snapshots) = &mut self.snapshots {
+                if snapshots.is_due(epoch) {
+                    snapshots.record(epoch, self.layers.iter().map(Layer::snapshot).collect());
+                }
+            }
+            if matches!(&self.histograms, Some(histograms) if histograms.is_due(epoch)) {
+                self.record_histograms(epoch);
+            }
+
+            let phase: Phase = phase!(self.profile, "validation");
+            (prediction, sampled_outputs) = self.predict_validation(
+                &validation_inputs,
+                &validation_outputs,
+                encoder,
+                epoch,
+                &mut rng,
+            );
+            self.end_phase(phase);
+
+            let epoch_log = EpochLog {
+                epoch,
+                loss,
+                regularization: self.regularization_penalty(),
+                gradient_norm: self.gradient_norms.as_ref().map(GradientNorms::epoch_norm),
+                layer_gradient_norms: self
+                    .gradient_norms
+                    .as_ref()
+                    .map(|gradient_norms| gradient_norms.epoch_layer_norms().to_vec()),
+                metric: metric.value(
+                    &prediction,
+                    sampled_outputs.as_ref().unwrap_or(&validation_outputs),
+                ),
+                elapsed_time: start.map_or(0.0, |start| start.elapsed().as_secs_f32()),
+            };
+            if !on_epoch(&epoch_log, self) {
+                last_epoch = epoch;
+                break;
+            }
         }
+        self.rng = rng;
         last_epoch
     }
 
-    /// Shuffle matrix rows or cols in-place
+    /// Re-initialize the dead neurons of every hidden Layer whose
+    /// re-initialization check is due after the given epoch
     ///
     /// # Arguments
     ///
-    /// * `values` - Matrix to be shuffled
-    /// * `indices` - Generated list of shuffled indices along given axis
-    /// * `axis` - Axis in which vectors are shuffled
-    fn shuffle_on_axis(&self, values: &mut Array2<f64>, indices: &Vec<usize>, axis: Axis) {
-        let new_rows: Vec<Array1<f64>> = indices
+    /// * `epoch` - Epoch that has just finished
+    /// * `inputs` - Training input vectors (features x samples)
+    /// * `rng` - Random number generator the new weights are drawn from
+    fn reinit_dead_neurons(&mut self, epoch: usize, inputs: &Array2<f64>, rng: &mut StdRng) {
+        let hidden_layers: usize = self.layers.len().saturating_sub(1);
+        let due: Vec<(usize, DeadNeuronReinit)> = self.layers[..hidden_layers]
             .iter()
-            .map(|index| values.index_axis(axis, *index).to_owned())
+            .enumerate()
+            .filter_map(|(layer, hidden_layer)| hidden_layer.dead_reinit().map(|c| (layer, c)))
+            .filter(|(_, config)| epoch.is_multiple_of(config.every.max(1)))
             .collect();
+        if due.is_empty() {
+            return;
+        }
 
-        for (i, new_row) in new_rows.iter().enumerate() {
-            let mut row: ArrayViewMut1<f64> = values.index_axis_mut(axis, i);
-            row.assign(new_row);
+        let max_activations: Vec<Array1<f64>> = activations::max_activations(self, inputs);
+        for (layer, config) in due {
+            let dead: Vec<usize> = max_activations[layer]
+                .iter()
+                .enumerate()
+                .filter(|(_, value)| **value <= config.threshold)
+                .map(|(neuron, _)| neuron)
+                .collect();
+            self.layers[layer].reinitialize_neurons(&dead, rng);
+            self.reinitialized_neurons += dead.len();
         }
     }
 
-    fn batch(
+    /// Predict the validation set for the early stopping metric. When validation
+    /// sampling is enabled, only a random subsample is predicted except on full
+    /// evaluation epochs, and the subsample's expected outputs are returned too
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - Validation input vectors (features x samples)
+    /// * `outputs` - Validation output values (samples x outputs)
+    /// * `encoder` - Method for decoding output to readable values
+    /// * `epoch` - Epoch that has just finished (0 before training)
+    /// * `rng` - Random number generator that chooses the subsample
+    fn predict_validation(
         &self,
-        values: &Array2<f64>,
-        start: usize,
-        batch_size: usize,
-        axis: Axis,
-    ) -> Array2<f64> {
-        let end: usize = start + batch_size;
-        let end = end.min(values.len_of(axis));
-        let indices: Slice = Slice::from(start..end);
+        inputs: &Array2<f64>,
+        outputs: &Array2<f64>,
+        encoder: &dyn Encoder,
+        epoch: usize,
+        rng: &mut StdRng,
+    ) -> (Array2<f64>, Option<Array2<f64>>) {
+        let network: &Perceptron = self.evaluation_network();
+        match self.validation_sampling {
+            Some(sampling) if !sampling.is_full_epoch(epoch) => {
+                let (sampled_inputs, sampled_outputs) = sampling.sample(inputs, outputs, rng);
+                let prediction: Array2<f64> =
+                    network.par_predict(&sampled_inputs, encoder, VALIDATION_CHUNK_SIZE);
+                (prediction, Some(sampled_outputs))
+            }
+            _ => (
+                network.par_predict(inputs, encoder, VALIDATION_CHUNK_SIZE),
+                None,
+            ),
+        }
+    }
+
+    /// Performs a single step of gradient descent on one minibatch
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - Input vectors of the minibatch
+    /// * `expected` - Encoded output vectors of the minibatch
+    /// * `optimizer` - Optimization method used when performing gradient descent
+    /// * `cost` - Loss function used to compute the output layer's deltas
+    /// * `input_rows` - Number of rows in the training input set
+    ///
+    /// # Returns
+    ///
+    /// The minibatch's loss before the update
+    pub fn train_batch(
+        &mut self,
+        inputs: &Array2<f64>,
+        expected: &Array2<f64>,
+        optimizer: &mut dyn Optimizer,
+        cost: &dyn Cost,
+        input_rows: usize,
+    ) -> f64 {
+        let phase: Phase = phase!(self.profile, "feed_forward");
+        let loss: f64 = cost.call(self.feed_forward(inputs), expected);
+        self.end_phase(phase);
+
+        let phase: Phase = phase!(self.profile, "back_prop");
+        self.back_prop_cost(cost, expected);
+        self.end_phase(phase);
 
-        values.slice_axis(axis, indices).to_owned()
+        let phase: Phase = phase!(self.profile, "optimize");
+        self.update(optimizer, input_rows);
+        self.end_phase(phase);
+        loss
+    }
+
+    /// Update the Network's weights/biases from the deltas computed by
+    /// the most recent backprop step, using the given Optimizer
+    ///
+    /// # Arguments
+    ///
+    /// * `optimizer` - Optimization method used when performing gradient descent
+    /// * `input_rows` - Number of rows in the training input set
+    pub fn update(&mut self, optimizer: &mut dyn Optimizer, input_rows: usize) {
+        optimize(optimizer, &mut self.layers, input_rows);
+        if let Some(regularization) = &self.regularization {
+            regularization::update(&mut self.layers, regularization, optimizer.learning_rate());
+        }
+        if let Some((config, averaged)) = &mut self.averaged {
+            averaging::update(&mut averaged.layers, &self.layers, config.tau);
+        }
     }
 
     /// Performs the feedforward step for all Layers to return the
@@ -236,12 +1086,23 @@ impl Perceptron {
     /// # Arguments
     ///
     /// * `inputs` - Matrix of input vectors
-    pub fn feed_forward(&mut self, inputs: &Array2<f64>) -> Array2<f64> {
-        let mut output: Array2<f64> = inputs.to_owned();
-        for layer in self.layers.iter_mut() {
-            output = layer.feed_forward(&output);
+    pub fn feed_forward(&mut self, inputs: &Array2<f64>) -> &Array2<f64> {
+        match &mut self.input_dropout {
+            Some((rate, rng)) => {
+                let inputs: Array2<f64> = drop_inputs(inputs, *rate, rng);
+                self.layers[0].feed_forward(&inputs);
+            }
+            None => {
+                self.layers[0].feed_forward(inputs);
+            }
+        }
+        for i in 1..self.layers.len() {
+            // Each layer reads directly from the previous
+            // layer's output buffer
+            let (prev_layers, next_layers) = self.layers.split_at_mut(i);
+            next_layers[0].feed_forward(prev_layers[i - 1].outputs());
         }
-        output
+        self.layers.last().unwrap().outputs()
     }
 
     /// Performs the backpropogation step for all layers to calculate
@@ -261,6 +1122,47 @@ impl Perceptron {
         }
     }
 
+    /// Backpropagate the cost of the most recent feedforward pass. When the
+    /// cost has a combined derivative for the output layer's activation
+    /// (e.g. softmax + cross-entropy), it's used in place of the chain rule
+    ///
+    /// # Arguments
+    ///
+    /// * `cost` - Loss function used to compute the output layer's deltas
+    /// * `expected` - Encoded output vectors of the most recent feedforward pass
+    pub fn back_prop_cost(&mut self, cost: &dyn Cost, expected: &Array2<f64>) {
+        let output_layer: &Layer = match self.layers.last() {
+            Some(output_layer) => output_layer,
+            None => return,
+        };
+        let actual: &Array2<f64> = output_layer.outputs();
+        match cost.logit_delta(actual, expected, output_layer.activation_name()) {
+            Some(logit_deltas) => {
+                let (output_layer, hidden_layers) = self.layers.split_last_mut().unwrap();
+                output_layer.back_prop_with_logit_deltas(&logit_deltas);
+                let mut attached_layer: &Layer = output_layer;
+                for layer in hidden_layers.iter_mut().rev() {
+                    layer.back_prop(attached_layer);
+                    attached_layer = layer;
+                }
+            }
+            None => {
+                let deltas: Array2<f64> = cost.prime(actual, expected);
+                self.back_prop(&deltas);
+            }
+        }
+    }
+
+    /// Gradient of the cost with respect to the Network's inputs for the
+    /// most recent backprop step. Used to continue backprop into another
+    /// Network whose outputs are this Network's inputs
+    pub fn input_deltas(&self) -> Array2<f64> {
+        match self.layers.first() {
+            Some(first_layer) => first_layer.input_deltas(),
+            None => Array2::zeros((0, 0)),
+        }
+    }
+
     /// Computes the network's prediction for a given input.
     /// Assumes the network has already been trained, therefore
     /// Dropout Regularization is not taken into account
@@ -269,12 +1171,215 @@ impl Perceptron {
     ///
     /// * `inputs` - Matrix of input vectors
     /// * `encoder` - Method for decoding output to readable values
-    pub fn predict(&mut self, inputs: &Array2<f64>, encoder: &dyn Encoder) -> Array2<f64> {
+    pub fn predict(&self, inputs: &Array2<f64>, encoder: &dyn Encoder) -> Array2<f64> {
+        encoder.decode(&self.predict_raw(inputs))
+    }
+
+    /// Same as `predict`, but the output layer's values are
+    /// returned without being decoded
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - Matrix of input vectors
+    pub fn predict_raw(&self, inputs: &Array2<f64>) -> Array2<f64> {
+        match (self.temperature, self.layers.last()) {
+            (Some(temperature), Some(output_layer)) => {
+                output_layer.activate_scaled(&self.predict_logits(inputs), temperature)
+            }
+            _ => {
+                let mut prev_outputs: Array2<f64> = inputs.to_owned();
+                for layer in self.layers.iter() {
+                    prev_outputs = layer.predict(&prev_outputs);
+                }
+                prev_outputs
+            }
+        }
+    }
+
+    /// Output layer's values before its activation function
+    /// is applied (and before any temperature scaling)
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - Matrix of input vectors
+    pub fn predict_logits(&self, inputs: &Array2<f64>) -> Array2<f64> {
         let mut prev_outputs: Array2<f64> = inputs.to_owned();
-        for layer in self.layers.iter_mut() {
-            prev_outputs = layer.predict(&prev_outputs);
+        if let Some((output_layer, hidden_layers)) = self.layers.split_last() {
+            for layer in hidden_layers {
+                prev_outputs = layer.predict(&prev_outputs);
+            }
+            prev_outputs = output_layer.predict_logits(&prev_outputs);
+        }
+        prev_outputs
+    }
+
+    /// Apply the output layer's activation function to logits divided by a temperature
+    ///
+    /// # Arguments
+    ///
+    /// * `logits` - Output layer values before activation (output of `predict_logits`)
+    /// * `temperature` - Value each logit is divided by
+    pub fn activate_logits(&self, logits: &Array2<f64>, temperature: f64) -> Array2<f64> {
+        match self.layers.last() {
+            Some(output_layer) => output_layer.activate_scaled(logits, temperature),
+            None => logits.to_owned(),
+        }
+    }
+
+    /// Feeds the given inputs through the trained network (without dropout)
+    /// and returns every Layer's output, so intermediate layers can be used
+    /// as extracted features or inspected directly
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - Matrix of input vectors
+    ///
+    /// # Returns
+    ///
+    /// The output matrix of each Layer, in order (the last
+    /// matrix is the same as the output of `predict_raw`)
+    pub fn forward_with_activations(&self, inputs: &Array2<f64>) -> Vec<Array2<f64>> {
+        let mut all_outputs: Vec<Array2<f64>> = Vec::with_capacity(self.layers.len());
+        for layer in self.layers.iter() {
+            let outputs: Array2<f64> = match all_outputs.last() {
+                Some(prev_outputs) => layer.predict(prev_outputs),
+                None => layer.predict(inputs),
+            };
+            all_outputs.push(outputs);
+        }
+        all_outputs
+    }
+
+    /// Backpropagates a gradient with respect to the (raw) output layer
+    /// values all the way back to the Network's inputs
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - Matrix of input vectors
+    /// * `output_gradient` - Gradient with respect to each raw output value
+    ///   (same shape as the output of `predict_raw`)
+    pub fn input_gradient(
+        &self,
+        inputs: &Array2<f64>,
+        output_gradient: &Array2<f64>,
+    ) -> Array2<f64> {
+        // Inputs of each Layer are the outputs of the previous one
+        // (the last entry is the output layer's, which isn't needed)
+        let mut layer_inputs: Vec<Array2<f64>> = vec![inputs.to_owned()];
+        layer_inputs.extend(self.forward_with_activations(inputs));
+
+        let mut gradient: Array2<f64> = output_gradient.to_owned();
+        let last: usize = self.layers.len().saturating_sub(1);
+        for (i, (layer, inputs)) in self
+            .layers
+            .iter()
+            .zip(layer_inputs.iter())
+            .enumerate()
+            .rev()
+        {
+            // Calibrated temperature only scales the output layer
+            let temperature: f64 = match self.temperature {
+                Some(temperature) if i == last => temperature,
+                _ => 1.0,
+            };
+            gradient = layer.input_gradient(inputs, &gradient, temperature);
         }
-        encoder.decode(&prev_outputs)
+        gradient
+    }
+
+    /// Same as `predict_raw`, but the input vectors are fed forward one chunk
+    /// at a time on the current thread, so memory used by intermediate layer
+    /// outputs is bounded by `chunk_size` no matter how many inputs there are
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - Matrix of input vectors
+    /// * `chunk_size` - Maximum number of input vectors fed forward at once
+    pub fn predict_chunked(&self, inputs: &Array2<f64>, chunk_size: usize) -> Array2<f64> {
+        let mut outputs: Array2<f64> = Array2::zeros((self.output_size(), inputs.ncols()));
+        let chunks = inputs.axis_chunks_iter(Axis(1), chunk_size.max(1));
+        let output_chunks = outputs.axis_chunks_iter_mut(Axis(1), chunk_size.max(1));
+        for (chunk, mut output_chunk) in chunks.zip(output_chunks) {
+            output_chunk.assign(&self.predict_raw(&chunk.to_owned()));
+        }
+        outputs
+    }
+
+    /// Same as `predict`, but large input matrices are split into chunks
+    /// of input vectors that are fed forward in parallel. Inputs with no
+    /// more than `chunk_size` vectors are predicted on the current thread.
+    /// Without the "threads" feature, chunks are predicted sequentially
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - Matrix of input vectors
+    /// * `encoder` - Method for decoding output to readable values
+    /// * `chunk_size` - Maximum number of input vectors in each chunk
+    pub fn par_predict(
+        &self,
+        inputs: &Array2<f64>,
+        encoder: &dyn Encoder,
+        chunk_size: usize,
+    ) -> Array2<f64> {
+        if inputs.ncols() <= chunk_size {
+            return self.predict(inputs, encoder);
+        }
+        #[cfg(feature = "threads")]
+        let chunks = inputs.axis_chunks_iter(Axis(1), chunk_size).into_par_iter();
+        #[cfg(not(feature = "threads"))]
+        let chunks = inputs.axis_chunks_iter(Axis(1), chunk_size);
+
+        let chunks: Vec<Array2<f64>> = chunks
+            .map(|chunk| self.predict(&chunk.to_owned(), encoder))
+            .collect();
+
+        // Decoded outputs store each vector as a row
+        let views: Vec<ArrayView2<f64>> = chunks.iter().map(|chunk| chunk.view()).collect();
+        concatenate(Axis(0), &views).unwrap()
+    }
+}
+
+/// Seed of one dropout stream, mixed from the Network's seed and the
+/// stream's index with the SplitMix64 finalizer, so neighbouring
+/// streams are uncorrelated
+///
+/// # Arguments
+///
+/// * `seed` - Seed of the Network
+/// * `stream` - Index of the stream (0 for input dropout, then one per Layer)
+fn dropout_stream_seed(seed: u64, stream: u64) -> u64 {
+    let mut z: u64 = seed.wrapping_add((stream + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Copy of the input vectors where randomly chosen input features are
+/// zeroed for every vector, and the rest are scaled by 1 / (1 - rate)
+///
+/// # Arguments
+///
+/// * `inputs` - Matrix of input vectors (features x samples)
+/// * `rate` - Rate at which input features are dropped
+/// * `rng` - Random number generator that chooses dropped features
+fn drop_inputs(inputs: &Array2<f64>, rate: f32, rng: &mut StdRng) -> Array2<f64> {
+    let range: Uniform<f32> = Uniform::new(0.0, 1.0);
+    let scale: f64 = layer::dropout_scale(rate);
+    let mut inputs: Array2<f64> = inputs.to_owned();
+
+    for mut row in inputs.axis_iter_mut(Axis(0)) {
+        if range.sample(rng) < rate {
+            row.fill(0.0);
+        } else {
+            row *= scale;
+        }
+    }
+    inputs
+}
+
+impl Default for Perceptron {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -283,12 +1388,27 @@ impl Serialize for Perceptron {
     where
         S: Serializer,
     {
-        let mut s = serializer.serialize_struct("Perceptron", 1)?;
+        // Binary formats write the number of fields up front
+        let fields: usize = 3 + self.temperature.is_some() as usize;
+        let mut s = serializer.serialize_struct("Perceptron", fields)?;
+        s.serialize_field("num_parameters", &self.num_parameters())?;
+        s.serialize_field("layer_shapes", &self.layer_shapes())?;
         s.serialize_field("layers", &self.layers)?;
+        // Only present for calibrated networks
+        match self.temperature {
+            Some(temperature) => s.serialize_field("temperature", &temperature)?,
+            None => s.skip_field("temperature")?,
+        }
         s.end()
     }
 }
 
+impl Display for Perceptron {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.summary(1))
+    }
+}
+
 impl Debug for Perceptron {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         // Only returns number of layers, not the information contained
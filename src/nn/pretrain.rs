@@ -0,0 +1,133 @@
+use super::batches::BatchConfig;
+use super::data_source::{DataMetadata, DataSource};
+use super::functions::cost::MSE;
+use super::functions::encoder::Identity;
+use super::functions::metric::{Metric, RMSE};
+use super::functions::optimizer::Optimizer;
+use super::perceptron::Perceptron;
+use ndarray::Array2;
+use serde::Serialize;
+use serde_json::Map;
+
+/// Options for unsupervised (autoencoder) pre-training
+#[derive(Clone)]
+pub struct PretrainConfig {
+    /// Number of epochs the autoencoder is trained for
+    pub epochs: usize,
+
+    /// Maximum number of input vectors trained during each cycle
+    /// (defaults to the supervised batch size when `None`)
+    pub batch_size: Option<usize>,
+
+    /// Gradient descent method used to train the autoencoder
+    pub optimizer: Box<dyn Optimizer>,
+}
+
+/// Reconstruction error of the autoencoder before and after pre-training
+#[derive(Serialize, Debug, Clone)]
+pub struct Pretraining {
+    /// Number of epochs the autoencoder was trained for
+    pub epochs: usize,
+
+    /// Root mean squared reconstruction error of
+    /// the validation inputs before pre-training
+    pub rmse_before: f32,
+
+    /// Root mean squared reconstruction error of
+    /// the validation inputs after pre-training
+    pub rmse_after: f32,
+}
+
+/// Dataset whose expected outputs are its own inputs
+struct ReconstructionData {
+    /// Training input vectors (features x samples)
+    train_inputs: Array2<f64>,
+
+    /// Validation input vectors (features x samples)
+    validation_inputs: Array2<f64>,
+}
+
+impl DataSource for ReconstructionData {
+    fn metadata(&self) -> DataMetadata {
+        DataMetadata {
+            train_samples: self.train_inputs.ncols(),
+            validation_samples: self.validation_inputs.ncols(),
+            test_samples: 0,
+            input_size: self.train_inputs.nrows(),
+            output_size: self.train_inputs.nrows(),
+        }
+    }
+
+    fn training_set(&self) -> (Array2<f64>, Array2<f64>) {
+        (
+            self.train_inputs.to_owned(),
+            self.train_inputs.t().to_owned(),
+        )
+    }
+
+    fn validation_set(&self) -> (Array2<f64>, Array2<f64>) {
+        (
+            self.validation_inputs.to_owned(),
+            self.validation_inputs.t().to_owned(),
+        )
+    }
+}
+
+/// Train every hidden layer of a Network as the encoder of an autoencoder
+/// that reconstructs the training inputs (with MSE), then copy the trained
+/// encoder weights back into the Network. Returns `None` (and leaves the
+/// Network untouched) when the Network doesn't have any hidden layers
+///
+/// # Arguments
+///
+/// * `network` - Untrained Network whose hidden layers are initialized
+/// * `data` - Source of the training and validation inputs (outputs are ignored)
+/// * `config` - Number of epochs, batch size, and optimizer of the autoencoder
+/// * `batch_config` - Shuffling options (and the default batch size)
+pub fn pretrain(
+    network: &mut Perceptron,
+    data: &dyn DataSource,
+    config: &PretrainConfig,
+    batch_config: BatchConfig,
+) -> Option<Pretraining> {
+    let mut autoencoder: Perceptron = network.autoencoder()?;
+
+    let (train_inputs, _) = data.training_set();
+    let (validation_inputs, _) = data.validation_set();
+    let reconstruction = ReconstructionData {
+        train_inputs,
+        validation_inputs,
+    };
+
+    // Reconstructions are compared directly against the inputs
+    let encoder: Identity = Identity::new(&Map::new());
+    let metric: RMSE = RMSE::new(&Map::new());
+    let rmse = |autoencoder: &Perceptron| -> f32 {
+        let (inputs, expected) = reconstruction.validation_set();
+        metric.value(&autoencoder.predict(&inputs, &encoder), &expected)
+    };
+
+    let rmse_before: f32 = rmse(&autoencoder);
+    let mut optimizer: Box<dyn Optimizer> = config.optimizer.clone();
+    let batch_config = BatchConfig {
+        batch_size: config.batch_size.or(batch_config.batch_size),
+        ..batch_config
+    };
+    let epochs: usize = autoencoder.fit(
+        &reconstruction,
+        optimizer.as_mut(),
+        &metric,
+        &MSE,
+        &encoder,
+        config.epochs,
+        batch_config,
+    );
+    let rmse_after: f32 = rmse(&autoencoder);
+
+    network.load_encoder(autoencoder);
+    Some(Pretraining {
+        epochs,
+        rmse_before,
+        rmse_after,
+    })
+}
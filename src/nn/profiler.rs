@@ -0,0 +1,86 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+use tracing::span::EnteredSpan;
+
+/// Start timing a phase of the training loop. Also enters a
+/// tracing span with the same name, so external subscribers
+/// (e.g. flamegraph layers) see the same phase markers.
+/// Time is only measured when the given profile is enabled
+#[doc(hidden)]
+#[macro_export]
+macro_rules! phase {
+    ($profile:expr, $name:literal) => {
+        $crate::nn::profiler::Phase::start(
+            $name,
+            tracing::trace_span!($name).entered(),
+            $profile.is_some(),
+        )
+    };
+}
+
+/// A single in-progress phase of the training loop
+pub struct Phase {
+    /// Name of the phase
+    name: &'static str,
+
+    /// Time when the phase started (only measured when profiling)
+    start: Option<Instant>,
+
+    /// Tracing span that's exited when the phase is dropped
+    _span: EnteredSpan,
+}
+
+impl Phase {
+    /// # Arguments
+    ///
+    /// * `name` - Name of the phase
+    /// * `span` - Entered tracing span for the phase
+    /// * `timed` - Whether or not the phase's elapsed time is measured
+    pub fn start(name: &'static str, span: EnteredSpan, timed: bool) -> Self {
+        Self {
+            name,
+            start: if timed { Some(Instant::now()) } else { None },
+            _span: span,
+        }
+    }
+}
+
+/// Accumulated timing for a single phase
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct PhaseTiming {
+    /// Number of times the phase was run
+    calls: usize,
+
+    /// Total time spent in the phase (in seconds)
+    total_secs: f64,
+
+    /// Average time spent in each call (in seconds)
+    mean_secs: f64,
+}
+
+/// Per-phase timing breakdown for a training run
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct Profile {
+    /// Timings for each phase, keyed by phase name
+    phases: BTreeMap<&'static str, PhaseTiming>,
+}
+
+impl Profile {
+    /// Stop timing a phase and add its elapsed time to the totals
+    ///
+    /// # Arguments
+    ///
+    /// * `phase` - Phase that has just finished
+    pub fn record(&mut self, phase: Phase) {
+        let elapsed: Duration = match phase.start {
+            Some(start) => start.elapsed(),
+            None => return,
+        };
+        let timing: &mut PhaseTiming = self.phases.entry(phase.name).or_default();
+
+        timing.calls += 1;
+        timing.total_secs += elapsed.as_secs_f64();
+        timing.mean_secs = timing.total_secs / timing.calls as f64;
+    }
+}
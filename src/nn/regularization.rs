@@ -0,0 +1,58 @@
+use super::layer::Layer;
+use ndarray::Array2;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// L1 and L2 penalties on the size of a Network's weights, which
+/// shrink the weights towards zero after every training step
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, Default)]
+pub struct Regularization {
+    /// Strength of the L1 (absolute value) penalty
+    #[serde(default)]
+    pub l1: f64,
+
+    /// Strength of the L2 (squared value) penalty
+    #[serde(default)]
+    pub l2: f64,
+}
+
+impl Regularization {
+    /// Penalty of a matrix of weights: `l1 * sum(|w|) + l2 / 2 * sum(w^2)`
+    ///
+    /// # Arguments
+    ///
+    /// * `weights` - Matrix of weights
+    pub fn penalty(&self, weights: &Array2<f64>) -> f64 {
+        weights
+            .iter()
+            .map(|weight| self.l1 * weight.abs() + 0.5 * self.l2 * weight * weight)
+            .sum()
+    }
+
+    /// Derivative of the penalty with respect to each weight
+    ///
+    /// # Arguments
+    ///
+    /// * `weights` - Matrix of weights
+    pub fn gradient(&self, weights: &Array2<f64>) -> Array2<f64> {
+        // The L1 penalty's subgradient at zero is zero, so weights don't oscillate around it
+        weights.mapv(|weight| {
+            let sign: f64 = if weight == 0.0 { 0.0 } else { weight.signum() };
+            self.l1 * sign + self.l2 * weight
+        })
+    }
+}
+
+/// Move every Layer's weights (but not biases) down the
+/// gradient of the regularization penalty
+///
+/// # Arguments
+///
+/// * `layers` - Layers being trained
+/// * `regularization` - Strength of the L1 and L2 penalties
+/// * `learning_rate` - Step size of the optimizer
+pub fn update(layers: &mut [Layer], regularization: &Regularization, learning_rate: f64) {
+    for layer in layers.iter_mut() {
+        layer.regularize(regularization, learning_rate);
+    }
+}
@@ -0,0 +1,90 @@
+use super::functions::encoder::Encoder;
+use super::functions::metric::Metric;
+use super::perceptron::{Perceptron, VALIDATION_CHUNK_SIZE};
+use ndarray::{Array2, Axis};
+use ndarray_rand::rand_distr::StandardNormal;
+use ndarray_rand::RandomExt;
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+
+/// Noise levels that validation inputs are corrupted with
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct RobustnessConfig {
+    /// Highest noise level, as a multiple of each input
+    /// feature's standard deviation
+    pub max_noise: f64,
+
+    /// Number of evenly spaced noise levels above zero
+    pub steps: usize,
+}
+
+impl RobustnessConfig {
+    /// Every noise level from zero (clean inputs) up to `max_noise`
+    pub fn levels(&self) -> Vec<f64> {
+        let steps: usize = self.steps.max(1);
+        (0..=steps)
+            .map(|step| self.max_noise * step as f64 / steps as f64)
+            .collect()
+    }
+}
+
+/// Metric score of the validation set at a single noise level
+#[derive(Serialize, Debug, Clone)]
+pub struct NoiseLevel {
+    /// Standard deviation of the added Gaussian noise, as
+    /// a multiple of each input feature's standard deviation
+    pub noise: f64,
+
+    /// Metric score for the corrupted inputs
+    pub metric: f32,
+
+    /// Whether or not the score is a "passing" score
+    pub passed: bool,
+}
+
+/// Corrupt the validation inputs with zero-mean Gaussian noise at
+/// every configured level, and score the Network's predictions for
+/// each. Noise is scaled by each input feature's standard deviation,
+/// so the curve is comparable across differently scaled datasets
+///
+/// # Arguments
+///
+/// * `network` - Trained Network
+/// * `inputs` - Validation input vectors (features x samples)
+/// * `outputs` - Validation output values (samples x outputs)
+/// * `metric` - Method used to score the predictions
+/// * `encoder` - Method for decoding output to readable values
+/// * `config` - Noise levels
+/// * `rng` - Random number generator the noise is drawn from
+pub fn robustness_curve(
+    network: &Perceptron,
+    inputs: &Array2<f64>,
+    outputs: &Array2<f64>,
+    metric: &dyn Metric,
+    encoder: &dyn Encoder,
+    config: RobustnessConfig,
+    rng: &mut StdRng,
+) -> Vec<NoiseLevel> {
+    // Features are stored as rows, so each one's spread is taken along the samples
+    let feature_std: Array2<f64> = inputs.std_axis(Axis(1), 0.0).insert_axis(Axis(1));
+
+    config
+        .levels()
+        .into_iter()
+        .map(|noise| {
+            let corrupted: Array2<f64> = if noise > 0.0 {
+                let gaussian: Array2<f64> = Array2::random_using(inputs.dim(), StandardNormal, rng);
+                inputs + &(gaussian * &feature_std * noise)
+            } else {
+                inputs.to_owned()
+            };
+            let prediction: Array2<f64> =
+                network.par_predict(&corrupted, encoder, VALIDATION_CHUNK_SIZE);
+            NoiseLevel {
+                noise,
+                metric: metric.value(&prediction, outputs),
+                passed: metric.check(&prediction, outputs),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,80 @@
+use super::perceptron::Perceptron;
+use crate::error::OpenPbError;
+use ndarray::{Array2, Axis};
+use ndarray_stats::QuantileExt;
+use serde::Serialize;
+
+/// Input-gradient magnitudes of a single sample
+#[derive(Serialize, Debug, Clone)]
+pub struct SampleSaliency {
+    /// Index of the sample (column of the input matrix)
+    pub sample: usize,
+
+    /// Output neuron the gradient was taken of (the predicted class)
+    pub class: usize,
+
+    /// Absolute gradient of the output with respect to each input value
+    pub gradients: Vec<f64>,
+}
+
+impl SampleSaliency {
+    /// Indices of the `n` inputs with the largest gradient magnitudes,
+    /// in descending order
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - Maximum number of indices returned
+    pub fn most_salient(&self, n: usize) -> Vec<usize> {
+        let mut indices: Vec<usize> = (0..self.gradients.len()).collect();
+        indices.sort_by(|&a, &b| self.gradients[b].total_cmp(&self.gradients[a]));
+        indices.truncate(n);
+        indices
+    }
+}
+
+/// Backpropagate the highest raw output of each selected sample to the
+/// network's inputs, showing which input values the prediction is most
+/// sensitive to
+///
+/// # Arguments
+///
+/// * `network` - Trained network
+/// * `inputs` - Matrix of input vectors, one per column
+/// * `samples` - Indices of the samples (columns) to compute saliency for
+pub fn saliency(
+    network: &Perceptron,
+    inputs: &Array2<f64>,
+    samples: &[usize],
+) -> Result<Vec<SampleSaliency>, OpenPbError> {
+    if let Some(&sample) = samples.iter().find(|&&sample| sample >= inputs.ncols()) {
+        return Err(format!(
+            "Sample {sample} is out of range (only {} samples)",
+            inputs.ncols()
+        )
+        .into());
+    }
+    let selected: Array2<f64> = inputs.select(Axis(1), samples);
+    let outputs: Array2<f64> = network.predict_raw(&selected);
+
+    // Only the predicted class of each sample has a gradient of 1
+    let mut output_gradient: Array2<f64> = Array2::zeros(outputs.raw_dim());
+    let classes: Vec<usize> = outputs
+        .axis_iter(Axis(1))
+        .map(|output| output.argmax().unwrap_or_default())
+        .collect();
+    for (col, &class) in classes.iter().enumerate() {
+        output_gradient[[class, col]] = 1.0;
+    }
+    let gradients: Array2<f64> = network.input_gradient(&selected, &output_gradient);
+
+    Ok(samples
+        .iter()
+        .zip(classes)
+        .zip(gradients.axis_iter(Axis(1)))
+        .map(|((&sample, class), gradient)| SampleSaliency {
+            sample,
+            class,
+            gradients: gradient.iter().map(|value| value.abs()).collect(),
+        })
+        .collect())
+}
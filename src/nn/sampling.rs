@@ -0,0 +1,56 @@
+use ndarray::{Array2, Axis};
+use rand::rngs::StdRng;
+use rand::seq::index;
+use serde::{Deserialize, Serialize};
+
+/// Options for checking the early stopping metric against a random
+/// subsample of the validation set, with periodic full evaluations
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct ValidationSampling {
+    /// Fraction of the validation samples drawn each epoch
+    pub fraction: f64,
+
+    /// Number of epochs between evaluations of the full validation set
+    pub full_every: usize,
+}
+
+impl ValidationSampling {
+    /// Whether or not the full validation set is evaluated after the given epoch
+    ///
+    /// # Arguments
+    ///
+    /// * `epoch` - Epoch that has just finished
+    pub fn is_full_epoch(&self, epoch: usize) -> bool {
+        epoch.is_multiple_of(self.full_every.max(1))
+    }
+
+    /// Number of samples drawn from a validation set (always at least one)
+    ///
+    /// # Arguments
+    ///
+    /// * `samples` - Number of samples in the validation set
+    pub fn sample_size(&self, samples: usize) -> usize {
+        ((samples as f64 * self.fraction).ceil() as usize).clamp(1, samples.max(1))
+    }
+
+    /// Draw a random subsample (without replacement) of the validation set
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - Validation input vectors (features x samples)
+    /// * `outputs` - Validation output values (samples x outputs)
+    /// * `rng` - Random number generator that chooses the samples
+    pub fn sample(
+        &self,
+        inputs: &Array2<f64>,
+        outputs: &Array2<f64>,
+        rng: &mut StdRng,
+    ) -> (Array2<f64>, Array2<f64>) {
+        let samples: usize = outputs.nrows();
+        let indices: Vec<usize> = index::sample(rng, samples, self.sample_size(samples)).into_vec();
+        (
+            inputs.select(Axis(1), &indices),
+            outputs.select(Axis(0), &indices),
+        )
+    }
+}
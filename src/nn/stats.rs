@@ -0,0 +1,351 @@
+use ndarray::Array2;
+use serde::{Deserialize, Serialize};
+
+/// Summary statistics of a matrix of values
+#[derive(Serialize, Debug, Clone)]
+pub struct ValueStats {
+    /// Mean of every value
+    pub mean: f64,
+
+    /// Standard deviation of every value
+    pub std: f64,
+
+    /// Smallest value
+    pub min: f64,
+
+    /// Largest value
+    pub max: f64,
+}
+
+impl ValueStats {
+    /// # Arguments
+    ///
+    /// * `values` - Matrix of values to summarize
+    pub fn new(values: &Array2<f64>) -> Self {
+        if values.is_empty() {
+            return Self {
+                mean: 0.0,
+                std: 0.0,
+                min: 0.0,
+                max: 0.0,
+            };
+        }
+        Self {
+            mean: values.mean().unwrap_or_default(),
+            std: values.std(0.0),
+            min: values.iter().copied().fold(f64::INFINITY, f64::min),
+            max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        }
+    }
+}
+
+/// Weight and gradient statistics of a single Layer
+#[derive(Serialize, Debug, Clone)]
+pub struct LayerStats {
+    /// Statistics of the weights matrix
+    pub weights: ValueStats,
+
+    /// Statistics of the bias vector
+    pub biases: ValueStats,
+
+    /// L2 norm of the weight gradient from the most recent minibatch
+    pub weight_gradient_norm: f64,
+
+    /// L2 norm of the bias gradient from the most recent minibatch
+    pub bias_gradient_norm: f64,
+}
+
+/// Statistics of every Layer at the end of a single epoch
+#[derive(Serialize, Debug, Clone)]
+pub struct EpochStats {
+    /// Epoch the statistics were recorded after
+    pub epoch: usize,
+
+    /// Statistics of each Layer (in order)
+    pub layers: Vec<LayerStats>,
+}
+
+/// Per-layer statistics recorded at a fixed epoch interval during training
+#[derive(Serialize, Debug, Clone)]
+pub struct TrainingStats {
+    /// Number of epochs between recordings
+    pub interval: usize,
+
+    /// Recorded statistics, in order of epoch
+    pub epochs: Vec<EpochStats>,
+}
+
+impl TrainingStats {
+    /// # Arguments
+    ///
+    /// * `interval` - Number of epochs between recordings
+    pub fn new(interval: usize) -> Self {
+        Self {
+            interval: interval.max(1),
+            epochs: vec![],
+        }
+    }
+
+    /// Whether or not statistics are recorded after the given epoch
+    ///
+    /// # Arguments
+    ///
+    /// * `epoch` - Epoch that has just finished (starting at 1)
+    pub fn is_due(&self, epoch: usize) -> bool {
+        epoch.is_multiple_of(self.interval)
+    }
+}
+
+/// L2 (Frobenius) norm of a matrix
+///
+/// # Arguments
+///
+/// * `values` - Matrix of values
+pub fn l2_norm(values: &Array2<f64>) -> f64 {
+    values.iter().map(|value| value * value).sum::<f64>().sqrt()
+}
+
+/// Relative change below which a Layer is considered frozen
+pub const FROZEN_THRESHOLD: f64 = 1e-4;
+
+/// Copy of a single Layer's weights and biases at one point during training
+#[derive(Debug, Clone)]
+pub struct LayerSnapshot {
+    /// Matrix of weights (shape: neurons x inputs)
+    pub weights: Array2<f64>,
+
+    /// Vector of bias offsets
+    pub biases: Array2<f64>,
+}
+
+impl LayerSnapshot {
+    /// Change in a single Layer's values between this snapshot and a newer one
+    ///
+    /// # Arguments
+    ///
+    /// * `newer` - Snapshot of the same Layer taken later in training
+    pub fn distance(&self, newer: &LayerSnapshot) -> LayerDistance {
+        let weights: f64 = l2_norm(&(&newer.weights - &self.weights));
+        let biases: f64 = l2_norm(&(&newer.biases - &self.biases));
+        // Relative to the size of the older values, so layers of
+        // different widths can be compared to each other
+        let total: f64 = (weights * weights + biases * biases).sqrt();
+        let norm: f64 = (l2_norm(&self.weights).powi(2) + l2_norm(&self.biases).powi(2)).sqrt();
+        let relative: f64 = total / norm.max(f64::EPSILON);
+
+        LayerDistance {
+            weights,
+            biases,
+            relative,
+            frozen: relative < FROZEN_THRESHOLD,
+        }
+    }
+}
+
+/// L2 distance between two snapshots of a single Layer
+#[derive(Serialize, Debug, Clone)]
+pub struct LayerDistance {
+    /// L2 distance between the weight matrices
+    pub weights: f64,
+
+    /// L2 distance between the bias vectors
+    pub biases: f64,
+
+    /// Combined distance divided by the L2 norm of the older values
+    pub relative: f64,
+
+    /// Whether or not the relative change is below `FROZEN_THRESHOLD`
+    pub frozen: bool,
+}
+
+/// Per-layer change between two consecutive snapshots
+#[derive(Serialize, Debug, Clone)]
+pub struct SnapshotDiff {
+    /// Epoch the older snapshot was taken after (0 is before training)
+    pub from_epoch: usize,
+
+    /// Epoch the newer snapshot was taken after
+    pub to_epoch: usize,
+
+    /// Change of each Layer (in order)
+    pub layers: Vec<LayerDistance>,
+}
+
+/// Weight snapshots taken at a fixed epoch interval during training,
+/// reported as the per-layer distance between consecutive snapshots
+#[derive(Serialize, Debug, Clone)]
+pub struct WeightSnapshots {
+    /// Number of epochs between snapshots
+    pub interval: usize,
+
+    /// Change between each pair of consecutive snapshots, in order of epoch
+    pub diffs: Vec<SnapshotDiff>,
+
+    /// Most recent snapshot and the epoch it was taken after
+    #[serde(skip)]
+    latest: Option<(usize, Vec<LayerSnapshot>)>,
+}
+
+impl WeightSnapshots {
+    /// # Arguments
+    ///
+    /// * `interval` - Number of epochs between snapshots
+    pub fn new(interval: usize) -> Self {
+        Self {
+            interval: interval.max(1),
+            diffs: vec![],
+            latest: None,
+        }
+    }
+
+    /// Whether or not a snapshot is taken after the given epoch
+    ///
+    /// # Arguments
+    ///
+    /// * `epoch` - Epoch that has just finished (starting at 1)
+    pub fn is_due(&self, epoch: usize) -> bool {
+        epoch.is_multiple_of(self.interval)
+    }
+
+    /// Whether or not the first snapshot has been taken
+    pub fn has_snapshot(&self) -> bool {
+        self.latest.is_some()
+    }
+
+    /// Store a new snapshot, recording its distance from the previous one
+    ///
+    /// # Arguments
+    ///
+    /// * `epoch` - Epoch the snapshot was taken after (0 is before training)
+    /// * `snapshot` - Weights and biases of each Layer (in order)
+    pub fn record(&mut self, epoch: usize, snapshot: Vec<LayerSnapshot>) {
+        if let Some((from_epoch, previous)) = &self.latest {
+            let layers: Vec<LayerDistance> = previous
+                .iter()
+                .zip(snapshot.iter())
+                .map(|(older, newer)| older.distance(newer))
+                .collect();
+            self.diffs.push(SnapshotDiff {
+                from_epoch: *from_epoch,
+                to_epoch: epoch,
+                layers,
+            });
+        }
+        self.latest = Some((epoch, snapshot));
+    }
+}
+
+/// Default number of bins of each weight histogram
+pub const DEFAULT_HISTOGRAM_BINS: usize = 20;
+
+/// Options for recording per-layer weight histograms during training
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct HistogramConfig {
+    /// Number of epochs between histograms
+    pub interval: usize,
+
+    /// Number of equal-width bins of each histogram
+    pub bins: usize,
+}
+
+/// Counts of a matrix's values in equal-width bins between
+/// its smallest and largest value
+#[derive(Serialize, Debug, Clone)]
+pub struct Histogram {
+    /// Lower edge of the first bin
+    pub min: f64,
+
+    /// Upper edge of the last bin
+    pub max: f64,
+
+    /// Number of values in each bin (the last bin includes `max`)
+    pub counts: Vec<usize>,
+}
+
+impl Histogram {
+    /// # Arguments
+    ///
+    /// * `values` - Matrix of values to bin
+    /// * `bins` - Number of bins
+    pub fn new(values: &Array2<f64>, bins: usize) -> Self {
+        let bins: usize = bins.max(1);
+        let mut counts: Vec<usize> = vec![0; bins];
+        if values.is_empty() {
+            return Self {
+                min: 0.0,
+                max: 0.0,
+                counts,
+            };
+        }
+
+        let min: f64 = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max: f64 = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let width: f64 = (max - min) / bins as f64;
+        for value in values {
+            // Constant matrices (zero width) put every value in the first bin
+            let bin: usize = if width > 0.0 {
+                ((value - min) / width) as usize
+            } else {
+                0
+            };
+            counts[bin.min(bins - 1)] += 1;
+        }
+        Self { min, max, counts }
+    }
+}
+
+/// Weight and bias histograms of a single Layer
+#[derive(Serialize, Debug, Clone)]
+pub struct LayerHistogram {
+    /// Histogram of the weights matrix
+    pub weights: Histogram,
+
+    /// Histogram of the bias vector
+    pub biases: Histogram,
+}
+
+/// Histograms of every Layer at the end of a single epoch
+#[derive(Serialize, Debug, Clone)]
+pub struct EpochHistograms {
+    /// Epoch the histograms were recorded after (0 is before training)
+    pub epoch: usize,
+
+    /// Histograms of each Layer (in order)
+    pub layers: Vec<LayerHistogram>,
+}
+
+/// Per-layer weight histograms recorded at a fixed epoch interval during
+/// training, a lightweight alternative to storing full weight snapshots
+#[derive(Serialize, Debug, Clone)]
+pub struct WeightHistograms {
+    /// Number of epochs between recordings
+    pub interval: usize,
+
+    /// Number of bins of each histogram
+    pub bins: usize,
+
+    /// Recorded histograms, in order of epoch
+    pub epochs: Vec<EpochHistograms>,
+}
+
+impl WeightHistograms {
+    /// # Arguments
+    ///
+    /// * `config` - Number of epochs between recordings, and number of bins
+    pub fn new(config: HistogramConfig) -> Self {
+        Self {
+            interval: config.interval.max(1),
+            bins: config.bins.max(1),
+            epochs: vec![],
+        }
+    }
+
+    /// Whether or not histograms are recorded after the given epoch
+    ///
+    /// # Arguments
+    ///
+    /// * `epoch` - Epoch that has just finished (starting at 1)
+    pub fn is_due(&self, epoch: usize) -> bool {
+        epoch.is_multiple_of(self.interval)
+    }
+}
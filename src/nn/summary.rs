@@ -0,0 +1,95 @@
+use serde::Serialize;
+use std::fmt;
+
+/// Architecture and estimated compute cost of a single Layer
+#[derive(Serialize, Debug, Clone)]
+pub struct LayerSummary {
+    /// Size of the Layer's input vectors
+    pub inputs: usize,
+
+    /// Number of neurons
+    pub neurons: usize,
+
+    /// Name of the activation function
+    pub activation: String,
+
+    /// Whether or not the activation values are batch normalized
+    pub batch_norm: bool,
+
+    /// Number of trainable values
+    pub parameters: usize,
+
+    /// Estimated floating point operations to feed one sample forward
+    pub flops_per_sample: usize,
+}
+
+/// Architecture of a Network and the estimated compute cost of
+/// a forward pass, shown before training
+#[derive(Serialize, Debug, Clone)]
+pub struct NetworkSummary {
+    /// Summary of each Layer, in order
+    pub layers: Vec<LayerSummary>,
+
+    /// Rate at which input features are dropped during training (if enabled)
+    pub input_dropout: Option<f32>,
+
+    /// Total number of trainable values
+    pub parameters: usize,
+
+    /// Number of samples in each forward pass
+    pub batch_size: usize,
+
+    /// Estimated floating point operations to feed one sample forward
+    pub flops_per_sample: usize,
+
+    /// Estimated floating point operations of a forward pass over a full batch
+    pub flops_per_batch: usize,
+}
+
+impl fmt::Display for NetworkSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Perceptron ({} layers, {} parameters)",
+            self.layers.len(),
+            self.parameters
+        )?;
+        if let Some(rate) = self.input_dropout {
+            writeln!(f, "  Input dropout: {rate}")?;
+        }
+        for (i, layer) in self.layers.iter().enumerate() {
+            let batch_norm: &str = if layer.batch_norm { ", batch norm" } else { "" };
+            writeln!(
+                f,
+                "  Layer {i}: {} -> {} ({}{batch_norm}), {} parameters, {} FLOPs per sample",
+                layer.inputs,
+                layer.neurons,
+                layer.activation,
+                layer.parameters,
+                format_count(layer.flops_per_sample)
+            )?;
+        }
+        writeln!(
+            f,
+            "  Forward pass: {} FLOPs per sample, {} FLOPs per batch of {}",
+            format_count(self.flops_per_sample),
+            format_count(self.flops_per_batch),
+            self.batch_size
+        )
+    }
+}
+
+/// Abbreviate a large count with a metric prefix (e.g. 1.25M)
+///
+/// # Arguments
+///
+/// * `count` - Number being formatted
+fn format_count(count: usize) -> String {
+    let prefixes: [(f64, &str); 4] = [(1e12, "T"), (1e9, "G"), (1e6, "M"), (1e3, "k")];
+    for (scale, prefix) in prefixes {
+        if count as f64 >= scale {
+            return format!("{:.2}{prefix}", count as f64 / scale);
+        }
+    }
+    count.to_string()
+}
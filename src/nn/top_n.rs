@@ -0,0 +1,35 @@
+use ndarray::{Array2, Axis};
+use serde::Serialize;
+
+/// Raw network output for a single class of a single sample
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct ClassProbability {
+    /// Index of the output neuron (the decoded class)
+    pub class: usize,
+
+    /// Raw value of the output neuron
+    pub probability: f64,
+}
+
+/// The `n` highest raw outputs of each sample, in descending order
+///
+/// # Arguments
+///
+/// * `raw_outputs` - Raw (encoded) network output vectors, one per column
+/// * `n` - Maximum number of classes kept for each sample
+pub fn top_n(raw_outputs: &Array2<f64>, n: usize) -> Vec<Vec<ClassProbability>> {
+    raw_outputs
+        .axis_iter(Axis(1))
+        .map(|sample| {
+            let mut classes: Vec<ClassProbability> = sample
+                .iter()
+                .enumerate()
+                .map(|(class, &probability)| ClassProbability { class, probability })
+                .collect();
+            // Stable sort keeps the lower class first when outputs are equal
+            classes.sort_by(|a, b| b.probability.total_cmp(&a.probability));
+            classes.truncate(n);
+            classes
+        })
+        .collect()
+}
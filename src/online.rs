@@ -0,0 +1,416 @@
+use crate::error::OpenPbError;
+use crate::file_io::json_de::{DataDe, NetworkDataDe};
+use crate::file_io::model_de::Checkpoint;
+use crate::file_io::save_output;
+use crate::nn::batch_norm;
+use crate::nn::functions::cost::Cost;
+use crate::nn::functions::encoder::Encoder;
+use crate::nn::functions::metric::Metric;
+use crate::nn::functions::optimizer::Optimizer;
+use crate::nn::perceptron::Perceptron;
+use ndarray::Array2;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::BufRead;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Default number of most recent samples the rolling loss and metric cover
+pub const DEFAULT_ONLINE_WINDOW: usize = 1000;
+
+/// Default number of samples between rolling metric reports
+pub const DEFAULT_ONLINE_REPORT_EVERY: usize = 1000;
+
+/// Options for learning from a stream of samples
+#[derive(Debug, Clone, Copy)]
+pub struct OnlineOptions {
+    /// Number of samples in each update of the weights
+    pub batch_size: usize,
+
+    /// Number of most recent samples the rolling loss and metric cover
+    pub window: usize,
+
+    /// Number of samples between rolling metric reports
+    pub report_every: usize,
+
+    /// Number of samples between checkpoints of the network and
+    /// optimizer state (only saved once the stream ends when `None`)
+    pub checkpoint_every: Option<usize>,
+
+    /// Seed of the network's random number generator (optional)
+    pub seed: Option<u64>,
+}
+
+/// Single line of an NDJSON sample stream
+#[derive(Deserialize, Debug)]
+struct StreamSampleDe {
+    /// Input values of the sample
+    input: Vec<f64>,
+
+    /// Expected (unencoded) output values of the sample
+    output: Vec<f64>,
+}
+
+/// Rolling loss and metric of the most recent samples of a stream. Every
+/// sample is predicted before the network is updated with it (prequential
+/// evaluation), so the metric reflects samples the network hasn't seen yet
+#[derive(Serialize, Debug, Clone)]
+pub struct OnlineReport {
+    /// Number of samples learned from so far
+    pub samples: usize,
+
+    /// Number of updates of the weights so far
+    pub updates: usize,
+
+    /// Mean training loss of the samples within the window
+    pub rolling_loss: f64,
+
+    /// Name of the metric
+    pub metric: String,
+
+    /// Metric of the predictions made for the samples within
+    /// the window, before the network was updated with them
+    pub rolling_metric: f32,
+
+    /// Time since the first sample was read (in seconds)
+    pub elapsed_time: f32,
+
+    /// Samples learned from per second
+    pub samples_per_second: f32,
+}
+
+impl fmt::Display for OnlineReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} samples ({} updates): rolling loss {:.6}, rolling {} {:.4} ({:.0} samples/s)",
+            self.samples,
+            self.updates,
+            self.rolling_loss,
+            self.metric,
+            self.rolling_metric,
+            self.samples_per_second
+        )
+    }
+}
+
+/// Network and optimizer state saved while learning from a stream, which
+/// `--resume` and `predict` read like a results file
+#[derive(Serialize)]
+struct OnlineCheckpointSer<'a> {
+    /// Network learned so far
+    network: &'a Perceptron,
+
+    /// Optimizer hyperparameters and internal state
+    optimizer_state: Map<String, Value>,
+
+    /// Rolling metrics when the checkpoint was saved
+    report: &'a OnlineReport,
+}
+
+/// Prediction, expected output, and loss of a single sample within the window
+struct WindowSample {
+    /// Decoded prediction made before the network was updated with the sample
+    prediction: Vec<f64>,
+
+    /// Expected (unencoded) output values
+    expected: Vec<f64>,
+
+    /// Mean loss of the update the sample was part of
+    loss: f64,
+}
+
+/// Network, optimizer, and rolling window of a stream being learned from
+struct OnlineLearner<'a> {
+    network: Perceptron,
+    optimizer: Box<dyn Optimizer>,
+    cost: &'a dyn Cost,
+    metric: &'a dyn Metric,
+    encoder: &'a dyn Encoder,
+    options: OnlineOptions,
+    input_size: usize,
+    output_size: usize,
+    window: VecDeque<WindowSample>,
+    samples: usize,
+    updates: usize,
+    start: Instant,
+}
+
+impl OnlineLearner<'_> {
+    /// Predict a batch of samples, then update the network with them
+    ///
+    /// # Arguments
+    ///
+    /// * `batch` - Samples of the batch, in stream order
+    /// * `first_line` - Line of the stream the batch starts at (for error messages)
+    fn learn(&mut self, batch: &[StreamSampleDe], first_line: usize) -> Result<(), OpenPbError> {
+        let (inputs, outputs) =
+            batch_matrices(batch, self.input_size, self.output_size, self.samples)?;
+        let label: String = format!(
+            "outputs on lines {first_line}-{}",
+            first_line + batch.len() - 1
+        );
+        self.encoder.check(&label, &outputs)?;
+
+        // Test-then-train: each sample is predicted before it's learned from
+        let predictions: Array2<f64> = self.network.predict(&inputs, self.encoder);
+        let expected: Array2<f64> = self.encoder.encode(&outputs).t().to_owned();
+        let input_rows: usize = inputs.nrows();
+        let loss: f64 = self.network.train_batch(
+            &inputs,
+            &expected,
+            self.optimizer.as_mut(),
+            self.cost,
+            input_rows,
+        );
+        self.updates += 1;
+        self.samples += batch.len();
+
+        for (prediction, sample) in predictions.rows().into_iter().zip(batch) {
+            self.window.push_back(WindowSample {
+                prediction: prediction.to_vec(),
+                expected: sample.output.clone(),
+                loss,
+            });
+        }
+        while self.window.len() > self.options.window.max(1) {
+            self.window.pop_front();
+        }
+        Ok(())
+    }
+
+    /// Rolling loss and metric of the samples within the window
+    fn report(&self) -> Result<OnlineReport, OpenPbError> {
+        let samples: usize = self.window.len();
+        let rolling_loss: f64 =
+            self.window.iter().map(|sample| sample.loss).sum::<f64>() / samples.max(1) as f64;
+        let rows =
+            |values: &dyn Fn(&WindowSample) -> &Vec<f64>| -> Result<Array2<f64>, OpenPbError> {
+                let columns: usize = self.window.front().map_or(0, |sample| values(sample).len());
+                let flat: Vec<f64> = self.window.iter().flat_map(values).cloned().collect();
+                match Array2::from_shape_vec((samples, columns), flat) {
+                    Ok(rows) => Ok(rows),
+                    Err(error) => {
+                        Err(format!("Samples in the window differ in size: {error}").into())
+                    }
+                }
+            };
+        let rolling_metric: f32 = self.metric.value(
+            &rows(&|sample| &sample.prediction)?,
+            &rows(&|sample| &sample.expected)?,
+        );
+        let elapsed_time: f32 = self.start.elapsed().as_secs_f32();
+
+        Ok(OnlineReport {
+            samples: self.samples,
+            updates: self.updates,
+            rolling_loss,
+            metric: self.metric.label().to_string(),
+            rolling_metric,
+            elapsed_time,
+            samples_per_second: self.samples as f32 / elapsed_time.max(f32::EPSILON),
+        })
+    }
+
+    /// Save the network and optimizer state (overwriting any previous checkpoint)
+    ///
+    /// # Arguments
+    ///
+    /// * `report` - Rolling metrics saved alongside the network
+    /// * `filepath` - File the checkpoint is written to
+    fn save_checkpoint(&self, report: &OnlineReport, filepath: &Path) -> Result<(), OpenPbError> {
+        let mut optimizer_state: Map<String, Value> = self.optimizer.hyperparameters();
+        optimizer_state.extend(self.optimizer.state());
+        let checkpoint = OnlineCheckpointSer {
+            network: &self.network,
+            optimizer_state,
+            report,
+        };
+        save_output::save_json("online checkpoint", &checkpoint, filepath)
+    }
+}
+
+/// Learn from samples read continuously from a stream of NDJSON lines (e.g.
+/// stdin), each an object with `input` and `output` arrays. Every `batch_size`
+/// samples, the batch is predicted and then used to update the network with
+/// the configured optimizer. Rolling metrics are written to stdout as NDJSON
+/// every `report_every` samples, and the network is checkpointed every
+/// `checkpoint_every` samples and once the stream ends. The network is built
+/// (and any stateful encoder fit) once the first batch has been read
+///
+/// # Arguments
+///
+/// * `reader` - Stream of NDJSON samples (blank lines are skipped)
+/// * `network_json` - Raw contents of the network config
+/// * `checkpoint` - Trained network and optimizer state to continue from (optional)
+/// * `output_path` - File the checkpoints are written to
+/// * `options` - Batch size, window size, and report and checkpoint intervals
+pub fn learn_stream<R: BufRead>(
+    reader: R,
+    network_json: &str,
+    checkpoint: Option<Checkpoint>,
+    output_path: &Path,
+    options: OnlineOptions,
+) -> Result<OnlineReport, OpenPbError> {
+    let batch_size: usize = options.batch_size.max(1);
+    let mut lines = reader.lines().enumerate();
+    let mut next_batch = |batch: &mut Vec<StreamSampleDe>| -> Result<usize, OpenPbError> {
+        let mut first_line: usize = 0;
+        while batch.len() < batch_size {
+            let (index, line) = match lines.next() {
+                Some((index, Ok(line))) => (index, line),
+                Some((index, Err(error))) => {
+                    return Err(format!("Failed to read line {}: {error}", index + 1).into())
+                }
+                None => break,
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            let sample: StreamSampleDe = match serde_json::from_str(&line) {
+                Ok(sample) => sample,
+                Err(error) => {
+                    return Err(format!("Invalid sample on line {}: {error}", index + 1).into())
+                }
+            };
+            if batch.is_empty() {
+                first_line = index + 1;
+            }
+            batch.push(sample);
+        }
+        Ok(first_line)
+    };
+
+    // Network (and encoder) is created from the first batch
+    let mut batch: Vec<StreamSampleDe> = Vec::with_capacity(batch_size);
+    let mut first_line: usize = next_batch(&mut batch)?;
+    if batch.is_empty() {
+        return Err("The sample stream is empty".into());
+    }
+    let (input_size, output_size) = (batch[0].input.len(), batch[0].output.len());
+    let (inputs, outputs) = batch_matrices(&batch, input_size, output_size, 0)?;
+    let data_de: DataDe = DataDe::new(
+        inputs.t().to_owned(),
+        outputs,
+        Array2::zeros((0, input_size)),
+        Array2::zeros((0, output_size)),
+    )?;
+    let mut network_data_de: NetworkDataDe =
+        NetworkDataDe::from_source(Arc::new(data_de), network_json)?;
+    if let Some(checkpoint) = checkpoint {
+        network_data_de.resume_from(checkpoint)?;
+    }
+    let network: Perceptron = match options.seed {
+        Some(seed) => network_data_de.create_seeded_network(seed)?,
+        None => network_data_de.create_network()?,
+    };
+    if network.has_batch_norm() {
+        batch_norm::check_batch_size(options.batch_size)?;
+    }
+
+    let mut learner = OnlineLearner {
+        network,
+        optimizer: network_data_de.optimizer.clone(),
+        cost: network_data_de.cost.as_ref(),
+        metric: network_data_de.metric.as_ref(),
+        encoder: network_data_de.encoder.as_ref(),
+        options,
+        input_size,
+        output_size,
+        window: VecDeque::with_capacity(options.window.max(1)),
+        samples: 0,
+        updates: 0,
+        start: Instant::now(),
+    };
+    let report_every: usize = options.report_every.max(1);
+    eprintln!("Network initialized, learning from the sample stream...");
+
+    while !batch.is_empty() {
+        let previous_samples: usize = learner.samples;
+        learner.learn(&batch, first_line)?;
+
+        // Intervals are crossed rather than hit exactly when batches hold several samples
+        if learner.samples / report_every > previous_samples / report_every {
+            print_report(&learner.report()?)?;
+        }
+        if let Some(every) = options.checkpoint_every {
+            let every: usize = every.max(1);
+            if learner.samples / every > previous_samples / every {
+                learner.save_checkpoint(&learner.report()?, output_path)?;
+            }
+        }
+
+        batch.clear();
+        first_line = next_batch(&mut batch)?;
+    }
+
+    let report: OnlineReport = learner.report()?;
+    if !learner.samples.is_multiple_of(report_every) {
+        print_report(&report)?;
+    }
+    eprintln!("Stream ended after {report}");
+    learner.save_checkpoint(&report, output_path)?;
+    Ok(report)
+}
+
+/// Write a rolling report to stdout as a single NDJSON line
+///
+/// # Arguments
+///
+/// * `report` - Rolling metrics of the stream so far
+fn print_report(report: &OnlineReport) -> Result<(), OpenPbError> {
+    match serde_json::to_string(report) {
+        Ok(report_json) => {
+            println!("{report_json}");
+            Ok(())
+        }
+        Err(error) => Err(format!("Failed to serialize the online report: {error}").into()),
+    }
+}
+
+/// Input vectors (features x samples) and unencoded output
+/// vectors (samples x outputs) of a batch of samples
+///
+/// # Arguments
+///
+/// * `batch` - Samples of the batch, in stream order
+/// * `input_size` - Number of inputs every sample must have
+/// * `output_size` - Number of outputs every sample must have
+/// * `previous_samples` - Number of samples before the batch (for error messages)
+fn batch_matrices(
+    batch: &[StreamSampleDe],
+    input_size: usize,
+    output_size: usize,
+    previous_samples: usize,
+) -> Result<(Array2<f64>, Array2<f64>), OpenPbError> {
+    for (offset, sample) in batch.iter().enumerate() {
+        if sample.input.len() != input_size || sample.output.len() != output_size {
+            return Err(format!(
+                "Sample {} has {} inputs and {} outputs, expected {input_size} and {output_size}",
+                previous_samples + offset + 1,
+                sample.input.len(),
+                sample.output.len()
+            )
+            .into());
+        }
+    }
+    let inputs: Vec<f64> = batch
+        .iter()
+        .flat_map(|sample| sample.input.clone())
+        .collect();
+    let outputs: Vec<f64> = batch
+        .iter()
+        .flat_map(|sample| sample.output.clone())
+        .collect();
+    let inputs: Array2<f64> = match Array2::from_shape_vec((batch.len(), input_size), inputs) {
+        Ok(inputs) => inputs.reversed_axes(),
+        Err(error) => return Err(format!("Invalid batch inputs: {error}").into()),
+    };
+    match Array2::from_shape_vec((batch.len(), output_size), outputs) {
+        Ok(outputs) => Ok((inputs, outputs)),
+        Err(error) => Err(format!("Invalid batch outputs: {error}").into()),
+    }
+}
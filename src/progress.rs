@@ -0,0 +1,343 @@
+use crate::error::OpenPbError;
+use crate::file_io::checksum;
+use crate::nn::history::EpochLog;
+use crate::nn::perceptron::Perceptron;
+use serde::Serialize;
+#[cfg(feature = "fetch")]
+use std::convert::TryFrom;
+use std::io::prelude::*;
+use std::net::{SocketAddr, TcpStream};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+#[cfg(feature = "fetch")]
+use std::sync::Arc;
+use std::sync::Mutex;
+use url::{Host, Position, Url};
+
+/// Characters of the standard base64 alphabet
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Appended to the handshake key before hashing it into the
+/// `Sec-WebSocket-Accept` header the server must reply with (RFC 6455)
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// Single progress event, sent as one JSON object tagged by `"event"`
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    /// Training is about to start
+    Start {
+        /// Number of training runs
+        runs: usize,
+        /// Maximum number of epochs of each run
+        epochs: usize,
+    },
+
+    /// A training run has finished an epoch
+    Epoch {
+        /// ID of the training run
+        run: usize,
+        /// Epoch that has just finished
+        epoch: usize,
+        /// Mean training loss over the epoch
+        loss: f64,
+        /// Validation metric score at the end of the epoch
+        metric: f32,
+        /// Time since the run started training (in seconds)
+        elapsed_time: f32,
+    },
+
+    /// Every training run has finished
+    Finish {
+        /// Number of training runs
+        runs: usize,
+        /// Number of runs whose final metric score passed
+        passed: usize,
+    },
+}
+
+/// Byte stream a WebSocket runs over (plain TCP, or TLS for `wss://`)
+trait Socket: Read + Write + Send {}
+
+impl<T: Read + Write + Send> Socket for T {}
+
+/// Open connection that progress events are written to
+enum Connection {
+    /// WebSocket, where every event is sent as a single text frame
+    WebSocket(Box<dyn Socket>),
+
+    /// Plain TCP or Unix socket, where every event is written as one line
+    Lines(Box<dyn Write + Send>),
+}
+
+/// Stream of per-epoch progress events (as JSON) for external dashboards.
+/// Shared between training threads, so every event is written whole
+pub struct ProgressStream {
+    /// Connection events are written to (`None` once writing has failed)
+    connection: Mutex<Option<Connection>>,
+}
+
+impl ProgressStream {
+    /// Connect to the process listening for progress events
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - `ws://host:port/path` (or `wss://` over TLS) for a
+    ///   WebSocket, `tcp://host:port` for newline-delimited JSON over TCP,
+    ///   or the path of a Unix socket for newline-delimited JSON
+    pub fn connect(address: &str) -> Result<ProgressStream, OpenPbError> {
+        let connection: Connection = if address.starts_with("ws://")
+            || address.starts_with("wss://")
+        {
+            Connection::WebSocket(connect_websocket(address)?)
+        } else if let Some(host) = address.strip_prefix("tcp://") {
+            match TcpStream::connect(host) {
+                Ok(stream) => Connection::Lines(Box::new(stream)),
+                Err(error) => return Err(format!("Failed to connect to {host}: {error}").into()),
+            }
+        } else {
+            Connection::Lines(connect_unix(address)?)
+        };
+        Ok(ProgressStream {
+            connection: Mutex::new(Some(connection)),
+        })
+    }
+
+    /// Send a single event. When writing fails, a warning is printed and
+    /// every later event is dropped, so training is never interrupted
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - Event to send
+    pub fn send(&self, event: &ProgressEvent) {
+        let event_json: String = match serde_json::to_string(event) {
+            Ok(event_json) => event_json,
+            Err(error) => {
+                eprintln!("Warning: failed to serialize progress event: {error}");
+                return;
+            }
+        };
+
+        let mut connection = match self.connection.lock() {
+            Ok(connection) => connection,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let result: std::io::Result<()> = match connection.as_mut() {
+            Some(Connection::WebSocket(stream)) => write_text_frame(stream.as_mut(), &event_json),
+            Some(Connection::Lines(stream)) => {
+                writeln!(stream, "{event_json}").and_then(|_| stream.flush())
+            }
+            None => return,
+        };
+        if let Err(error) = result {
+            eprintln!("Warning: progress stream closed: {error}");
+            *connection = None;
+        }
+    }
+
+    /// Send the epoch's summary. Meant to be used as a `trainer::EpochCallback`
+    ///
+    /// # Arguments
+    ///
+    /// * `run` - ID of the training run
+    /// * `epoch_log` - Summary of the epoch that has just finished
+    /// * `_network` - Network at the end of the epoch (unused)
+    pub fn on_epoch(&self, run: usize, epoch_log: &EpochLog, _network: &Perceptron) -> bool {
+        self.send(&ProgressEvent::Epoch {
+            run,
+            epoch: epoch_log.epoch,
+            loss: epoch_log.loss,
+            metric: epoch_log.metric,
+            elapsed_time: epoch_log.elapsed_time,
+        });
+        true
+    }
+}
+
+/// Connect to a Unix socket
+///
+/// # Arguments
+///
+/// * `path` - Path of the socket
+#[cfg(unix)]
+fn connect_unix(path: &str) -> Result<Box<dyn Write + Send>, OpenPbError> {
+    match UnixStream::connect(path) {
+        Ok(stream) => Ok(Box::new(stream)),
+        Err(error) => Err(format!("Failed to connect to {:#?}: {error}", path).into()),
+    }
+}
+
+#[cfg(not(unix))]
+fn connect_unix(path: &str) -> Result<Box<dyn Write + Send>, OpenPbError> {
+    Err(format!(
+        "Unix sockets aren't supported on this platform ({:#?}), use ws:// or tcp://",
+        path
+    )
+    .into())
+}
+
+/// Open a TCP (or TLS) connection and perform the WebSocket opening
+/// handshake, checking that the server accepted the handshake's key
+///
+/// # Arguments
+///
+/// * `address` - `ws://` or `wss://` URL of the WebSocket. The port defaults
+///   to 80 (443 for `wss://`), and IPv6 hosts are written in brackets
+fn connect_websocket(address: &str) -> Result<Box<dyn Socket>, OpenPbError> {
+    let url: Url = match Url::parse(address) {
+        Ok(url) => url,
+        Err(error) => return Err(format!("Invalid WebSocket URL \"{address}\": {error}").into()),
+    };
+    let host: Host<&str> = match url.host() {
+        Some(host) => host,
+        None => return Err(format!("WebSocket URL \"{address}\" has no host").into()),
+    };
+    let addresses: Vec<SocketAddr> = match url.socket_addrs(|| None) {
+        Ok(addresses) => addresses,
+        Err(error) => return Err(format!("Failed to resolve {host}: {error}").into()),
+    };
+    let stream: TcpStream = match TcpStream::connect(&addresses[..]) {
+        Ok(stream) => stream,
+        Err(error) => return Err(format!("Failed to connect to {address}: {error}").into()),
+    };
+    let mut stream: Box<dyn Socket> = if url.scheme() == "wss" {
+        tls_stream(stream, &host)?
+    } else {
+        Box::new(stream)
+    };
+
+    let key: String = base64(&rand::random::<[u8; 16]>());
+    let request: String = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+         Sec-WebSocket-Key: {key}\r\nSec-WebSocket-Version: 13\r\n\r\n",
+        &url[Position::BeforePath..Position::AfterQuery],
+        &url[Position::BeforeHost..Position::AfterPort],
+    );
+    if let Err(error) = stream.write_all(request.as_bytes()) {
+        return Err(format!("Failed to send WebSocket handshake: {error}").into());
+    }
+
+    // Response headers end with an empty line
+    let mut response: Vec<u8> = vec![];
+    let mut byte: [u8; 1] = [0];
+    while !response.ends_with(b"\r\n\r\n") {
+        match stream.read(&mut byte) {
+            Ok(1) => response.push(byte[0]),
+            Ok(_) => return Err("WebSocket handshake ended early".into()),
+            Err(error) => return Err(format!("Failed to read WebSocket handshake: {error}").into()),
+        }
+    }
+    let response: String = String::from_utf8_lossy(&response).to_string();
+    let status_line: &str = response.lines().next().unwrap_or_default();
+    if status_line.split_whitespace().nth(1) != Some("101") {
+        return Err(format!("WebSocket handshake rejected: {status_line}").into());
+    }
+
+    // Proves the server understood the handshake (and isn't, e.g., a cache replaying it)
+    let expected: String = base64(&checksum::sha1(format!("{key}{WEBSOCKET_GUID}").as_bytes()));
+    let accept: Option<&str> = response.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        if name.trim().eq_ignore_ascii_case("sec-websocket-accept") {
+            Some(value.trim())
+        } else {
+            None
+        }
+    });
+    if accept != Some(expected.as_str()) {
+        return Err("WebSocket handshake rejected: missing or wrong Sec-WebSocket-Accept".into());
+    }
+    Ok(stream)
+}
+
+/// Wrap a TCP connection in TLS, verifying the server's
+/// certificate against the bundled web PKI roots
+///
+/// # Arguments
+///
+/// * `stream` - Open TCP connection to the server
+/// * `host` - Host name (or IP address) the certificate must be valid for
+#[cfg(feature = "fetch")]
+fn tls_stream(stream: TcpStream, host: &Host<&str>) -> Result<Box<dyn Socket>, OpenPbError> {
+    let roots: rustls::RootCertStore = rustls::RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let config: rustls::ClientConfig = match rustls::ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+    {
+        Ok(builder) => builder.with_root_certificates(roots).with_no_client_auth(),
+        Err(error) => return Err(format!("Failed to configure TLS: {error}").into()),
+    };
+    let server_name: String = match host {
+        Host::Domain(domain) => domain.to_string(),
+        Host::Ipv4(ip) => ip.to_string(),
+        Host::Ipv6(ip) => ip.to_string(),
+    };
+    let server_name = match rustls::pki_types::ServerName::try_from(server_name) {
+        Ok(server_name) => server_name,
+        Err(error) => return Err(format!("Invalid TLS server name {host}: {error}").into()),
+    };
+    match rustls::ClientConnection::new(Arc::new(config), server_name) {
+        Ok(connection) => Ok(Box::new(rustls::StreamOwned::new(connection, stream))),
+        Err(error) => Err(format!("Failed to start TLS with {host}: {error}").into()),
+    }
+}
+
+#[cfg(not(feature = "fetch"))]
+fn tls_stream(_stream: TcpStream, host: &Host<&str>) -> Result<Box<dyn Socket>, OpenPbError> {
+    Err(format!("wss:// ({host}) requires the fetch feature, use ws://").into())
+}
+
+/// Write a single (masked, as required of clients) WebSocket text frame
+///
+/// # Arguments
+///
+/// * `stream` - Connection the WebSocket handshake was performed on
+/// * `text` - Payload of the frame
+fn write_text_frame(stream: &mut dyn Socket, text: &str) -> std::io::Result<()> {
+    let payload: &[u8] = text.as_bytes();
+    // FIN bit set, with the text opcode
+    let mut frame: Vec<u8> = vec![0x81];
+
+    // Mask bit is always set, followed by the 7, 16, or 64 bit length
+    if payload.len() < 126 {
+        frame.push(0x80 | payload.len() as u8);
+    } else if payload.len() <= u16::MAX as usize {
+        frame.push(0x80 | 126);
+        frame.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+    } else {
+        frame.push(0x80 | 127);
+        frame.extend_from_slice(&(payload.len() as u64).to_be_bytes());
+    }
+
+    let mask: [u8; 4] = rand::random();
+    frame.extend_from_slice(&mask);
+    frame.extend(payload.iter().zip(mask.iter().cycle()).map(|(b, m)| b ^ m));
+    stream.write_all(&frame)?;
+    stream.flush()
+}
+
+/// Standard (padded) base64 encoding
+///
+/// # Arguments
+///
+/// * `bytes` - Bytes to encode
+fn base64(bytes: &[u8]) -> String {
+    let mut encoded: String = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group: u32 = chunk
+            .iter()
+            .enumerate()
+            .fold(0, |group, (i, &byte)| group | (byte as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let index: usize = ((group >> (18 - 6 * i)) & 0x3f) as usize;
+                encoded.push(BASE64_ALPHABET[index] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
@@ -0,0 +1,213 @@
+use crate::error::OpenPbError;
+use crate::file_io::save_output;
+use crate::nn::history::EpochLog;
+use crate::nn::perceptron::Perceptron;
+use crate::trainer::TrainerOptions;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::fs::{self, OpenOptions};
+use std::hash::{Hash, Hasher};
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// Provenance of a single invocation, written to `manifest.json`
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RunManifest {
+    /// Short hexadecimal ID of the invocation
+    pub run_id: String,
+
+    /// Time the run directory was created (RFC 3339)
+    pub created: String,
+
+    /// Full command line the invocation was started with
+    pub command: Vec<String>,
+
+    /// Network config file (resolved copy saved as `network.json`)
+    pub network: String,
+
+    /// Data files, in the order they were merged
+    pub data: Vec<String>,
+
+    /// Training options, including the seed of the first run
+    pub options: TrainerOptions,
+}
+
+/// Timestamped directory that holds the configs, per-epoch logs,
+/// checkpoints, and results of a single invocation:
+///
+/// * `manifest.json` - Run ID, command line, seed, and training options
+/// * `network.json` - Resolved network config
+/// * `epochs/run_N.csv` - Per-epoch loss, metric, and elapsed time of each run
+/// * `checkpoints/run_N_epoch_M.json` - Periodic copies of each run's network
+/// * `results.json` - Final training results
+#[derive(Debug, Clone)]
+pub struct RunDir {
+    /// Directory everything is written to
+    path: PathBuf,
+
+    /// Short hexadecimal ID of the invocation
+    run_id: String,
+
+    /// Number of epochs between checkpoints (no checkpoints when `None`)
+    checkpoint_interval: Option<usize>,
+
+    /// Number of epochs between lines of the epoch logs
+    log_interval: usize,
+}
+
+impl RunDir {
+    /// Create a new run directory named after the current time and a new run ID
+    ///
+    /// # Arguments
+    ///
+    /// * `base_dir` - Directory that every run directory is created in
+    /// * `seed` - Seed of the run (mixed into the run ID)
+    /// * `checkpoint_interval` - Number of epochs between checkpoints
+    /// * `log_interval` - Number of epochs between lines of the epoch logs
+    ///   (every epoch is logged when `None`)
+    pub fn create(
+        base_dir: &str,
+        seed: Option<u64>,
+        checkpoint_interval: Option<usize>,
+        log_interval: Option<usize>,
+    ) -> Result<RunDir, OpenPbError> {
+        let now: DateTime<Utc> = Utc::now();
+
+        // Git-style short ID, unique to the time, seed, and process
+        let mut hasher = DefaultHasher::new();
+        now.timestamp_nanos_opt().hash(&mut hasher);
+        seed.hash(&mut hasher);
+        std::process::id().hash(&mut hasher);
+        let run_id: String = format!("{:016x}", hasher.finish())[..7].to_string();
+
+        let path: PathBuf =
+            Path::new(base_dir).join(format!("{}-{run_id}", now.format("%Y%m%d-%H%M%S")));
+        for sub_dir in ["epochs", "checkpoints"] {
+            if let Err(error) = fs::create_dir_all(path.join(sub_dir)) {
+                return Err(format!("Failed to create run directory {:#?}: {error}", path).into());
+            }
+        }
+        Ok(RunDir {
+            path,
+            run_id,
+            checkpoint_interval: checkpoint_interval.map(|interval| interval.max(1)),
+            log_interval: log_interval.unwrap_or(1).max(1),
+        })
+    }
+
+    /// Directory everything is written to
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Short hexadecimal ID of the invocation
+    pub fn run_id(&self) -> &str {
+        &self.run_id
+    }
+
+    /// Path of the final training results
+    pub fn results_path(&self) -> PathBuf {
+        self.path.join("results.json")
+    }
+
+    /// Save the run's provenance and its resolved network config
+    ///
+    /// # Arguments
+    ///
+    /// * `manifest` - Run ID, command line, and training options
+    /// * `network_json` - Raw contents of the resolved network config
+    pub fn write_manifest(
+        &self,
+        manifest: &RunManifest,
+        network_json: &str,
+    ) -> Result<(), OpenPbError> {
+        let network: serde_json::Value = match serde_json::from_str(network_json) {
+            Ok(network) => network,
+            Err(error) => return Err(format!("Invalid network config: {error}").into()),
+        };
+        save_output::save_json("run manifest", manifest, &self.path.join("manifest.json"))?;
+        save_output::save_json("network config", &network, &self.path.join("network.json"))
+    }
+
+    /// Append the epoch's summary to the run's log, and save a checkpoint of
+    /// the network, when either is due. Failures are reported without stopping
+    /// training. Meant to be used as a `trainer::EpochCallback`
+    ///
+    /// # Arguments
+    ///
+    /// * `run` - ID of the training run
+    /// * `epoch_log` - Summary of the epoch that has just finished
+    /// * `network` - Network at the end of the epoch
+    pub fn on_epoch(&self, run: usize, epoch_log: &EpochLog, network: &Perceptron) -> bool {
+        if epoch_log.epoch.is_multiple_of(self.log_interval) {
+            if let Err(error) = self.append_epoch_log(run, epoch_log) {
+                eprintln!("Warning: failed to log epoch {}: {error}", epoch_log.epoch);
+            }
+        }
+        if let Some(interval) = self.checkpoint_interval {
+            if epoch_log.epoch.is_multiple_of(interval) {
+                if let Err(error) = self.write_checkpoint(run, epoch_log.epoch, network) {
+                    eprintln!("Warning: failed to save checkpoint: {error}");
+                }
+            }
+        }
+        true
+    }
+
+    /// Append a single line to a run's CSV log (the header is written first)
+    ///
+    /// # Arguments
+    ///
+    /// * `run` - ID of the training run
+    /// * `epoch_log` - Summary of the epoch that has just finished
+    fn append_epoch_log(&self, run: usize, epoch_log: &EpochLog) -> std::io::Result<()> {
+        let log_path: PathBuf = self.path.join("epochs").join(format!("run_{run}.csv"));
+        let is_new: bool = !log_path.exists();
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(log_path)?;
+        if is_new {
+            writeln!(file, "epoch,loss,regularization,metric,elapsed_time")?;
+        }
+        writeln!(
+            file,
+            "{},{},{},{},{}",
+            epoch_log.epoch,
+            epoch_log.loss,
+            epoch_log.regularization,
+            epoch_log.metric,
+            epoch_log.elapsed_time
+        )
+    }
+
+    /// Save a copy of the network that `model_de` can load
+    ///
+    /// # Arguments
+    ///
+    /// * `run` - ID of the training run
+    /// * `epoch` - Epoch that has just finished
+    /// * `network` - Network at the end of the epoch
+    fn write_checkpoint(
+        &self,
+        run: usize,
+        epoch: usize,
+        network: &Perceptron,
+    ) -> Result<(), OpenPbError> {
+        let checkpoint_path: PathBuf = self
+            .path
+            .join("checkpoints")
+            .join(format!("run_{run}_epoch_{epoch}.json"));
+        let network_json: String = match serde_json::to_string(network) {
+            Ok(network_json) => network_json,
+            Err(error) => return Err(error.to_string().into()),
+        };
+        match fs::write(&checkpoint_path, network_json) {
+            Ok(_) => Ok(()),
+            Err(error) => {
+                Err(format!("Failed to write file {:#?}: {error}", checkpoint_path).into())
+            }
+        }
+    }
+}
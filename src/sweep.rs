@@ -0,0 +1,197 @@
+use crate::error::OpenPbError;
+use crate::file_io::json_de::{DataDe, NetworkDataDe, NetworkDe};
+use crate::file_io::plot::{self, LearningCurve};
+use crate::file_io::results_ser::{ThreadedResultsSer, TrialSummarySer};
+use crate::file_io::sweep_de::{SweepDe, TrialDe, ARCHITECTURE_PATH};
+use crate::file_io::{config, save_output};
+use crate::nn::batches::{BatchConfig, Sampler};
+use crate::nn::data_source::DataSource;
+use crate::trainer::{self, TrainerOptions};
+use chrono::{DateTime, Utc};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Directory and trial summaries of a finished sweep
+pub struct SweepOutcome {
+    /// Directory the sweep was archived in
+    pub sweep_dir: PathBuf,
+
+    /// Summary of every trial, in order
+    pub summaries: Vec<TrialSummarySer>,
+
+    /// Whether or not lower metric scores are better
+    pub lower_is_better: bool,
+}
+
+/// Run every trial of a hyperparameter sweep, archiving each trial's resolved
+/// config and training results (plus a summary of all trials) under a new,
+/// timestamped directory inside the sweep's run directory. When the trials
+/// use more than one optimizer config, their learning curves are overlaid
+/// in `optimizers.svg`
+///
+/// # Arguments
+///
+/// * `sweep_de` - Deserialized sweep config
+///
+/// Returns the directory the sweep was archived in, with every trial's summary
+pub fn run_sweep(sweep_de: &SweepDe) -> Result<SweepOutcome, OpenPbError> {
+    let network_json: String = config::read_config(&sweep_de.network)?;
+    let base_network: Value = match serde_json::from_str(&network_json) {
+        Ok(base_network) => base_network,
+        Err(error) => return Err(format!("Invalid network config: {error}").into()),
+    };
+    // Data is only loaded once and shared by every trial
+    let data: Arc<dyn DataSource> = Arc::new(DataDe::from_files(&sweep_de.data.paths())?);
+
+    // The archived config always holds the seed, so random trials can be reproduced
+    let seed: u64 = match sweep_de.seed {
+        Some(seed) => seed,
+        None => rand::random(),
+    };
+    let sweep_de: &SweepDe = &SweepDe {
+        seed: Some(seed),
+        ..sweep_de.clone()
+    };
+
+    let now: DateTime<Utc> = Utc::now();
+    let sweep_dir: PathBuf =
+        Path::new(&sweep_de.run_dir).join(now.format("%d%m%y%H%M%S").to_string());
+    save_output::save_json("sweep config", sweep_de, &sweep_dir.join("sweep.json"))?;
+
+    let assignments: Vec<Map<String, Value>> =
+        sweep_de.assignments(&mut StdRng::seed_from_u64(seed));
+    let mut summaries: Vec<TrialSummarySer> = Vec::with_capacity(assignments.len());
+    // Every trial shares the base config's metric
+    let mut lower_is_better: bool = false;
+    let mut metric_label: String = String::new();
+    let mut optimizers: Vec<Value> = vec![];
+    let mut curves: Vec<LearningCurve> = vec![];
+
+    for (trial, parameters) in assignments.into_iter().enumerate() {
+        eprintln!(
+            "\nTrial {}/{}: {}",
+            trial + 1,
+            sweep_de.trials,
+            Value::from(parameters.clone())
+        );
+        let trial_de: TrialDe = sweep_de.resolve_trial(&base_network, parameters)?;
+        let trial_dir: PathBuf = sweep_dir.join(format!("trial_{trial:03}"));
+        save_output::save_json("trial config", &trial_de, &trial_dir.join("config.json"))?;
+
+        let network_de: NetworkDe = match serde_json::from_value(trial_de.network.clone()) {
+            Ok(network_de) => network_de,
+            Err(error) => {
+                return Err(format!("Invalid network config for trial {trial}: {error}").into())
+            }
+        };
+        let network_data_de: NetworkDataDe = NetworkDataDe::from_config(data.clone(), network_de)?;
+        lower_is_better = network_data_de.metric.lower_is_better();
+        metric_label = network_data_de.metric.label().to_string();
+
+        let options = TrainerOptions {
+            threads: trial_de.training.threads,
+            pin_cores: trial_de.training.pin_cores.clone(),
+            runs: trial_de.training.runs,
+            epochs: trial_de.training.epochs,
+            batch_config: BatchConfig {
+                batch_size: trial_de.training.batch_size,
+                shuffle: trial_de.training.shuffle,
+                drop_last: false,
+                sampler: Sampler::Sequential,
+            },
+            auto_batch_size: false,
+            profile: false,
+            stats_interval: None,
+            snapshot_interval: None,
+            histograms: None,
+            top_n: None,
+            activation_samples: None,
+            dead_neurons: None,
+            calibrate: false,
+            raw_output: false,
+            robustness: None,
+            bootstrap: None,
+            seed: None,
+            restarts: 1,
+            validation_sampling: None,
+            early_stopping: true,
+            save_optimizer_state: false,
+            jitter: None,
+            log_every: trial_de.training.log_every,
+            gradient_norms: None,
+            divergence: trial_de.training.divergence,
+        };
+        let results: ThreadedResultsSer = trainer::train_from_json(&network_data_de, &options)?;
+
+        let optimizer: Value = trial_de.network["optimizer"].clone();
+        let label: String = format!("trial {trial:03} ({})", plot::optimizer_label(&optimizer));
+        curves.push(results.learning_curve(label));
+        if !optimizers.contains(&optimizer) {
+            optimizers.push(optimizer);
+        }
+
+        summaries.push(TrialSummarySer::new(trial, trial_de.parameters, &results));
+        save_output::save_json("trial results", &results, &trial_dir.join("results.json"))?;
+    }
+
+    save_output::save_json("sweep summary", &summaries, &sweep_dir.join("summary.json"))?;
+    if optimizers.len() > 1 {
+        let svg: String = plot::learning_curves_svg(&curves, &metric_label);
+        save_output::save_text(
+            "optimizer comparison plot",
+            &svg,
+            &sweep_dir.join("optimizers.svg"),
+        )?;
+    }
+    if sweep_de.architecture.is_some() {
+        let front: Vec<&TrialSummarySer> = pareto_front(&summaries, lower_is_better);
+        eprintln!("\nPareto front (metric vs. parameter count):");
+        for summary in &front {
+            eprintln!(
+                "  trial {:03}: {} parameters, mean metric {} ({})",
+                summary.trial(),
+                summary.parameter_count(),
+                summary.mean_value(),
+                summary.parameters()[ARCHITECTURE_PATH]
+            );
+        }
+        save_output::save_json("Pareto front", &front, &sweep_dir.join("pareto.json"))?;
+    }
+    Ok(SweepOutcome {
+        sweep_dir,
+        summaries,
+        lower_is_better,
+    })
+}
+
+/// Trials that no other trial beats on both mean metric score and parameter
+/// count (a smaller network with an equal or better score), by parameter count
+///
+/// # Arguments
+///
+/// * `summaries` - Summary of every trial
+/// * `lower_is_better` - Whether or not lower metric scores are better
+fn pareto_front(summaries: &[TrialSummarySer], lower_is_better: bool) -> Vec<&TrialSummarySer> {
+    let score = |summary: &TrialSummarySer| -> f32 {
+        if lower_is_better {
+            -summary.mean_value()
+        } else {
+            summary.mean_value()
+        }
+    };
+    let dominates = |a: &TrialSummarySer, b: &TrialSummarySer| -> bool {
+        a.parameter_count() <= b.parameter_count()
+            && score(a) >= score(b)
+            && (a.parameter_count() < b.parameter_count() || score(a) > score(b))
+    };
+
+    let mut front: Vec<&TrialSummarySer> = summaries
+        .iter()
+        .filter(|summary| !summaries.iter().any(|other| dominates(other, summary)))
+        .collect();
+    front.sort_by_key(|summary| summary.parameter_count());
+    front
+}
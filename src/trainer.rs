@@ -1,138 +1,1195 @@
-use crate::args::Args;
-use crate::file_io::json_de::NetworkDataDe;
-use crate::file_io::results_ser::{ThreadedResultsSer, TrainingResultsSer};
-use crate::file_io::save_output;
+use crate::affinity;
+use crate::error::OpenPbError;
+use crate::file_io::ensemble_de::{CombineDe, EnsembleDe, DEFAULT_STACKING_FOLDS};
+use crate::file_io::json_de::{DataDe, NetworkDataDe, NetworkDe};
+use crate::file_io::multi_head_de::MultiHeadDe;
+use crate::file_io::results_ser::{
+    AttemptSer, EnsembleSer, HeadResultsSer, MemorySer, MultiHeadResultsSer, RestartsSer,
+    ThreadedResultsSer, TimingSer, TrainingResultsSer,
+};
+use crate::nn::activations::{self, DeadNeuronConfig, LayerActivations, LayerDeadNeurons};
+use crate::nn::batch_norm;
+use crate::nn::batch_tuner::{self, BatchSizeTuning, AUTO_BATCH_EPOCHS};
+use crate::nn::batches::{BatchConfig, Sampler};
+use crate::nn::bootstrap::{self, BootstrapConfig, ConfidenceInterval};
+use crate::nn::calibration::{self, Calibration};
+use crate::nn::cancellation::CancellationToken;
+use crate::nn::confusion::ConfusionMatrix;
+use crate::nn::data_source::{DataMetadata, DataSource};
+use crate::nn::divergence::{Divergence, DivergenceConfig};
+use crate::nn::ensemble;
 use crate::nn::functions::cost::Cost;
 use crate::nn::functions::encoder::Encoder;
 use crate::nn::functions::metric::Metric;
 use crate::nn::functions::optimizer::Optimizer;
-use crate::nn::perceptron::Perceptron;
-use ndarray::Array2;
-use std::sync::{Arc, Mutex};
+use crate::nn::gradient_norms::GradientNormConfig;
+use crate::nn::history::EpochLog;
+use crate::nn::multi_head::{Head, HeadData, MultiHeadPerceptron};
+use crate::nn::perceptron::{Perceptron, VALIDATION_CHUNK_SIZE};
+use crate::nn::pretrain::{self, Pretraining};
+use crate::nn::profiler::Profile;
+use crate::nn::robustness::{self, NoiseLevel, RobustnessConfig};
+use crate::nn::sampling::ValidationSampling;
+use crate::nn::stats::{HistogramConfig, TrainingStats, WeightHistograms, WeightSnapshots};
+use crate::nn::top_n::{self, ClassProbability};
+use cpu_time::ThreadTime;
+use ndarray::{concatenate, Array2, ArrayView2, Axis};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+use rayon::ThreadPool;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::ops::Range;
+use std::sync::Arc;
 use std::thread::{self, JoinHandle};
 use std::time::SystemTime;
 
+/// Options that control how many networks are trained and how
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TrainerOptions {
+    /// Number of worker threads in the pool used to train each run
+    pub threads: usize,
+
+    /// IDs of the CPU cores the worker threads are pinned to, one
+    /// per thread in order (threads aren't pinned when `None`)
+    pub pin_cores: Option<Vec<usize>>,
+
+    /// Number of samples of the same network setup to train
+    pub runs: usize,
+
+    /// Maximum number of epochs (iterations) until training loop finishes
+    pub epochs: usize,
+
+    /// Minibatch size, shuffling, and drop-last options
+    pub batch_config: BatchConfig,
+
+    /// Whether or not to benchmark candidate batch sizes and pick
+    /// one before training (overrides `batch_config.batch_size`)
+    pub auto_batch_size: bool,
+
+    /// Whether or not to record a per-phase timing breakdown of training
+    pub profile: bool,
+
+    /// Number of epochs between recordings of per-layer weight
+    /// and gradient statistics (not recorded when `None`)
+    pub stats_interval: Option<usize>,
+
+    /// Number of epochs between weight snapshots, reported as the
+    /// per-layer distance between consecutive snapshots (not taken when `None`)
+    pub snapshot_interval: Option<usize>,
+
+    /// Number of epochs between, and number of bins of, per-layer
+    /// weight histograms (not recorded when `None`)
+    pub histograms: Option<HistogramConfig>,
+
+    /// Number of highest raw output values kept for each
+    /// validation sample (not included when `None`)
+    pub top_n: Option<usize>,
+
+    /// Number of validation samples whose per-layer activation
+    /// percentiles are recorded after training (not recorded when `None`)
+    pub activation_samples: Option<usize>,
+
+    /// Threshold of the dead neuron analysis run after training, and how
+    /// often to warn about dead neurons during training (not run when `None`)
+    pub dead_neurons: Option<DeadNeuronConfig>,
+
+    /// Whether or not to fit an output temperature to the
+    /// validation set after training (temperature scaling)
+    pub calibrate: bool,
+
+    /// Whether or not to include the undecoded output layer values of
+    /// each validation sample, so callers can apply their own thresholds
+    pub raw_output: bool,
+
+    /// Noise levels the validation inputs are corrupted with after
+    /// training to measure robustness (not measured when `None`)
+    pub robustness: Option<RobustnessConfig>,
+
+    /// Number of bootstrap resamples of the validation predictions, and the
+    /// confidence level of the metric's interval (not estimated when `None`)
+    pub bootstrap: Option<BootstrapConfig>,
+
+    /// Seed of the first run's random number generator (run `i`
+    /// is seeded with `seed + i`). Runs are unseeded when `None`
+    pub seed: Option<u64>,
+
+    /// Number of random initializations trained one after another
+    /// within each run, keeping the one with the best validation metric
+    pub restarts: usize,
+
+    /// Subsampling of the validation set for the early stopping
+    /// metric (the full set is evaluated every epoch when `None`)
+    pub validation_sampling: Option<ValidationSampling>,
+
+    /// Whether or not each run stops once its metric is satisfied
+    /// (otherwise it trains for the maximum number of epochs)
+    pub early_stopping: bool,
+
+    /// Whether or not the optimizer's internal state (e.g. momentum
+    /// vectors) is saved with the results, so training can be resumed
+    pub save_optimizer_state: bool,
+
+    /// Largest relative change randomly applied to each run's learning
+    /// rate (e.g. 0.2 scales it by a factor in [0.8, 1.2]). Every run
+    /// uses the configured learning rate when `None`
+    pub jitter: Option<f64>,
+
+    /// Number of epochs between console lines with each run's training
+    /// loss and validation metric (nothing is printed when `None`)
+    pub log_every: Option<usize>,
+
+    /// Tracking of the global and per-layer gradient norms of every
+    /// training step, and the norm that halts training (not tracked when `None`)
+    pub gradient_norms: Option<GradientNormConfig>,
+
+    /// Growth of the training loss that stops a run and marks it
+    /// as diverged in the results (never stopped when `None`)
+    pub divergence: Option<DivergenceConfig>,
+}
+
+/// Called with the run ID, the epoch's summary, and the network at the end
+/// of every epoch of every run. Returning `false` stops that run's training
+pub type EpochCallback<'a> = dyn Fn(usize, &EpochLog, &Perceptron) -> bool + Sync + 'a;
+
 /// Train network with deserailzed JSON data
 ///
 /// # Arguments
 ///
 /// * `network_data_de` - Deserialized network parameters with
-/// training and validation data
-/// * `args` - Command line arguments
-pub fn train_from_json(network_data_de: NetworkDataDe, args: Args) -> Result<(), String> {
-    let mut training_threads: Vec<JoinHandle<TrainingResultsSer>> = vec![];
-    let mut all_results: Vec<TrainingResultsSer> = vec![];
-
-    // Isolate validation inputs
-    let validation_inputs: Array2<f64> = network_data_de.test_inputs.t().to_owned();
-    // Isolate validation outputs
-    let validation_outputs: Array2<f64> = network_data_de.test_outputs.to_owned();
-
-    // Create training threads
-    for id in 0..args.threads {
-        let network_data_arc = Arc::new(Mutex::new(network_data_de.clone()));
-        training_threads.push(train_single_thread(
-            id,
-            network_data_arc,
-            args.shuffle,
-            args.epochs,
-            args.batch_size,
-        ));
+///   training and validation data
+/// * `options` - Number of runs, thread pool size, and training options
+pub fn train_from_json(
+    network_data_de: &NetworkDataDe,
+    options: &TrainerOptions,
+) -> Result<ThreadedResultsSer, OpenPbError> {
+    train_from_json_with_callback(network_data_de, options, &|_, _, _| true)
+}
+
+/// Same as `train_from_json`, but `on_epoch` is called at the
+/// end of every epoch of every run (from the worker threads)
+///
+/// # Arguments
+///
+/// * `network_data_de` - Deserialized network parameters with
+///   training and validation data
+/// * `options` - Number of runs, thread pool size, and training options
+/// * `on_epoch` - Called with the run ID, epoch summary, and network
+pub fn train_from_json_with_callback(
+    network_data_de: &NetworkDataDe,
+    options: &TrainerOptions,
+    on_epoch: &EpochCallback,
+) -> Result<ThreadedResultsSer, OpenPbError> {
+    // Isolate validation inputs and outputs
+    let (validation_inputs, validation_outputs) = network_data_de.data.validation_set();
+
+    let mut options: TrainerOptions = options.clone();
+    let batch_tuning: Option<BatchSizeTuning> = if options.auto_batch_size {
+        let shuffle: bool = options.batch_config.shuffle;
+        let tuning: BatchSizeTuning = tune_batch_size(network_data_de, shuffle, options.seed)?;
+        options.batch_config.batch_size = Some(tuning.chosen);
+        Some(tuning)
+    } else {
+        None
+    };
+
+    // Number of runs is independent of the pool size, so
+    // many runs don't spawn an equal number of threads
+    let pool: ThreadPool = affinity::thread_pool(options.threads, options.pin_cores.as_deref())?;
+
+    // Each run is queued on the pool, results are
+    // collected in the same order as the run IDs
+    let all_results: Vec<TrainingResultsSer> = pool.install(|| {
+        (0..options.runs)
+            .into_par_iter()
+            .map(|id| train_single_run(id, network_data_de.clone(), &options, on_epoch))
+            .collect::<Result<Vec<TrainingResultsSer>, OpenPbError>>()
+    })?;
+
+    Ok(ThreadedResultsSer::new(
+        all_results,
+        validation_inputs,
+        validation_outputs,
+        options.batch_config.batch_size,
+    )
+    .with_optimizer(network_data_de.optimizer_config())
+    .with_batch_tuning(batch_tuning))
+}
+
+/// Same as `train_from_json`, but every run stops cleanly at the end of its
+/// current epoch once `token` is cancelled. Runs that haven't started yet
+/// stop after their first epoch, and the results are marked as cancelled
+///
+/// # Arguments
+///
+/// * `network_data_de` - Deserialized network parameters with
+///   training and validation data
+/// * `options` - Number of runs, thread pool size, and training options
+/// * `token` - Checked at the end of every epoch of every run
+pub fn train_from_json_cancellable(
+    network_data_de: &NetworkDataDe,
+    options: &TrainerOptions,
+    token: &CancellationToken,
+) -> Result<ThreadedResultsSer, OpenPbError> {
+    let threaded_results: ThreadedResultsSer =
+        train_from_json_with_callback(network_data_de, options, &|_, _, _| !token.is_cancelled())?;
+    Ok(threaded_results.with_cancelled(token.is_cancelled()))
+}
+
+/// Training started on a background thread by `spawn_training`, which
+/// can be polled and cancelled without blocking (e.g. from a GUI event loop)
+pub struct TrainingHandle {
+    /// Token shared with every training run
+    token: CancellationToken,
+
+    /// Background thread the runs are trained from
+    thread: JoinHandle<Result<ThreadedResultsSer, OpenPbError>>,
+}
+
+impl TrainingHandle {
+    /// Ask every run to stop at the end of its current epoch
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    /// Token shared with every training run
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
     }
 
-    // Wait for each training thread to finish, then add the data
-    // to a Vec containing all training results
-    for thread in training_threads {
-        all_results.push(thread.join().unwrap());
+    /// Whether or not training has finished (or been cancelled and stopped)
+    pub fn is_finished(&self) -> bool {
+        self.thread.is_finished()
     }
 
-    let threaded_results = ThreadedResultsSer::new(
+    /// Wait for training to finish and return its results
+    pub fn join(self) -> Result<ThreadedResultsSer, OpenPbError> {
+        match self.thread.join() {
+            Ok(results) => results,
+            Err(_) => Err("Training thread panicked".into()),
+        }
+    }
+}
+
+/// Start training on a background thread and return immediately
+///
+/// # Arguments
+///
+/// * `network_data_de` - Deserialized network parameters with
+///   training and validation data
+/// * `options` - Number of runs, thread pool size, and training options
+pub fn spawn_training(network_data_de: NetworkDataDe, options: TrainerOptions) -> TrainingHandle {
+    let token: CancellationToken = CancellationToken::new();
+    let thread_token: CancellationToken = token.clone();
+    let thread = thread::spawn(move || {
+        train_from_json_cancellable(&network_data_de, &options, &thread_token)
+    });
+    TrainingHandle { token, thread }
+}
+
+/// Train each (differently configured) member of an ensemble once,
+/// then combine their predictions into a single ensemble prediction
+///
+/// # Arguments
+///
+/// * `data` - Training and validation data shared by every member
+/// * `ensemble_de` - Deserialized member networks and combination method
+/// * `options` - Thread pool size and training options (`runs` is ignored,
+///   since every member is trained exactly once)
+pub fn train_ensemble(
+    data: Arc<dyn DataSource>,
+    ensemble_de: &EnsembleDe,
+    options: &TrainerOptions,
+) -> Result<ThreadedResultsSer, OpenPbError> {
+    let mut members: Vec<NetworkDataDe> = Vec::with_capacity(ensemble_de.members.len());
+    for member_de in &ensemble_de.members {
+        members.push(NetworkDataDe::from_config(data.clone(), member_de.clone())?);
+    }
+    let (validation_inputs, validation_outputs) = data.validation_set();
+
+    let pool: ThreadPool = affinity::thread_pool(options.threads, options.pin_cores.as_deref())?;
+
+    let all_results: Vec<TrainingResultsSer> = pool.install(|| {
+        members
+            .par_iter()
+            .enumerate()
+            .map(|(id, member)| train_single_run(id, member.clone(), options, &|_, _, _| true))
+            .collect::<Result<Vec<TrainingResultsSer>, OpenPbError>>()
+    })?;
+
+    // Raw (encoded) output of every member for the validation inputs
+    let validation_raw: Vec<Array2<f64>> = all_results
+        .iter()
+        .map(|results| results.network().predict_raw(&validation_inputs))
+        .collect();
+
+    let ensemble: EnsembleSer = match ensemble_de.combine {
+        CombineDe::Vote => {
+            let decoded: Vec<Array2<f64>> = members
+                .iter()
+                .zip(&validation_raw)
+                .map(|(member, raw)| member.encoder.decode(raw))
+                .collect();
+            check_shapes(&decoded)?;
+            combined_results(CombineDe::Vote, &members[0], ensemble::vote(&decoded))
+        }
+        CombineDe::Average => {
+            check_shapes(&validation_raw)?;
+            let averaged: Array2<f64> = ensemble::average(&validation_raw);
+            let decoded: Array2<f64> = members[0].encoder.decode(&averaged);
+            combined_results(CombineDe::Average, &members[0], decoded)
+        }
+        CombineDe::Stacking => {
+            // Raw outputs of every member become the inputs of the stacking network.
+            // It trains on predictions of samples the members never saw, since members
+            // fit their own training samples better than anything they're used on
+            let folds: usize = ensemble_de.stacking_folds.unwrap_or(DEFAULT_STACKING_FOLDS);
+            eprintln!("Training members on {folds} folds for stacking...");
+            let training_raw: Vec<Array2<f64>> = pool.install(|| {
+                out_of_fold_outputs(data.as_ref(), &ensemble_de.members, folds, options)
+            })?;
+            let (_, training_outputs) = data.training_set();
+            let stacked_data: DataDe = DataDe::new(
+                stack_outputs(&training_raw)?,
+                training_outputs,
+                stack_outputs(&validation_raw)?,
+                validation_outputs.clone(),
+            )?
+            .with_soft_targets(data.soft_targets())?;
+
+            // Presence of the stacking config was checked during deserialization
+            let stacking_de = ensemble_de.stacking.clone().unwrap();
+            let stacking: NetworkDataDe =
+                NetworkDataDe::from_config(Arc::new(stacked_data), stacking_de)?;
+            let stacking_results: TrainingResultsSer =
+                train_single_run(members.len(), stacking, options, &|_, _, _| true)?;
+            let (label, value, passed) = stacking_results.metric();
+            EnsembleSer::new(
+                CombineDe::Stacking,
+                label.to_string(),
+                value,
+                passed,
+                stacking_results.predicted_output().clone(),
+            )
+            .with_stacking(stacking_results)
+        }
+    };
+    let (label, value, _) = ensemble.metric();
+    eprintln!("Ensemble {label}: {value}");
+
+    Ok(ThreadedResultsSer::new(
         all_results,
         validation_inputs,
         validation_outputs,
-        args.batch_size,
+        options.batch_config.batch_size,
+    )
+    .with_ensemble(ensemble))
+}
+
+/// Train a network with multiple output heads branching from shared
+/// layers. Each run trains the trunk and every head together, with the
+/// gradients of every head's cost summed into the trunk
+///
+/// # Arguments
+///
+/// * `data` - Training and validation data, whose output columns
+///   are split between the heads (in order)
+/// * `multi_head_de` - Deserialized trunk and head networks
+/// * `options` - Thread pool size, runs, epochs, and minibatch options
+pub fn train_multi_head(
+    data: Arc<dyn DataSource>,
+    multi_head_de: &MultiHeadDe,
+    options: &TrainerOptions,
+) -> Result<Vec<MultiHeadResultsSer>, OpenPbError> {
+    let metadata: DataMetadata = data.metadata();
+    if metadata.output_size != multi_head_de.output_size() {
+        return Err(OpenPbError::Shape {
+            context: "Output columns of the data (one per column the heads predict)".to_string(),
+            expected: multi_head_de.output_size(),
+            found: metadata.output_size,
+        });
+    }
+    if options.batch_config.sampler == Sampler::Balanced {
+        return Err("Balanced sampling isn't supported for multi-head networks".into());
+    }
+
+    // Each head is configured like a whole network trained on its own output columns
+    let mut heads: Vec<(String, Range<usize>, NetworkDataDe)> =
+        Vec::with_capacity(multi_head_de.heads.len());
+    let mut start: usize = 0;
+    for head_de in &multi_head_de.heads {
+        let columns: Range<usize> = start..start + head_de.outputs;
+        start = columns.end;
+        let head_data: HeadData = HeadData::new(data.clone(), columns.clone());
+        let network_data_de: NetworkDataDe =
+            NetworkDataDe::from_config(Arc::new(head_data), head_de.network.clone())?;
+        heads.push((head_de.name.clone(), columns, network_data_de));
+    }
+    let optimizer: Box<dyn Optimizer> = multi_head_de.trunk.optimizer()?;
+    let (validation_inputs, validation_outputs) = data.validation_set();
+
+    let pool: ThreadPool = affinity::thread_pool(options.threads, options.pin_cores.as_deref())?;
+
+    let all_results: Vec<MultiHeadResultsSer> = pool.install(|| {
+        (0..options.runs)
+            .into_par_iter()
+            .map(|id| -> Result<MultiHeadResultsSer, OpenPbError> {
+                let seed: u64 = match options.seed {
+                    Some(seed) => seed.wrapping_add(id as u64),
+                    None => rand::random(),
+                };
+                let input_shape: (usize, usize) = (metadata.input_size, metadata.train_samples);
+                let trunk: Perceptron = multi_head_de
+                    .trunk
+                    .create_seeded_network(input_shape, seed)?;
+
+                let mut run_heads: Vec<Head> = Vec::with_capacity(heads.len());
+                for (index, (name, columns, head_de)) in heads.iter().enumerate() {
+                    let head_seed: u64 = seed.wrapping_add((index as u64 + 1) << 32);
+                    let network: Perceptron =
+                        head_de.create_seeded_head(trunk.output_size(), head_seed)?;
+                    let (_, head_outputs) = head_de.data.training_set();
+                    let encoded_size: usize = head_de.encoder.encode(&head_outputs).ncols();
+                    if network.output_size() != encoded_size {
+                        return Err(format!(
+                            "Head \"{name}\" has {} output neurons, but its encoded outputs have {encoded_size} columns",
+                            network.output_size()
+                        ).into());
+                    }
+                    run_heads.push(Head {
+                        name: name.clone(),
+                        network,
+                        cost: head_de.cost.clone(),
+                        metric: head_de.metric.clone(),
+                        encoder: head_de.encoder.clone(),
+                        optimizer: head_de.optimizer.clone(),
+                        columns: columns.clone(),
+                    });
+                }
+
+                let mut network: MultiHeadPerceptron =
+                    MultiHeadPerceptron::new(trunk, optimizer.clone(), run_heads, seed);
+                if !options.early_stopping {
+                    network.disable_early_stopping();
+                }
+                let now: SystemTime = SystemTime::now();
+                let total_epochs: usize =
+                    network.fit(data.as_ref(), options.epochs, options.batch_config);
+                let elapsed_time: f32 = now.elapsed().unwrap().as_secs_f32();
+
+                let metrics: Vec<(String, f32, bool)> =
+                    network.evaluate(&validation_inputs, &validation_outputs);
+                let head_results: Vec<HeadResultsSer> = network
+                    .heads()
+                    .iter()
+                    .zip(network.predict(&validation_inputs))
+                    .zip(metrics)
+                    .map(|((head, prediction), (label, value, passed))| {
+                        HeadResultsSer::new(
+                            head.name.clone(),
+                            head.network.clone(),
+                            label,
+                            value,
+                            passed,
+                            prediction,
+                        )
+                    })
+                    .collect();
+                Ok(MultiHeadResultsSer::new(
+                    network.trunk().clone(),
+                    head_results,
+                    seed,
+                    elapsed_time,
+                    total_epochs,
+                ))
+            })
+            .collect::<Result<Vec<MultiHeadResultsSer>, OpenPbError>>()
+    })?;
+
+    for (run, results) in all_results.iter().enumerate() {
+        for head in results.heads() {
+            let (label, value, passed) = head.metric();
+            let result: &str = if passed { "pass" } else { "fail" };
+            eprintln!(
+                "Run {run}, head \"{}\" {label}: {value} ({result}, {} epochs)",
+                head.name(),
+                results.total_epochs()
+            );
+        }
+    }
+    Ok(all_results)
+}
+
+/// Benchmark candidate batch sizes on a freshly initialized network
+///
+/// # Arguments
+///
+/// * `network_data_de` - Deserialized network parameters with
+///   training and validation data
+/// * `shuffle` - Whether or not training data is shuffled each epoch
+/// * `seed` - Seed of the network and shuffling (unseeded when `None`)
+fn tune_batch_size(
+    network_data_de: &NetworkDataDe,
+    shuffle: bool,
+    seed: Option<u64>,
+) -> Result<BatchSizeTuning, OpenPbError> {
+    // Seeded jobs benchmark the same network and batches every time,
+    // so the chosen batch size (and everything after it) is reproducible
+    let (network, mut rng): (Perceptron, StdRng) = match seed {
+        Some(seed) => (
+            network_data_de.create_seeded_network(seed)?,
+            StdRng::seed_from_u64(seed),
+        ),
+        None => (network_data_de.create_network()?, StdRng::from_entropy()),
+    };
+    eprintln!("Benchmarking candidate batch sizes...");
+
+    let tuning: BatchSizeTuning = batch_tuner::tune_batch_size(
+        &network,
+        network_data_de.data.as_ref(),
+        network_data_de.optimizer.as_ref(),
+        network_data_de.cost.as_ref(),
+        network_data_de.encoder.as_ref(),
+        shuffle,
+        AUTO_BATCH_EPOCHS,
+        &mut rng,
+    );
+    for candidate in &tuning.candidates {
+        eprintln!(
+            "  batch size {:>6}: {:.4}s/epoch, loss slope {:.4e}/s",
+            candidate.batch_size, candidate.seconds_per_epoch, candidate.loss_slope
+        );
+    }
+    eprintln!("Chose batch size {}", tuning.chosen);
+    Ok(tuning)
+}
+
+/// Score a combined prediction with the first member's metric
+///
+/// # Arguments
+///
+/// * `combine` - Method used to combine the member predictions
+/// * `member` - First member of the ensemble
+/// * `predicted_output` - Combined (decoded) prediction for the validation set
+fn combined_results(
+    combine: CombineDe,
+    member: &NetworkDataDe,
+    predicted_output: Array2<f64>,
+) -> EnsembleSer {
+    let (_, validation_outputs) = member.data.validation_set();
+    let metric: &dyn Metric = member.metric.as_ref();
+
+    EnsembleSer::new(
+        combine,
+        metric.label().to_string(),
+        metric.value(&predicted_output, &validation_outputs),
+        metric.check(&predicted_output, &validation_outputs),
+        predicted_output,
+    )
+}
+
+/// Check that every member produced predictions of the same shape
+///
+/// # Arguments
+///
+/// * `predictions` - Prediction of each member
+fn check_shapes(predictions: &[Array2<f64>]) -> Result<(), OpenPbError> {
+    let shape: &[usize] = predictions[0].shape();
+    match predictions.iter().find(|p| p.shape() != shape) {
+        Some(mismatched) => Err(format!(
+            "Ensemble members have mismatched outputs ({:?} != {:?})",
+            mismatched.shape(),
+            shape
+        )
+        .into()),
+        None => Ok(()),
+    }
+}
+
+/// Raw outputs of every member for every training sample, where each sample
+/// is predicted by a copy of the member that was trained without it: the
+/// training set is split into folds, and a copy of every member is trained
+/// on all but one fold, then predicts the fold it didn't see
+///
+/// # Arguments
+///
+/// * `data` - Training and validation data shared by every member
+/// * `members` - Deserialized member networks
+/// * `folds` - Number of folds the training set is split into
+/// * `options` - Training options of every copy
+fn out_of_fold_outputs(
+    data: &dyn DataSource,
+    members: &[NetworkDe],
+    folds: usize,
+    options: &TrainerOptions,
+) -> Result<Vec<Array2<f64>>, OpenPbError> {
+    let (training_inputs, training_outputs) = data.training_set();
+    let (validation_inputs, validation_outputs) = data.validation_set();
+    let samples: usize = training_inputs.ncols();
+    if samples < folds {
+        return Err(format!(
+            "Stacking with {folds} folds needs at least {folds} training samples, found {samples}"
+        )
+        .into());
+    }
+
+    let copies: Vec<(usize, usize)> = (0..members.len())
+        .flat_map(|member| (0..folds).map(move |fold| (member, fold)))
+        .collect();
+    let predictions: Vec<(usize, Vec<usize>, Array2<f64>)> = copies
+        .par_iter()
+        .map(|&(member, fold)| {
+            // Every `folds`-th sample is held out, so each fold has a mix of the whole set
+            let (held_out, kept): (Vec<usize>, Vec<usize>) =
+                (0..samples).partition(|sample| sample % folds == fold);
+            let fold_data: DataDe = DataDe::new(
+                training_inputs.select(Axis(1), &kept).reversed_axes(),
+                training_outputs.select(Axis(0), &kept),
+                validation_inputs.t().to_owned(),
+                validation_outputs.clone(),
+            )?
+            .with_soft_targets(data.soft_targets())?;
+            let network_data_de: NetworkDataDe =
+                NetworkDataDe::from_config(Arc::new(fold_data), members[member].clone())?;
+            let results: TrainingResultsSer =
+                train_single_run(member, network_data_de, options, &|_, _, _| true)?;
+            let predicted: Array2<f64> = results
+                .network()
+                .predict_raw(&training_inputs.select(Axis(1), &held_out));
+            Ok((member, held_out, predicted))
+        })
+        .collect::<Result<Vec<(usize, Vec<usize>, Array2<f64>)>, OpenPbError>>()?;
+
+    let mut outputs: Vec<Option<Array2<f64>>> = vec![None; members.len()];
+    for (member, held_out, predicted) in predictions {
+        let output: &mut Array2<f64> =
+            outputs[member].get_or_insert_with(|| Array2::zeros((predicted.nrows(), samples)));
+        for (column, &sample) in held_out.iter().enumerate() {
+            output.column_mut(sample).assign(&predicted.column(column));
+        }
+    }
+    Ok(outputs.into_iter().flatten().collect())
+}
+
+/// Concatenate the raw outputs of every member into
+/// a single matrix with one row per sample
+///
+/// # Arguments
+///
+/// * `outputs` - Raw output of each member (one column per sample)
+fn stack_outputs(outputs: &[Array2<f64>]) -> Result<Array2<f64>, OpenPbError> {
+    let views: Vec<ArrayView2<f64>> = outputs.iter().map(|output| output.view()).collect();
+    match concatenate(Axis(0), &views) {
+        Ok(stacked) => Ok(stacked.reversed_axes()),
+        Err(error) => Err(format!("Failed to stack ensemble outputs: {error}").into()),
+    }
+}
+
+/// Network trained from a single random initialization of a run
+struct Attempt {
+    /// Trained network
+    network: Perceptron,
+
+    /// Optimizer with its state after training
+    optimizer: Box<dyn Optimizer>,
+
+    /// Reconstruction error of pre-training (if configured)
+    pretraining: Option<Pretraining>,
+
+    /// Number of epochs it took for training to complete
+    total_epochs: usize,
+
+    /// Loss and metric at the end of every epoch
+    history: Vec<EpochLog>,
+
+    /// Metric score of the validation set prediction
+    metric_value: f32,
+
+    /// Whether or not the score is a "passing" score
+    metric_passed: bool,
+
+    /// Epoch whose training loss exploded (only present for diverged attempts)
+    divergence: Option<Divergence>,
+}
+
+impl Attempt {
+    /// Whether or not the attempt should be kept over another one: attempts
+    /// that didn't diverge beat ones that did, passing scores beat failing
+    /// ones, otherwise the better score wins
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - Best attempt so far
+    /// * `metric` - Method the attempts were scored with
+    fn is_better_than(&self, other: &Attempt, metric: &dyn Metric) -> bool {
+        if self.divergence.is_some() != other.divergence.is_some() {
+            return self.divergence.is_none();
+        }
+        if self.metric_passed != other.metric_passed {
+            return self.metric_passed;
+        }
+        if metric.lower_is_better() {
+            self.metric_value < other.metric_value
+        } else {
+            self.metric_value > other.metric_value
+        }
+    }
+}
+
+/// Seed of one of a run's random initializations. The first attempt uses
+/// the run's own seed, and later attempts are offset far enough that they
+/// never share a seed with another run
+///
+/// # Arguments
+///
+/// * `run_seed` - Seed of the run (unseeded when `None`)
+/// * `attempt` - Index of the initialization
+fn attempt_seed(run_seed: Option<u64>, attempt: usize) -> Option<u64> {
+    run_seed.map(|seed| seed.wrapping_add((attempt as u64) << 32))
+}
+
+/// Create, pre-train, and train a network from a single random initialization
+///
+/// # Arguments
+///
+/// * `id` - Unique ID for the training run
+/// * `attempt` - Index of the initialization within the run
+/// * `seed` - Seed of the network's random number generator (optional)
+/// * `network_data_de` - Run-local copy of the network training data
+/// * `options` - Epochs, minibatch, profiling, and statistics options
+/// * `on_epoch` - Called with the run ID, epoch summary, and network after every epoch
+///
+/// Returns an error when a gradient norm exceeded the configured maximum
+fn train_attempt(
+    id: usize,
+    attempt: usize,
+    seed: Option<u64>,
+    network_data_de: &NetworkDataDe,
+    options: &TrainerOptions,
+    on_epoch: &EpochCallback,
+) -> Result<Attempt, OpenPbError> {
+    // Create new network with randomized (or seeded) weights and biases
+    let mut network: Perceptron = match seed {
+        Some(seed) => network_data_de.create_seeded_network(seed)?,
+        None => network_data_de.create_network()?,
+    };
+    let train_samples: usize = network_data_de.data.metadata().train_samples;
+    if network.has_batch_norm() {
+        batch_norm::check_batch_size(options.batch_config.samples_per_batch(train_samples))?;
+    }
+    if options.profile {
+        network.enable_profiling();
+    }
+    if let Some(interval) = options.stats_interval {
+        network.enable_stats(interval);
+    }
+    if let Some(interval) = options.snapshot_interval {
+        network.enable_snapshots(interval);
+    }
+    if let Some(config) = options.histograms {
+        network.enable_histograms(config);
+    }
+    if let Some(config) = options.gradient_norms {
+        network.enable_gradient_norms(config);
+    }
+    if let Some(config) = options.divergence {
+        network.enable_divergence_guard(config);
+    }
+    if let Some(sampling) = options.validation_sampling {
+        network.enable_validation_sampling(sampling);
+    }
+    if !options.early_stopping {
+        network.disable_early_stopping();
+    }
+    // Summary is the same for every run, so it's only printed once
+    if id == 0 && attempt == 0 {
+        let batch_size: usize = options.batch_config.samples_per_batch(train_samples);
+        eprint!("{}", network.summary(batch_size));
+    }
+
+    // Hidden layers are initialized by an autoencoder (if configured)
+    let pretraining: Option<Pretraining> = match &network_data_de.pretrain {
+        Some(config) => {
+            eprintln!("Pre-training hidden layers for run {id}...");
+            pretrain::pretrain(
+                &mut network,
+                network_data_de.data.as_ref(),
+                config,
+                options.batch_config,
+            )
+        }
+        None => None,
+    };
+    // Averaged copy starts from the (possibly pre-trained) initial weights
+    if let Some(config) = network_data_de.averaging {
+        network.enable_averaging(config);
+    }
+
+    // Every attempt starts from a fresh copy of the optimizer's state
+    let mut optimizer: Box<dyn Optimizer> = network_data_de.optimizer.clone();
+    let metric: &dyn Metric = network_data_de.metric.as_ref();
+    let cost: &dyn Cost = network_data_de.cost.as_ref();
+    let encoder: &dyn Encoder = network_data_de.encoder.as_ref();
+
+    let data: &dyn DataSource = network_data_de.data.as_ref();
+
+    let attempt_label: String = if options.restarts > 1 {
+        format!(" (attempt {}/{})", attempt + 1, options.restarts)
+    } else {
+        String::new()
+    };
+    // Validation inputs are only copied when dead neurons are checked during training
+    let dead_neuron_warnings: Option<(DeadNeuronConfig, usize, Array2<f64>)> =
+        options.dead_neurons.and_then(|config| {
+            config
+                .warn_every
+                .map(|warn_every| (config, warn_every, data.validation_set().0))
+        });
+
+    let start: &str = if network_data_de.is_resumed() {
+        "resumed from checkpoint"
+    } else {
+        "initialized"
+    };
+    eprintln!("Network {start}, starting training cycle for run {id}{attempt_label}...");
+    let mut history: Vec<EpochLog> = vec![];
+    let total_epochs: usize = network.fit_with_callback(
+        data,
+        optimizer.as_mut(),
+        metric,
+        cost,
+        encoder,
+        options.epochs,
+        options.batch_config,
+        &mut |epoch_log, network| {
+            history.push(epoch_log.clone());
+            if let Some(log_every) = options.log_every {
+                if epoch_log.epoch.is_multiple_of(log_every.max(1)) {
+                    // The penalty is only shown when the network is regularized
+                    let penalty: String = if epoch_log.regularization > 0.0 {
+                        format!(" (+ regularization {:.6})", epoch_log.regularization)
+                    } else {
+                        String::new()
+                    };
+                    eprintln!(
+                        "Run {id}{attempt_label}, epoch {}: loss {:.6}{penalty}, metric {:.4} ({:.3}s)",
+                        epoch_log.epoch, epoch_log.loss, epoch_log.metric, epoch_log.elapsed_time
+                    );
+                }
+            }
+            if let Some((config, warn_every, inputs)) = &dead_neuron_warnings {
+                if epoch_log.epoch.is_multiple_of((*warn_every).max(1)) {
+                    warn_dead_neurons(id, epoch_log.epoch, network, inputs, config.threshold);
+                }
+            }
+            on_epoch(id, epoch_log, network)
+        },
     );
-    save_output::save_to_dir(args, threaded_results)
+    if let Some(explosion) = network.take_gradient_explosion() {
+        return Err(format!("Run {id}{attempt_label}: {explosion}").into());
+    }
+    // Diverged runs are kept (and marked) rather than failing the whole training
+    let divergence: Option<Divergence> = network.take_divergence();
+    match &divergence {
+        Some(divergence) => eprintln!("Warning: run {id}{attempt_label}: {divergence}"),
+        None => eprintln!("Training finished for run {id}{attempt_label}!"),
+    }
+    network.finish_averaging();
+
+    let (validation_inputs, validation_outputs) = data.validation_set();
+    let prediction: Array2<f64> =
+        network.par_predict(&validation_inputs, encoder, VALIDATION_CHUNK_SIZE);
+
+    Ok(Attempt {
+        network,
+        optimizer,
+        pretraining,
+        total_epochs,
+        history,
+        metric_value: metric.value(&prediction, &validation_outputs),
+        metric_passed: divergence.is_none() && metric.check(&prediction, &validation_outputs),
+        divergence,
+    })
 }
 
-/// Create new training thread
+/// Print a warning for every hidden layer with dead neurons
 ///
 /// # Arguments
 ///
-/// * `id` - Unique ID for new thread
-/// * `network_data_arc` Thread safe reference counted
-/// mutex containing network training data
-/// * `shuffle` - Where or not training set should be
-/// shuffled each training cycle
-/// * `epochs` - Maximum allowed epochs for this thread
-fn train_single_thread(
+/// * `id` - Unique ID for the training run
+/// * `epoch` - Epoch that has just finished
+/// * `network` - Network at the end of the epoch
+/// * `inputs` - Validation input vectors (features x samples)
+/// * `threshold` - Largest absolute activation of a dead neuron
+fn warn_dead_neurons(
     id: usize,
-    network_data_arc: Arc<Mutex<NetworkDataDe>>,
-    shuffle: bool,
-    epochs: usize,
-    batch_size: Option<usize>,
-) -> JoinHandle<TrainingResultsSer> {
-    thread::spawn(move || {
-        // Block current thread until it has ownership of Mutex data
-        let network_data_de: &mut NetworkDataDe = &mut *network_data_arc.lock().unwrap();
-        // Create new network with randomized weights and biases
-        let mut network: Perceptron = network_data_de.create_network().unwrap();
-
-        // Get dyn references from boxed traits
+    epoch: usize,
+    network: &Perceptron,
+    inputs: &Array2<f64>,
+    threshold: f64,
+) {
+    for layer in activations::dead_neurons(network, inputs, threshold) {
+        if layer.dead > 0 {
+            eprintln!(
+                "Warning: run {id}, epoch {epoch}: {}/{} neurons of hidden layer {} are dead",
+                layer.dead, layer.neurons, layer.layer
+            );
+        }
+    }
+}
+
+/// Train a single network on one of the pool's worker threads. With
+/// restarts, several random initializations are trained one after another
+/// and the one with the best validation metric is kept
+///
+/// # Arguments
+///
+/// * `id` - Unique ID for the training run
+/// * `network_data_de` - Run-local copy of the network training data
+/// * `options` - Epochs, minibatch, profiling, and statistics options
+/// * `on_epoch` - Called with the run ID, epoch summary, and network after every epoch
+///
+/// Returns an error when a gradient norm exceeded the configured maximum
+fn train_single_run(
+    id: usize,
+    mut network_data_de: NetworkDataDe,
+    options: &TrainerOptions,
+    on_epoch: &EpochCallback,
+) -> Result<TrainingResultsSer, OpenPbError> {
+    let run_seed: Option<u64> = options.seed.map(|seed| seed.wrapping_add(id as u64));
+
+    // Checked here rather than by callers, since a negative (or NaN)
+    // jitter would make the range below panic
+    if let Some(jitter) = options.jitter {
+        if !(0.0..1.0).contains(&jitter) {
+            return Err(format!("Jitter must be in [0, 1), got {jitter}").into());
+        }
+    }
+
+    // Each run's learning rate is scaled by its own random factor (if enabled)
+    let jitter_scale: Option<f64> = options.jitter.map(|jitter| {
+        let mut rng: StdRng = match run_seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        let scale: f64 = 1.0 + rng.gen_range(-jitter..=jitter);
         let optimizer: &mut dyn Optimizer = network_data_de.optimizer.as_mut();
-        let metric: &dyn Metric = network_data_de.metric.as_ref();
-        let cost: &dyn Cost = network_data_de.cost.as_ref();
-        let encoder: &dyn Encoder = network_data_de.encoder.as_ref();
-
-        // Isolate training set
-        let training_set: (Array2<f64>, Array2<f64>) = (
-            network_data_de.train_inputs.t().to_owned(),
-            network_data_de.train_outputs.to_owned(),
-        );
-        // Isolate validation set
-        let validation_set: (Array2<f64>, Array2<f64>) = (
-            network_data_de.test_inputs.t().to_owned(),
-            network_data_de.test_outputs.to_owned(),
-        );
+        optimizer.set_learning_rate(optimizer.learning_rate() * scale);
+        scale
+    });
+    // Exact hyperparameters the run starts from, so it can be reproduced
+    let hyperparameters: Map<String, Value> = network_data_de.optimizer_config();
+
+    let metric: &dyn Metric = network_data_de.metric.as_ref();
+    let encoder: &dyn Encoder = network_data_de.encoder.as_ref();
+    let data: &dyn DataSource = network_data_de.data.as_ref();
+
+    // Start time (wall-clock and CPU) before training begins. The CPU time
+    // is only the current thread's, so it misses work run on other threads
+    let now: SystemTime = SystemTime::now();
+    let cpu_now: ThreadTime = ThreadTime::now();
 
-        // Start time before training begins
-        let now: SystemTime = SystemTime::now();
+    let restarts: usize = options.restarts.max(1);
+    let mut attempts: Vec<AttemptSer> = Vec::with_capacity(restarts);
+    let mut best: Option<(usize, Attempt)> = None;
+    for attempt in 0..restarts {
+        let seed: Option<u64> = attempt_seed(run_seed, attempt);
+        let attempt_now: SystemTime = SystemTime::now();
+        let trained: Attempt =
+            train_attempt(id, attempt, seed, &network_data_de, options, on_epoch)?;
+        attempts.push(AttemptSer::new(
+            attempt,
+            seed,
+            trained.total_epochs,
+            trained.metric_value,
+            trained.metric_passed,
+            trained.divergence.is_some(),
+            attempt_now.elapsed().unwrap().as_secs_f32(),
+        ));
+        let is_best: bool = match &best {
+            Some((_, best)) => trained.is_better_than(best, metric),
+            None => true,
+        };
+        if is_best {
+            best = Some((attempt, trained));
+        }
+    }
+    let (best_attempt, best) = best.unwrap();
+    let Attempt {
+        mut network,
+        optimizer,
+        pretraining,
+        total_epochs,
+        history,
+        divergence,
+        ..
+    } = best;
+    let seed: Option<u64> = attempt_seed(run_seed, best_attempt);
+
+    let (validation_inputs, validation_outputs) = data.validation_set();
+
+    // Total time after training finished (including every restart)
+    let elapsed_time: f32 = now.elapsed().unwrap().as_secs_f32();
+    let cpu_time: f32 = cpu_now.elapsed().as_secs_f32();
+
+    // Fit the output temperature, so predicted probabilities are calibrated
+    let calibration: Option<Calibration> = if options.calibrate {
+        let expected: Array2<f64> = encoder.encode(&validation_outputs);
+        let calibration: Calibration =
+            calibration::fit_temperature(&network, &validation_inputs, &expected);
+        network.set_temperature(Some(calibration.temperature));
+        Some(calibration)
+    } else {
+        None
+    };
+
+    // Prediction from feeding validation inputs into trained network
+    let predicted_output: Array2<f64> =
+        network.par_predict(&validation_inputs, encoder, VALIDATION_CHUNK_SIZE);
+
+    // Metric results
+    let metric_label: String = metric.label().to_string();
+    let metric_value: f32 = metric.value(&predicted_output, &validation_outputs);
+    let metric_passed: bool =
+        divergence.is_none() && metric.check(&predicted_output, &validation_outputs);
+    let confusion_matrix: Option<ConfusionMatrix> =
+        ConfusionMatrix::new(&predicted_output, &validation_outputs);
 
-        println!("Network initialized, starting training cycle for thread {id}...");
-        let total_epochs: usize = network.fit(
-            &training_set,
-            &validation_set,
-            optimizer,
+    // Confidence interval of the metric from resampled validation predictions (if enabled)
+    let confidence_interval: Option<ConfidenceInterval> = options.bootstrap.and_then(|config| {
+        let mut rng: StdRng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        bootstrap::confidence_interval(
+            &predicted_output,
+            &validation_outputs,
+            metric,
+            config,
+            &mut rng,
+        )
+    });
+
+    // Hold-out test set is only ever evaluated here, once training is over
+    let test_metric: Option<(f32, bool)> = data.test_set().map(|(test_inputs, test_outputs)| {
+        let prediction: Array2<f64> =
+            network.par_predict(&test_inputs, encoder, VALIDATION_CHUNK_SIZE);
+        (
+            metric.value(&prediction, &test_outputs),
+            metric.check(&prediction, &test_outputs),
+        )
+    });
+
+    // Memory used by the dataset, network, and optimizer
+    let metadata: DataMetadata = data.metadata();
+    let dataset_len: usize =
+        (metadata.train_samples + metadata.validation_samples + metadata.test_samples)
+            * (metadata.input_size + metadata.output_size);
+    let memory = MemorySer::new(&network, dataset_len, optimizer.state_len());
+
+    // CPU usage and throughput of the worker thread (over every restart)
+    let epochs_trained: usize = attempts.iter().map(AttemptSer::total_epochs).sum();
+    let samples_trained: usize = metadata.train_samples * epochs_trained;
+    let timing = TimingSer::new(
+        rayon::current_thread_index(),
+        cpu_time,
+        elapsed_time,
+        samples_trained,
+    );
+
+    // Optimizer hyperparameters and schedule state once training finished
+    let mut optimizer_state: Map<String, Value> = optimizer.hyperparameters();
+    if options.save_optimizer_state {
+        optimizer_state.extend(optimizer.state());
+    }
+
+    // Timing breakdown, layer statistics, weight changes, and histograms (if enabled)
+    let profile: Option<Profile> = network.take_profile();
+    let stats: Option<TrainingStats> = network.take_stats();
+    let weight_changes: Option<WeightSnapshots> = network.take_snapshots();
+    let weight_histograms: Option<WeightHistograms> = network.take_histograms();
+
+    // Highest raw outputs of each validation sample (if enabled)
+    let top_predictions: Option<Vec<Vec<ClassProbability>>> = options
+        .top_n
+        .map(|n| top_n::top_n(&network.predict_raw(&validation_inputs), n));
+
+    // Distribution of each layer's activations for a validation batch (if enabled)
+    let layer_activations: Option<Vec<LayerActivations>> = options
+        .activation_samples
+        .map(|samples| activations::activation_summaries(&network, &validation_inputs, samples));
+
+    // Dead neurons rescued during training (if any)
+    let reinitialized_neurons: Option<usize> =
+        Some(network.reinitialized_neurons()).filter(|count| *count > 0);
+
+    // Hidden neurons that are inactive for every validation sample (if enabled)
+    let dead_neurons: Option<Vec<LayerDeadNeurons>> = options
+        .dead_neurons
+        .map(|config| activations::dead_neurons(&network, &validation_inputs, config.threshold));
+
+    // Metric for increasingly noisy validation inputs (if enabled)
+    let robustness: Option<Vec<NoiseLevel>> = options.robustness.map(|config| {
+        let mut rng: StdRng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        robustness::robustness_curve(
+            &network,
+            &validation_inputs,
+            &validation_outputs,
             metric,
-            cost,
             encoder,
-            epochs,
-            shuffle,
-            batch_size,
-        );
-        println!("Training finished for thread {id}!");
-
-        let validation_inputs: &Array2<f64> = &validation_set.0;
-        let validation_outputs: &Array2<f64> = &validation_set.1;
-
-        // Total time after training finished
-        let elapsed_time: f32 = now.elapsed().unwrap().as_secs_f32();
-        // Prediction from feeding validation inputs into trained network
-        let predicted_output: Array2<f64> = network.predict(validation_inputs, encoder);
-
-        // Metric results
-        let metric_label: String = metric.label().to_string();
-        let metric_value: f32 = metric.value(&predicted_output, validation_outputs);
-        let metric_passed: bool = metric.check(&predicted_output, validation_outputs);
-
-        TrainingResultsSer::new(
-            network,
-            metric_label,
-            metric_value,
-            metric_passed,
-            elapsed_time,
-            total_epochs,
-            predicted_output,
+            config,
+            &mut rng,
         )
-    })
+    });
+
+    // Undecoded outputs, stored like the decoded ones with each vector as a row (if enabled)
+    let raw_output: Option<Array2<f64>> = if options.raw_output {
+        Some(network.predict_raw(&validation_inputs).reversed_axes())
+    } else {
+        None
+    };
+
+    // Every attempt is only recorded when there was more than one
+    let restarts_ser: Option<RestartsSer> = if restarts > 1 {
+        Some(RestartsSer::new(best_attempt, attempts))
+    } else {
+        None
+    };
+
+    Ok(TrainingResultsSer::new(
+        network,
+        metric_label,
+        metric_value,
+        metric_passed,
+        elapsed_time,
+        total_epochs,
+        predicted_output,
+    )
+    .with_profile(profile)
+    .with_memory(memory)
+    .with_timing(timing)
+    .with_stats(stats)
+    .with_weight_changes(weight_changes)
+    .with_weight_histograms(weight_histograms)
+    .with_confusion_matrix(confusion_matrix)
+    .with_divergence(divergence)
+    .with_top_predictions(top_predictions)
+    .with_raw_output(raw_output)
+    .with_pretraining(pretraining)
+    .with_robustness(robustness)
+    .with_optimizer_state(optimizer_state)
+    .with_calibration(calibration)
+    .with_seed(seed)
+    .with_hyperparameters(hyperparameters, jitter_scale)
+    .with_restarts(restarts_ser)
+    .with_test_metric(test_metric)
+    .with_confidence_interval(confidence_interval)
+    .with_activations(layer_activations)
+    .with_dead_neurons(dead_neurons)
+    .with_reinitialized_neurons(reinitialized_neurons)
+    .with_history(history))
 }
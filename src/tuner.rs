@@ -0,0 +1,292 @@
+use crate::error::OpenPbError;
+use crate::file_io::results_ser::TrialSummarySer;
+use crate::file_io::save_output;
+use crate::file_io::sweep_de::{
+    ArchitectureDe, DataFilesDe, ParameterDe, RangeDe, StrategyDe, SweepDe, SweepTrainingDe,
+    ARCHITECTURE_PATH, HIDDEN_DROPOUT_PATH,
+};
+use crate::sweep::{self, SweepOutcome};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::fmt::Write;
+use std::path::PathBuf;
+
+/// Default number of top-ranked configurations printed after a search
+pub const DEFAULT_SEARCH_TOP: usize = 10;
+
+/// Values a searched hyperparameter can take: either an
+/// explicit list or a numeric range (see `RangeDe`)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum SearchSpaceDe {
+    /// Explicit list of values to try
+    Values(Vec<Value>),
+
+    /// Numeric range of values to try
+    Range(RangeDe),
+}
+
+impl SearchSpaceDe {
+    /// Swept parameter that assigns these values to a path
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Parameter path in the network config or training options
+    /// * `integer` - Whether or not range values are rounded to integers
+    fn parameter(&self, path: &str, integer: bool) -> ParameterDe {
+        match self {
+            SearchSpaceDe::Values(values) => ParameterDe {
+                path: path.to_string(),
+                values: Some(values.clone()),
+                range: None,
+            },
+            SearchSpaceDe::Range(range) => ParameterDe {
+                path: path.to_string(),
+                values: None,
+                range: Some(RangeDe {
+                    integer: range.integer || integer,
+                    ..range.clone()
+                }),
+            },
+        }
+    }
+}
+
+/// Deserialized values representing a hyperparameter search in JSON or YAML.
+/// A search is a sweep over a fixed set of common hyperparameters, whose
+/// trials are ranked by their mean validation metric
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SearchDe {
+    /// Network config file every configuration starts from
+    pub network: String,
+
+    /// Training and validation data file, or a list of files that are merged
+    pub data: DataFilesDe,
+
+    /// How configurations are chosen
+    #[serde(default = "default_strategy")]
+    pub strategy: StrategyDe,
+
+    /// Maximum number of configurations trained
+    pub trials: usize,
+
+    /// Learning rates of the optimizer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub learning_rate: Option<SearchSpaceDe>,
+
+    /// Number of hidden layers and neurons in each of them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layer_sizes: Option<ArchitectureDe>,
+
+    /// Dropout rate of every hidden layer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dropout: Option<SearchSpaceDe>,
+
+    /// Maximum number of samples in each minibatch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch_size: Option<SearchSpaceDe>,
+
+    /// Training options shared by every configuration
+    pub training: SweepTrainingDe,
+
+    /// Directory where each search's trials and ranking are archived
+    #[serde(default = "default_run_dir")]
+    pub run_dir: String,
+
+    /// Number of top-ranked configurations printed once the search finishes
+    #[serde(default = "default_top")]
+    pub top: usize,
+
+    /// Seed of the random strategy's configurations (optional)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+}
+
+#[doc(hidden)]
+fn default_strategy() -> StrategyDe {
+    StrategyDe::Grid
+}
+
+#[doc(hidden)]
+fn default_run_dir() -> String {
+    "searches".to_string()
+}
+
+#[doc(hidden)]
+fn default_top() -> usize {
+    DEFAULT_SEARCH_TOP
+}
+
+/// Trial summary with its position in the ranking
+#[derive(Serialize, Debug)]
+pub struct RankedTrialSer<'a> {
+    /// Position in the ranking (starting at 1 for the best configuration)
+    pub rank: usize,
+
+    /// Parameters and metric scores of the trial
+    #[serde(flatten)]
+    pub summary: &'a TrialSummarySer,
+}
+
+impl SearchDe {
+    /// # Arguments
+    ///
+    /// * `search_str` - Raw contents of the search spec file
+    /// * `yaml` - Whether the contents are YAML (otherwise JSON)
+    pub fn from_str(search_str: &str, yaml: bool) -> Result<SearchDe, OpenPbError> {
+        let search_de: SearchDe = if yaml {
+            match serde_yaml::from_str(search_str) {
+                Ok(search_de) => search_de,
+                Err(error) => return Err(format!("Invalid search spec: {error}").into()),
+            }
+        } else {
+            match serde_json::from_str(search_str) {
+                Ok(search_de) => search_de,
+                Err(error) => return Err(format!("Invalid search spec: {error}").into()),
+            }
+        };
+        search_de.to_sweep()?;
+        Ok(search_de)
+    }
+
+    /// Sweep over every searched hyperparameter
+    pub fn to_sweep(&self) -> Result<SweepDe, OpenPbError> {
+        let mut parameters: Vec<ParameterDe> = vec![];
+        if let Some(learning_rate) = &self.learning_rate {
+            parameters.push(learning_rate.parameter("optimizer.learning_rate", false));
+        }
+        if let Some(dropout) = &self.dropout {
+            parameters.push(dropout.parameter(HIDDEN_DROPOUT_PATH, false));
+        }
+        if let Some(batch_size) = &self.batch_size {
+            parameters.push(batch_size.parameter("training.batch_size", true));
+        }
+        if parameters.is_empty() && self.layer_sizes.is_none() {
+            return Err("A search needs at least one hyperparameter to search over".into());
+        }
+
+        let sweep_de = SweepDe {
+            network: self.network.clone(),
+            data: self.data.clone(),
+            strategy: self.strategy,
+            trials: self.trials,
+            parameters,
+            architecture: self.layer_sizes.clone(),
+            training: self.training.clone(),
+            run_dir: self.run_dir.clone(),
+            seed: self.seed,
+        };
+        sweep_de.validate()?;
+        Ok(sweep_de)
+    }
+}
+
+/// Rank trials from best to worst mean metric score. Trials with more
+/// passing runs win ties, and trials without a score (e.g. NaN after
+/// diverging) are ranked last
+///
+/// # Arguments
+///
+/// * `summaries` - Summary of every trial
+/// * `lower_is_better` - Whether or not lower metric scores are better
+pub fn rank_trials(
+    summaries: &[TrialSummarySer],
+    lower_is_better: bool,
+) -> Vec<RankedTrialSer<'_>> {
+    let mut ranked: Vec<&TrialSummarySer> = summaries.iter().collect();
+    ranked.sort_by(|a, b| {
+        let (a_value, b_value) = (a.mean_value(), b.mean_value());
+        let by_value: Ordering = match (a_value.is_nan(), b_value.is_nan()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) if lower_is_better => a_value.partial_cmp(&b_value).unwrap(),
+            (false, false) => b_value.partial_cmp(&a_value).unwrap(),
+        };
+        by_value.then(b.passed_runs().cmp(&a.passed_runs()))
+    });
+    ranked
+        .into_iter()
+        .enumerate()
+        .map(|(i, summary)| RankedTrialSer {
+            rank: i + 1,
+            summary,
+        })
+        .collect()
+}
+
+/// Aligned table of the top-ranked trials' metric scores and parameters
+///
+/// # Arguments
+///
+/// * `ranked` - Ranked trials, best first
+/// * `top` - Number of trials in the table
+pub fn ranking_table(ranked: &[RankedTrialSer], top: usize) -> String {
+    let metric_label: &str = ranked
+        .first()
+        .map_or("metric", |ranked_trial| ranked_trial.summary.metric());
+    let mut table = String::new();
+    let _ = writeln!(
+        table,
+        "{:>4}  {:>5}  {:>12}  {:>6}  parameters",
+        "rank", "trial", metric_label, "passed"
+    );
+    for ranked_trial in ranked.iter().take(top) {
+        let summary: &TrialSummarySer = ranked_trial.summary;
+        let parameters: Vec<String> = summary
+            .parameters()
+            .iter()
+            .map(|(path, value)| format!("{}={value}", parameter_name(path)))
+            .collect();
+        let _ = writeln!(
+            table,
+            "{:>4}  {:>5}  {:>12.6}  {:>6}  {}",
+            ranked_trial.rank,
+            summary.trial(),
+            summary.mean_value(),
+            format!("{}/{}", summary.passed_runs(), summary.runs()),
+            parameters.join(", ")
+        );
+    }
+    table
+}
+
+/// Name of a swept parameter as it's written in a search spec
+///
+/// # Arguments
+///
+/// * `path` - Parameter path in the network config or training options
+fn parameter_name(path: &str) -> &str {
+    match path {
+        "optimizer.learning_rate" => "learning_rate",
+        "training.batch_size" => "batch_size",
+        ARCHITECTURE_PATH => "layer_sizes",
+        HIDDEN_DROPOUT_PATH => "dropout",
+        path => path,
+    }
+}
+
+/// Train every configuration of a search with the multi-threaded trainer,
+/// then rank them by mean validation metric. The trials are archived like
+/// a sweep's, next to a `ranking.json` of every trial from best to worst
+///
+/// # Arguments
+///
+/// * `search_de` - Deserialized search spec
+///
+/// Returns the directory the search was archived in
+pub fn run_search(search_de: &SearchDe) -> Result<PathBuf, OpenPbError> {
+    let outcome: SweepOutcome = sweep::run_sweep(&search_de.to_sweep()?)?;
+    let ranked: Vec<RankedTrialSer> = rank_trials(&outcome.summaries, outcome.lower_is_better);
+    save_output::save_json(
+        "search ranking",
+        &ranked,
+        &outcome.sweep_dir.join("ranking.json"),
+    )?;
+
+    let shown: usize = search_de.top.min(ranked.len());
+    println!("\nTop {shown} of {} configurations:", ranked.len());
+    print!("{}", ranking_table(&ranked, search_de.top));
+    Ok(outcome.sweep_dir)
+}
@@ -0,0 +1,128 @@
+use crate::error::OpenPbError;
+use crate::file_io::compare::ResultsSummaryDe;
+use crate::file_io::json_de::{DataDe, NetworkDataDe, NetworkDe};
+use crate::file_io::results_ser::ThreadedResultsSer;
+use crate::run_dir::RunManifest;
+use crate::trainer::{self, TrainerOptions};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Largest difference between a recorded and a reproduced
+/// metric score that still counts as a match
+pub const DEFAULT_VERIFY_TOLERANCE: f32 = 1e-6;
+
+/// Recorded and reproduced outcome of a single run
+#[derive(Serialize, Debug, Clone)]
+pub struct RunCheck {
+    /// ID of the training run
+    pub run: usize,
+
+    /// Seed of the run's random number generator
+    pub seed: Option<u64>,
+
+    /// Metric score in the recorded results
+    pub recorded: f32,
+
+    /// Metric score after training again
+    pub reproduced: f32,
+
+    /// Total epochs in the recorded results
+    pub recorded_epochs: usize,
+
+    /// Total epochs after training again
+    pub reproduced_epochs: usize,
+
+    /// Whether or not the scores are within the tolerance of each other
+    pub matches: bool,
+}
+
+/// Train every run of a run directory again, from the seed, training
+/// options, and resolved network config in its manifest, and check
+/// that each run's metric score matches the recorded results
+///
+/// # Arguments
+///
+/// * `run_dir` - Run directory with `manifest.json`, `network.json`, and `results.json`
+/// * `data` - Data files to train on, defaults to the ones in the manifest when empty
+/// * `tolerance` - Largest difference between the scores that counts as a match
+pub fn verify_run_dir(
+    run_dir: &Path,
+    data: &[String],
+    tolerance: f32,
+) -> Result<Vec<RunCheck>, OpenPbError> {
+    let manifest: RunManifest =
+        match serde_json::from_str(&read_file(&run_dir.join("manifest.json"))?) {
+            Ok(manifest) => manifest,
+            Err(error) => return Err(format!("Invalid run manifest: {error}").into()),
+        };
+    let recorded: ResultsSummaryDe =
+        ResultsSummaryDe::from_json(&read_file(&run_dir.join("results.json"))?)?;
+    if manifest.options.seed.is_none() {
+        return Err(format!(
+            "Run {} wasn't seeded (train with --seed), so it can't be reproduced",
+            manifest.run_id
+        )
+        .into());
+    }
+
+    // Automatic batch sizes are chosen by timing, so the recorded choice is reused
+    let mut options: TrainerOptions = manifest.options;
+    if options.auto_batch_size {
+        options.auto_batch_size = false;
+        options.batch_config.batch_size = recorded.batch_size();
+    }
+
+    let data_files: &[String] = if data.is_empty() {
+        &manifest.data
+    } else {
+        data
+    };
+    let data_de: DataDe = DataDe::from_files(data_files)?;
+    let network_json: String = read_file(&run_dir.join("network.json"))?;
+    let network_de: NetworkDe = NetworkDe::from_json(&network_json)?;
+    let network_data_de: NetworkDataDe = NetworkDataDe::from_config(Arc::new(data_de), network_de)?;
+
+    let reproduced: ThreadedResultsSer = trainer::train_from_json(&network_data_de, &options)?;
+    let recorded_runs: Vec<(Option<u64>, f32, usize)> = recorded.run_outcomes();
+    if recorded_runs.len() != reproduced.runs().len() {
+        return Err(format!(
+            "Results have {} runs, but the manifest trains {}",
+            recorded_runs.len(),
+            reproduced.runs().len()
+        )
+        .into());
+    }
+
+    let checks: Vec<RunCheck> = recorded_runs
+        .into_iter()
+        .zip(reproduced.runs())
+        .enumerate()
+        .map(|(run, ((seed, recorded, recorded_epochs), results))| {
+            let (_, reproduced, _) = results.metric();
+            RunCheck {
+                run,
+                seed: results.seed().or(seed),
+                recorded,
+                reproduced,
+                recorded_epochs,
+                reproduced_epochs: results.total_epochs(),
+                matches: (recorded - reproduced).abs() <= tolerance,
+            }
+        })
+        .collect();
+    Ok(checks)
+}
+
+/// Read the contents of a file
+///
+/// # Arguments
+///
+/// * `path` - Path of the file
+fn read_file(path: &Path) -> Result<String, OpenPbError> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(contents),
+        Err(error) => Err(format!("Failed to read {:#?}: {error}", path).into()),
+    }
+}
@@ -0,0 +1,103 @@
+use crate::file_io::json_de::NetworkDataDe;
+use crate::nn::batches::{BatchConfig, Sampler};
+use crate::nn::perceptron::Perceptron;
+use ndarray::Array2;
+use wasm_bindgen::prelude::*;
+
+/// JavaScript-facing wrapper around a single network and its training data
+#[wasm_bindgen]
+pub struct WasmNetwork {
+    /// Network being trained
+    network: Perceptron,
+
+    /// Deserialized training/validation data and training functions
+    network_data_de: NetworkDataDe,
+}
+
+#[wasm_bindgen]
+impl WasmNetwork {
+    /// # Arguments
+    ///
+    /// * `data_json` - Raw contents of JSON file containing
+    ///   training and validation data
+    /// * `network_json` - Raw contents of JSON file containg
+    ///   network parameters
+    #[wasm_bindgen(constructor)]
+    pub fn new(data_json: &str, network_json: &str) -> Result<WasmNetwork, JsValue> {
+        let network_data_de: NetworkDataDe = NetworkDataDe::from_json(data_json, network_json)
+            .map_err(|error| JsValue::from(error.to_string()))?;
+        let network: Perceptron = network_data_de
+            .create_network()
+            .map_err(|error| JsValue::from(error.to_string()))?;
+
+        Ok(WasmNetwork {
+            network,
+            network_data_de,
+        })
+    }
+
+    /// Train the network on the training set until the metric is
+    /// satisfied or the maximum number of epochs is reached
+    ///
+    /// # Arguments
+    ///
+    /// * `epochs` - Maximum number of training cycles
+    /// * `batch_size` - Size of minibatches (optional)
+    /// * `shuffle` - When 'true', training inputs are shuffled each training cycle
+    ///
+    /// # Returns
+    ///
+    /// The number of epochs it took for the training to complete
+    pub fn train(&mut self, epochs: usize, batch_size: Option<usize>, shuffle: bool) -> usize {
+        let network_data_de: &mut NetworkDataDe = &mut self.network_data_de;
+        let batch_config = BatchConfig {
+            batch_size,
+            shuffle,
+            drop_last: false,
+            sampler: Sampler::Sequential,
+        };
+
+        self.network.fit(
+            network_data_de.data.as_ref(),
+            network_data_de.optimizer.as_mut(),
+            network_data_de.metric.as_ref(),
+            network_data_de.cost.as_ref(),
+            network_data_de.encoder.as_ref(),
+            epochs,
+            batch_config,
+        )
+    }
+
+    /// Decoded predictions for a flat, row-major matrix of input vectors
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - Input values, one input vector per row
+    /// * `rows` - Number of input vectors
+    pub fn predict(&self, inputs: Vec<f64>, rows: usize) -> Result<Vec<f64>, JsValue> {
+        let cols: usize = inputs.len().checked_div(rows).unwrap_or(0);
+        let inputs: Array2<f64> = Array2::from_shape_vec((rows, cols), inputs)
+            .map_err(|error| JsValue::from(error.to_string()))?;
+
+        let encoder = self.network_data_de.encoder.as_ref();
+        let predicted: Array2<f64> = self.network.predict(&inputs.t().to_owned(), encoder);
+        Ok(predicted.iter().copied().collect())
+    }
+
+    /// Metric score of the network's prediction on the validation set
+    pub fn metric(&self) -> f32 {
+        let network_data_de: &NetworkDataDe = &self.network_data_de;
+        let encoder = network_data_de.encoder.as_ref();
+        let (validation_inputs, validation_outputs) = network_data_de.data.validation_set();
+        let predicted: Array2<f64> = self.network.predict(&validation_inputs, encoder);
+
+        network_data_de
+            .metric
+            .value(&predicted, &validation_outputs)
+    }
+
+    /// Serialized weights and biases of each layer
+    pub fn to_json(&self) -> Result<String, JsValue> {
+        serde_json::to_string(&self.network).map_err(|error| JsValue::from(error.to_string()))
+    }
+}